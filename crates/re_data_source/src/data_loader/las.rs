@@ -0,0 +1,198 @@
+//! Reading uncompressed ASPRS `.las` files.
+//!
+//! See the [LAS 1.4 specification](https://www.asprs.org/wp-content/uploads/2019/07/LAS_1_4_r15.pdf).
+
+use anyhow::{bail, ensure};
+
+use super::loader_point_cloud::{Point, PointSource};
+
+/// The fixed part of the header, common to all versions.
+const HEADER_MIN_SIZE: usize = 227;
+
+/// The points of a `.las` file.
+pub struct Las<'a> {
+    records: &'a [u8],
+    record_len: usize,
+    format: u8,
+    num_points: usize,
+
+    scale: [f64; 3],
+    offset: [f64; 3],
+
+    /// The minimum corner of the bounding box, which positions are relative to.
+    ///
+    /// Coordinates are usually georeferenced, far too large to be stored as `f32` as they are.
+    origin: [f64; 3],
+
+    /// Colors are meant to be 16 bit, but many files store them as 8 bit.
+    colors_are_16_bit: bool,
+}
+
+impl<'a> Las<'a> {
+    pub fn new(contents: &'a [u8]) -> anyhow::Result<Self> {
+        ensure!(
+            contents.len() >= HEADER_MIN_SIZE && &contents[0..4] == b"LASF",
+            "Not a LAS file"
+        );
+
+        let u16_at = |at: usize| u16::from_le_bytes([contents[at], contents[at + 1]]);
+        let u32_at = |at: usize| {
+            u32::from_le_bytes([
+                contents[at],
+                contents[at + 1],
+                contents[at + 2],
+                contents[at + 3],
+            ])
+        };
+        let u64_at = |at: usize| (u64::from(u32_at(at + 4)) << 32) | u64::from(u32_at(at));
+        let f64_at = |at: usize| f64::from_bits(u64_at(at));
+
+        let (major, minor) = (contents[24], contents[25]);
+        let header_size = u16_at(94) as usize;
+        let point_data_offset = u32_at(96) as usize;
+        let format = contents[104];
+        let record_len = u16_at(105) as usize;
+
+        // LASzip marks compressed point data by setting the highest bits of the format.
+        if format & 0xc0 != 0 {
+            bail!(
+                "LAZ compressed point clouds are not supported, \
+                 decompress them to .las first, e.g. using `laszip`"
+            );
+        }
+
+        let mut num_points = u64::from(u32_at(107));
+        // LAS 1.4 files may have more points than the legacy counter can hold.
+        if (major, minor) >= (1, 4) && header_size >= 255 && contents.len() >= 255 {
+            num_points = u64_at(247);
+        }
+        let num_points = num_points as usize;
+
+        ensure!(format <= 10, "Unknown LAS point data format {format}");
+        ensure!(
+            record_len >= min_record_len(format),
+            "LAS point data format {format} needs records of at least {} bytes, \
+             but this file's are {record_len}",
+            min_record_len(format)
+        );
+        let records = contents
+            .get(point_data_offset..)
+            .and_then(|records| records.get(..num_points * record_len))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "LAS file is {} bytes long, too short for its {num_points} points",
+                    contents.len()
+                )
+            })?;
+
+        let mut las = Self {
+            records,
+            record_len,
+            format,
+            num_points,
+            scale: [f64_at(131), f64_at(139), f64_at(147)],
+            offset: [f64_at(155), f64_at(163), f64_at(171)],
+            origin: [f64_at(187), f64_at(203), f64_at(219)],
+            colors_are_16_bit: true,
+        };
+
+        if let Some(at) = las.color_offset() {
+            las.colors_are_16_bit = (0..num_points).any(|index| {
+                let record = las.record(index);
+                (0..3).any(|channel| {
+                    let at = at + 2 * channel;
+                    u16::from_le_bytes([record[at], record[at + 1]]) > 255
+                })
+            });
+        }
+
+        Ok(las)
+    }
+
+    fn record(&self, index: usize) -> &[u8] {
+        &self.records[index * self.record_len..(index + 1) * self.record_len]
+    }
+
+    /// Where the red, green and blue channels start in a record, if there are any.
+    fn color_offset(&self) -> Option<usize> {
+        match self.format {
+            2 => Some(20),
+            3 | 5 => Some(28),
+            7 | 8 | 10 => Some(30),
+            _ => None,
+        }
+    }
+}
+
+impl PointSource for Las<'_> {
+    fn num_points(&self) -> usize {
+        self.num_points
+    }
+
+    fn has_colors(&self) -> bool {
+        self.color_offset().is_some()
+    }
+
+    fn has_intensities(&self) -> bool {
+        true
+    }
+
+    fn origin(&self) -> Option<[f64; 3]> {
+        Some(self.origin)
+    }
+
+    fn point(&self, index: usize) -> anyhow::Result<Point> {
+        let record = self.record(index);
+        let i32_at = |at: usize| {
+            i32::from_le_bytes([record[at], record[at + 1], record[at + 2], record[at + 3]])
+        };
+        let u16_at = |at: usize| u16::from_le_bytes([record[at], record[at + 1]]);
+
+        let coordinate = |axis: usize| {
+            let value = f64::from(i32_at(4 * axis)) * self.scale[axis] + self.offset[axis];
+            (value - self.origin[axis]) as f32
+        };
+
+        let class_id = if self.format <= 5 {
+            // The upper bits are flags: synthetic, key-point & withheld.
+            record[15] & 0x1f
+        } else {
+            record[16]
+        };
+
+        let color = self.color_offset().map(|at| {
+            let channel = |channel: usize| {
+                let value = u16_at(at + 2 * channel);
+                if self.colors_are_16_bit {
+                    (value >> 8) as u8
+                } else {
+                    value as u8
+                }
+            };
+            [channel(0), channel(1), channel(2), 255]
+        });
+
+        Ok(Point {
+            position: [coordinate(0), coordinate(1), coordinate(2)],
+            color,
+            intensity: Some(f32::from(u16_at(12))),
+            class_id: Some(class_id.into()),
+        })
+    }
+}
+
+fn min_record_len(format: u8) -> usize {
+    match format {
+        0 => 20,
+        1 => 28,
+        2 => 26,
+        3 => 34,
+        4 => 57,
+        5 => 63,
+        6 => 30,
+        7 => 36,
+        8 => 38,
+        9 => 59,
+        _ => 67,
+    }
+}
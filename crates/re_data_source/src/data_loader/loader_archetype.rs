@@ -99,8 +99,13 @@ impl DataLoader for ArchetypeLoader {
                 entity_path,
                 contents.into_owned(),
             )?);
-        } else if crate::SUPPORTED_POINT_CLOUD_EXTENSIONS.contains(&extension.as_str()) {
-            re_log::debug!(?filepath, loader = self.name(), "Loading 3D point cloud…",);
+        } else if extension == "ply" {
+            // Other point clouds are loaded by the `PointCloudLoader`.
+            re_log::debug!(
+                ?filepath,
+                loader = self.name(),
+                "Loading point cloud or mesh…",
+            );
             rows.extend(load_point_cloud(timepoint, entity_path, &contents)?);
         } else if crate::SUPPORTED_TEXT_EXTENSIONS.contains(&extension.as_str()) {
             re_log::debug!(?filepath, loader = self.name(), "Loading text document…",);
@@ -176,11 +181,7 @@ fn load_point_cloud(
     re_tracing::profile_function!();
 
     let rows = [
-        {
-            // TODO(#4532): `.ply` data loader should support 2D point cloud & meshes
-            let points3d = re_types::archetypes::Points3D::from_file_contents(contents)?;
-            DataRow::from_archetype(RowId::new(), timepoint, entity_path, &points3d)?
-        },
+        super::ply::load_ply(timepoint, entity_path, contents)?,
         //
     ];
 
@@ -0,0 +1,577 @@
+use arrow2::{
+    array::{Array, ListArray, PrimitiveArray, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema, TimeUnit},
+};
+use re_log_types::{
+    DataCell, DataCellError, DataRow, DataTable, EntityPath, RowId, TableId, TimeInt, TimePoint,
+    TimeType, Timeline,
+};
+use re_types::{Component, ComponentName};
+
+use crate::{DataLoader, DataLoaderError, LoadedData};
+
+// ---
+
+/// Describes how the columns of an Arrow IPC or Parquet table map to Rerun data.
+///
+/// Every row of the table becomes a [`DataRow`]. Unmapped columns are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnMapping {
+    /// Column holding the entity path of each row, as a string.
+    ///
+    /// If `None`, or if a row has no value in it, the entity path is derived from the file path.
+    pub entity_path_column: Option<String>,
+
+    /// Columns holding times.
+    ///
+    /// Integer columns on [`TimeType::Time`] timelines are nanoseconds since the Unix epoch;
+    /// Arrow timestamp columns are converted to that automatically.
+    pub timelines: Vec<(String, Timeline)>,
+
+    /// Columns holding component data, and the datatype of each component.
+    ///
+    /// A column of the component's datatype holds a single instance per row, a list column
+    /// of it several instances per row.
+    /// Other columns are cast to the component's datatype; loading fails if that's impossible.
+    pub components: Vec<(String, ComponentName, DataType)>,
+}
+
+impl ColumnMapping {
+    #[inline]
+    pub fn with_entity_path_column(mut self, column: impl Into<String>) -> Self {
+        self.entity_path_column = Some(column.into());
+        self
+    }
+
+    #[inline]
+    pub fn with_timeline(mut self, column: impl Into<String>, typ: TimeType) -> Self {
+        let column = column.into();
+        let timeline = Timeline::new(column.as_str(), typ);
+        self.timelines.push((column, timeline));
+        self
+    }
+
+    #[inline]
+    pub fn with_component<C: Component>(self, column: impl Into<String>) -> Self {
+        self.with_component_datatype(column, C::name(), C::arrow_datatype())
+    }
+
+    /// Like [`Self::with_component`], for components that aren't known at compile time.
+    #[inline]
+    pub fn with_component_datatype(
+        mut self,
+        column: impl Into<String>,
+        component: impl Into<ComponentName>,
+        datatype: DataType,
+    ) -> Self {
+        self.components
+            .push((column.into(), component.into(), datatype));
+        self
+    }
+
+    /// All the columns referenced by this mapping.
+    fn columns(&self) -> impl Iterator<Item = &str> {
+        self.entity_path_column
+            .iter()
+            .map(String::as_str)
+            .chain(self.timelines.iter().map(|(column, _)| column.as_str()))
+            .chain(self.components.iter().map(|(column, _, _)| column.as_str()))
+    }
+}
+
+/// Loads tabular data from Arrow IPC (a.k.a. Feather v2) and Parquet files.
+///
+/// Without a [`ColumnMapping`], only tables that carry Rerun's own column metadata are
+/// supported, e.g. the ones written by `rerun export`.
+///
+/// Any other table needs a mapping: register a loader created with
+/// [`ArrowTableLoader::with_mapping`] using [`crate::register_custom_data_loader`].
+///
+/// Parquet support requires the `parquet` feature.
+#[derive(Default)]
+pub struct ArrowTableLoader {
+    mapping: Option<ColumnMapping>,
+}
+
+impl ArrowTableLoader {
+    #[inline]
+    pub fn with_mapping(mapping: ColumnMapping) -> Self {
+        Self {
+            mapping: Some(mapping),
+        }
+    }
+}
+
+impl DataLoader for ArrowTableLoader {
+    #[inline]
+    fn name(&self) -> String {
+        "rerun.data_loaders.ArrowTable".into()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_from_path(
+        &self,
+        store_id: re_log_types::StoreId,
+        filepath: std::path::PathBuf,
+        tx: std::sync::mpsc::Sender<LoadedData>,
+    ) -> Result<(), crate::DataLoaderError> {
+        use anyhow::Context as _;
+
+        if filepath.is_dir() || !is_arrow_table_file(&filepath) {
+            return Err(DataLoaderError::Incompatible(filepath.clone()));
+        }
+
+        re_tracing::profile_function!(filepath.display().to_string());
+
+        let contents = std::fs::read(&filepath)
+            .with_context(|| format!("Failed to read file {filepath:?}"))?;
+        let contents = std::borrow::Cow::Owned(contents);
+
+        self.load_from_file_contents(store_id, filepath, contents, tx)
+    }
+
+    fn load_from_file_contents(
+        &self,
+        _store_id: re_log_types::StoreId,
+        filepath: std::path::PathBuf,
+        contents: std::borrow::Cow<'_, [u8]>,
+        tx: std::sync::mpsc::Sender<LoadedData>,
+    ) -> Result<(), crate::DataLoaderError> {
+        if !is_arrow_table_file(&filepath) {
+            return Err(DataLoaderError::Incompatible(filepath));
+        }
+
+        re_tracing::profile_function!(filepath.display().to_string());
+
+        let (schema, chunks) = read_table(&filepath, &contents)?;
+
+        let rows = if let Some(mapping) = &self.mapping {
+            if let Some(missing) = mapping
+                .columns()
+                .find(|column| !schema.fields.iter().any(|field| field.name == *column))
+            {
+                re_log::warn!(
+                    ?filepath,
+                    "Table has no column {missing:?}, which the column mapping requires"
+                );
+                return Err(DataLoaderError::Incompatible(filepath));
+            }
+
+            re_log::debug!(?filepath, loader = self.name(), "Loading mapped table…");
+            let default_entity_path = EntityPath::from_file_path(&filepath);
+            chunks
+                .iter()
+                .map(|chunk| rows_from_mapping(mapping, &schema, chunk, &default_entity_path))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            if !has_rerun_metadata(&schema) {
+                re_log::debug!(
+                    ?filepath,
+                    "Table has no Rerun metadata, a column mapping is required to load it"
+                );
+                return Err(DataLoaderError::Incompatible(filepath));
+            }
+
+            re_log::debug!(?filepath, loader = self.name(), "Loading Rerun table…");
+            chunks
+                .iter()
+                .map(|chunk| rows_from_rerun_table(&schema, chunk))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        for row in rows.into_iter().flatten() {
+            if tx.send(row.into()).is_err() {
+                break; // The other end has decided to hang up, not our problem.
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ---
+
+fn is_arrow_table_file(filepath: &std::path::Path) -> bool {
+    crate::SUPPORTED_ARROW_TABLE_EXTENSIONS.contains(&crate::extension(filepath).as_str())
+}
+
+type ArrowChunk = Chunk<Box<dyn Array>>;
+
+fn read_table(
+    filepath: &std::path::Path,
+    contents: &[u8],
+) -> Result<(Schema, Vec<ArrowChunk>), DataLoaderError> {
+    re_tracing::profile_function!();
+
+    let mut reader = std::io::Cursor::new(contents);
+
+    if crate::extension(filepath) == "parquet" {
+        #[cfg(feature = "parquet")]
+        {
+            use arrow2::io::parquet::read;
+
+            let metadata = read::read_metadata(&mut reader).map_err(DataCellError::from)?;
+            let schema = read::infer_schema(&metadata).map_err(DataCellError::from)?;
+            let chunks = read::FileReader::new(
+                reader,
+                metadata.row_groups,
+                schema.clone(),
+                None,
+                None,
+                None,
+            )
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(DataCellError::from)?;
+
+            return Ok((schema, chunks));
+        }
+
+        #[cfg(not(feature = "parquet"))]
+        return Err(anyhow::anyhow!(
+            "Cannot load {filepath:?}: Parquet support requires the `parquet` feature"
+        )
+        .into());
+    }
+
+    use arrow2::io::ipc::read;
+
+    let metadata = read::read_file_metadata(&mut reader).map_err(DataCellError::from)?;
+    let schema = metadata.schema.clone();
+    let chunks = read::FileReader::new(reader, metadata, None, None)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(DataCellError::from)?;
+
+    Ok((schema, chunks))
+}
+
+fn has_rerun_metadata(schema: &Schema) -> bool {
+    schema
+        .fields
+        .iter()
+        .any(|field| field.metadata.contains_key(re_log_types::METADATA_KIND))
+}
+
+/// Tables written by Rerun itself are regular [`DataTable`]s.
+fn rows_from_rerun_table(
+    schema: &Schema,
+    chunk: &ArrowChunk,
+) -> Result<Vec<DataRow>, DataLoaderError> {
+    re_tracing::profile_function!();
+
+    let (schema, chunk) = restore_original_datatypes(schema, chunk)?;
+
+    let table = DataTable::deserialize(TableId::new(), &schema, &chunk)
+        .map_err(|err| anyhow::anyhow!("Failed to deserialize Rerun table: {err}"))?;
+
+    Ok(table
+        .to_rows()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| anyhow::anyhow!("Failed to read rows: {err}"))?)
+}
+
+/// Undoes the conversions done when writing a table to a format that can't represent all of
+/// its columns as-is, see [`re_log_types::METADATA_ORIGINAL_DATATYPE`].
+fn restore_original_datatypes(
+    schema: &Schema,
+    chunk: &ArrowChunk,
+) -> Result<(Schema, ArrowChunk), DataLoaderError> {
+    let mut schema = schema.clone();
+    let mut columns = chunk.arrays().to_vec();
+
+    for (field, column) in schema.fields.iter_mut().zip(&mut columns) {
+        let Some(original) = field
+            .metadata
+            .remove(re_log_types::METADATA_ORIGINAL_DATATYPE)
+        else {
+            continue;
+        };
+        let original = DataTable::decode_datatype(&original).ok_or_else(|| {
+            anyhow::anyhow!("Column {:?} has an invalid original datatype", field.name)
+        })?;
+
+        *column = arrow2::compute::cast::cast(
+            column.as_ref(),
+            &without_extensions(&original),
+            Default::default(),
+        )
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "Column {:?} cannot be converted back to {original:?}: {err}",
+                field.name
+            )
+        })?;
+        field.data_type = column.data_type().clone();
+    }
+
+    Ok((schema, Chunk::new(columns)))
+}
+
+fn rows_from_mapping(
+    mapping: &ColumnMapping,
+    schema: &Schema,
+    chunk: &ArrowChunk,
+    default_entity_path: &EntityPath,
+) -> Result<Vec<DataRow>, DataLoaderError> {
+    use anyhow::Context as _;
+
+    re_tracing::profile_function!();
+
+    let column = |name: &str| -> &dyn Array {
+        // NOTE: cannot fail, the loader made sure all mapped columns exist.
+        let index = schema
+            .fields
+            .iter()
+            .position(|field| field.name == name)
+            .unwrap();
+        chunk.arrays()[index].as_ref()
+    };
+
+    let entity_paths = mapping
+        .entity_path_column
+        .as_deref()
+        .map(|name| {
+            let array =
+                arrow2::compute::cast::cast(column(name), &DataType::Utf8, Default::default())
+                    .map_err(DataCellError::from)?;
+            Ok::<_, DataLoaderError>(
+                array
+                    .as_any()
+                    .downcast_ref::<Utf8Array<i32>>()
+                    .unwrap() // NOTE: cannot fail, we just cast it.
+                    .iter()
+                    .map(|path| path.map(EntityPath::parse_forgiving))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .transpose()?;
+
+    let timelines = mapping
+        .timelines
+        .iter()
+        .map(|(name, timeline)| Ok((*timeline, time_column(name, column(name))?)))
+        .collect::<Result<Vec<_>, DataLoaderError>>()?;
+
+    let components = mapping
+        .components
+        .iter()
+        .map(|(name, component, datatype)| {
+            component_column(name, *component, datatype, column(name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut rows = Vec::with_capacity(chunk.len());
+    for i in 0..chunk.len() {
+        let entity_path = entity_paths
+            .as_ref()
+            .and_then(|paths| paths[i].clone())
+            .unwrap_or_else(|| default_entity_path.clone());
+
+        let timepoint = timelines
+            .iter()
+            .filter_map(|(timeline, times)| times[i].map(|time| (*timeline, TimeInt::from(time))))
+            .collect::<TimePoint>();
+
+        let cells = components
+            .iter()
+            .filter_map(|column| column.cell(i))
+            .collect::<Vec<_>>();
+        let num_instances = cells
+            .iter()
+            .map(|cell| cell.num_instances())
+            .max()
+            .unwrap_or(0);
+
+        rows.push(
+            DataRow::from_cells(RowId::new(), timepoint, entity_path, num_instances, cells)
+                .with_context(|| format!("Failed to read row #{i}"))?,
+        );
+    }
+
+    Ok(rows)
+}
+
+/// Reads a time column as a sequence of (optional) raw time values.
+///
+/// Timestamps are converted to nanoseconds since the epoch.
+fn time_column(name: &str, array: &dyn Array) -> Result<Vec<Option<i64>>, DataLoaderError> {
+    let nanos_per_unit = match array.data_type().to_logical_type() {
+        DataType::Timestamp(unit, _) => match unit {
+            TimeUnit::Second => 1_000_000_000,
+            TimeUnit::Millisecond => 1_000_000,
+            TimeUnit::Microsecond => 1_000,
+            TimeUnit::Nanosecond => 1,
+        },
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => 1,
+        datatype => {
+            return Err(anyhow::anyhow!(
+                "Column {name:?} of type {datatype:?} cannot be used as a timeline"
+            )
+            .into())
+        }
+    };
+
+    let array = arrow2::compute::cast::cast(array, &DataType::Int64, Default::default())
+        .map_err(DataCellError::from)?;
+    let array = array
+        .as_any()
+        .downcast_ref::<PrimitiveArray<i64>>()
+        .unwrap(); // NOTE: cannot fail, we just cast it.
+
+    Ok(array
+        .iter()
+        .map(|time| time.map(|time| time.saturating_mul(nanos_per_unit)))
+        .collect())
+}
+
+/// A mapped component column, checked against the component's datatype.
+struct ComponentColumn {
+    component: ComponentName,
+
+    /// Does each row hold a list of instances, rather than a single one?
+    is_list: bool,
+
+    array: Box<dyn Array>,
+}
+
+impl ComponentColumn {
+    /// The instances of row `index`, if any.
+    fn cell(&self, index: usize) -> Option<DataCell> {
+        if self.array.is_null(index) {
+            return None;
+        }
+
+        let values = if !self.is_list {
+            self.array.sliced(index, 1)
+        } else if let Some(array) = self.array.as_any().downcast_ref::<ListArray<i32>>() {
+            array.value(index)
+        } else {
+            self.array
+                .as_any()
+                .downcast_ref::<ListArray<i64>>()?
+                .value(index)
+        };
+
+        Some(DataCell::from_arrow(self.component, values))
+    }
+}
+
+/// Checks the column `name` against the datatype of `component`, casting it if needed.
+///
+/// List columns are read as several instances per row if possible, e.g. `List<List<f32>>` for
+/// `Vec2D`s, and as a single instance per row otherwise, e.g. `List<f32>` for `Vec2D`s.
+fn component_column(
+    name: &str,
+    component: ComponentName,
+    datatype: &DataType,
+    array: &dyn Array,
+) -> Result<ComponentColumn, DataLoaderError> {
+    let datatype = without_extensions(datatype);
+    let expected = normalized(&datatype);
+    let item = || Box::new(Field::new("item", datatype.clone(), true));
+
+    let mut candidates = Vec::new();
+    match array.data_type().to_logical_type() {
+        column_type if normalized(column_type) == expected => {
+            return Ok(ComponentColumn {
+                component,
+                is_list: false,
+                array: array.to_boxed(),
+            });
+        }
+        DataType::List(column_item) | DataType::LargeList(column_item)
+            if normalized(&column_item.data_type) == expected =>
+        {
+            return Ok(ComponentColumn {
+                component,
+                is_list: true,
+                array: array.to_boxed(),
+            });
+        }
+        DataType::List(_) => candidates.push((true, DataType::List(item()))),
+        DataType::LargeList(_) => candidates.push((true, DataType::LargeList(item()))),
+        _ => {}
+    }
+    candidates.push((false, datatype.clone()));
+
+    let mut last_err = None;
+    for (is_list, target) in candidates {
+        match arrow2::compute::cast::cast(array, &target, Default::default()) {
+            Ok(array) => {
+                return Ok(ComponentColumn {
+                    component,
+                    is_list,
+                    array,
+                });
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "Column {name:?} of type {:?} cannot be read as {component} ({datatype:?}): {}",
+        array.data_type(),
+        last_err.map_or_else(String::new, |err| err.to_string()),
+    )
+    .into())
+}
+
+/// `arrow2` can't cast to or from extension types, so we use their storage types instead.
+fn without_extensions(datatype: &DataType) -> DataType {
+    fn field(field: &Field) -> Field {
+        Field {
+            data_type: without_extensions(&field.data_type),
+            ..field.clone()
+        }
+    }
+
+    match datatype {
+        DataType::Extension(_, storage, _) => without_extensions(storage),
+        DataType::List(item) => DataType::List(Box::new(field(item))),
+        DataType::LargeList(item) => DataType::LargeList(Box::new(field(item))),
+        DataType::FixedSizeList(item, size) => {
+            DataType::FixedSizeList(Box::new(field(item)), *size)
+        }
+        DataType::Struct(fields) => DataType::Struct(fields.iter().map(field).collect()),
+        DataType::Union(fields, ids, mode) => {
+            DataType::Union(fields.iter().map(field).collect(), ids.clone(), *mode)
+        }
+        DataType::Map(item, is_sorted) => DataType::Map(Box::new(field(item)), *is_sorted),
+        _ => datatype.clone(),
+    }
+}
+
+/// Ignores what doesn't affect the layout of the data when comparing datatypes: extensions,
+/// nullability, metadata and the names of list items.
+fn normalized(datatype: &DataType) -> DataType {
+    fn field(name: &str, field: &Field) -> Field {
+        Field::new(name, normalized(&field.data_type), true)
+    }
+
+    match without_extensions(datatype) {
+        DataType::List(item) => DataType::List(Box::new(field("item", &item))),
+        DataType::LargeList(item) => DataType::LargeList(Box::new(field("item", &item))),
+        DataType::FixedSizeList(item, size) => {
+            DataType::FixedSizeList(Box::new(field("item", &item)), size)
+        }
+        DataType::Struct(fields) => {
+            DataType::Struct(fields.iter().map(|item| field(&item.name, item)).collect())
+        }
+        DataType::Union(fields, ids, mode) => DataType::Union(
+            fields.iter().map(|item| field(&item.name, item)).collect(),
+            ids,
+            mode,
+        ),
+        DataType::Map(item, is_sorted) => {
+            DataType::Map(Box::new(field(&item.name, &item)), is_sorted)
+        }
+        datatype => datatype,
+    }
+}
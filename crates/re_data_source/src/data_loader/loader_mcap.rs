@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+
+use itertools::Itertools as _;
+
+use re_log_types::{DataRow, EntityPath, RowId, Time, TimePoint, Timeline};
+use re_types::archetypes::TextDocument;
+use re_types::components::MediaType;
+
+use super::ros_msgs::{self, Encoding, RosDecoder};
+use crate::{DataLoader, DataLoaderError, LoadedData};
+
+// ---
+
+/// Loads robotics recordings: [MCAP](https://mcap.dev) files, and ROS 1 `.bag` files.
+///
+/// Messages of the most common ROS types are converted, whether serialized for ROS 1 or 2:
+/// - `sensor_msgs/Image` and `sensor_msgs/CompressedImage` as
+///   [`re_types::archetypes::Image`]s, or as [`re_types::archetypes::DepthImage`]s for the
+///   `16UC1` and `32FC1` encodings,
+/// - `sensor_msgs/PointCloud2` as [`re_types::archetypes::Points3D`],
+/// - `tf2_msgs/TFMessage` as [`re_types::archetypes::Transform3D`]s, at
+///   `tf/<root frame>/…/<frame>`,
+/// - `rosgraph_msgs/Log` and `rcl_interfaces/msg/Log` as [`re_types::archetypes::TextLog`]s,
+/// - `std_msgs` numbers, such as `Float64` or `Int32`, as
+///   [`re_types::archetypes::TimeSeriesScalar`]s.
+///
+/// Each topic is an entity, and messages are logged at the time they were recorded on the
+/// `log_time` timeline, and at the time they were published on the `publish_time` one for MCAP
+/// files. Static transforms, on `/tf_static`, are timeless.
+///
+/// Channels of any other type or encoding, such as Protobuf or JSON, aren't dropped silently:
+/// each gets a timeless [`TextDocument`] at its topic's entity saying so, and a warning lists them.
+pub struct McapLoader;
+
+impl DataLoader for McapLoader {
+    #[inline]
+    fn name(&self) -> String {
+        "rerun.data_loaders.Mcap".into()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_from_path(
+        &self,
+        store_id: re_log_types::StoreId,
+        filepath: std::path::PathBuf,
+        tx: std::sync::mpsc::Sender<LoadedData>,
+    ) -> Result<(), crate::DataLoaderError> {
+        use anyhow::Context as _;
+
+        if filepath.is_dir() || !is_robotics_log_file(&filepath) {
+            return Err(DataLoaderError::Incompatible(filepath.clone()));
+        }
+
+        re_tracing::profile_function!(filepath.display().to_string());
+
+        let contents = std::fs::read(&filepath)
+            .with_context(|| format!("Failed to read file {filepath:?}"))?;
+        let contents = std::borrow::Cow::Owned(contents);
+
+        self.load_from_file_contents(store_id, filepath, contents, tx)
+    }
+
+    fn load_from_file_contents(
+        &self,
+        _store_id: re_log_types::StoreId,
+        filepath: std::path::PathBuf,
+        contents: std::borrow::Cow<'_, [u8]>,
+        tx: std::sync::mpsc::Sender<LoadedData>,
+    ) -> Result<(), crate::DataLoaderError> {
+        if !is_robotics_log_file(&filepath) {
+            return Err(DataLoaderError::Incompatible(filepath));
+        }
+
+        re_tracing::profile_function!(filepath.display().to_string());
+
+        re_log::debug!(
+            ?filepath,
+            loader = self.name(),
+            "Loading robotics recording…"
+        );
+
+        let mut decoder = RosDecoder::default();
+        let mut topics = BTreeMap::<String, TopicStats>::new();
+        let mut hung_up = false;
+
+        let mut on_message = |topic: &str,
+                              type_name: &str,
+                              message_encoding: &str,
+                              timepoint: TimePoint,
+                              data: &[u8]| {
+            let stats = topics
+                .entry(topic.to_owned())
+                .or_insert_with(|| TopicStats {
+                    type_name: type_name.to_owned(),
+                    message_encoding: message_encoding.to_owned(),
+                    num_messages: 0,
+                    num_failures: 0,
+                });
+            stats.num_messages += 1;
+
+            let Some(encoding) = Encoding::from_name(message_encoding) else {
+                return ControlFlow::Continue(());
+            };
+            if !ros_msgs::is_supported(type_name) {
+                return ControlFlow::Continue(());
+            }
+
+            match decoder.decode(topic, type_name, encoding, &timepoint, data) {
+                Ok(rows) => {
+                    for row in rows {
+                        if tx.send(row.into()).is_err() {
+                            hung_up = true;
+                            // The other end has decided to hang up, not our problem.
+                            return ControlFlow::Break(());
+                        }
+                    }
+                }
+                Err(err) => {
+                    if stats.num_failures == 0 {
+                        re_log::warn!(?filepath, topic, "Failed to decode message: {err}");
+                    }
+                    stats.num_failures += 1;
+                }
+            }
+            ControlFlow::Continue(())
+        };
+
+        let result = if crate::extension(&filepath) == "bag" {
+            super::rosbag::read_messages(&contents, |message| {
+                let mut timepoint = TimePoint::default();
+                timepoint.insert(
+                    Timeline::new_temporal("log_time"),
+                    Time::from_ns_since_epoch(message.time as _).into(),
+                );
+                on_message(
+                    &message.connection.topic,
+                    &message.connection.message_type,
+                    "ros1",
+                    timepoint,
+                    message.data,
+                )
+            })
+        } else {
+            super::mcap::read_messages(&contents, |message| {
+                let mut timepoint = TimePoint::default();
+                timepoint.insert(
+                    Timeline::new_temporal("log_time"),
+                    Time::from_ns_since_epoch(message.log_time as _).into(),
+                );
+                timepoint.insert(
+                    Timeline::new_temporal("publish_time"),
+                    Time::from_ns_since_epoch(message.publish_time as _).into(),
+                );
+                on_message(
+                    &message.channel.topic,
+                    &message.channel.schema_name,
+                    &message.channel.message_encoding,
+                    timepoint,
+                    message.data,
+                )
+            })
+        };
+
+        if hung_up {
+            return Ok(());
+        }
+
+        // Whatever could be read is worth showing, even if the rest of the file is broken.
+        for (topic, stats) in &topics {
+            if stats.num_failures > 0 {
+                re_log::warn!(
+                    ?filepath,
+                    topic,
+                    "Failed to decode {} of {} messages",
+                    stats.num_failures,
+                    stats.num_messages
+                );
+            }
+        }
+
+        let unsupported = topics
+            .iter()
+            .filter(|(_, stats)| !stats.is_supported())
+            .collect_vec();
+        if !unsupported.is_empty() {
+            re_log::warn!(
+                ?filepath,
+                "Skipped the messages of unsupported channels: {}",
+                unsupported
+                    .iter()
+                    .map(|(topic, stats)| format!(
+                        "{topic} ({}, {})",
+                        stats.type_name, stats.message_encoding
+                    ))
+                    .join(", ")
+            );
+        }
+        for (topic, stats) in unsupported {
+            let row = unsupported_topic_row(topic, stats)?;
+            if tx.send(row.into()).is_err() {
+                break; // The other end has decided to hang up, not our problem.
+            }
+        }
+
+        Ok(result?)
+    }
+}
+
+// ---
+
+fn is_robotics_log_file(filepath: &std::path::Path) -> bool {
+    crate::SUPPORTED_ROBOTICS_LOG_EXTENSIONS.contains(&crate::extension(filepath).as_str())
+}
+
+/// What was found on a topic.
+struct TopicStats {
+    type_name: String,
+    message_encoding: String,
+    num_messages: usize,
+    num_failures: usize,
+}
+
+impl TopicStats {
+    fn is_supported(&self) -> bool {
+        Encoding::from_name(&self.message_encoding).is_some()
+            && ros_msgs::is_supported(&self.type_name)
+    }
+}
+
+/// A timeless document saying why nothing else shows up at a topic's entity.
+fn unsupported_topic_row(topic: &str, stats: &TopicStats) -> anyhow::Result<DataRow> {
+    let type_name = if stats.type_name.is_empty() {
+        "none"
+    } else {
+        stats.type_name.as_str()
+    };
+    let text = format!(
+        "The messages of this topic were not loaded: their type or encoding isn't supported.\n\n\
+         - Type: `{type_name}`\n\
+         - Encoding: `{}`\n\
+         - Messages: {}\n",
+        stats.message_encoding, stats.num_messages
+    );
+
+    DataRow::from_archetype(
+        RowId::new(),
+        TimePoint::timeless(),
+        EntityPath::from(topic),
+        &TextDocument::new(text).with_media_type(MediaType::markdown()),
+    )
+}
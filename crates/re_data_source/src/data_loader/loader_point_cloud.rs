@@ -0,0 +1,274 @@
+use arrow2::array::PrimitiveArray;
+
+use re_log_types::{DataCell, DataRow, EntityPath, EntityPathPart, RowId, TimePoint};
+use re_types::archetypes::{Points3D, Transform3D};
+use re_types::components::{ClassId, Color, Position3D};
+use re_types::{AsComponents as _, ComponentName};
+
+use crate::{DataLoader, DataLoaderError, LoadedData};
+
+// ---
+
+/// The additional component holding the raw intensity of each point.
+const INTENSITY_COMPONENT: &str = "intensity";
+
+/// Loads large point clouds, such as the ones produced by lidars, as [`Points3D`]:
+/// - PCL's `.pcd` files, whether their data is `ascii`, `binary` or `binary_compressed`,
+/// - uncompressed ASPRS `.las` files.
+///
+/// LAZ compressed `.laz` files aren't supported yet: they are left to other loaders.
+///
+/// Points get their colors from the file if it has any, or from their intensities otherwise.
+/// Intensities are also kept as is, in an additional `intensity` component.
+/// PCD labels and LAS classifications become [`ClassId`]s.
+///
+/// LAS coordinates are logged relative to the minimum corner of the cloud's bounding box,
+/// with a [`Transform3D`] back to the file's coordinates: these are often georeferenced,
+/// far too large to be stored as `f32` as they are.
+///
+/// Clouds of more than [`PointCloudLoader::max_points_per_row`] points are split into
+/// several rows, one child entity each.
+pub struct PointCloudLoader {
+    /// The maximum number of points in a single [`DataRow`].
+    pub max_points_per_row: usize,
+}
+
+impl PointCloudLoader {
+    /// Around 20 MiB worth of positions, colors, intensities and class ids.
+    pub const DEFAULT_MAX_POINTS_PER_ROW: usize = 1 << 20;
+}
+
+impl Default for PointCloudLoader {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_points_per_row: Self::DEFAULT_MAX_POINTS_PER_ROW,
+        }
+    }
+}
+
+impl DataLoader for PointCloudLoader {
+    #[inline]
+    fn name(&self) -> String {
+        "rerun.data_loaders.PointCloud".into()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_from_path(
+        &self,
+        store_id: re_log_types::StoreId,
+        filepath: std::path::PathBuf,
+        tx: std::sync::mpsc::Sender<LoadedData>,
+    ) -> Result<(), crate::DataLoaderError> {
+        use anyhow::Context as _;
+
+        if filepath.is_dir() || !is_point_cloud_file(&filepath) {
+            return Err(DataLoaderError::Incompatible(filepath.clone()));
+        }
+
+        re_tracing::profile_function!(filepath.display().to_string());
+
+        let contents = std::fs::read(&filepath)
+            .with_context(|| format!("Failed to read file {filepath:?}"))?;
+        let contents = std::borrow::Cow::Owned(contents);
+
+        self.load_from_file_contents(store_id, filepath, contents, tx)
+    }
+
+    fn load_from_file_contents(
+        &self,
+        _store_id: re_log_types::StoreId,
+        filepath: std::path::PathBuf,
+        contents: std::borrow::Cow<'_, [u8]>,
+        tx: std::sync::mpsc::Sender<LoadedData>,
+    ) -> Result<(), crate::DataLoaderError> {
+        if !is_point_cloud_file(&filepath) {
+            return Err(DataLoaderError::Incompatible(filepath));
+        }
+
+        re_tracing::profile_function!(filepath.display().to_string());
+
+        let source: Box<dyn PointSource + '_> = match crate::extension(&filepath).as_str() {
+            "pcd" => Box::new(super::pcd::Pcd::new(&contents)?),
+            "las" => Box::new(super::las::Las::new(&contents)?),
+            _ => return Err(DataLoaderError::Incompatible(filepath)),
+        };
+
+        re_log::debug!(
+            ?filepath,
+            loader = self.name(),
+            num_points = source.num_points(),
+            "Loading point cloud…"
+        );
+
+        let entity_path = EntityPath::from_file_path(&filepath);
+
+        if let Some([x, y, z]) = source.origin() {
+            let transform = Transform3D::from_translation([x as f32, y as f32, z as f32]);
+            let row = DataRow::from_archetype(
+                RowId::new(),
+                TimePoint::timeless(),
+                entity_path.clone(),
+                &transform,
+            )?;
+            if tx.send(row.into()).is_err() {
+                return Ok(()); // The other end has decided to hang up, not our problem.
+            }
+        }
+
+        let intensity_range = if !source.has_colors() && source.has_intensities() {
+            intensity_range(source.as_ref())?
+        } else {
+            None
+        };
+
+        // Points are only read one row at a time, so that a single row's worth of them is
+        // ever in memory on top of the file's contents.
+        let num_points = source.num_points();
+        let max_points_per_row = self.max_points_per_row.max(1);
+        let num_rows = (num_points + max_points_per_row - 1) / max_points_per_row;
+        for row_index in 0..num_rows {
+            let entity_path = if num_rows == 1 {
+                entity_path.clone()
+            } else {
+                entity_path.join(&EntityPath::from(vec![EntityPathPart::from(
+                    row_index.to_string(),
+                )]))
+            };
+            let start = row_index * max_points_per_row;
+            let end = (start + max_points_per_row).min(num_points);
+
+            let row = points_row(
+                source.as_ref(),
+                start..end,
+                intensity_range,
+                TimePoint::timeless(),
+                entity_path,
+            )?;
+            if tx.send(row.into()).is_err() {
+                break; // The other end has decided to hang up, not our problem.
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ---
+
+fn is_point_cloud_file(filepath: &std::path::Path) -> bool {
+    let extension = crate::extension(filepath);
+    extension != "ply" && crate::SUPPORTED_POINT_CLOUD_EXTENSIONS.contains(&extension.as_str())
+}
+
+// ---
+
+/// A single point, as read from a file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub position: [f32; 3],
+    pub color: Option<[u8; 4]>,
+    pub intensity: Option<f32>,
+    pub class_id: Option<u16>,
+}
+
+/// The points of a file, read one at a time.
+pub trait PointSource {
+    fn num_points(&self) -> usize;
+
+    /// Do the points have colors?
+    fn has_colors(&self) -> bool;
+
+    /// Do the points have intensities?
+    fn has_intensities(&self) -> bool;
+
+    /// Where the positions are relative to, if they aren't in the file's coordinates.
+    fn origin(&self) -> Option<[f64; 3]>;
+
+    fn point(&self, index: usize) -> anyhow::Result<Point>;
+}
+
+/// The minimum and maximum intensities, if they differ.
+pub(super) fn intensity_range(source: &dyn PointSource) -> anyhow::Result<Option<(f32, f32)>> {
+    re_tracing::profile_function!();
+
+    let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+    for index in 0..source.num_points() {
+        if let Some(intensity) = source.point(index)?.intensity {
+            min = min.min(intensity);
+            max = max.max(intensity);
+        }
+    }
+
+    Ok((min < max).then_some((min, max)))
+}
+
+pub(super) fn points_row(
+    source: &dyn PointSource,
+    indices: std::ops::Range<usize>,
+    intensity_range: Option<(f32, f32)>,
+    timepoint: TimePoint,
+    entity_path: EntityPath,
+) -> anyhow::Result<DataRow> {
+    re_tracing::profile_function!();
+
+    let mut positions = Vec::with_capacity(indices.len());
+    let mut colors = Vec::new();
+    let mut intensities = Vec::new();
+    let mut class_ids = Vec::new();
+
+    for index in indices {
+        let point = source.point(index)?;
+
+        // Organized clouds mark missing measurements with NaNs.
+        if !point.position.iter().all(|c| c.is_finite()) {
+            continue;
+        }
+
+        positions.push(Position3D::from(point.position));
+        if let Some([r, g, b, a]) = point.color {
+            colors.push(Color::from_unmultiplied_rgba(r, g, b, a));
+        }
+        if let Some(intensity) = point.intensity {
+            intensities.push(intensity);
+            if let Some((min, max)) = intensity_range {
+                let [r, g, b] = super::ply::turbo((intensity - min) / (max - min));
+                colors.push(Color::from_rgb(r, g, b));
+            }
+        }
+        if let Some(class_id) = point.class_id {
+            class_ids.push(ClassId::from(class_id));
+        }
+    }
+
+    let num_instances = positions.len();
+    let mut points = Points3D::new(positions);
+    if !colors.is_empty() {
+        points = points.with_colors(colors);
+    }
+    if !class_ids.is_empty() {
+        points = points.with_class_ids(class_ids);
+    }
+
+    let mut cells = points
+        .as_component_batches()
+        .iter()
+        .map(|batch| DataCell::from_component_batch(batch.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !intensities.is_empty() {
+        cells.push(DataCell::from_arrow(
+            ComponentName::from(INTENSITY_COMPONENT),
+            PrimitiveArray::from_vec(intensities).boxed(),
+        ));
+    }
+
+    let mut row = DataRow::from_cells(
+        RowId::new(),
+        timepoint,
+        entity_path,
+        num_instances as _,
+        cells,
+    )?;
+    row.compute_all_size_bytes();
+    Ok(row)
+}
@@ -0,0 +1,75 @@
+use crate::{DataLoader, DataLoaderError, LoadedData, UrdfTree};
+
+// ---
+
+/// Loads robots described by [URDF](https://wiki.ros.org/urdf/XML) files.
+///
+/// Each link becomes an entity with the transform of the joint it's the child of, and its
+/// visuals become child entities: meshes through [`re_types::archetypes::Asset3D`], boxes,
+/// cylinders and spheres as [`re_types::archetypes::Mesh3D`]s.
+///
+/// The robot is logged at rest, timeless: use [`UrdfTree::joint_transform`] to move its joints.
+pub struct UrdfLoader;
+
+impl DataLoader for UrdfLoader {
+    #[inline]
+    fn name(&self) -> String {
+        "rerun.data_loaders.Urdf".into()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_from_path(
+        &self,
+        store_id: re_log_types::StoreId,
+        filepath: std::path::PathBuf,
+        tx: std::sync::mpsc::Sender<LoadedData>,
+    ) -> Result<(), crate::DataLoaderError> {
+        use anyhow::Context as _;
+
+        if filepath.is_dir() || !is_urdf_file(&filepath) {
+            return Err(DataLoaderError::Incompatible(filepath.clone()));
+        }
+
+        re_tracing::profile_function!(filepath.display().to_string());
+
+        let contents = std::fs::read(&filepath)
+            .with_context(|| format!("Failed to read file {filepath:?}"))?;
+        let contents = std::borrow::Cow::Owned(contents);
+
+        self.load_from_file_contents(store_id, filepath, contents, tx)
+    }
+
+    fn load_from_file_contents(
+        &self,
+        _store_id: re_log_types::StoreId,
+        filepath: std::path::PathBuf,
+        contents: std::borrow::Cow<'_, [u8]>,
+        tx: std::sync::mpsc::Sender<LoadedData>,
+    ) -> Result<(), crate::DataLoaderError> {
+        if !is_urdf_file(&filepath) {
+            return Err(DataLoaderError::Incompatible(filepath));
+        }
+
+        re_tracing::profile_function!(filepath.display().to_string());
+
+        let robot = UrdfTree::from_file_contents(&filepath, &contents)?;
+        re_log::debug!(
+            ?filepath,
+            loader = self.name(),
+            robot = robot.name(),
+            "Loading robot…"
+        );
+
+        for row in robot.to_data_rows()? {
+            if tx.send(row.into()).is_err() {
+                break; // The other end has decided to hang up, not our problem.
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_urdf_file(filepath: &std::path::Path) -> bool {
+    crate::SUPPORTED_URDF_EXTENSIONS.contains(&crate::extension(filepath).as_str())
+}
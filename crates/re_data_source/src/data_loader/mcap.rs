@@ -0,0 +1,184 @@
+//! Reads the messages of [MCAP](https://mcap.dev/spec) files, in the order they were written.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use anyhow::{anyhow, bail, Context as _};
+
+use super::ros_msgs::{Encoding, Reader};
+
+// ---
+
+pub const MAGIC: &[u8] = b"\x89MCAP0\r\n";
+
+const OP_FOOTER: u8 = 0x02;
+const OP_SCHEMA: u8 = 0x03;
+const OP_CHANNEL: u8 = 0x04;
+const OP_MESSAGE: u8 = 0x05;
+const OP_CHUNK: u8 = 0x06;
+const OP_DATA_END: u8 = 0x0F;
+
+/// A channel, and the schema of its messages.
+#[derive(Debug, Clone, Default)]
+pub struct Channel {
+    pub topic: String,
+
+    /// E.g. `ros1`, `cdr`, `protobuf` or `json`.
+    pub message_encoding: String,
+
+    /// Empty for schemaless channels.
+    pub schema_name: String,
+}
+
+/// A message, as found in the file.
+pub struct Message<'a> {
+    pub channel: &'a Channel,
+
+    /// When the message was recorded, in nanoseconds since the epoch.
+    pub log_time: u64,
+
+    /// When the message was published, in nanoseconds since the epoch.
+    pub publish_time: u64,
+
+    pub data: &'a [u8],
+}
+
+/// Calls `on_message` for each message of an MCAP file, until it breaks.
+///
+/// Only the data section is read, and the summary section ignored: the file may well be
+/// truncated, as when the recorder crashed.
+pub fn read_messages(
+    contents: &[u8],
+    mut on_message: impl FnMut(Message<'_>) -> ControlFlow<()>,
+) -> anyhow::Result<()> {
+    re_tracing::profile_function!();
+
+    let records = contents
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| anyhow!("Not an MCAP file"))?;
+
+    let mut reader = McapReader::default();
+    reader.read_records(records, &mut on_message)?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct McapReader {
+    /// The names of the schemas seen so far, by id.
+    schemas: HashMap<u16, String>,
+    channels: HashMap<u16, Channel>,
+}
+
+impl McapReader {
+    fn read_records(
+        &mut self,
+        records: &[u8],
+        on_message: &mut impl FnMut(Message<'_>) -> ControlFlow<()>,
+    ) -> anyhow::Result<ControlFlow<()>> {
+        let mut reader = Reader::new(records, Encoding::Ros1)?;
+        while !reader.is_empty() {
+            let opcode = reader.u8()?;
+            let len = reader.u64()?;
+            let mut record = Reader::new(reader.bytes(len as usize)?, Encoding::Ros1)?;
+
+            match opcode {
+                OP_SCHEMA => {
+                    let id = record.u16()?;
+                    let name = record.string()?;
+                    self.schemas.insert(id, name);
+                }
+                OP_CHANNEL => {
+                    let id = record.u16()?;
+                    let schema_id = record.u16()?;
+                    let topic = record.string()?;
+                    let message_encoding = record.string()?;
+                    let schema_name = self.schemas.get(&schema_id).cloned().unwrap_or_default();
+                    self.channels.insert(
+                        id,
+                        Channel {
+                            topic,
+                            message_encoding,
+                            schema_name,
+                        },
+                    );
+                }
+                OP_MESSAGE => {
+                    let channel_id = record.u16()?;
+                    let _sequence = record.u32()?;
+                    let log_time = record.u64()?;
+                    let publish_time = record.u64()?;
+                    let channel = self
+                        .channels
+                        .get(&channel_id)
+                        .ok_or_else(|| anyhow!("Message on unknown channel {channel_id}"))?;
+                    let message = Message {
+                        channel,
+                        log_time,
+                        publish_time,
+                        data: record.rest(),
+                    };
+                    if on_message(message).is_break() {
+                        return Ok(ControlFlow::Break(()));
+                    }
+                }
+                OP_CHUNK => {
+                    let _message_start_time = record.u64()?;
+                    let _message_end_time = record.u64()?;
+                    let uncompressed_size = record.u64()?;
+                    let _uncompressed_crc = record.u32()?;
+                    let compression = record.string()?;
+                    let compressed_size = record.u64()?;
+                    let compressed = record.bytes(compressed_size as usize)?;
+
+                    let records = decompress(&compression, compressed, uncompressed_size as usize)?;
+                    if self.read_records(&records, on_message)?.is_break() {
+                        return Ok(ControlFlow::Break(()));
+                    }
+                }
+                OP_DATA_END | OP_FOOTER => return Ok(ControlFlow::Break(())),
+                // Headers, indices, attachments, metadata, statistics…
+                _ => {}
+            }
+        }
+
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+fn decompress<'a>(
+    compression: &str,
+    compressed: &'a [u8],
+    uncompressed_size: usize,
+) -> anyhow::Result<Cow<'a, [u8]>> {
+    use std::io::Read as _;
+
+    re_tracing::profile_function!(compression);
+
+    if compression.is_empty() {
+        return Ok(Cow::Borrowed(compressed));
+    }
+
+    // The size is only a hint from the file: a corrupt one mustn't allocate gigabytes up front.
+    let mut uncompressed =
+        Vec::with_capacity(uncompressed_size.min(compressed.len().saturating_mul(16)));
+    match compression {
+        "lz4" => {
+            lz4_flex::frame::FrameDecoder::new(compressed)
+                .read_to_end(&mut uncompressed)
+                .context("Failed to decompress LZ4 chunk")?;
+        }
+        "zstd" => {
+            // NOTE: `ruzstd` rather than `zstd`, so that decoding doesn't need a C toolchain
+            // and works on the web too.
+            let mut compressed = compressed;
+            ruzstd::StreamingDecoder::new(&mut compressed)
+                .map_err(|err| anyhow!("Failed to decompress Zstandard chunk: {err}"))?
+                .read_to_end(&mut uncompressed)
+                .context("Failed to decompress Zstandard chunk")?;
+        }
+        _ => bail!("Unsupported chunk compression {compression:?}"),
+    }
+
+    Ok(Cow::Owned(uncompressed))
+}
@@ -26,8 +26,12 @@ use re_log_types::{ArrowMsg, DataRow, LogMsg};
 /// - [`ArchetypeLoader`] for:
 ///     - [3D models]
 ///     - [Images]
-///     - [Point clouds]
+///     - [Point clouds] in `.ply` files
 ///     - [Text files]
+/// - [`ArrowTableLoader`] for [Arrow IPC and Parquet tables].
+/// - [`PointCloudLoader`] for [Point clouds] in `.pcd` and `.las` files.
+/// - [`UrdfLoader`] for [URDF robot descriptions].
+/// - [`McapLoader`] for [MCAP and ROS bag recordings].
 /// - [`DirectoryLoader`] for recursively loading folders.
 /// - [`ExternalLoader`], which looks for user-defined data loaders in $PATH.
 ///
@@ -55,6 +59,9 @@ use re_log_types::{ArrowMsg, DataRow, LogMsg};
 /// [Images]: crate::SUPPORTED_IMAGE_EXTENSIONS
 /// [Point clouds]: crate::SUPPORTED_POINT_CLOUD_EXTENSIONS
 /// [Text files]: crate::SUPPORTED_TEXT_EXTENSIONS
+/// [Arrow IPC and Parquet tables]: crate::SUPPORTED_ARROW_TABLE_EXTENSIONS
+/// [URDF robot descriptions]: crate::SUPPORTED_URDF_EXTENSIONS
+/// [MCAP and ROS bag recordings]: crate::SUPPORTED_ROBOTICS_LOG_EXTENSIONS
 //
 // TODO(#4525): `DataLoader`s should support arbitrary URIs
 // TODO(#4526): `DataLoader`s should be exposed to the SDKs
@@ -228,7 +235,11 @@ static BUILTIN_LOADERS: Lazy<Vec<Arc<dyn DataLoader>>> = Lazy::new(|| {
     vec![
         Arc::new(RrdLoader) as Arc<dyn DataLoader>,
         Arc::new(ArchetypeLoader),
+        Arc::new(ArrowTableLoader::default()),
         Arc::new(DirectoryLoader),
+        Arc::new(PointCloudLoader::default()),
+        Arc::new(UrdfLoader),
+        Arc::new(McapLoader),
         #[cfg(not(target_arch = "wasm32"))]
         Arc::new(ExternalLoader),
     ]
@@ -260,16 +271,32 @@ pub fn register_custom_data_loader(loader: impl DataLoader + 'static) {
 
 // ---
 
+mod las;
 mod loader_archetype;
+mod loader_arrow;
 mod loader_directory;
+mod loader_mcap;
+mod loader_point_cloud;
 mod loader_rrd;
+mod loader_urdf;
+mod mcap;
+mod pcd;
+mod ply;
+mod ros_msgs;
+mod rosbag;
+mod urdf;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod loader_external;
 
 pub use self::loader_archetype::ArchetypeLoader;
+pub use self::loader_arrow::{ArrowTableLoader, ColumnMapping};
 pub use self::loader_directory::DirectoryLoader;
+pub use self::loader_mcap::McapLoader;
+pub use self::loader_point_cloud::PointCloudLoader;
 pub use self::loader_rrd::RrdLoader;
+pub use self::loader_urdf::UrdfLoader;
+pub use self::urdf::UrdfTree;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use self::loader_external::{
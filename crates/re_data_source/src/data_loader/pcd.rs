@@ -0,0 +1,382 @@
+//! Reading PCL's `.pcd` files, whether their data is `ascii`, `binary` or `binary_compressed`.
+//!
+//! See the [file format specification](https://pointclouds.org/documentation/tutorials/pcd_file_format.html).
+
+use anyhow::{bail, ensure, Context as _};
+
+use super::loader_point_cloud::{Point, PointSource};
+
+const FIELD_X: &str = "x";
+const FIELD_Y: &str = "y";
+const FIELD_Z: &str = "z";
+const FIELDS_COLOR: &[&str] = &["rgb", "rgba"];
+const FIELD_INTENSITY: &str = "intensity";
+const FIELD_LABEL: &str = "label";
+
+/// One of the `FIELDS` of a `.pcd` file.
+#[derive(Debug)]
+struct Field {
+    name: String,
+
+    /// `I`, `U` or `F`.
+    typ: u8,
+
+    /// In bytes.
+    size: usize,
+
+    /// Number of elements.
+    count: usize,
+
+    /// In bytes, from the start of a point.
+    offset: usize,
+
+    /// Index of the first element in a line of `ascii` data.
+    column: usize,
+}
+
+impl Field {
+    fn decode(&self, bytes: &[u8]) -> anyhow::Result<f64> {
+        Ok(match (self.typ, self.size) {
+            (b'I', 1) => f64::from(i8::from_le_bytes(le(bytes))),
+            (b'I', 2) => f64::from(i16::from_le_bytes(le(bytes))),
+            (b'I', 4) => f64::from(i32::from_le_bytes(le(bytes))),
+            (b'I', 8) => i64::from_le_bytes(le(bytes)) as f64,
+            (b'U', 1) => f64::from(bytes[0]),
+            (b'U', 2) => f64::from(u16::from_le_bytes(le(bytes))),
+            (b'U', 4) => f64::from(u32::from_le_bytes(le(bytes))),
+            (b'U', 8) => u64::from_le_bytes(le(bytes)) as f64,
+            (b'F', 4) => f64::from(f32::from_le_bytes(le(bytes))),
+            (b'F', 8) => f64::from_le_bytes(le(bytes)),
+            (typ, size) => bail!(
+                "Unsupported type {:?} of size {size} for field {:?}",
+                typ as char,
+                self.name
+            ),
+        })
+    }
+}
+
+fn le<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut array = [0; N];
+    array.copy_from_slice(&bytes[..N]);
+    array
+}
+
+/// How the points are stored.
+enum Data<'a> {
+    /// One line per point.
+    Ascii(Vec<&'a str>),
+
+    /// One point after the other.
+    Binary(&'a [u8]),
+
+    /// All the values of a field, then all the values of the next one, and so on.
+    Columns(Vec<u8>),
+}
+
+/// The points of a `.pcd` file.
+pub struct Pcd<'a> {
+    fields: Vec<Field>,
+    point_size: usize,
+    num_points: usize,
+    data: Data<'a>,
+
+    x: usize,
+    y: usize,
+    z: usize,
+    color: Option<usize>,
+    intensity: Option<usize>,
+    label: Option<usize>,
+}
+
+impl<'a> Pcd<'a> {
+    pub fn new(contents: &'a [u8]) -> anyhow::Result<Self> {
+        let mut names = Vec::new();
+        let mut sizes = Vec::new();
+        let mut types = Vec::new();
+        let mut counts = Vec::new();
+        let mut width = None;
+        let mut height = None;
+        let mut points = None;
+
+        let mut rest = contents;
+        let data_kind = loop {
+            let Some(end) = rest.iter().position(|&c| c == b'\n') else {
+                bail!("The .pcd header has no DATA line");
+            };
+            let line = std::str::from_utf8(&rest[..end]).context("Invalid .pcd header")?;
+            rest = &rest[end + 1..];
+
+            let mut words = line.split_whitespace();
+            let Some(key) = words.next() else {
+                continue;
+            };
+            let words = words.collect::<Vec<_>>();
+            let numbers = || {
+                words
+                    .iter()
+                    .map(|word| word.parse::<usize>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("Invalid .pcd header line {line:?}"))
+            };
+            match key {
+                "FIELDS" | "COLUMNS" => names = words.iter().map(|&w| w.to_owned()).collect(),
+                "SIZE" => sizes = numbers()?,
+                "TYPE" => types = words.iter().map(|w| w.as_bytes()[0]).collect(),
+                "COUNT" => counts = numbers()?,
+                "WIDTH" => width = numbers()?.first().copied(),
+                "HEIGHT" => height = numbers()?.first().copied(),
+                "POINTS" => points = numbers()?.first().copied(),
+                "DATA" => break words.first().copied().unwrap_or_default(),
+                // Comments, `VERSION` and `VIEWPOINT`.
+                _ => {}
+            }
+        };
+
+        if counts.is_empty() {
+            counts = vec![1; names.len()];
+        }
+        ensure!(
+            sizes.len() == names.len() && types.len() == names.len() && counts.len() == names.len(),
+            "The .pcd header has {} FIELDS, but {} SIZE, {} TYPE and {} COUNT",
+            names.len(),
+            sizes.len(),
+            types.len(),
+            counts.len()
+        );
+
+        let mut fields = Vec::with_capacity(names.len());
+        let (mut offset, mut column) = (0, 0);
+        for (((name, size), typ), count) in names.into_iter().zip(sizes).zip(types).zip(counts) {
+            fields.push(Field {
+                name,
+                typ,
+                size,
+                count,
+                offset,
+                column,
+            });
+            offset = size
+                .checked_mul(count)
+                .and_then(|field_size| offset.checked_add(field_size))
+                .context("The .pcd header has too large fields")?;
+            column += count;
+        }
+        let point_size = offset;
+
+        let num_points = match (points, width) {
+            (Some(points), _) => points,
+            (None, Some(width)) => width
+                .checked_mul(height.unwrap_or(1))
+                .context("The .pcd header has too many points")?,
+            (None, None) => bail!("The .pcd header has neither POINTS nor WIDTH"),
+        };
+        let data_size = num_points
+            .checked_mul(point_size)
+            .context("The .pcd header has too many points")?;
+
+        let data = match data_kind {
+            "ascii" => {
+                let lines = std::str::from_utf8(rest)
+                    .context("Invalid ascii .pcd data")?
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .take(num_points)
+                    .collect::<Vec<_>>();
+                ensure!(
+                    lines.len() == num_points,
+                    "The .pcd file has {} points, but its header says {num_points}",
+                    lines.len()
+                );
+                Data::Ascii(lines)
+            }
+            "binary" => {
+                let data = rest.get(..data_size).with_context(|| {
+                    format!("The .pcd file is too short for its {num_points} points")
+                })?;
+                Data::Binary(data)
+            }
+            "binary_compressed" => {
+                ensure!(rest.len() >= 8, "The compressed .pcd data has no header");
+                let compressed_size = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]);
+                let uncompressed_size = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]);
+                let compressed = rest[8..]
+                    .get(..compressed_size as usize)
+                    .context("The compressed .pcd data is truncated")?;
+                let data = lzf_decompress(compressed, uncompressed_size as usize)?;
+                ensure!(
+                    data.len() >= data_size,
+                    "The .pcd file is too short for its {num_points} points"
+                );
+                Data::Columns(data)
+            }
+            _ => bail!("Unknown .pcd DATA {data_kind:?}"),
+        };
+
+        let find = |names: &[&str]| {
+            fields
+                .iter()
+                .position(|field| names.contains(&field.name.as_str()))
+        };
+        let (Some(x), Some(y), Some(z)) = (find(&[FIELD_X]), find(&[FIELD_Y]), find(&[FIELD_Z]))
+        else {
+            bail!("The .pcd file has no x, y and z fields");
+        };
+        let color = find(FIELDS_COLOR);
+        let intensity = find(&[FIELD_INTENSITY]);
+        let label = find(&[FIELD_LABEL]);
+
+        Ok(Self {
+            fields,
+            point_size,
+            num_points,
+            data,
+            x,
+            y,
+            z,
+            color,
+            intensity,
+            label,
+        })
+    }
+
+    /// The bytes of the first element of a field, for binary data.
+    fn bytes(&self, index: usize, field: &Field) -> &[u8] {
+        let (data, start) = match &self.data {
+            Data::Binary(data) => (*data, index * self.point_size + field.offset),
+            Data::Columns(data) => (
+                data.as_slice(),
+                self.num_points * field.offset + index * field.size * field.count,
+            ),
+            Data::Ascii(_) => unreachable!("ascii data has no bytes"),
+        };
+        &data[start..start + field.size]
+    }
+
+    fn value(&self, index: usize, field: usize) -> anyhow::Result<f64> {
+        let field = &self.fields[field];
+        if let Data::Ascii(lines) = &self.data {
+            let word = lines[index]
+                .split_whitespace()
+                .nth(field.column)
+                .with_context(|| format!("Point {index} has no {:?}", field.name))?;
+            word.parse()
+                .with_context(|| format!("Invalid {:?} for point {index}: {word:?}", field.name))
+        } else {
+            field.decode(self.bytes(index, field))
+        }
+    }
+
+    /// Colors are packed into 4 bytes, often stored as a float: `0xAARRGGBB`.
+    fn packed_color(&self, index: usize, field_index: usize) -> anyhow::Result<[u8; 4]> {
+        let field = &self.fields[field_index];
+        ensure!(
+            field.size == 4,
+            "The .pcd {:?} field isn't 4 bytes",
+            field.name
+        );
+
+        let packed = if let Data::Ascii(_) = &self.data {
+            let value = self.value(index, field_index)?;
+            if field.typ == b'F' {
+                (value as f32).to_bits()
+            } else {
+                value as u32
+            }
+        } else {
+            u32::from_le_bytes(le(self.bytes(index, field)))
+        };
+
+        let [b, g, r, a] = packed.to_le_bytes();
+        // Plain `rgb` fields usually leave their alpha at zero.
+        let a = if field.name == "rgba" { a } else { 255 };
+        Ok([r, g, b, a])
+    }
+}
+
+impl PointSource for Pcd<'_> {
+    fn num_points(&self) -> usize {
+        self.num_points
+    }
+
+    fn has_colors(&self) -> bool {
+        self.color.is_some()
+    }
+
+    fn has_intensities(&self) -> bool {
+        self.intensity.is_some()
+    }
+
+    fn origin(&self) -> Option<[f64; 3]> {
+        None
+    }
+
+    fn point(&self, index: usize) -> anyhow::Result<Point> {
+        Ok(Point {
+            position: [
+                self.value(index, self.x)? as f32,
+                self.value(index, self.y)? as f32,
+                self.value(index, self.z)? as f32,
+            ],
+            color: self
+                .color
+                .map(|field| self.packed_color(index, field))
+                .transpose()?,
+            intensity: self
+                .intensity
+                .map(|field| self.value(index, field).map(|v| v as f32))
+                .transpose()?,
+            class_id: self
+                .label
+                .map(|field| self.value(index, field).map(|v| v as u16))
+                .transpose()?,
+        })
+    }
+}
+
+/// Decompresses [LZF](http://oldhome.schmorp.de/marc/liblzf.html) data, as used by PCL.
+fn lzf_decompress(input: &[u8], uncompressed_size: usize) -> anyhow::Result<Vec<u8>> {
+    // The size is only a hint from the file: a corrupt one mustn't allocate gigabytes up front.
+    let mut output = Vec::with_capacity(uncompressed_size.min(input.len().saturating_mul(16)));
+    let mut i = 0;
+
+    while i < input.len() {
+        let control = input[i] as usize;
+        i += 1;
+
+        if control < 32 {
+            // A run of literal bytes.
+            let literal = input
+                .get(i..i + control + 1)
+                .context("Truncated LZF literal")?;
+            output.extend_from_slice(literal);
+            i += control + 1;
+        } else {
+            // A back-reference into what was already decompressed.
+            let mut len = control >> 5;
+            if len == 7 {
+                len += *input.get(i).context("Truncated LZF back-reference")? as usize;
+                i += 1;
+            }
+            len += 2;
+
+            let low = *input.get(i).context("Truncated LZF back-reference")? as usize;
+            i += 1;
+            let distance = ((control & 0x1f) << 8) + low + 1;
+            ensure!(distance <= output.len(), "Invalid LZF back-reference");
+
+            // The source and destination may overlap, so copy byte by byte.
+            let start = output.len() - distance;
+            for j in start..start + len {
+                output.push(output[j]);
+            }
+        }
+    }
+
+    ensure!(
+        output.len() == uncompressed_size,
+        "LZF data decompressed to {} bytes instead of {uncompressed_size}",
+        output.len()
+    );
+    Ok(output)
+}
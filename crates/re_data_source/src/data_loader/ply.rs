@@ -0,0 +1,485 @@
+//! Loading `.ply` files as point clouds or meshes.
+
+use anyhow::Context as _;
+use arrow2::array::{Array, PrimitiveArray};
+use ply_rs::ply::{DefaultElement, ElementDef, Property, PropertyType, ScalarType};
+
+use re_log_types::{DataCell, DataRow, EntityPath, RowId, TimePoint};
+use re_types::archetypes::{Mesh3D, Points2D, Points3D};
+use re_types::components::{ClassId, Color, MeshProperties, Radius, Text};
+use re_types::{AsComponents, ComponentName};
+
+// NOTE: Empirical evidence points to these being de-facto standard…
+const ELEMENT_VERTEX: &str = "vertex";
+const ELEMENT_FACE: &str = "face";
+
+const PROP_X: &str = "x";
+const PROP_Y: &str = "y";
+const PROP_Z: &str = "z";
+const PROP_NX: &str = "nx";
+const PROP_NY: &str = "ny";
+const PROP_NZ: &str = "nz";
+const PROP_RED: &str = "red";
+const PROP_GREEN: &str = "green";
+const PROP_BLUE: &str = "blue";
+const PROP_ALPHA: &str = "alpha";
+const PROP_RADIUS: &str = "radius";
+const PROP_LABEL: &str = "label";
+const PROPS_CLASS_ID: &[&str] = &["class", "class_id", PROP_LABEL];
+const PROPS_VERTEX_INDICES: &[&str] = &["vertex_indices", "vertex_index"];
+
+/// The extra property preferred for coloring the vertices, if there are several.
+const PROP_INTENSITY: &str = "intensity";
+
+/// Loads a `.ply` file as a single row.
+///
+/// * Vertices with faces become a [`Mesh3D`], with their normals (`nx`, `ny`, `nz`) if any.
+///   Polygons are split into triangles.
+/// * Vertices without faces become [`Points3D`], or [`Points2D`] if they have no `z`.
+/// * `red`, `green`, `blue` and `alpha` become colors, `radius` radii, and a `label` list of
+///   characters becomes labels.
+/// * An integer `class`, `class_id` or `label` becomes [`ClassId`]s.
+/// * Every other scalar vertex property is kept as an additional component of the same name.
+///   If the vertices have neither colors nor class ids, they are colored by one of them
+///   through a colormap: `intensity` if present, the first one otherwise.
+pub fn load_ply(
+    timepoint: TimePoint,
+    entity_path: EntityPath,
+    contents: &[u8],
+) -> anyhow::Result<DataRow> {
+    re_tracing::profile_function!();
+
+    let parser = ply_rs::parser::Parser::<DefaultElement>::new();
+    let ply = parser
+        .read_ply(&mut std::io::Cursor::new(contents))
+        .context("Failed to parse the .ply file")?;
+
+    let (Some(vertex_def), Some(vertices)) = (
+        ply.header.elements.get(ELEMENT_VERTEX),
+        ply.payload.get(ELEMENT_VERTEX),
+    ) else {
+        // Not a standard layout: look for points in every element.
+        let points3d = Points3D::from_file_contents(contents)?;
+        return DataRow::from_archetype(RowId::new(), timepoint, entity_path, &points3d);
+    };
+
+    let vertices = Vertices::new(vertex_def, vertices)?;
+    let triangles = ply
+        .payload
+        .get(ELEMENT_FACE)
+        .map(|faces| triangulate(faces, vertices.len()))
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut colors = vertices.colors();
+    let class_ids = vertices.class_ids();
+    if colors.is_none() && class_ids.is_none() {
+        let colormapped = vertices
+            .extra
+            .iter()
+            .find(|(name, _)| name == PROP_INTENSITY)
+            .or_else(|| vertices.extra.first());
+        colors = colormapped.map(|(_, values)| colormap(values.as_ref()));
+    }
+
+    let arch: Box<dyn AsComponents> = if !triangles.is_empty() {
+        let mut mesh = Mesh3D::new(vertices.positions_3d())
+            .with_mesh_properties(MeshProperties::from_triangle_indices(triangles));
+        if let Some(normals) = vertices.normals() {
+            mesh = mesh.with_vertex_normals(normals);
+        }
+        if let Some(colors) = colors {
+            mesh = mesh.with_vertex_colors(colors);
+        }
+        if let Some(class_ids) = class_ids {
+            mesh = mesh.with_class_ids(class_ids);
+        }
+        Box::new(mesh)
+    } else if vertices.has_z() {
+        let mut points = Points3D::new(vertices.positions_3d());
+        if let Some(colors) = colors {
+            points = points.with_colors(colors);
+        }
+        if let Some(radii) = vertices.radii() {
+            points = points.with_radii(radii);
+        }
+        if let Some(labels) = vertices.labels() {
+            points = points.with_labels(labels);
+        }
+        if let Some(class_ids) = class_ids {
+            points = points.with_class_ids(class_ids);
+        }
+        Box::new(points)
+    } else {
+        let mut points = Points2D::new(vertices.positions_2d());
+        if let Some(colors) = colors {
+            points = points.with_colors(colors);
+        }
+        if let Some(radii) = vertices.radii() {
+            points = points.with_radii(radii);
+        }
+        if let Some(labels) = vertices.labels() {
+            points = points.with_labels(labels);
+        }
+        if let Some(class_ids) = class_ids {
+            points = points.with_class_ids(class_ids);
+        }
+        Box::new(points)
+    };
+
+    let mut cells = arch
+        .as_component_batches()
+        .iter()
+        .map(|batch| DataCell::from_component_batch(batch.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (name, values) in &vertices.extra {
+        cells.push(DataCell::from_arrow(
+            ComponentName::from(name.as_str()),
+            values.clone(),
+        ));
+    }
+
+    let mut row = DataRow::from_cells(
+        RowId::new(),
+        timepoint,
+        entity_path,
+        vertices.len() as _,
+        cells,
+    )?;
+    row.compute_all_size_bytes();
+    Ok(row)
+}
+
+/// The vertex properties of a `.ply` file, by column.
+struct Vertices<'a> {
+    def: &'a ElementDef,
+    vertices: &'a [DefaultElement],
+
+    /// The integer property holding class ids, if any.
+    class_id: Option<&'static str>,
+
+    /// The scalar properties we don't know about.
+    extra: Vec<(String, Box<dyn Array>)>,
+}
+
+impl<'a> Vertices<'a> {
+    fn new(def: &'a ElementDef, vertices: &'a [DefaultElement]) -> anyhow::Result<Self> {
+        for prop in [PROP_X, PROP_Y] {
+            anyhow::ensure!(
+                def.properties.contains_key(prop),
+                "The vertices of the .ply file have no {prop:?}"
+            );
+        }
+
+        let known = [
+            PROP_X,
+            PROP_Y,
+            PROP_Z,
+            PROP_NX,
+            PROP_NY,
+            PROP_NZ,
+            PROP_RED,
+            PROP_GREEN,
+            PROP_BLUE,
+            PROP_ALPHA,
+            PROP_RADIUS,
+        ];
+        let mut this = Self {
+            def,
+            vertices,
+            class_id: PROPS_CLASS_ID
+                .iter()
+                .copied()
+                .find(|prop| is_integer(def, prop)),
+            extra: Vec::new(),
+        };
+        let extra = def
+            .properties
+            .values()
+            .filter(|prop| !known.contains(&prop.name.as_str()))
+            .filter(|prop| Some(prop.name.as_str()) != this.class_id)
+            .filter_map(|prop| match &prop.data_type {
+                PropertyType::Scalar(scalar_type) => {
+                    Some((prop.name.clone(), this.column(&prop.name, scalar_type)))
+                }
+                PropertyType::List(_, _) => {
+                    if prop.name != PROP_LABEL {
+                        re_log::debug!(
+                            "Ignoring the list property {:?} of .ply vertices",
+                            prop.name
+                        );
+                    }
+                    None
+                }
+            })
+            .collect();
+        this.extra = extra;
+
+        Ok(this)
+    }
+
+    fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn has(&self, prop: &str) -> bool {
+        self.def.properties.contains_key(prop)
+    }
+
+    fn has_z(&self) -> bool {
+        self.has(PROP_Z)
+    }
+
+    /// The values of a scalar property, missing ones being zero.
+    fn f32s(&self, prop: &str) -> impl Iterator<Item = f32> + '_ {
+        let prop = prop.to_owned();
+        self.vertices
+            .iter()
+            .map(move |vertex| vertex.get(&prop).and_then(f64).unwrap_or(0.0) as f32)
+    }
+
+    fn positions_3d(&self) -> Vec<[f32; 3]> {
+        itertools::izip!(self.f32s(PROP_X), self.f32s(PROP_Y), self.f32s(PROP_Z))
+            .map(|(x, y, z)| [x, y, z])
+            .collect()
+    }
+
+    fn positions_2d(&self) -> Vec<[f32; 2]> {
+        itertools::izip!(self.f32s(PROP_X), self.f32s(PROP_Y))
+            .map(|(x, y)| [x, y])
+            .collect()
+    }
+
+    fn normals(&self) -> Option<Vec<[f32; 3]>> {
+        (self.has(PROP_NX) && self.has(PROP_NY) && self.has(PROP_NZ)).then(|| {
+            itertools::izip!(self.f32s(PROP_NX), self.f32s(PROP_NY), self.f32s(PROP_NZ))
+                .map(|(x, y, z)| [x, y, z])
+                .collect()
+        })
+    }
+
+    fn colors(&self) -> Option<Vec<Color>> {
+        if !(self.has(PROP_RED) && self.has(PROP_GREEN) && self.has(PROP_BLUE)) {
+            return None;
+        }
+
+        let channel = |vertex: &DefaultElement, prop: &str| vertex.get(prop).and_then(u8);
+        Some(
+            self.vertices
+                .iter()
+                .map(|vertex| {
+                    Color::from_unmultiplied_rgba(
+                        channel(vertex, PROP_RED).unwrap_or(255),
+                        channel(vertex, PROP_GREEN).unwrap_or(255),
+                        channel(vertex, PROP_BLUE).unwrap_or(255),
+                        channel(vertex, PROP_ALPHA).unwrap_or(255),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn radii(&self) -> Option<Vec<Radius>> {
+        self.has(PROP_RADIUS)
+            .then(|| self.f32s(PROP_RADIUS).map(Radius).collect())
+    }
+
+    fn labels(&self) -> Option<Vec<Text>> {
+        matches!(
+            self.def
+                .properties
+                .get(PROP_LABEL)
+                .map(|def| &def.data_type),
+            Some(PropertyType::List(_, _))
+        )
+        .then(|| {
+            self.vertices
+                .iter()
+                .map(|vertex| match vertex.get(PROP_LABEL) {
+                    Some(Property::ListUChar(chars)) => {
+                        Text(String::from_utf8_lossy(chars).into_owned().into())
+                    }
+                    _ => Text("undef".into()),
+                })
+                .collect()
+        })
+    }
+
+    fn class_ids(&self) -> Option<Vec<ClassId>> {
+        let prop = self.class_id?;
+        Some(
+            self.vertices
+                .iter()
+                .map(|vertex| {
+                    let class_id = vertex.get(prop).and_then(f64).unwrap_or(0.0);
+                    ClassId::from(class_id as u16)
+                })
+                .collect(),
+        )
+    }
+
+    /// A scalar property as an arrow array of its own type.
+    fn column(&self, prop: &str, scalar_type: &ScalarType) -> Box<dyn Array> {
+        macro_rules! column {
+            ($variant:ident, $T:ty) => {
+                PrimitiveArray::<$T>::from_iter(self.vertices.iter().map(|vertex| {
+                    match vertex.get(prop) {
+                        Some(Property::$variant(value)) => Some(*value),
+                        _ => None,
+                    }
+                }))
+                .boxed()
+            };
+        }
+
+        match scalar_type {
+            ScalarType::Char => column!(Char, i8),
+            ScalarType::UChar => column!(UChar, u8),
+            ScalarType::Short => column!(Short, i16),
+            ScalarType::UShort => column!(UShort, u16),
+            ScalarType::Int => column!(Int, i32),
+            ScalarType::UInt => column!(UInt, u32),
+            ScalarType::Float => column!(Float, f32),
+            ScalarType::Double => column!(Double, f64),
+        }
+    }
+}
+
+fn is_integer(def: &ElementDef, prop: &str) -> bool {
+    matches!(
+        def.properties.get(prop).map(|prop| &prop.data_type),
+        Some(PropertyType::Scalar(
+            ScalarType::Char
+                | ScalarType::UChar
+                | ScalarType::Short
+                | ScalarType::UShort
+                | ScalarType::Int
+                | ScalarType::UInt
+        ))
+    )
+}
+
+fn f64(prop: &Property) -> Option<f64> {
+    match *prop {
+        Property::Char(v) => Some(v as f64),
+        Property::UChar(v) => Some(v as f64),
+        Property::Short(v) => Some(v as f64),
+        Property::UShort(v) => Some(v as f64),
+        Property::Int(v) => Some(v as f64),
+        Property::UInt(v) => Some(v as f64),
+        Property::Float(v) => Some(v as f64),
+        Property::Double(v) => Some(v),
+        Property::ListChar(_)
+        | Property::ListUChar(_)
+        | Property::ListShort(_)
+        | Property::ListUShort(_)
+        | Property::ListInt(_)
+        | Property::ListUInt(_)
+        | Property::ListFloat(_)
+        | Property::ListDouble(_) => None,
+    }
+}
+
+/// A color channel: integers as is, floats in `[0, 1]`.
+fn u8(prop: &Property) -> Option<u8> {
+    match *prop {
+        Property::Float(v) => Some((v * 255.0) as u8),
+        Property::Double(v) => Some((v * 255.0) as u8),
+        _ => f64(prop).map(|v| v as u8),
+    }
+}
+
+/// Splits the polygons of the faces into triangles, as a fan around their first vertex.
+fn triangulate(faces: &[DefaultElement], num_vertices: usize) -> anyhow::Result<Vec<[u32; 3]>> {
+    re_tracing::profile_function!();
+
+    let mut triangles = Vec::with_capacity(faces.len());
+    for face in faces {
+        let Some(indices) = PROPS_VERTEX_INDICES.iter().find_map(|prop| face.get(*prop)) else {
+            continue;
+        };
+
+        let indices: Vec<u32> = match indices {
+            Property::ListChar(indices) => indices.iter().map(|&i| i as u32).collect(),
+            Property::ListUChar(indices) => indices.iter().map(|&i| i as u32).collect(),
+            Property::ListShort(indices) => indices.iter().map(|&i| i as u32).collect(),
+            Property::ListUShort(indices) => indices.iter().map(|&i| i as u32).collect(),
+            Property::ListInt(indices) => indices.iter().map(|&i| i as u32).collect(),
+            Property::ListUInt(indices) => indices.clone(),
+            _ => continue,
+        };
+        if let Some(index) = indices.iter().find(|&&i| num_vertices <= i as usize) {
+            anyhow::bail!(
+                "A face of the .ply file refers to vertex {index}, but there are only {num_vertices}"
+            );
+        }
+
+        for i in 2..indices.len() {
+            triangles.push([indices[0], indices[i - 1], indices[i]]);
+        }
+    }
+
+    Ok(triangles)
+}
+
+/// Colors the values of a numeric array from their minimum to their maximum.
+fn colormap(values: &dyn Array) -> Vec<Color> {
+    let values = arrow2::compute::cast::cast(
+        values,
+        &arrow2::datatypes::DataType::Float64,
+        Default::default(),
+    )
+    .ok();
+    let values = values
+        .as_ref()
+        .and_then(|values| values.as_any().downcast_ref::<PrimitiveArray<f64>>());
+    let Some(values) = values else {
+        return Vec::new();
+    };
+
+    let (min, max) = values
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+            (min.min(v), max.max(v))
+        });
+    let range = if min < max { max - min } else { 1.0 };
+
+    values
+        .iter()
+        .map(|value| {
+            let t = value.map_or(0.0, |&v| ((v - min) / range) as f32);
+            let [r, g, b] = turbo(t.clamp(0.0, 1.0));
+            Color::from_rgb(r, g, b)
+        })
+        .collect()
+}
+
+// Polynomial approximation of the Turbo colormap, see `re_renderer::colormap`.
+//
+// Copyright 2019 Google LLC.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Authors:
+//   Colormap Design: Anton Mikhailov (mikhailov@google.com)
+//   GLSL Approximation: Ruofei Du (ruofei@google.com)
+#[allow(clippy::excessive_precision)]
+pub(super) fn turbo(t: f32) -> [u8; 3] {
+    const R4: [f32; 4] = [0.13572138, 4.61539260, -42.66032258, 132.13108234];
+    const G4: [f32; 4] = [0.09140261, 2.19418839, 4.84296658, -14.18503333];
+    const B4: [f32; 4] = [0.10667330, 12.64194608, -60.58204836, 110.36276771];
+
+    const R2: [f32; 2] = [-152.94239396, 59.28637943];
+    const G2: [f32; 2] = [4.27729857, 2.82956604];
+    const B2: [f32; 2] = [-89.90310912, 27.34824973];
+
+    let v4 = [1.0, t, t * t, t * t * t];
+    let v2 = [v4[2] * v4[2], v4[3] * v4[2]];
+    let channel = |c4: [f32; 4], c2: [f32; 2]| {
+        let v = c4.iter().zip(v4).map(|(c, v)| c * v).sum::<f32>()
+            + c2.iter().zip(v2).map(|(c, v)| c * v).sum::<f32>();
+        (v * 255.0) as u8
+    };
+
+    [channel(R4, R2), channel(G4, G2), channel(B4, B2)]
+}
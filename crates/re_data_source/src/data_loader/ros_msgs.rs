@@ -0,0 +1,638 @@
+//! Converts the most common ROS messages to archetypes, whether they're serialized for ROS 1 or
+//! as ROS 2's [CDR](https://www.omg.org/spec/DDSI-RTPS/2.3/PDF).
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+
+use re_log_types::{DataRow, EntityPath, EntityPathPart, RowId, TimePoint};
+use re_types::archetypes::{DepthImage, Image, TextLog, TimeSeriesScalar, Transform3D};
+use re_types::components::TextLogLevel;
+use re_types::datatypes::{Quaternion, TensorBuffer, TensorData, TensorDimension};
+use re_types::AsComponents;
+
+use super::loader_point_cloud::{intensity_range, points_row, Point, PointSource};
+
+// ---
+
+/// How the messages of a channel are serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Little endian, packed.
+    Ros1,
+
+    /// An encapsulation header giving the endianness, then aligned fields.
+    Cdr,
+}
+
+impl Encoding {
+    /// The encoding with this MCAP name, if supported.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ros1" => Some(Self::Ros1),
+            "cdr" => Some(Self::Cdr),
+            _ => None,
+        }
+    }
+}
+
+/// The message types that get converted, by their ROS 1 names.
+const SUPPORTED_TYPES: &[&str] = &[
+    "sensor_msgs/Image",
+    "sensor_msgs/CompressedImage",
+    "sensor_msgs/PointCloud2",
+    "tf2_msgs/TFMessage",
+    "tf/tfMessage",
+    "rosgraph_msgs/Log",
+    "rcl_interfaces/Log",
+    "std_msgs/Float32",
+    "std_msgs/Float64",
+    "std_msgs/Int8",
+    "std_msgs/Int16",
+    "std_msgs/Int32",
+    "std_msgs/Int64",
+    "std_msgs/UInt8",
+    "std_msgs/UInt16",
+    "std_msgs/UInt32",
+    "std_msgs/UInt64",
+];
+
+/// Static transforms are published once, and hold for the whole recording.
+const TF_STATIC_TOPIC: &str = "/tf_static";
+
+/// ROS 2 names its types `package/msg/Type`, ROS 1 `package/Type`.
+fn ros1_type_name(type_name: &str) -> String {
+    type_name.replacen("/msg/", "/", 1)
+}
+
+/// Are messages of this type converted by [`RosDecoder::decode`]?
+pub fn is_supported(type_name: &str) -> bool {
+    SUPPORTED_TYPES.contains(&ros1_type_name(type_name).as_str())
+}
+
+/// Converts messages to rows, keeping track of the TF frame tree along the way.
+#[derive(Default)]
+pub struct RosDecoder {
+    /// The parent of each TF frame seen so far.
+    frame_parents: HashMap<String, String>,
+}
+
+impl RosDecoder {
+    /// Converts a message published on `topic`, which must be of a [supported](is_supported) type.
+    ///
+    /// Messages are logged at their topic's entity, except for transforms which are logged at
+    /// their frame's: `tf/<root frame>/…/<frame>`.
+    pub fn decode(
+        &mut self,
+        topic: &str,
+        type_name: &str,
+        encoding: Encoding,
+        timepoint: &TimePoint,
+        data: &[u8],
+    ) -> anyhow::Result<Vec<DataRow>> {
+        re_tracing::profile_function!(type_name);
+
+        let mut reader = Reader::new(data, encoding)?;
+        let entity_path = EntityPath::from(topic);
+        let row = |archetype: &dyn AsComponents| {
+            DataRow::from_archetype(
+                RowId::new(),
+                timepoint.clone(),
+                entity_path.clone(),
+                archetype,
+            )
+        };
+
+        let type_name = ros1_type_name(type_name);
+        let row = match type_name.as_str() {
+            "sensor_msgs/Image" => row(image(&mut reader)?.as_ref())?,
+            "sensor_msgs/CompressedImage" => {
+                reader.header()?;
+                let _format = reader.string()?;
+                let data = reader.byte_sequence()?;
+                row(&Image::from_file_contents(data.to_vec(), None)?)?
+            }
+            "sensor_msgs/PointCloud2" => {
+                let cloud = PointCloud2::read(&mut reader)?;
+                let intensity_range = if cloud.has_colors() {
+                    None
+                } else {
+                    intensity_range(&cloud)?
+                };
+                points_row(
+                    &cloud,
+                    0..cloud.num_points(),
+                    intensity_range,
+                    timepoint.clone(),
+                    entity_path.clone(),
+                )?
+            }
+            "tf2_msgs/TFMessage" | "tf/tfMessage" => {
+                let timepoint = if topic == TF_STATIC_TOPIC {
+                    TimePoint::timeless()
+                } else {
+                    timepoint.clone()
+                };
+                return self.transforms(&mut reader, &timepoint);
+            }
+            "rosgraph_msgs/Log" | "rcl_interfaces/Log" => {
+                let level = if type_name == "rosgraph_msgs/Log" {
+                    reader.header()?;
+                    match reader.u8()? {
+                        1 => TextLogLevel::DEBUG,
+                        2 => TextLogLevel::INFO,
+                        4 => TextLogLevel::WARN,
+                        8 => TextLogLevel::ERROR,
+                        _ => TextLogLevel::CRITICAL,
+                    }
+                } else {
+                    reader.time()?;
+                    match reader.u8()? {
+                        ..=10 => TextLogLevel::DEBUG,
+                        11..=20 => TextLogLevel::INFO,
+                        21..=30 => TextLogLevel::WARN,
+                        31..=40 => TextLogLevel::ERROR,
+                        _ => TextLogLevel::CRITICAL,
+                    }
+                };
+                // All nodes log to the same topic: the name tells them apart.
+                let name = reader.string()?;
+                let msg = reader.string()?;
+                row(&TextLog::new(format!("[{name}] {msg}")).with_level(level))?
+            }
+            _ if type_name.starts_with("std_msgs/") => {
+                row(&TimeSeriesScalar::new(scalar(&mut reader, &type_name)?))?
+            }
+            _ => bail!("Unsupported message type {type_name:?}"),
+        };
+
+        Ok(vec![row])
+    }
+
+    /// One [`Transform3D`] per frame of a `tf2_msgs/TFMessage`, from the frame to its parent.
+    fn transforms(
+        &mut self,
+        reader: &mut Reader<'_>,
+        timepoint: &TimePoint,
+    ) -> anyhow::Result<Vec<DataRow>> {
+        let num_transforms = reader.u32()?;
+        let mut rows = Vec::new();
+        for _ in 0..num_transforms {
+            let parent = reader.header()?;
+            let child = reader.string()?;
+            let [x, y, z] = [reader.f64()?, reader.f64()?, reader.f64()?];
+            let [qx, qy, qz, qw] = [reader.f64()?, reader.f64()?, reader.f64()?, reader.f64()?];
+
+            // ROS 1 frame ids used to start with a slash, ROS 2 ones don't.
+            let parent = parent.trim_start_matches('/');
+            let child = child.trim_start_matches('/');
+            self.frame_parents
+                .insert(child.to_owned(), parent.to_owned());
+
+            let transform = Transform3D::from_translation_rotation(
+                [x as f32, y as f32, z as f32],
+                Quaternion::from_xyzw([qx as f32, qy as f32, qz as f32, qw as f32]),
+            );
+            rows.push(DataRow::from_archetype(
+                RowId::new(),
+                timepoint.clone(),
+                self.frame_entity_path(child),
+                &transform,
+            )?);
+        }
+        Ok(rows)
+    }
+
+    /// `tf/<root frame>/…/<frame>`, as far as the frame tree is known.
+    fn frame_entity_path(&self, frame: &str) -> EntityPath {
+        let mut frames = vec![frame];
+        while let Some(parent) = self.frame_parents.get(frames[frames.len() - 1]) {
+            if frames.contains(&parent.as_str()) {
+                break; // A cycle: the tree is being rewired.
+            }
+            frames.push(parent);
+        }
+
+        EntityPath::from(
+            std::iter::once("tf")
+                .chain(frames.into_iter().rev())
+                .map(EntityPathPart::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+// ---
+
+/// The `data` of a `std_msgs` number.
+fn scalar(reader: &mut Reader<'_>, type_name: &str) -> anyhow::Result<f64> {
+    Ok(match type_name {
+        "std_msgs/Float32" => f32::from_le_bytes(reader.array()?) as f64,
+        "std_msgs/Float64" => reader.f64()?,
+        "std_msgs/Int8" => reader.u8()? as i8 as f64,
+        "std_msgs/Int16" => i16::from_le_bytes(reader.array()?) as f64,
+        "std_msgs/Int32" => i32::from_le_bytes(reader.array()?) as f64,
+        "std_msgs/Int64" => i64::from_le_bytes(reader.array()?) as f64,
+        "std_msgs/UInt8" => reader.u8()? as f64,
+        "std_msgs/UInt16" => reader.u16()? as f64,
+        "std_msgs/UInt32" => reader.u32()? as f64,
+        "std_msgs/UInt64" => reader.u64()? as f64,
+        _ => bail!("Unsupported message type {type_name:?}"),
+    })
+}
+
+/// A `sensor_msgs/Image`, as an [`Image`], or a [`DepthImage`] for depth encodings.
+fn image(reader: &mut Reader<'_>) -> anyhow::Result<Box<dyn AsComponents>> {
+    reader.header()?;
+    let height = reader.u32()? as usize;
+    let width = reader.u32()? as usize;
+    let encoding = reader.string()?;
+    let big_endian = reader.u8()? != 0;
+    let step = reader.u32()? as usize;
+    let data = reader.byte_sequence()?;
+
+    let (num_channels, channel_size) = match encoding.as_str() {
+        "mono8" | "8UC1" => (1, 1),
+        "rgb8" | "bgr8" | "8UC3" => (3, 1),
+        "rgba8" | "bgra8" | "8UC4" => (4, 1),
+        "mono16" | "16UC1" => (1, 2),
+        "32FC1" => (1, 4),
+        _ => bail!("Unsupported image encoding {encoding:?}"),
+    };
+
+    // Rows may be padded.
+    let row_len = width * num_channels * channel_size;
+    if step < row_len || data.len() < step * height.saturating_sub(1) + row_len {
+        bail!("Image data is too short for {width}x{height} {encoding} pixels");
+    }
+    let mut pixels = Vec::with_capacity(row_len * height);
+    for row in 0..height {
+        pixels.extend_from_slice(&data[row * step..row * step + row_len]);
+    }
+    if encoding.starts_with("bgr") {
+        for pixel in pixels.chunks_exact_mut(num_channels) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    let mut shape = vec![
+        TensorDimension::height(height as _),
+        TensorDimension::width(width as _),
+    ];
+    if num_channels > 1 {
+        shape.push(TensorDimension::depth(num_channels as _));
+    }
+
+    Ok(match channel_size {
+        1 => Box::new(Image::new(TensorData::new(
+            shape,
+            TensorBuffer::U8(pixels.into()),
+        ))),
+        2 => {
+            let pixels = pixels
+                .chunks_exact(2)
+                .map(|bytes| {
+                    let bytes = [bytes[0], bytes[1]];
+                    if big_endian {
+                        u16::from_be_bytes(bytes)
+                    } else {
+                        u16::from_le_bytes(bytes)
+                    }
+                })
+                .collect::<Vec<_>>();
+            let data = TensorData::new(shape, TensorBuffer::U16(pixels.into()));
+            if encoding == "16UC1" {
+                // Depth cameras measure in millimeters.
+                Box::new(DepthImage::new(data).with_meter(1000.0))
+            } else {
+                Box::new(Image::new(data))
+            }
+        }
+        _ => {
+            let pixels = pixels
+                .chunks_exact(4)
+                .map(|bytes| {
+                    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+                    if big_endian {
+                        f32::from_be_bytes(bytes)
+                    } else {
+                        f32::from_le_bytes(bytes)
+                    }
+                })
+                .collect::<Vec<_>>();
+            Box::new(
+                DepthImage::new(TensorData::new(shape, TensorBuffer::F32(pixels.into())))
+                    .with_meter(1.0),
+            )
+        }
+    })
+}
+
+// ---
+
+/// A field of the points of a `sensor_msgs/PointCloud2`.
+#[derive(Debug, Clone, Copy)]
+struct PointField {
+    offset: usize,
+    datatype: u8,
+}
+
+impl PointField {
+    fn read(&self, point: &[u8], big_endian: bool) -> anyhow::Result<f64> {
+        let size = match self.datatype {
+            1 | 2 => 1,
+            3 | 4 => 2,
+            5..=7 => 4,
+            8 => 8,
+            datatype => bail!("Unknown point field datatype {datatype}"),
+        };
+        let bytes = point
+            .get(self.offset..self.offset + size)
+            .ok_or_else(|| anyhow!("Point field is out of bounds"))?;
+        let mut buffer = [0; 8];
+        buffer[..size].copy_from_slice(bytes);
+        if big_endian {
+            buffer[..size].reverse();
+        }
+
+        Ok(match self.datatype {
+            1 => buffer[0] as i8 as f64,
+            2 => buffer[0] as f64,
+            3 => i16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+            4 => u16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+            5 => i32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+            6 => u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+            7 => f32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+            _ => f64::from_le_bytes(buffer),
+        })
+    }
+
+    /// The raw bits of a 4 bytes field, such as PCL's packed colors.
+    fn bits(&self, point: &[u8], big_endian: bool) -> anyhow::Result<u32> {
+        let bytes: [u8; 4] = point
+            .get(self.offset..self.offset + 4)
+            .ok_or_else(|| anyhow!("Point field is out of bounds"))?
+            .try_into()?;
+        Ok(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+}
+
+/// A `sensor_msgs/PointCloud2`, its points read in place.
+struct PointCloud2<'a> {
+    width: usize,
+    height: usize,
+    point_step: usize,
+    row_step: usize,
+    big_endian: bool,
+    data: &'a [u8],
+    position: [PointField; 3],
+
+    /// Packed as `0x00RRGGBB`, or `0xAARRGGBB` if the field is named `rgba`.
+    color: Option<(PointField, bool)>,
+    intensity: Option<PointField>,
+    label: Option<PointField>,
+}
+
+impl<'a> PointCloud2<'a> {
+    fn read(reader: &mut Reader<'a>) -> anyhow::Result<Self> {
+        reader.header()?;
+        let height = reader.u32()? as usize;
+        let width = reader.u32()? as usize;
+
+        let mut fields = HashMap::new();
+        for _ in 0..reader.u32()? {
+            let name = reader.string()?;
+            let offset = reader.u32()? as usize;
+            let datatype = reader.u8()?;
+            let _count = reader.u32()?;
+            fields.insert(name, PointField { offset, datatype });
+        }
+
+        let big_endian = reader.u8()? != 0;
+        let point_step = reader.u32()? as usize;
+        let row_step = reader.u32()? as usize;
+        let data = reader.byte_sequence()?;
+
+        let field = |name: &str| {
+            fields
+                .get(name)
+                .copied()
+                .ok_or_else(|| anyhow!("Point clouds without {name:?} fields aren't supported"))
+        };
+        let position = [field("x")?, field("y")?, field("z")?];
+        let color = fields
+            .get("rgb")
+            .map(|field| (*field, false))
+            .or_else(|| fields.get("rgba").map(|field| (*field, true)));
+
+        Ok(Self {
+            width,
+            height,
+            point_step,
+            row_step,
+            big_endian,
+            data,
+            position,
+            color,
+            intensity: fields.get("intensity").copied(),
+            label: fields.get("label").copied(),
+        })
+    }
+}
+
+impl PointSource for PointCloud2<'_> {
+    fn num_points(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn has_colors(&self) -> bool {
+        self.color.is_some()
+    }
+
+    fn has_intensities(&self) -> bool {
+        self.intensity.is_some()
+    }
+
+    fn origin(&self) -> Option<[f64; 3]> {
+        None
+    }
+
+    fn point(&self, index: usize) -> anyhow::Result<Point> {
+        let start = (index / self.width) * self.row_step + (index % self.width) * self.point_step;
+        let point = self
+            .data
+            .get(start..start + self.point_step)
+            .ok_or_else(|| anyhow!("Point cloud data is too short"))?;
+
+        let [x, y, z] = self.position;
+        let position = [
+            x.read(point, self.big_endian)? as f32,
+            y.read(point, self.big_endian)? as f32,
+            z.read(point, self.big_endian)? as f32,
+        ];
+        let color = self
+            .color
+            .map(|(field, has_alpha)| {
+                let [a, r, g, b] = field.bits(point, self.big_endian)?.to_be_bytes();
+                anyhow::Ok([r, g, b, if has_alpha { a } else { 255 }])
+            })
+            .transpose()?;
+        let intensity = self
+            .intensity
+            .map(|field| field.read(point, self.big_endian))
+            .transpose()?
+            .map(|intensity| intensity as f32);
+        let class_id = self
+            .label
+            .map(|field| field.read(point, self.big_endian))
+            .transpose()?
+            .map(|label| label as u16);
+
+        Ok(Point {
+            position,
+            color,
+            intensity,
+            class_id,
+        })
+    }
+}
+
+// ---
+
+/// Reads the fields of a serialized message, one after the other.
+///
+/// MCAP records and ROS 1 bag records are serialized just like ROS 1 messages, so this reads
+/// those too.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    encoding: Encoding,
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8], encoding: Encoding) -> anyhow::Result<Self> {
+        let (data, big_endian) = match encoding {
+            Encoding::Ros1 => (data, false),
+            Encoding::Cdr => {
+                // Alignment is relative to the end of the encapsulation header.
+                if data.len() < 4 {
+                    bail!("Message is missing its CDR encapsulation header");
+                }
+                let (header, data) = data.split_at(4);
+                let big_endian = match header[1] {
+                    0x00 => true,
+                    0x01 => false,
+                    representation => {
+                        bail!("Unsupported CDR representation {representation:#04x}")
+                    }
+                };
+                (data, big_endian)
+            }
+        };
+
+        Ok(Self {
+            data,
+            position: 0,
+            encoding,
+            big_endian,
+        })
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    pub fn bytes(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let bytes = self
+            .position
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(|| anyhow!("Data is truncated"))?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Everything left to read.
+    pub fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.position.min(self.data.len())..];
+        self.position = self.data.len();
+        rest
+    }
+
+    /// The next `N` bytes, aligned and in little endian order.
+    pub fn array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        if self.encoding == Encoding::Cdr {
+            self.position = (self.position + N - 1) / N * N;
+        }
+        let mut bytes: [u8; N] = self.bytes(N)?.try_into()?;
+        if self.big_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    #[inline]
+    pub fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    #[inline]
+    pub fn u16(&mut self) -> anyhow::Result<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    #[inline]
+    pub fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    #[inline]
+    pub fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    #[inline]
+    pub fn f64(&mut self) -> anyhow::Result<f64> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    pub fn byte_sequence(&mut self) -> anyhow::Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+
+    pub fn string(&mut self) -> anyhow::Result<String> {
+        let mut bytes = self.byte_sequence()?;
+        // CDR strings end with a null character, counted in their length.
+        if self.encoding == Encoding::Cdr {
+            bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+        }
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// A `time`, or a ROS 2 `builtin_interfaces/Time`, in nanoseconds.
+    pub fn time(&mut self) -> anyhow::Result<i64> {
+        let secs = self.u32()?;
+        let nanos = self.u32()?;
+        let secs = match self.encoding {
+            Encoding::Ros1 => secs as i64,
+            Encoding::Cdr => secs as i32 as i64,
+        };
+        Ok(secs * 1_000_000_000 + nanos as i64)
+    }
+
+    /// A `std_msgs/Header`, of which only the frame id is kept.
+    pub fn header(&mut self) -> anyhow::Result<String> {
+        if self.encoding == Encoding::Ros1 {
+            let _seq = self.u32()?;
+        }
+        self.time()?;
+        self.string()
+    }
+}
@@ -0,0 +1,173 @@
+//! Reads the messages of ROS 1 [bag files](http://wiki.ros.org/Bags/Format/2.0), in the order
+//! they were written.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use anyhow::{anyhow, bail, Context as _};
+
+use super::ros_msgs::{Encoding, Reader};
+
+// ---
+
+pub const MAGIC: &[u8] = b"#ROSBAG V2.0\n";
+
+const OP_MESSAGE_DATA: u8 = 0x02;
+const OP_CHUNK: u8 = 0x05;
+const OP_CONNECTION: u8 = 0x07;
+
+/// A connection: the topic a node published on, and the type of its messages.
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub topic: String,
+
+    /// E.g. `sensor_msgs/Image`.
+    pub message_type: String,
+}
+
+/// A message, as found in the file.
+pub struct Message<'a> {
+    pub connection: &'a Connection,
+
+    /// When the message was recorded, in nanoseconds since the epoch.
+    pub time: u64,
+
+    /// Serialized the ROS 1 way.
+    pub data: &'a [u8],
+}
+
+/// Calls `on_message` for each message of a bag file, until it breaks.
+pub fn read_messages(
+    contents: &[u8],
+    mut on_message: impl FnMut(Message<'_>) -> ControlFlow<()>,
+) -> anyhow::Result<()> {
+    re_tracing::profile_function!();
+
+    let records = contents.strip_prefix(MAGIC).ok_or_else(|| {
+        anyhow!("Not a ROS bag file, or not of version 2.0: migrate it with `rosbag fix`")
+    })?;
+
+    let mut connections = HashMap::new();
+    read_records(records, &mut connections, &mut on_message)?;
+    Ok(())
+}
+
+fn read_records(
+    records: &[u8],
+    connections: &mut HashMap<u32, Connection>,
+    on_message: &mut impl FnMut(Message<'_>) -> ControlFlow<()>,
+) -> anyhow::Result<ControlFlow<()>> {
+    let mut reader = Reader::new(records, Encoding::Ros1)?;
+    while !reader.is_empty() {
+        let header = Fields::read(reader.byte_sequence()?)?;
+        let data = reader.byte_sequence()?;
+
+        match header.u8("op")? {
+            OP_CONNECTION => {
+                let id = header.u32("conn")?;
+                // The type is in the connection header, which is the record's data.
+                let message_type = Fields::read(data)?.string("type")?;
+                // Connections are listed again after the chunks.
+                connections.entry(id).or_insert(Connection {
+                    topic: header.string("topic")?,
+                    message_type,
+                });
+            }
+            OP_MESSAGE_DATA => {
+                let id = header.u32("conn")?;
+                let connection = connections
+                    .get(&id)
+                    .ok_or_else(|| anyhow!("Message on unknown connection {id}"))?;
+                let mut time = Reader::new(header.get("time")?, Encoding::Ros1)?;
+                let message = Message {
+                    connection,
+                    time: time.time()? as u64,
+                    data,
+                };
+                if on_message(message).is_break() {
+                    return Ok(ControlFlow::Break(()));
+                }
+            }
+            OP_CHUNK => {
+                let compression = header.string("compression")?;
+                let size = header.u32("size")? as usize;
+                let records = decompress(&compression, data, size)?;
+                if read_records(&records, connections, on_message)?.is_break() {
+                    return Ok(ControlFlow::Break(()));
+                }
+            }
+            // Bag header, indices and chunk infos.
+            _ => {}
+        }
+    }
+
+    Ok(ControlFlow::Continue(()))
+}
+
+fn decompress<'a>(
+    compression: &str,
+    compressed: &'a [u8],
+    size: usize,
+) -> anyhow::Result<Cow<'a, [u8]>> {
+    use std::io::Read as _;
+
+    re_tracing::profile_function!(compression);
+
+    match compression {
+        "none" => Ok(Cow::Borrowed(compressed)),
+        "lz4" => {
+            // The size is only a hint from the file: a corrupt one mustn't allocate gigabytes
+            // up front.
+            let mut uncompressed =
+                Vec::with_capacity(size.min(compressed.len().saturating_mul(16)));
+            lz4_flex::frame::FrameDecoder::new(compressed)
+                .read_to_end(&mut uncompressed)
+                .context("Failed to decompress LZ4 chunk")?;
+            Ok(Cow::Owned(uncompressed))
+        }
+        "bz2" => bail!(
+            "BZ2 compressed bags are not supported, \
+             decompress them first, e.g. using `rosbag decompress`"
+        ),
+        _ => bail!("Unsupported chunk compression {compression:?}"),
+    }
+}
+
+/// The `name=value` fields of a record header, or of a connection header.
+struct Fields<'a>(HashMap<&'a [u8], &'a [u8]>);
+
+impl<'a> Fields<'a> {
+    fn read(data: &'a [u8]) -> anyhow::Result<Self> {
+        let mut reader = Reader::new(data, Encoding::Ros1)?;
+        let mut fields = HashMap::new();
+        while !reader.is_empty() {
+            let field = reader.byte_sequence()?;
+            let separator = field
+                .iter()
+                .position(|&byte| byte == b'=')
+                .ok_or_else(|| anyhow!("Record header field without a name"))?;
+            fields.insert(&field[..separator], &field[separator + 1..]);
+        }
+        Ok(Self(fields))
+    }
+
+    fn get(&self, name: &str) -> anyhow::Result<&'a [u8]> {
+        self.0
+            .get(name.as_bytes())
+            .copied()
+            .ok_or_else(|| anyhow!("Record header is missing its {name:?} field"))
+    }
+
+    fn u8(&self, name: &str) -> anyhow::Result<u8> {
+        Reader::new(self.get(name)?, Encoding::Ros1)?.u8()
+    }
+
+    fn u32(&self, name: &str) -> anyhow::Result<u32> {
+        Reader::new(self.get(name)?, Encoding::Ros1)?.u32()
+    }
+
+    fn string(&self, name: &str) -> anyhow::Result<String> {
+        Ok(String::from_utf8_lossy(self.get(name)?).into_owned())
+    }
+}
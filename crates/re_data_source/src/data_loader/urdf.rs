@@ -0,0 +1,694 @@
+//! Reading [URDF](https://wiki.ros.org/urdf/XML) robot descriptions.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _};
+use glam::{DQuat, DVec3, EulerRot};
+
+use re_log_types::{DataRow, EntityPath, EntityPathPart, RowId, TimePoint};
+use re_types::archetypes::{Asset3D, Mesh3D, Transform3D};
+use re_types::components::{Material, MediaType, MeshProperties};
+use re_types::datatypes::{Quaternion, Rgba32, TranslationRotationScale3D};
+
+/// A robot, as described by a URDF file.
+///
+/// Each link becomes an entity, nested under its parent link's, with the origin of the joint
+/// connecting them as its [`Transform3D`].
+/// The visuals of a link become child entities of it: `visual_0`, `visual_1`…
+///
+/// Use [`UrdfTree::to_data_rows`] to log the robot at rest, then [`UrdfTree::joint_transform`]
+/// to move its joints over time.
+pub struct UrdfTree {
+    name: String,
+
+    /// Where the `.urdf` file is, to find the meshes it references.
+    filepath: PathBuf,
+
+    links: Vec<Link>,
+    joints: Vec<Joint>,
+
+    /// The entity path of each link.
+    link_paths: HashMap<String, EntityPath>,
+}
+
+struct Link {
+    name: String,
+    visuals: Vec<Visual>,
+}
+
+struct Visual {
+    origin: Pose,
+    geometry: Geometry,
+    color: Option<Rgba32>,
+}
+
+enum Geometry {
+    Box { size: [f32; 3] },
+    Cylinder { radius: f32, length: f32 },
+    Sphere { radius: f32 },
+    Mesh { filename: String, scale: [f32; 3] },
+}
+
+struct Joint {
+    name: String,
+    typ: String,
+    parent: String,
+    child: String,
+    origin: Pose,
+    axis: DVec3,
+}
+
+/// A position and an orientation, as roll, pitch and yaw around the fixed x, y and z axes.
+#[derive(Clone, Copy, Default)]
+struct Pose {
+    xyz: DVec3,
+    rpy: DVec3,
+}
+
+impl Pose {
+    fn rotation(&self) -> DQuat {
+        DQuat::from_euler(EulerRot::ZYX, self.rpy.z, self.rpy.y, self.rpy.x)
+    }
+}
+
+impl UrdfTree {
+    /// Reads a `.urdf` file, whose links will be logged under the entity path of the file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file_path(filepath: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let filepath = filepath.as_ref();
+        let contents =
+            std::fs::read(filepath).with_context(|| format!("Failed to read file {filepath:?}"))?;
+        Self::from_file_contents(filepath, &contents)
+    }
+
+    /// Parses the contents of a `.urdf` file, whose links will be logged under the entity path
+    /// of the file.
+    pub fn from_file_contents(filepath: &Path, contents: &[u8]) -> anyhow::Result<Self> {
+        re_tracing::profile_function!();
+
+        let contents = std::str::from_utf8(contents).context("URDF files must be UTF-8")?;
+        let robot = Element::parse(contents)?;
+        if robot.name != "robot" {
+            bail!("Expected a <robot> element, found <{}>", robot.name);
+        }
+
+        let materials: HashMap<&str, Rgba32> = robot
+            .children("material")
+            .filter_map(|material| Some((material.attr("name")?, material_color(material)?)))
+            .collect();
+
+        let links = robot
+            .children("link")
+            .map(|link| {
+                let name = link.required_attr("name")?.to_owned();
+                let visuals = link
+                    .children("visual")
+                    .map(|visual| {
+                        let color = visual.child("material").and_then(|material| {
+                            material_color(material)
+                                .or_else(|| materials.get(material.attr("name")?).copied())
+                        });
+                        Ok(Visual {
+                            origin: pose(visual.child("origin"))?,
+                            geometry: geometry(visual.child("geometry"))
+                                .with_context(|| format!("Invalid visual in link {name:?}"))?,
+                            color,
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                Ok(Link { name, visuals })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let joints = robot
+            .children("joint")
+            .map(|joint| {
+                let name = joint.required_attr("name")?.to_owned();
+                let link = |tag: &str| -> anyhow::Result<String> {
+                    let element = joint
+                        .child(tag)
+                        .with_context(|| format!("Joint {name:?} has no <{tag}>"))?;
+                    Ok(element.required_attr("link")?.to_owned())
+                };
+                Ok(Joint {
+                    typ: joint.required_attr("type")?.to_owned(),
+                    parent: link("parent")?,
+                    child: link("child")?,
+                    origin: pose(joint.child("origin"))?,
+                    axis: joint
+                        .child("axis")
+                        .and_then(|axis| axis.attr("xyz"))
+                        .map(dvec3)
+                        .transpose()?
+                        .unwrap_or(DVec3::X),
+                    name,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let link_paths = link_paths(EntityPath::from_file_path(filepath), &links, &joints)?;
+
+        Ok(Self {
+            name: robot.attr("name").unwrap_or_default().to_owned(),
+            filepath: filepath.to_owned(),
+            links,
+            joints,
+            link_paths,
+        })
+    }
+
+    /// Logs the robot under the given entity path, instead of the file's.
+    pub fn with_entity_path(mut self, entity_path: EntityPath) -> anyhow::Result<Self> {
+        self.link_paths = link_paths(entity_path, &self.links, &self.joints)?;
+        Ok(self)
+    }
+
+    /// The name of the robot.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of all joints, in the order of the file.
+    pub fn joint_names(&self) -> impl Iterator<Item = &str> {
+        self.joints.iter().map(|joint| joint.name.as_str())
+    }
+
+    /// The entity path of a link.
+    pub fn link_entity_path(&self, link: &str) -> Option<&EntityPath> {
+        self.link_paths.get(link)
+    }
+
+    /// The robot at rest: the transforms of all joints, and the visuals of all links.
+    ///
+    /// All rows are timeless. Meshes that can't be read are skipped with a warning.
+    pub fn to_data_rows(&self) -> anyhow::Result<Vec<DataRow>> {
+        re_tracing::profile_function!();
+
+        let mut rows = Vec::new();
+
+        for joint in &self.joints {
+            let transform = Transform3D::from_translation_rotation(
+                vec3(joint.origin.xyz),
+                quaternion(joint.origin.rotation()),
+            );
+            rows.push(DataRow::from_archetype(
+                RowId::new(),
+                TimePoint::timeless(),
+                self.link_paths[&joint.child].clone(),
+                &transform,
+            )?);
+        }
+
+        for link in &self.links {
+            for (index, visual) in link.visuals.iter().enumerate() {
+                let entity_path = self.link_paths[&link.name].join(&EntityPath::from(vec![
+                    EntityPathPart::from(format!("visual_{index}")),
+                ]));
+
+                let transform = Transform3D::from_translation_rotation(
+                    vec3(visual.origin.xyz),
+                    quaternion(visual.origin.rotation()),
+                );
+                rows.push(DataRow::from_archetype(
+                    RowId::new(),
+                    TimePoint::timeless(),
+                    entity_path.clone(),
+                    &transform,
+                )?);
+
+                let row = match &visual.geometry {
+                    Geometry::Mesh { filename, scale } => {
+                        let Some(asset) = self.load_mesh(filename, *scale) else {
+                            continue;
+                        };
+                        DataRow::from_archetype(
+                            RowId::new(),
+                            TimePoint::timeless(),
+                            entity_path,
+                            &asset,
+                        )?
+                    }
+                    primitive => {
+                        let mut mesh = primitive_mesh(primitive);
+                        if let Some(color) = visual.color {
+                            mesh = mesh.with_mesh_material(Material::from_albedo_factor(color));
+                        }
+                        DataRow::from_archetype(
+                            RowId::new(),
+                            TimePoint::timeless(),
+                            entity_path,
+                            &mesh,
+                        )?
+                    }
+                };
+                rows.push(row);
+            }
+        }
+
+        Ok(rows)
+    }
+
+    /// The transform of a joint that moved to the given position, and the entity path to log it to.
+    ///
+    /// The position is an angle in radians for `revolute` and `continuous` joints, and a distance
+    /// in meters for `prismatic` ones. Other types of joints can't be moved.
+    pub fn joint_transform(
+        &self,
+        joint: &str,
+        position: f64,
+    ) -> anyhow::Result<(EntityPath, Transform3D)> {
+        let Some(joint) = self.joints.iter().find(|j| j.name == joint) else {
+            bail!("Robot {:?} has no joint {joint:?}", self.name);
+        };
+
+        let axis = joint.axis.normalize_or_zero();
+        let (translation, rotation) = match joint.typ.as_str() {
+            "revolute" | "continuous" => (
+                joint.origin.xyz,
+                joint.origin.rotation() * DQuat::from_axis_angle(axis, position),
+            ),
+            "prismatic" => (
+                joint.origin.xyz + joint.origin.rotation() * (axis * position),
+                joint.origin.rotation(),
+            ),
+            typ => bail!(
+                "Joint {:?} is {typ}, only revolute, continuous and prismatic joints can move",
+                joint.name
+            ),
+        };
+
+        Ok((
+            self.link_paths[&joint.child].clone(),
+            Transform3D::from_translation_rotation(vec3(translation), quaternion(rotation)),
+        ))
+    }
+
+    fn load_mesh(&self, filename: &str, scale: [f32; 3]) -> Option<Asset3D> {
+        let Some(media_type) = MediaType::guess_from_path(filename) else {
+            re_log::warn!(?filename, "Unsupported mesh format, skipping it");
+            return None;
+        };
+
+        let contents = self.read_mesh(filename);
+        let contents = match contents {
+            Ok(contents) => contents,
+            Err(err) => {
+                re_log::warn!(?filename, "Failed to read mesh, skipping it: {err}");
+                return None;
+            }
+        };
+
+        let mut asset = Asset3D::from_file_contents(contents, Some(media_type));
+        if scale != [1.0; 3] {
+            asset = asset.with_transform(re_types::datatypes::Transform3D::from(
+                TranslationRotationScale3D::from_scale(scale),
+            ));
+        }
+        Some(asset)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_mesh(&self, filename: &str) -> anyhow::Result<Vec<u8>> {
+        let path = resolve_mesh_path(&self.filepath, filename)
+            .with_context(|| format!("Couldn't find {filename:?}"))?;
+        std::fs::read(&path).with_context(|| format!("Failed to read file {path:?}"))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read_mesh(&self, filename: &str) -> anyhow::Result<Vec<u8>> {
+        bail!(
+            "Can't read {filename:?}, referenced by {:?}: meshes referenced by URDF files can't be read on the web",
+            self.filepath
+        )
+    }
+}
+
+/// Where a mesh referenced by a URDF file is on disk.
+///
+/// `package://` URIs are looked up in the ancestors of the URDF file, then in `$ROS_PACKAGE_PATH`.
+/// Other paths are relative to the URDF file.
+#[cfg(not(target_arch = "wasm32"))]
+fn resolve_mesh_path(urdf_path: &Path, filename: &str) -> Option<PathBuf> {
+    let dir = urdf_path.parent().unwrap_or(Path::new("."));
+
+    if let Some(path) = filename.strip_prefix("file://") {
+        return Some(dir.join(path));
+    }
+    let Some(uri) = filename.strip_prefix("package://") else {
+        return Some(dir.join(filename));
+    };
+
+    let (package, path) = uri.split_once('/')?;
+    let ros_package_path = std::env::var_os("ROS_PACKAGE_PATH").unwrap_or_default();
+    dir.ancestors()
+        .flat_map(|ancestor| {
+            let package_dir =
+                (ancestor.file_name() == Some(package.as_ref())).then(|| ancestor.to_owned());
+            package_dir.into_iter().chain([ancestor.join(package)])
+        })
+        .chain(std::env::split_paths(&ros_package_path).map(|root| root.join(package)))
+        .map(|package_dir| package_dir.join(path))
+        .find(|path| path.is_file())
+}
+
+/// Every link's entity path is its parent link's, followed by its name.
+fn link_paths(
+    root: EntityPath,
+    links: &[Link],
+    joints: &[Joint],
+) -> anyhow::Result<HashMap<String, EntityPath>> {
+    let parents: HashMap<&str, &str> = joints
+        .iter()
+        .map(|joint| (joint.child.as_str(), joint.parent.as_str()))
+        .collect();
+
+    for joint in joints {
+        for link in [&joint.parent, &joint.child] {
+            if !links.iter().any(|l| &l.name == link) {
+                bail!("Joint {:?} refers to an unknown link {link:?}", joint.name);
+            }
+        }
+    }
+
+    links
+        .iter()
+        .map(|link| {
+            let mut chain = vec![link.name.as_str()];
+            while let Some(&parent) = chain.last().and_then(|child| parents.get(child)) {
+                if chain.len() > links.len() {
+                    bail!("The joints of link {:?} form a cycle", link.name);
+                }
+                chain.push(parent);
+            }
+            let path = chain
+                .iter()
+                .rev()
+                .map(|&name| EntityPathPart::from(name))
+                .collect::<EntityPath>();
+            Ok((link.name.clone(), root.join(&path)))
+        })
+        .collect()
+}
+
+fn pose(origin: Option<&Element>) -> anyhow::Result<Pose> {
+    let Some(origin) = origin else {
+        return Ok(Pose::default());
+    };
+    Ok(Pose {
+        xyz: origin
+            .attr("xyz")
+            .map(dvec3)
+            .transpose()?
+            .unwrap_or_default(),
+        rpy: origin
+            .attr("rpy")
+            .map(dvec3)
+            .transpose()?
+            .unwrap_or_default(),
+    })
+}
+
+fn geometry(geometry: Option<&Element>) -> anyhow::Result<Geometry> {
+    let shape = geometry
+        .and_then(|geometry| geometry.children.first())
+        .context("Missing <geometry>")?;
+    let number = |name: &str| -> anyhow::Result<f32> {
+        let value = shape.required_attr(name)?;
+        value
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid {name} {value:?}"))
+    };
+
+    Ok(match shape.name.as_str() {
+        "box" => Geometry::Box {
+            size: dvec3(shape.required_attr("size")?)?.as_vec3().to_array(),
+        },
+        "cylinder" => Geometry::Cylinder {
+            radius: number("radius")?,
+            length: number("length")?,
+        },
+        "sphere" => Geometry::Sphere {
+            radius: number("radius")?,
+        },
+        "mesh" => Geometry::Mesh {
+            filename: shape.required_attr("filename")?.to_owned(),
+            scale: shape
+                .attr("scale")
+                .map(dvec3)
+                .transpose()?
+                .map_or([1.0; 3], |scale| scale.as_vec3().to_array()),
+        },
+        shape => bail!("Unknown geometry <{shape}>"),
+    })
+}
+
+fn material_color(material: &Element) -> Option<Rgba32> {
+    let rgba = material.child("color")?.attr("rgba")?;
+    let rgba = rgba
+        .split_whitespace()
+        .map(|c| c.parse::<f32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let [r, g, b, a] = rgba.as_slice() else {
+        return None;
+    };
+    let u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Some(Rgba32::from_unmultiplied_rgba(
+        u8(*r),
+        u8(*g),
+        u8(*b),
+        u8(*a),
+    ))
+}
+
+fn dvec3(value: &str) -> anyhow::Result<DVec3> {
+    let numbers = value
+        .split_whitespace()
+        .map(|n| n.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid vector {value:?}"))?;
+    let [x, y, z] = numbers.as_slice() else {
+        bail!("Expected 3 numbers, got {value:?}");
+    };
+    Ok(DVec3::new(*x, *y, *z))
+}
+
+fn vec3(v: DVec3) -> [f32; 3] {
+    v.as_vec3().to_array()
+}
+
+fn quaternion(q: DQuat) -> Quaternion {
+    Quaternion::from_xyzw(q.as_f32().to_array())
+}
+
+// ---
+
+/// A triangle mesh for the primitive shapes of URDF, centered on the origin.
+fn primitive_mesh(geometry: &Geometry) -> Mesh3D {
+    /// Number of segments around circles.
+    const SEGMENTS: u32 = 32;
+
+    let mut positions: Vec<glam::Vec3> = Vec::new();
+    let mut normals: Vec<glam::Vec3> = Vec::new();
+    let mut triangles: Vec<[u32; 3]> = Vec::new();
+
+    match *geometry {
+        Geometry::Box { size } => {
+            let half = glam::Vec3::from(size) * 0.5;
+            for normal in [
+                glam::Vec3::X,
+                glam::Vec3::NEG_X,
+                glam::Vec3::Y,
+                glam::Vec3::NEG_Y,
+                glam::Vec3::Z,
+                glam::Vec3::NEG_Z,
+            ] {
+                let u = if normal.x == 0.0 {
+                    glam::Vec3::X
+                } else {
+                    glam::Vec3::Y
+                };
+                let v = normal.cross(u);
+                let first = positions.len() as u32;
+                for (a, b) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                    positions.push((normal + a * u + b * v) * half);
+                    normals.push(normal);
+                }
+                triangles.push([first, first + 1, first + 2]);
+                triangles.push([first, first + 2, first + 3]);
+            }
+        }
+
+        Geometry::Cylinder { radius, length } => {
+            // Along the z axis.
+            let half_length = length * 0.5;
+            let circle = |i: u32| {
+                let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+                glam::vec3(angle.cos(), angle.sin(), 0.0)
+            };
+
+            for i in 0..SEGMENTS {
+                let first = positions.len() as u32;
+                for direction in [circle(i), circle(i + 1)] {
+                    for z in [-half_length, half_length] {
+                        positions.push(direction * radius + glam::Vec3::Z * z);
+                        normals.push(direction);
+                    }
+                }
+                triangles.push([first, first + 2, first + 3]);
+                triangles.push([first, first + 3, first + 1]);
+            }
+
+            for (z, normal) in [
+                (half_length, glam::Vec3::Z),
+                (-half_length, glam::Vec3::NEG_Z),
+            ] {
+                let center = positions.len() as u32;
+                positions.push(normal * z.abs());
+                normals.push(normal);
+                for i in 0..SEGMENTS {
+                    positions.push(circle(i) * radius + glam::Vec3::Z * z);
+                    normals.push(normal);
+                }
+                for i in 0..SEGMENTS {
+                    let (a, b) = (center + 1 + i, center + 1 + (i + 1) % SEGMENTS);
+                    triangles.push(if z > 0.0 {
+                        [center, a, b]
+                    } else {
+                        [center, b, a]
+                    });
+                }
+            }
+        }
+
+        Geometry::Sphere { radius } => {
+            let rings = SEGMENTS / 2;
+            for ring in 0..=rings {
+                let polar = ring as f32 / rings as f32 * std::f32::consts::PI;
+                for i in 0..=SEGMENTS {
+                    let azimuth = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+                    let normal = glam::vec3(
+                        polar.sin() * azimuth.cos(),
+                        polar.sin() * azimuth.sin(),
+                        polar.cos(),
+                    );
+                    positions.push(normal * radius);
+                    normals.push(normal);
+                }
+            }
+            for ring in 0..rings {
+                for i in 0..SEGMENTS {
+                    let a = ring * (SEGMENTS + 1) + i;
+                    let b = a + SEGMENTS + 1;
+                    triangles.push([a, b, b + 1]);
+                    triangles.push([a, b + 1, a + 1]);
+                }
+            }
+        }
+
+        Geometry::Mesh { .. } => {}
+    }
+
+    Mesh3D::new(positions.into_iter().map(|p| p.to_array()))
+        .with_vertex_normals(normals.into_iter().map(|n| n.to_array()))
+        .with_mesh_properties(MeshProperties::from_triangle_indices(triangles))
+}
+
+// ---
+
+/// Just enough of a DOM to read URDF files.
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+}
+
+impl Element {
+    /// Parses the root element of an XML document.
+    fn parse(xml: &str) -> anyhow::Result<Self> {
+        use quick_xml::events::{BytesStart, Event};
+
+        fn element(start: &BytesStart<'_>) -> anyhow::Result<Element> {
+            let attributes = start
+                .attributes()
+                .map(|attribute| {
+                    let attribute = attribute?;
+                    Ok((
+                        String::from_utf8_lossy(attribute.key.as_ref()).into_owned(),
+                        attribute.unescape_value()?.into_owned(),
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?;
+            Ok(Element {
+                name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+                attributes,
+                children: Vec::new(),
+            })
+        }
+
+        let mut reader = quick_xml::Reader::from_str(xml);
+        reader.trim_text(true);
+
+        // The elements being read, from the root down.
+        let mut stack: Vec<Element> = Vec::new();
+        loop {
+            let event = reader
+                .read_event()
+                .with_context(|| format!("Invalid XML at byte {}", reader.buffer_position()))?;
+            let done = match event {
+                Event::Start(start) => {
+                    stack.push(element(&start)?);
+                    None
+                }
+                Event::Empty(start) => {
+                    let element = element(&start)?;
+                    match stack.last_mut() {
+                        Some(parent) => {
+                            parent.children.push(element);
+                            None
+                        }
+                        None => Some(element),
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().context("Unbalanced XML")?;
+                    match stack.last_mut() {
+                        Some(parent) => {
+                            parent.children.push(element);
+                            None
+                        }
+                        None => Some(element),
+                    }
+                }
+                Event::Eof => bail!("The XML document is empty or truncated"),
+                // Declarations, comments, text…
+                _ => None,
+            };
+            if let Some(root) = done {
+                return Ok(root);
+            }
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn required_attr(&self, name: &str) -> anyhow::Result<&str> {
+        self.attr(name)
+            .with_context(|| format!("<{}> has no {name:?} attribute", self.name))
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
@@ -15,8 +15,9 @@ mod web_sockets;
 mod load_stdin;
 
 pub use self::data_loader::{
-    iter_loaders, register_custom_data_loader, ArchetypeLoader, DataLoader, DataLoaderError,
-    DirectoryLoader, LoadedData, RrdLoader,
+    iter_loaders, register_custom_data_loader, ArchetypeLoader, ArrowTableLoader, ColumnMapping,
+    DataLoader, DataLoaderError, DirectoryLoader, LoadedData, McapLoader, PointCloudLoader,
+    RrdLoader, UrdfLoader, UrdfTree,
 };
 pub use self::data_source::DataSource;
 pub use self::load_file::{extension, load_from_file_contents};
@@ -50,13 +51,21 @@ pub const SUPPORTED_IMAGE_EXTENSIONS: &[&str] = &[
     "pbm", "pgm", "png", "ppm", "tga", "tif", "tiff", "webp",
 ];
 
-pub const SUPPORTED_MESH_EXTENSIONS: &[&str] = &["glb", "gltf", "obj"];
+pub const SUPPORTED_MESH_EXTENSIONS: &[&str] = &["glb", "gltf", "obj", "stl"];
 
-// TODO(#4532): `.ply` data loader should support 2D point cloud & meshes
-pub const SUPPORTED_POINT_CLOUD_EXTENSIONS: &[&str] = &["ply"];
+/// Point clouds, 2D or 3D, and meshes.
+pub const SUPPORTED_POINT_CLOUD_EXTENSIONS: &[&str] = &["ply", "pcd", "las"];
+
+pub const SUPPORTED_ARROW_TABLE_EXTENSIONS: &[&str] = &["arrow", "feather", "parquet"];
 
 pub const SUPPORTED_RERUN_EXTENSIONS: &[&str] = &["rrd"];
 
+/// Robot descriptions.
+pub const SUPPORTED_URDF_EXTENSIONS: &[&str] = &["urdf"];
+
+/// Robotics recordings: MCAP files, and ROS 1 bags.
+pub const SUPPORTED_ROBOTICS_LOG_EXTENSIONS: &[&str] = &["mcap", "bag"];
+
 // TODO(#4555): Add catch-all builtin `DataLoader` for text files
 pub const SUPPORTED_TEXT_EXTENSIONS: &[&str] = &["txt", "md"];
 
@@ -64,18 +73,24 @@ pub const SUPPORTED_TEXT_EXTENSIONS: &[&str] = &["txt", "md"];
 pub fn supported_extensions() -> impl Iterator<Item = &'static str> {
     SUPPORTED_RERUN_EXTENSIONS
         .iter()
+        .chain(SUPPORTED_ARROW_TABLE_EXTENSIONS)
         .chain(SUPPORTED_IMAGE_EXTENSIONS)
         .chain(SUPPORTED_MESH_EXTENSIONS)
         .chain(SUPPORTED_POINT_CLOUD_EXTENSIONS)
         .chain(SUPPORTED_TEXT_EXTENSIONS)
+        .chain(SUPPORTED_URDF_EXTENSIONS)
+        .chain(SUPPORTED_ROBOTICS_LOG_EXTENSIONS)
         .copied()
 }
 
 /// Is this a supported file extension by any of our builtin [`DataLoader`]s?
 pub fn is_supported_file_extension(extension: &str) -> bool {
-    SUPPORTED_IMAGE_EXTENSIONS.contains(&extension)
+    SUPPORTED_ARROW_TABLE_EXTENSIONS.contains(&extension)
+        || SUPPORTED_IMAGE_EXTENSIONS.contains(&extension)
         || SUPPORTED_MESH_EXTENSIONS.contains(&extension)
         || SUPPORTED_POINT_CLOUD_EXTENSIONS.contains(&extension)
         || SUPPORTED_RERUN_EXTENSIONS.contains(&extension)
         || SUPPORTED_TEXT_EXTENSIONS.contains(&extension)
+        || SUPPORTED_URDF_EXTENSIONS.contains(&extension)
+        || SUPPORTED_ROBOTICS_LOG_EXTENSIONS.contains(&extension)
 }
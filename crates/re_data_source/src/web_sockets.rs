@@ -6,16 +6,18 @@ pub fn connect_to_ws_url(
     url: &str,
     on_msg: Option<Box<dyn Fn() + Send + Sync>>,
 ) -> anyhow::Result<Receiver<LogMsg>> {
+    // The url is shown in the UI, so keep any secret out of it.
+    let redacted_url = re_ws_comms::redact_token(url);
     let (tx, rx) = re_smart_channel::smart_channel(
         re_smart_channel::SmartMessageSource::WsClient {
-            ws_server_url: url.to_owned(),
+            ws_server_url: redacted_url.clone(),
         },
         re_smart_channel::SmartChannelSource::WsClient {
-            ws_server_url: url.to_owned(),
+            ws_server_url: redacted_url.clone(),
         },
     );
 
-    re_log::info!("Connecting to WebSocket server at {url:?}…");
+    re_log::info!("Connecting to WebSocket server at {redacted_url:?}…");
 
     let callback = move |binary: Vec<u8>| match re_ws_comms::decode_log_msg(&binary) {
         Ok(log_msg) => {
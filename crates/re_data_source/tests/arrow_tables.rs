@@ -0,0 +1,290 @@
+//! Loading Arrow IPC and Parquet tables with the [`ArrowTableLoader`].
+
+mod common;
+
+use arrow2::{
+    array::{Array, ListArray, PrimitiveArray, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    offset::Offsets,
+};
+use re_data_source::{ArrowTableLoader, ColumnMapping, DataLoaderError};
+use re_data_store::{test_row, DataStore, DataStoreConfig, ExportFormat};
+use re_log_types::{
+    build_frame_nr, DataRow, EntityPath, StoreId, StoreKind, Time, TimeInt, TimeType, Timeline,
+};
+use re_types::components::{Color, InstanceKey, Position2D};
+use re_types::Loggable as _;
+
+use common::natives;
+
+fn load(
+    loader: &ArrowTableLoader,
+    file_name: &str,
+    contents: Vec<u8>,
+) -> Result<Vec<DataRow>, DataLoaderError> {
+    let (rows, result) = common::try_load(loader, file_name, &contents);
+    result.map(|()| rows)
+}
+
+fn write_ipc(schema: Schema, chunk: &Chunk<Box<dyn Array>>) -> Vec<u8> {
+    use arrow2::io::ipc::write::{FileWriter, WriteOptions};
+
+    let mut bytes = Vec::new();
+    let options = WriteOptions { compression: None };
+    let mut writer = FileWriter::try_new(&mut bytes, schema, None, options).unwrap();
+    writer.write(chunk, None).unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+    bytes
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(schema: Schema, chunk: Chunk<Box<dyn Array>>) -> Vec<u8> {
+    use arrow2::io::parquet::write::{
+        transverse, CompressionOptions, Encoding, FileWriter, RowGroupIterator, Version,
+        WriteOptions,
+    };
+
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Uncompressed,
+        version: Version::V2,
+        data_pagesize_limit: None,
+    };
+    let encodings = schema
+        .fields
+        .iter()
+        .map(|field| transverse(&field.data_type, |_| Encoding::Plain))
+        .collect();
+    let row_groups =
+        RowGroupIterator::try_new(std::iter::once(Ok(chunk)), &schema, options, encodings).unwrap();
+
+    let mut bytes = Vec::new();
+    let mut writer = FileWriter::try_new(&mut bytes, schema, options).unwrap();
+    for group in row_groups {
+        writer.write(group.unwrap()).unwrap();
+    }
+    writer.end(None).unwrap();
+    drop(writer);
+    bytes
+}
+
+// --- Tables with a column mapping ---
+
+/// A table that knows nothing about Rerun: one row per frame, a single position per row
+/// (as a plain list, like Parquet would have it) and any number of colors.
+fn plain_table() -> (Schema, Chunk<Box<dyn Array>>) {
+    let list_of = |datatype: DataType| DataType::List(Box::new(Field::new("item", datatype, true)));
+
+    let entity = Utf8Array::<i32>::from([Some("world/points"), Some("world/points"), None]);
+    let frame = PrimitiveArray::<i64>::from_slice([0, 1, 2]);
+    let time = PrimitiveArray::<i64>::from_slice([1, 2, 3])
+        .to(DataType::Timestamp(TimeUnit::Second, None));
+    let position = ListArray::<i32>::new(
+        list_of(DataType::Float32),
+        Offsets::try_from_lengths([2, 2, 2].into_iter())
+            .unwrap()
+            .into(),
+        PrimitiveArray::<f32>::from_slice([0.0, 1.0, 2.0, 3.0, 4.0, 5.0]).boxed(),
+        None,
+    );
+    let colors = ListArray::<i32>::new(
+        list_of(DataType::UInt32),
+        Offsets::try_from_lengths([1, 0, 2].into_iter())
+            .unwrap()
+            .into(),
+        PrimitiveArray::<u32>::from_slice([0xFF00_00FF, 0x00FF_00FF, 0x0000_FFFF]).boxed(),
+        None,
+    );
+    let label = Utf8Array::<i32>::from_slice(["ignored", "ignored", "ignored"]);
+
+    let columns: Vec<Box<dyn Array>> = vec![
+        entity.boxed(),
+        frame.boxed(),
+        time.boxed(),
+        position.boxed(),
+        colors.boxed(),
+        label.boxed(),
+    ];
+    let schema = Schema::from(
+        ["entity", "frame", "time", "position", "colors", "label"]
+            .into_iter()
+            .zip(&columns)
+            .map(|(name, column)| Field::new(name, column.data_type().clone(), true))
+            .collect::<Vec<_>>(),
+    );
+
+    (schema, Chunk::new(columns))
+}
+
+fn plain_table_loader() -> ArrowTableLoader {
+    ArrowTableLoader::with_mapping(
+        ColumnMapping::default()
+            .with_entity_path_column("entity")
+            .with_timeline("frame", TimeType::Sequence)
+            .with_timeline("time", TimeType::Time)
+            .with_component::<Position2D>("position")
+            .with_component::<Color>("colors"),
+    )
+}
+
+fn check_plain_table_rows(file_name: &str, rows: &[DataRow]) {
+    assert_eq!(rows.len(), 3);
+
+    let frame = Timeline::new("frame", TimeType::Sequence);
+    let time = Timeline::new("time", TimeType::Time);
+
+    assert_eq!(rows[0].entity_path(), &EntityPath::from("world/points"));
+    assert_eq!(
+        rows[2].entity_path(),
+        &EntityPath::from_file_path(std::path::Path::new(file_name))
+    );
+
+    for (i, row) in rows.iter().enumerate() {
+        let i = i as i64;
+        assert_eq!(row.timepoint().get(&frame), Some(&TimeInt::from(i)));
+        assert_eq!(
+            row.timepoint().get(&time),
+            Some(&TimeInt::from(Time::from_seconds_since_epoch(
+                (i + 1) as f64
+            )))
+        );
+    }
+
+    assert_eq!(
+        rows.iter().map(natives::<Position2D>).collect::<Vec<_>>(),
+        vec![
+            vec![Position2D::new(0.0, 1.0)],
+            vec![Position2D::new(2.0, 3.0)],
+            vec![Position2D::new(4.0, 5.0)],
+        ]
+    );
+    assert_eq!(
+        rows.iter().map(natives::<Color>).collect::<Vec<_>>(),
+        vec![
+            vec![Color(0xFF00_00FF.into())],
+            vec![],
+            vec![Color(0x00FF_00FF.into()), Color(0x0000_FFFF.into())],
+        ]
+    );
+}
+
+#[test]
+fn mapped_arrow_ipc() {
+    let (schema, chunk) = plain_table();
+    let rows = load(
+        &plain_table_loader(),
+        "table.arrow",
+        write_ipc(schema, &chunk),
+    )
+    .unwrap();
+    check_plain_table_rows("table.arrow", &rows);
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn mapped_parquet() {
+    let (schema, chunk) = plain_table();
+    let rows = load(
+        &plain_table_loader(),
+        "table.parquet",
+        write_parquet(schema, chunk),
+    )
+    .unwrap();
+    check_plain_table_rows("table.parquet", &rows);
+}
+
+#[test]
+fn mapped_missing_column() {
+    let (schema, chunk) = plain_table();
+    let loader = ArrowTableLoader::with_mapping(
+        ColumnMapping::default().with_component::<Position2D>("not_a_column"),
+    );
+    let result = load(&loader, "table.arrow", write_ipc(schema, &chunk));
+    assert!(matches!(result, Err(DataLoaderError::Incompatible(_))));
+}
+
+#[test]
+fn mapped_wrong_datatype() {
+    let (schema, chunk) = plain_table();
+    let loader = ArrowTableLoader::with_mapping(
+        ColumnMapping::default().with_component::<Position2D>("label"),
+    );
+    let err = load(&loader, "table.arrow", write_ipc(schema, &chunk)).unwrap_err();
+    assert!(err.to_string().contains("\"label\""), "{err}");
+}
+
+#[test]
+fn unmapped_plain_table() {
+    let (schema, chunk) = plain_table();
+    let result = load(
+        &ArrowTableLoader::default(),
+        "table.arrow",
+        write_ipc(schema, &chunk),
+    );
+    assert!(matches!(result, Err(DataLoaderError::Incompatible(_))));
+}
+
+// --- Tables written by `rerun export` ---
+
+fn exported_rows() -> Vec<DataRow> {
+    let mut store = DataStore::new(
+        StoreId::random(StoreKind::Recording),
+        InstanceKey::name(),
+        DataStoreConfig::default(),
+    );
+
+    let points = EntityPath::from("world/points");
+    for frame_nr in 0..3 {
+        let positions = [
+            Position2D::new(frame_nr as f32, 1.0),
+            Position2D::new(2.0, 3.0),
+        ];
+        let colors = [Color::from_rgb(255, 0, 0), Color::from_rgb(0, 255, 0)];
+        let row = test_row!(points @ [build_frame_nr(frame_nr.into())] => 2; [
+            positions.as_slice(), colors.as_slice()
+        ]);
+        store.insert_row(&row).unwrap();
+    }
+
+    let tables = store.to_entity_tables().unwrap();
+    tables[&points].to_rows().map(Result::unwrap).collect()
+}
+
+fn check_exported_round_trip(format: ExportFormat) {
+    let rows = exported_rows();
+    let table = re_log_types::DataTable::from_rows(re_log_types::TableId::new(), rows.clone());
+
+    let mut bytes = Vec::new();
+    re_data_store::write_table(&table, format, &mut bytes).unwrap();
+
+    let file_name = format!("world.points.{}", format.file_extension());
+    let loaded = load(&ArrowTableLoader::default(), &file_name, bytes).unwrap();
+
+    let summarize = |rows: &[DataRow]| {
+        rows.iter()
+            .map(|row| {
+                (
+                    row.row_id(),
+                    row.entity_path().clone(),
+                    row.timepoint().clone(),
+                    natives::<Position2D>(row),
+                    natives::<Color>(row),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(summarize(&rows), summarize(&loaded));
+}
+
+#[test]
+fn exported_arrow_ipc() {
+    check_exported_round_trip(ExportFormat::ArrowIpc);
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn exported_parquet() {
+    check_exported_round_trip(ExportFormat::Parquet);
+}
@@ -0,0 +1,54 @@
+//! Helpers shared by the tests of the loaders.
+
+// Each test file only uses some of them.
+#![allow(dead_code)]
+
+use std::borrow::Cow;
+
+use re_data_source::{DataLoader, DataLoaderError, LoadedData};
+use re_log_types::{DataRow, StoreId, StoreKind};
+use re_types::{Component, ComponentName};
+
+/// Loads `contents` as a file named `filename`, returning the rows sent so far whatever the result.
+pub fn try_load(
+    loader: &dyn DataLoader,
+    filename: &str,
+    contents: &[u8],
+) -> (Vec<DataRow>, Result<(), DataLoaderError>) {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let result = loader.load_from_file_contents(
+        StoreId::random(StoreKind::Recording),
+        filename.into(),
+        Cow::Borrowed(contents),
+        tx,
+    );
+    let rows = rx
+        .try_iter()
+        .map(|data| match data {
+            LoadedData::DataRow(row) => row,
+            LoadedData::ArrowMsg(_) | LoadedData::LogMsg(_) => panic!("expected rows only"),
+        })
+        .collect();
+    (rows, result)
+}
+
+/// Like [`try_load`], panicking on failure.
+pub fn load(loader: &dyn DataLoader, filename: &str, contents: &[u8]) -> Vec<DataRow> {
+    let (rows, result) = try_load(loader, filename, contents);
+    result.unwrap();
+    rows
+}
+
+pub fn has_component(row: &DataRow, component: ComponentName) -> bool {
+    row.cells()
+        .iter()
+        .any(|cell| cell.component_name() == component)
+}
+
+/// The values of component `C` in the row, if any.
+pub fn natives<C: Component>(row: &DataRow) -> Vec<C> {
+    row.cells()
+        .iter()
+        .find(|cell| cell.component_name() == C::name())
+        .map_or_else(Vec::new, |cell| cell.to_native::<C>())
+}
@@ -0,0 +1,356 @@
+//! Loading `.mcap` and ROS 1 `.bag` files with the [`McapLoader`].
+
+mod common;
+
+use re_data_source::McapLoader;
+use re_log_types::{DataRow, EntityPath, Timeline};
+use re_types::components::{Position3D, Scalar, TensorData, Text, TextLogLevel};
+use re_types::datatypes::TensorBuffer;
+
+use common::{load, natives, try_load};
+
+fn row_at<'a>(rows: &'a [DataRow], entity_path: &str) -> &'a DataRow {
+    let entity_path = EntityPath::from(entity_path);
+    rows.iter()
+        .find(|row| row.entity_path() == &entity_path)
+        .unwrap_or_else(|| panic!("nothing logged at {entity_path}"))
+}
+
+fn time(row: &DataRow, timeline: &str) -> Option<i64> {
+    row.timepoint()
+        .get(&Timeline::new_temporal(timeline))
+        .map(|time| time.as_i64())
+}
+
+// ---
+
+/// Serializes messages, either the ROS 1 way or as little endian CDR.
+struct Writer {
+    cdr: bool,
+    buffer: Vec<u8>,
+}
+
+impl Writer {
+    fn ros1() -> Self {
+        Self {
+            cdr: false,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn cdr() -> Self {
+        Self {
+            cdr: true,
+            buffer: vec![0x00, 0x01, 0x00, 0x00],
+        }
+    }
+
+    fn align(&mut self, alignment: usize) {
+        if self.cdr {
+            while (self.buffer.len() - 4) % alignment != 0 {
+                self.buffer.push(0);
+            }
+        }
+    }
+
+    fn u8(mut self, value: u8) -> Self {
+        self.buffer.push(value);
+        self
+    }
+
+    fn u32(mut self, value: u32) -> Self {
+        self.align(4);
+        self.buffer.extend(value.to_le_bytes());
+        self
+    }
+
+    fn f32(mut self, value: f32) -> Self {
+        self.align(4);
+        self.buffer.extend(value.to_le_bytes());
+        self
+    }
+
+    fn f64(mut self, value: f64) -> Self {
+        self.align(8);
+        self.buffer.extend(value.to_le_bytes());
+        self
+    }
+
+    fn bytes(mut self, bytes: &[u8]) -> Self {
+        self = self.u32(bytes.len() as u32);
+        self.buffer.extend(bytes);
+        self
+    }
+
+    fn string(self, string: &str) -> Self {
+        if self.cdr {
+            self.bytes(&[string.as_bytes(), &[0]].concat())
+        } else {
+            self.bytes(string.as_bytes())
+        }
+    }
+
+    fn header(self, frame_id: &str) -> Self {
+        let this = if self.cdr { self } else { self.u32(0) };
+        this.u32(1).u32(0).string(frame_id)
+    }
+}
+
+// ---
+
+fn mcap_string(string: &str) -> Vec<u8> {
+    [&(string.len() as u32).to_le_bytes(), string.as_bytes()].concat()
+}
+
+fn mcap_record(mcap: &mut Vec<u8>, opcode: u8, content: &[u8]) {
+    mcap.push(opcode);
+    mcap.extend((content.len() as u64).to_le_bytes());
+    mcap.extend(content);
+}
+
+fn mcap_channel(mcap: &mut Vec<u8>, id: u16, topic: &str, type_name: &str, encoding: &str) {
+    let schema = [
+        &id.to_le_bytes()[..],
+        &mcap_string(type_name),
+        &mcap_string("ros2msg"),
+        &0_u32.to_le_bytes(),
+    ]
+    .concat();
+    mcap_record(mcap, 0x03, &schema);
+
+    let channel = [
+        &id.to_le_bytes()[..],
+        &id.to_le_bytes(),
+        &mcap_string(topic),
+        &mcap_string(encoding),
+        &0_u32.to_le_bytes(),
+    ]
+    .concat();
+    mcap_record(mcap, 0x04, &channel);
+}
+
+fn mcap_message(mcap: &mut Vec<u8>, channel_id: u16, log_time: u64, data: &[u8]) {
+    let message = [
+        &channel_id.to_le_bytes()[..],
+        &0_u32.to_le_bytes(),
+        &log_time.to_le_bytes(),
+        &(log_time - 1).to_le_bytes(),
+        data,
+    ]
+    .concat();
+    mcap_record(mcap, 0x05, &message);
+}
+
+#[test]
+fn mcap() {
+    let mut mcap = b"\x89MCAP0\r\n".to_vec();
+    mcap_record(
+        &mut mcap,
+        0x01,
+        &[mcap_string("ros2"), mcap_string("")].concat(),
+    );
+
+    // Numbers and logs, in an LZ4 compressed chunk.
+    let mut chunk = Vec::new();
+    mcap_channel(&mut chunk, 1, "/speed", "std_msgs/msg/Float64", "cdr");
+    mcap_message(&mut chunk, 1, 10, &Writer::cdr().f64(2.5).buffer);
+    mcap_channel(&mut chunk, 2, "/rosout", "rcl_interfaces/msg/Log", "cdr");
+    let log = Writer::cdr()
+        .u32(1)
+        .u32(0)
+        .u8(30)
+        .string("planner")
+        .string("Too close");
+    mcap_message(&mut chunk, 2, 20, &log.buffer);
+
+    let mut compressed = Vec::new();
+    {
+        use std::io::Write as _;
+        let mut encoder = lz4_flex::frame::FrameEncoder::new(&mut compressed);
+        encoder.write_all(&chunk).unwrap();
+        encoder.finish().unwrap();
+    }
+    let chunk_record = [
+        &10_u64.to_le_bytes()[..],
+        &20_u64.to_le_bytes(),
+        &(chunk.len() as u64).to_le_bytes(),
+        &0_u32.to_le_bytes(),
+        &mcap_string("lz4"),
+        &(compressed.len() as u64).to_le_bytes(),
+        &compressed,
+    ]
+    .concat();
+    mcap_record(&mut mcap, 0x06, &chunk_record);
+
+    // A frame tree, an image, a point cloud, and something else entirely.
+    mcap_channel(&mut mcap, 3, "/tf", "tf2_msgs/msg/TFMessage", "cdr");
+    let mut tf = Writer::cdr().u32(2);
+    for (parent, child, x) in [("map", "odom", 1.0), ("odom", "base_link", 2.0)] {
+        tf = tf.header(parent).string(child);
+        for value in [x, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0] {
+            tf = tf.f64(value);
+        }
+    }
+    mcap_message(&mut mcap, 3, 30, &tf.buffer);
+
+    mcap_channel(
+        &mut mcap,
+        4,
+        "/camera/image",
+        "sensor_msgs/msg/Image",
+        "cdr",
+    );
+    let image = Writer::cdr()
+        .header("camera")
+        .u32(1)
+        .u32(2)
+        .string("bgr8")
+        .u8(0)
+        .u32(8)
+        .bytes(&[1, 2, 3, 4, 5, 6, 0, 0]);
+    mcap_message(&mut mcap, 4, 40, &image.buffer);
+
+    mcap_channel(&mut mcap, 5, "/lidar", "sensor_msgs/msg/PointCloud2", "cdr");
+    let mut cloud = Writer::cdr().header("lidar").u32(1).u32(2).u32(3);
+    for (name, offset) in [("x", 0), ("y", 4), ("z", 8)] {
+        cloud = cloud.string(name).u32(offset).u8(7).u32(1);
+    }
+    let points = [1.0_f32, 2.0, 3.0, f32::NAN, 0.0, 0.0]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect::<Vec<_>>();
+    let cloud = cloud.u8(0).u32(12).u32(24).bytes(&points).u8(1);
+    mcap_message(&mut mcap, 5, 50, &cloud.buffer);
+
+    mcap_channel(&mut mcap, 6, "/gps", "foxglove.LocationFix", "protobuf");
+    mcap_message(&mut mcap, 6, 60, &[0x08, 0x01]);
+    mcap_message(&mut mcap, 6, 70, &[0x08, 0x02]);
+
+    mcap_record(&mut mcap, 0x0F, &0_u32.to_le_bytes());
+
+    let rows = load(&McapLoader, "drive.mcap", &mcap);
+
+    let speed = row_at(&rows, "/speed");
+    assert_eq!(natives::<Scalar>(speed), vec![Scalar(2.5)]);
+    assert_eq!(time(speed, "log_time"), Some(10));
+    assert_eq!(time(speed, "publish_time"), Some(9));
+
+    let log = row_at(&rows, "/rosout");
+    assert_eq!(
+        natives::<Text>(log),
+        vec![Text::from("[planner] Too close")]
+    );
+    assert_eq!(
+        natives::<TextLogLevel>(log),
+        vec![TextLogLevel::from(TextLogLevel::WARN)]
+    );
+
+    // Frames are nested along the tree.
+    assert_eq!(time(row_at(&rows, "tf/map/odom"), "log_time"), Some(30));
+    assert_eq!(
+        time(row_at(&rows, "tf/map/odom/base_link"), "log_time"),
+        Some(30)
+    );
+
+    // Padding is dropped, and blue and red swapped.
+    let tensor = &natives::<TensorData>(row_at(&rows, "/camera/image"))[0].0;
+    assert_eq!(
+        tensor.shape.iter().map(|dim| dim.size).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    assert!(tensor.buffer == TensorBuffer::U8(vec![3, 2, 1, 6, 5, 4].into()));
+
+    assert_eq!(
+        natives::<Position3D>(row_at(&rows, "/lidar")),
+        vec![Position3D::new(1.0, 2.0, 3.0)]
+    );
+
+    // Unsupported channels are listed where their data would have been.
+    let gps = natives::<Text>(row_at(&rows, "/gps"));
+    assert_eq!(gps.len(), 1);
+    for expected in ["foxglove.LocationFix", "protobuf", "Messages: 2"] {
+        assert!(gps[0].as_str().contains(expected), "{}", gps[0].as_str());
+    }
+}
+
+// ---
+
+fn bag_field(name: &str, value: &[u8]) -> Vec<u8> {
+    let field = [name.as_bytes(), b"=", value].concat();
+    [&(field.len() as u32).to_le_bytes(), &field[..]].concat()
+}
+
+fn bag_record(bag: &mut Vec<u8>, header: &[Vec<u8>], data: &[u8]) {
+    let header = header.concat();
+    bag.extend((header.len() as u32).to_le_bytes());
+    bag.extend(header);
+    bag.extend((data.len() as u32).to_le_bytes());
+    bag.extend(data);
+}
+
+fn bag(compression: &str) -> Vec<u8> {
+    let mut chunk = Vec::new();
+    for (conn, topic, message_type) in [
+        (0_u32, "/temperature", "std_msgs/Float32"),
+        (1, "/odom", "nav_msgs/Odometry"),
+    ] {
+        bag_record(
+            &mut chunk,
+            &[
+                bag_field("op", &[0x07]),
+                bag_field("conn", &conn.to_le_bytes()),
+                bag_field("topic", topic.as_bytes()),
+            ],
+            &[
+                bag_field("topic", topic.as_bytes()),
+                bag_field("type", message_type.as_bytes()),
+            ]
+            .concat(),
+        );
+    }
+    for (conn, data) in [
+        (0_u32, Writer::ros1().f32(21.5).buffer),
+        (1, Writer::ros1().header("odom").buffer),
+    ] {
+        let time = [3_u32.to_le_bytes(), 4_u32.to_le_bytes()].concat();
+        bag_record(
+            &mut chunk,
+            &[
+                bag_field("op", &[0x02]),
+                bag_field("conn", &conn.to_le_bytes()),
+                bag_field("time", &time),
+            ],
+            &data,
+        );
+    }
+
+    let mut bag = b"#ROSBAG V2.0\n".to_vec();
+    bag_record(
+        &mut bag,
+        &[
+            bag_field("op", &[0x05]),
+            bag_field("compression", compression.as_bytes()),
+            bag_field("size", &(chunk.len() as u32).to_le_bytes()),
+        ],
+        &chunk,
+    );
+    bag
+}
+
+#[test]
+fn ros1_bag() {
+    let rows = load(&McapLoader, "lab.bag", &bag("none"));
+
+    let temperature = row_at(&rows, "/temperature");
+    assert_eq!(natives::<Scalar>(temperature), vec![Scalar(21.5)]);
+    assert_eq!(time(temperature, "log_time"), Some(3_000_000_004));
+    assert_eq!(time(temperature, "publish_time"), None);
+
+    let odom = natives::<Text>(row_at(&rows, "/odom"));
+    assert!(odom[0].as_str().contains("nav_msgs/Odometry"));
+
+    let (rows, result) = try_load(&McapLoader, "lab.bag", &bag("bz2"));
+    assert!(rows.is_empty());
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("BZ2"), "{err}");
+}
@@ -0,0 +1,174 @@
+//! Loading `.ply` files with the [`ArchetypeLoader`].
+
+mod common;
+
+use re_data_source::ArchetypeLoader;
+use re_log_types::DataRow;
+use re_types::components::{
+    ClassId, Color, MeshProperties, Position2D, Position3D, Text, Vector3D,
+};
+use re_types::Archetype as _;
+
+use common::{has_component, natives};
+
+fn load(contents: &str) -> DataRow {
+    let mut rows = common::load(&ArchetypeLoader, "test.ply", contents.as_bytes());
+    assert_eq!(rows.len(), 1);
+    rows.remove(0)
+}
+
+#[test]
+fn faces_make_a_mesh() {
+    let row = load(
+        "ply
+format ascii 1.0
+element vertex 4
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 0 0 1
+1 0 0 0 0 1
+1 1 0 0 0 1
+0 1 0 0 0 1
+4 0 1 2 3
+",
+    );
+
+    assert!(has_component(
+        &row,
+        re_types::archetypes::Mesh3D::indicator().name()
+    ));
+    assert_eq!(natives::<Position3D>(&row).len(), 4);
+    assert_eq!(
+        natives::<Vector3D>(&row),
+        vec![Vector3D::from([0.0, 0.0, 1.0]); 4]
+    );
+    assert_eq!(
+        natives::<MeshProperties>(&row),
+        vec![MeshProperties::from_triangle_indices([
+            [0, 1, 2],
+            [0, 2, 3]
+        ])]
+    );
+}
+
+#[test]
+fn points_without_z_are_2d() {
+    let row = load(
+        "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float intensity
+property uchar class
+end_header
+0 0 0.5 1
+1 0 0.25 2
+2 0 1.0 1
+",
+    );
+
+    assert!(has_component(
+        &row,
+        re_types::archetypes::Points2D::indicator().name()
+    ));
+    assert_eq!(
+        natives::<Position2D>(&row),
+        vec![
+            Position2D::new(0.0, 0.0),
+            Position2D::new(1.0, 0.0),
+            Position2D::new(2.0, 0.0)
+        ]
+    );
+    assert_eq!(
+        natives::<ClassId>(&row),
+        vec![ClassId::from(1), ClassId::from(2), ClassId::from(1)]
+    );
+    // The class ids decide the colors…
+    assert!(natives::<Color>(&row).is_empty());
+    // …and the intensities are kept as they are.
+    assert!(has_component(&row, "intensity".into()));
+}
+
+#[test]
+fn extra_properties_are_colormapped() {
+    let row = load(
+        "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property ushort intensity
+property uchar label
+property list uchar uchar name
+end_header
+0 0 0 10 1 1 97
+0 0 1 20 2 1 98
+0 0 2 30 3 1 99
+",
+    );
+
+    // An integer label is a class id, which wins over the intensities for coloring.
+    assert_eq!(natives::<ClassId>(&row).len(), 3);
+    assert!(natives::<Color>(&row).is_empty());
+
+    let row = load(
+        "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property ushort intensity
+end_header
+0 0 0 10
+0 0 1 20
+0 0 2 30
+",
+    );
+    assert!(has_component(
+        &row,
+        re_types::archetypes::Points3D::indicator().name()
+    ));
+    let colors = natives::<Color>(&row);
+    assert_eq!(colors.len(), 3);
+    assert_ne!(colors[0], colors[2]);
+    assert!(has_component(&row, "intensity".into()));
+}
+
+#[test]
+fn colors_and_labels() {
+    let row = load(
+        "ply
+format ascii 1.0
+element vertex 2
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+property list uchar uchar label
+end_header
+0 0 0 255 0 0 2 104 105
+1 1 1 0 255 0 1 111
+",
+    );
+
+    assert_eq!(
+        natives::<Color>(&row),
+        vec![Color::from_rgb(255, 0, 0), Color::from_rgb(0, 255, 0)]
+    );
+    assert_eq!(
+        natives::<Text>(&row),
+        vec![Text::from("hi"), Text::from("o")]
+    );
+}
@@ -0,0 +1,222 @@
+//! Loading `.pcd` and `.las` files with the [`PointCloudLoader`].
+
+mod common;
+
+use re_data_source::{DataLoaderError, PointCloudLoader};
+use re_log_types::EntityPath;
+use re_types::components::{ClassId, Color, Position3D, Transform3D};
+
+use common::{has_component, load, natives, try_load};
+
+#[test]
+fn pcd_ascii() {
+    let rows = load(
+        &PointCloudLoader::default(),
+        "cloud.pcd",
+        b"# .PCD v0.7 - Point Cloud Data file format
+VERSION 0.7
+FIELDS x y z rgb label
+SIZE 4 4 4 4 4
+TYPE F F F F U
+COUNT 1 1 1 1 1
+WIDTH 2
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 2
+DATA ascii
+0 0 0 2.3418052e-38 3
+1 2 3 9.1476764e-41 5
+",
+    );
+
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!(row.entity_path(), &EntityPath::from("cloud.pcd"));
+    assert_eq!(
+        natives::<Position3D>(row),
+        vec![
+            Position3D::new(0.0, 0.0, 0.0),
+            Position3D::new(1.0, 2.0, 3.0)
+        ]
+    );
+    // 0x00ff0000 and 0x0000ff00, as floats.
+    assert_eq!(
+        natives::<Color>(row),
+        vec![Color::from_rgb(255, 0, 0), Color::from_rgb(0, 255, 0)]
+    );
+    assert_eq!(
+        natives::<ClassId>(row),
+        vec![ClassId::from(3), ClassId::from(5)]
+    );
+    assert!(!has_component(row, "intensity".into()));
+}
+
+fn pcd_header(data: &str, num_points: usize) -> Vec<u8> {
+    format!(
+        "VERSION .7
+FIELDS x y z intensity
+SIZE 4 4 4 2
+TYPE F F F U
+WIDTH {num_points}
+HEIGHT 1
+POINTS {num_points}
+DATA {data}
+"
+    )
+    .into_bytes()
+}
+
+#[test]
+fn pcd_binary_is_chunked() {
+    let points = [
+        ([0.0_f32, 0.0, 0.0], 10_u16),
+        ([f32::NAN, f32::NAN, f32::NAN], 0),
+        ([1.0, 0.0, 0.0], 20),
+        ([2.0, 0.0, 0.0], 30),
+    ];
+    let mut pcd = pcd_header("binary", points.len());
+    for (position, intensity) in points {
+        pcd.extend(position.iter().flat_map(|c| c.to_le_bytes()));
+        pcd.extend(intensity.to_le_bytes());
+    }
+
+    let loader = PointCloudLoader {
+        max_points_per_row: 2,
+    };
+    let rows = load(&loader, "scan.pcd", &pcd);
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].entity_path(), &EntityPath::from("scan.pcd/0"));
+    assert_eq!(rows[1].entity_path(), &EntityPath::from("scan.pcd/1"));
+
+    // Missing measurements are skipped.
+    assert_eq!(rows[0].num_instances().get(), 1);
+    assert_eq!(rows[1].num_instances().get(), 2);
+
+    // Intensities are colormapped over the whole cloud, and kept.
+    let colors = [natives::<Color>(&rows[0]), natives::<Color>(&rows[1])].concat();
+    assert_eq!(colors.len(), 3);
+    assert_ne!(colors[0], colors[1]);
+    assert_ne!(colors[1], colors[2]);
+    assert!(has_component(&rows[1], "intensity".into()));
+}
+
+#[test]
+fn pcd_binary_compressed() {
+    // Column after column, as PCL does.
+    let mut columns = Vec::new();
+    for axis in 0..3 {
+        for point in 0..2 {
+            columns.extend((point as f32 * (axis + 1) as f32).to_le_bytes());
+        }
+    }
+    columns.extend([7_u16, 7].iter().flat_map(|i| i.to_le_bytes()));
+
+    // LZF: a literal zero, a back-reference repeating it 3 times, then literals.
+    assert_eq!(&columns[..4], &[0; 4]);
+    let mut compressed = vec![0, 0, 1 << 5, 0];
+    compressed.push((columns.len() - 4 - 1) as u8);
+    compressed.extend(&columns[4..]);
+
+    let mut pcd = pcd_header("binary_compressed", 2);
+    pcd.extend((compressed.len() as u32).to_le_bytes());
+    pcd.extend((columns.len() as u32).to_le_bytes());
+    pcd.extend(compressed);
+
+    let rows = load(&PointCloudLoader::default(), "scan.pcd", &pcd);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(
+        natives::<Position3D>(&rows[0]),
+        vec![
+            Position3D::new(0.0, 0.0, 0.0),
+            Position3D::new(1.0, 2.0, 3.0)
+        ]
+    );
+    // All intensities are the same: nothing to colormap.
+    assert!(natives::<Color>(&rows[0]).is_empty());
+}
+
+#[test]
+fn las() {
+    const HEADER_SIZE: usize = 227;
+    const RECORD_LEN: usize = 26;
+
+    let mut las = vec![0_u8; HEADER_SIZE];
+    las[0..4].copy_from_slice(b"LASF");
+    las[24] = 1;
+    las[25] = 2;
+    las[94..96].copy_from_slice(&(HEADER_SIZE as u16).to_le_bytes());
+    las[96..100].copy_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+    las[104] = 2;
+    las[105..107].copy_from_slice(&(RECORD_LEN as u16).to_le_bytes());
+    las[107..111].copy_from_slice(&2_u32.to_le_bytes());
+    // Scales, offsets, then maximum and minimum of each axis.
+    for (at, value) in [
+        (131, 0.01),
+        (139, 0.01),
+        (147, 0.01),
+        (155, 500_000.0),
+        (163, 4_000_000.0),
+        (171, 0.0),
+        (179, 500_001.0),
+        (187, 500_000.0),
+        (195, 4_000_002.0),
+        (203, 4_000_000.0),
+        (211, 3.0),
+        (219, 0.0),
+    ] {
+        las[at..at + 8].copy_from_slice(&f64::to_le_bytes(value));
+    }
+
+    for ([x, y, z], classification, rgb) in [
+        ([0, 0, 0], 2_u8, [255_u16, 0, 0]),
+        ([100, 200, 300], 0x80 | 6, [0, 0, 255]),
+    ] {
+        let mut record = vec![0_u8; RECORD_LEN];
+        record[0..4].copy_from_slice(&i32::to_le_bytes(x));
+        record[4..8].copy_from_slice(&i32::to_le_bytes(y));
+        record[8..12].copy_from_slice(&i32::to_le_bytes(z));
+        record[15] = classification;
+        for (channel, value) in rgb.iter().enumerate() {
+            record[20 + 2 * channel..22 + 2 * channel].copy_from_slice(&value.to_le_bytes());
+        }
+        las.extend(record);
+    }
+
+    let rows = load(&PointCloudLoader::default(), "tile.las", &las);
+    assert_eq!(rows.len(), 2);
+
+    // Positions are relative to the minimum corner…
+    assert_eq!(
+        natives::<Transform3D>(&rows[0]),
+        vec![Transform3D::from(
+            re_types::datatypes::Transform3D::from_translation([500_000.0, 4_000_000.0, 0.0])
+        )]
+    );
+    assert_eq!(
+        natives::<Position3D>(&rows[1]),
+        vec![
+            Position3D::new(0.0, 0.0, 0.0),
+            Position3D::new(1.0, 2.0, 3.0)
+        ]
+    );
+    // …colors are 8 bit here…
+    assert_eq!(
+        natives::<Color>(&rows[1]),
+        vec![Color::from_rgb(255, 0, 0), Color::from_rgb(0, 0, 255)]
+    );
+    // …and classifications lose their flags.
+    assert_eq!(
+        natives::<ClassId>(&rows[1]),
+        vec![ClassId::from(2), ClassId::from(6)]
+    );
+
+    las[104] |= 0x80;
+    let (_, result) = try_load(&PointCloudLoader::default(), "tile.las", &las);
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("LAZ"), "{err}");
+
+    // LAZ files aren't supported yet.
+    let (_, result) = try_load(&PointCloudLoader::default(), "tile.laz", &las);
+    assert!(matches!(result, Err(DataLoaderError::Incompatible(_))));
+}
@@ -0,0 +1,199 @@
+//! Loading `.urdf` files with the [`UrdfLoader`], and moving their joints with [`UrdfTree`].
+
+mod common;
+
+use re_data_source::{UrdfLoader, UrdfTree};
+use re_log_types::{DataRow, EntityPath};
+use re_types::archetypes::{Asset3D, Mesh3D, Transform3D};
+use re_types::components::{Blob, Material, MediaType};
+use re_types::datatypes::{Rotation3D, TranslationRotationScale3D};
+use re_types::{Archetype as _, Component, ComponentName};
+
+const ROBOT: &str = r#"<?xml version="1.0"?>
+<!-- A base, with an arm that turns around z. -->
+<robot name="arm">
+  <material name="blue">
+    <color rgba="0 0 1 1"/>
+  </material>
+
+  <link name="base">
+    <visual>
+      <geometry><box size="1 1 0.2"/></geometry>
+      <material name="blue"/>
+    </visual>
+  </link>
+  <link name="arm">
+    <visual>
+      <origin xyz="0 0 0.5"/>
+      <geometry><cylinder radius="0.1" length="1"/></geometry>
+    </visual>
+    <visual>
+      <geometry><sphere radius="0.2"/></geometry>
+      <material name="red"><color rgba="1 0 0 1"/></material>
+    </visual>
+  </link>
+  <link name="gripper"/>
+
+  <joint name="shoulder" type="revolute">
+    <parent link="base"/>
+    <child link="arm"/>
+    <origin xyz="0 0 0.1" rpy="0 0 1.5707963267948966"/>
+    <axis xyz="0 0 1"/>
+  </joint>
+  <joint name="slide" type="prismatic">
+    <parent link="arm"/>
+    <child link="gripper"/>
+    <origin xyz="0 0 1"/>
+    <axis xyz="1 0 0"/>
+  </joint>
+</robot>
+"#;
+
+fn rows_at<'a>(rows: &'a [DataRow], entity_path: &str) -> Vec<&'a DataRow> {
+    let entity_path = EntityPath::from(entity_path);
+    rows.iter()
+        .filter(|row| row.entity_path() == &entity_path)
+        .collect()
+}
+
+fn has_component(rows: &[&DataRow], component: ComponentName) -> bool {
+    rows.iter().any(|row| common::has_component(row, component))
+}
+
+fn natives<C: Component>(rows: &[&DataRow]) -> Vec<C> {
+    rows.iter()
+        .find(|row| common::has_component(row, C::name()))
+        .map_or_else(Vec::new, |row| common::natives(row))
+}
+
+fn translation_rotation(transform: &Transform3D) -> ([f32; 3], [f32; 4]) {
+    let re_types::datatypes::Transform3D::TranslationRotationScale(TranslationRotationScale3D {
+        translation: Some(translation),
+        rotation: Some(Rotation3D::Quaternion(rotation)),
+        ..
+    }) = transform.transform.0
+    else {
+        panic!("unexpected transform {transform:?}");
+    };
+    (translation.0, rotation.0)
+}
+
+fn assert_close<const N: usize>(actual: [f32; N], expected: [f32; N]) {
+    assert!(
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(a, e)| (a - e).abs() < 1e-6),
+        "{actual:?} != {expected:?}"
+    );
+}
+
+#[test]
+fn one_entity_per_link() {
+    let rows = common::load(&UrdfLoader, "arm.urdf", ROBOT.as_bytes());
+
+    // Links are nested along the joints, which are their transforms.
+    assert!(rows_at(&rows, "arm.urdf/base").is_empty());
+    let arm = rows_at(&rows, "arm.urdf/base/arm");
+    let transforms = natives::<re_types::components::Transform3D>(&arm);
+    let (translation, rotation) = translation_rotation(&Transform3D::new(transforms[0].0));
+    assert_close(translation, [0.0, 0.0, 0.1]);
+    let half_sqrt = std::f32::consts::FRAC_1_SQRT_2;
+    assert_close(rotation, [0.0, 0.0, half_sqrt, half_sqrt]);
+    assert_eq!(rows_at(&rows, "arm.urdf/base/arm/gripper").len(), 1);
+
+    // Visuals are child entities, and primitives are meshes.
+    let base_visual = rows_at(&rows, "arm.urdf/base/visual_0");
+    assert!(has_component(&base_visual, Mesh3D::indicator().name()));
+    assert_eq!(
+        natives::<Material>(&base_visual),
+        vec![Material::from_albedo_factor(
+            re_types::datatypes::Rgba32::from_rgb(0, 0, 255)
+        )]
+    );
+    let arm_visuals = [
+        rows_at(&rows, "arm.urdf/base/arm/visual_0"),
+        rows_at(&rows, "arm.urdf/base/arm/visual_1"),
+    ];
+    assert!(arm_visuals
+        .iter()
+        .all(|rows| has_component(rows, Mesh3D::indicator().name())));
+    assert!(natives::<Material>(&arm_visuals[0]).is_empty());
+    assert_eq!(natives::<Material>(&arm_visuals[1]).len(), 1);
+}
+
+#[test]
+fn meshes_are_found_in_packages() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let dir = tempdir.path();
+    std::fs::create_dir_all(dir.join("robot_description/urdf")).unwrap();
+    std::fs::create_dir_all(dir.join("robot_description/meshes")).unwrap();
+    std::fs::write(
+        dir.join("robot_description/meshes/base.stl"),
+        "solid base\nendsolid base\n",
+    )
+    .unwrap();
+    let urdf_path = dir.join("robot_description/urdf/robot.urdf");
+    std::fs::write(
+        &urdf_path,
+        r#"<robot name="robot">
+  <link name="base">
+    <visual>
+      <geometry><mesh filename="package://robot_description/meshes/base.stl" scale="0.001 0.001 0.001"/></geometry>
+    </visual>
+    <visual>
+      <geometry><mesh filename="package://robot_description/meshes/missing.stl"/></geometry>
+    </visual>
+    <visual>
+      <geometry><mesh filename="../meshes/base.stl"/></geometry>
+    </visual>
+  </link>
+</robot>"#,
+    )
+    .unwrap();
+
+    let robot = UrdfTree::from_file_path(&urdf_path)
+        .unwrap()
+        .with_entity_path("robot".into())
+        .unwrap();
+    let rows = robot.to_data_rows().unwrap();
+
+    let found = rows_at(&rows, "robot/base/visual_0");
+    assert!(has_component(&found, Asset3D::indicator().name()));
+    assert_eq!(natives::<MediaType>(&found), vec![MediaType::stl()]);
+    assert_eq!(
+        natives::<Blob>(&found),
+        vec![Blob::from(b"solid base\nendsolid base\n".to_vec())]
+    );
+
+    // Missing meshes are skipped, but not the rest of the robot.
+    let missing = rows_at(&rows, "robot/base/visual_1");
+    assert!(!has_component(&missing, Asset3D::indicator().name()));
+    let relative = rows_at(&rows, "robot/base/visual_2");
+    assert!(has_component(&relative, Asset3D::indicator().name()));
+}
+
+#[test]
+fn joints_move() {
+    let robot = UrdfTree::from_file_contents("arm.urdf".as_ref(), ROBOT.as_bytes()).unwrap();
+    assert_eq!(
+        robot.joint_names().collect::<Vec<_>>(),
+        vec!["shoulder", "slide"]
+    );
+
+    // A quarter turn more around z makes it a half turn.
+    let (entity_path, transform) = robot
+        .joint_transform("shoulder", std::f64::consts::FRAC_PI_2)
+        .unwrap();
+    assert_eq!(entity_path, EntityPath::from("arm.urdf/base/arm"));
+    let (translation, rotation) = translation_rotation(&transform);
+    assert_close(translation, [0.0, 0.0, 0.1]);
+    assert_close(rotation, [0.0, 0.0, 1.0, 0.0]);
+
+    let (entity_path, transform) = robot.joint_transform("slide", 0.25).unwrap();
+    assert_eq!(entity_path, EntityPath::from("arm.urdf/base/arm/gripper"));
+    let (translation, _) = translation_rotation(&transform);
+    assert_close(translation, [0.25, 0.0, 1.0]);
+
+    assert!(robot.joint_transform("elbow", 0.0).is_err());
+}
@@ -19,11 +19,13 @@ mod store;
 mod store_arrow;
 mod store_dump;
 mod store_event;
+mod store_export;
 mod store_format;
 mod store_gc;
 mod store_helpers;
 mod store_read;
 mod store_sanity;
+mod store_spill;
 mod store_stats;
 mod store_subscriber;
 mod store_write;
@@ -40,9 +42,13 @@ pub mod test_util;
 pub use self::arrow_util::ArrayExt;
 pub use self::store::{DataStore, DataStoreConfig, StoreGeneration};
 pub use self::store_event::{StoreDiff, StoreDiffKind, StoreEvent};
-pub use self::store_gc::{GarbageCollectionOptions, GarbageCollectionTarget};
+pub use self::store_export::{write_table, ExportError, ExportFormat, ExportResult};
+pub use self::store_gc::{
+    GarbageCollectionOptions, GarbageCollectionTarget, RetentionPolicy, RetentionWindow,
+};
 pub use self::store_helpers::VersionedComponent;
 pub use self::store_read::{LatestAtQuery, RangeQuery};
+pub use self::store_spill::{BucketColumns, SpillError, SpillResult, SpilledColumns};
 pub use self::store_stats::{DataStoreRowStats, DataStoreStats, EntityStats};
 pub use self::store_subscriber::{StoreSubscriber, StoreSubscriberHandle};
 pub use self::store_write::{WriteError, WriteResult};
@@ -1,5 +1,5 @@
 use std::collections::{BTreeMap, VecDeque};
-use std::sync::atomic::AtomicU64;
+use std::sync::{atomic::AtomicU64, Arc};
 
 use arrow2::datatypes::DataType;
 use nohash_hasher::IntMap;
@@ -10,6 +10,11 @@ use re_log_types::{
 };
 use re_types_core::{ComponentName, ComponentNameSet, SizeBytes};
 
+use crate::{
+    store_spill::{SharedSpillCache, SpillCache, SpilledColumns},
+    RetentionPolicy,
+};
+
 // --- Data store ---
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -45,6 +50,21 @@ pub struct DataStoreConfig {
     ///
     /// Enabled by default in debug builds.
     pub enable_typecheck: bool,
+
+    /// If set, every [`DataStore::gc`] also drops the data outside of this retention window,
+    /// whatever its target.
+    ///
+    /// Disabled by default.
+    pub retention: Option<RetentionPolicy>,
+
+    /// How much of the data spilled to disk (see [`DataStore::spill_to_disk`]) is kept in memory
+    /// once read back, so that repeated queries don't read it back every time.
+    ///
+    /// Counted in [`crate::DataStoreStats::spill_cache`], and dropped first by
+    /// [`DataStore::spill_to_disk`]. `0` disables the cache.
+    ///
+    /// See [`Self::DEFAULT`] for defaults.
+    pub spill_cache_max_bytes: u64,
 }
 
 impl Default for DataStoreConfig {
@@ -64,6 +84,8 @@ impl DataStoreConfig {
         indexed_bucket_num_rows: 512,
         store_insert_ids: cfg!(debug_assertions),
         enable_typecheck: cfg!(debug_assertions),
+        retention: None,
+        spill_cache_max_bytes: 256 * 1024 * 1024,
     };
 }
 
@@ -227,6 +249,11 @@ pub struct DataStore {
 
     /// Monotonically increasing ID for store events.
     pub(crate) event_id: AtomicU64,
+
+    /// The spilled data read back from disk most recently.
+    ///
+    /// Shared with the clones of this store, which share its spilled buckets too.
+    pub(crate) spill_cache: SharedSpillCache,
 }
 
 impl Clone for DataStore {
@@ -244,6 +271,7 @@ impl Clone for DataStore {
             query_id: Default::default(),
             gc_id: Default::default(),
             event_id: Default::default(),
+            spill_cache: self.spill_cache.clone(),
         }
     }
 }
@@ -251,6 +279,9 @@ impl Clone for DataStore {
 impl DataStore {
     /// See [`Self::cluster_key`] for more information about the cluster key.
     pub fn new(id: StoreId, cluster_key: ComponentName, config: DataStoreConfig) -> Self {
+        let spill_cache = Arc::new(parking_lot::Mutex::new(SpillCache::new(
+            config.spill_cache_max_bytes,
+        )));
         Self {
             id,
             cluster_key,
@@ -264,6 +295,7 @@ impl DataStore {
             query_id: AtomicU64::new(0),
             gc_id: 0,
             event_id: AtomicU64::new(0),
+            spill_cache,
         }
     }
 
@@ -360,6 +392,8 @@ fn datastore_internal_repr() {
             indexed_bucket_num_rows: 0,
             store_insert_ids: true,
             enable_typecheck: true,
+            retention: None,
+            spill_cache_max_bytes: 0,
         },
     );
 
@@ -566,6 +600,12 @@ pub struct IndexedBucketInner {
     ///
     /// We cache this because there can be many, many buckets.
     pub size_bytes: u64,
+
+    /// If set, all component columns but the cluster key's live on disk rather than in
+    /// [`Self::columns`].
+    ///
+    /// See [`DataStore::spill_to_disk`].
+    pub spilled: Option<Arc<SpilledColumns>>,
 }
 
 impl Default for IndexedBucketInner {
@@ -580,6 +620,7 @@ impl Default for IndexedBucketInner {
             col_num_instances: Default::default(),
             columns: Default::default(),
             size_bytes: 0, // NOTE: computed below
+            spilled: None,
         };
         this.compute_size_bytes();
         this
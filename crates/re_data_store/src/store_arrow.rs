@@ -8,6 +8,7 @@ use re_types_core::ComponentName;
 use crate::store::{
     IndexedBucket, IndexedBucketInner, PersistentIndexedTable, PersistentIndexedTableInner,
 };
+use crate::SpillResult;
 
 // ---
 
@@ -21,7 +22,7 @@ impl IndexedBucket {
     /// - `num_instances`
     /// - `$cluster_key`
     /// - rest of component columns in ascending lexical order
-    pub fn serialize(&self) -> DataTableResult<(Schema, Chunk<Box<dyn Array>>)> {
+    pub fn serialize(&self) -> SpillResult<(Schema, Chunk<Box<dyn Array>>)> {
         re_tracing::profile_function!();
 
         let Self {
@@ -30,6 +31,7 @@ impl IndexedBucket {
             inner,
         } = self;
 
+        let inner = &*inner.read();
         let IndexedBucketInner {
             is_sorted: _,
             time_range: _,
@@ -38,18 +40,19 @@ impl IndexedBucket {
             col_row_id,
             max_row_id: _,
             col_num_instances,
-            columns,
+            columns: _,
             size_bytes: _,
-        } = &*inner.read();
+            spilled: _,
+        } = inner;
 
-        serialize(
+        Ok(serialize(
             cluster_key,
             Some((*timeline, col_time)),
             col_insert_id,
             col_row_id,
             col_num_instances,
-            columns,
-        )
+            &*inner.load_columns()?,
+        )?)
     }
 }
 
@@ -117,7 +117,7 @@ impl DataStore {
         self.tables.values().flat_map(|table| {
             re_tracing::profile_scope!("temporal_table");
 
-            table.buckets.values().map(move |bucket| {
+            table.buckets.values().filter_map(move |bucket| {
                 re_tracing::profile_scope!("temporal_bucket");
 
                 bucket.sort_indices_if_needed();
@@ -128,6 +128,7 @@ impl DataStore {
                     inner,
                 } = bucket;
 
+                let inner = &*inner.read();
                 let IndexedBucketInner {
                     is_sorted: _,
                     time_range: _,
@@ -136,11 +137,16 @@ impl DataStore {
                     col_row_id,
                     max_row_id: _,
                     col_num_instances,
-                    columns,
+                    columns: _,
                     size_bytes: _,
-                } = &*inner.read();
-
-                DataTable {
+                    spilled: _,
+                } = inner;
+                let columns = inner
+                    .load_columns()
+                    .map_err(|err| re_log::error_once!("Failed to read back spilled data: {err}"))
+                    .ok()?;
+
+                Some(DataTable {
                     table_id: TableId::new(),
                     col_row_id: col_row_id.clone(),
                     col_timelines: [(*timeline, col_time.iter().copied().map(Some).collect())]
@@ -149,8 +155,11 @@ impl DataStore {
                         .take(col_row_id.len())
                         .collect(),
                     col_num_instances: col_num_instances.clone(),
-                    columns: columns.clone().into_iter().collect(), // shallow
-                }
+                    columns: columns
+                        .iter()
+                        .map(|(component, column)| (*component, column.clone())) // shallow
+                        .collect(),
+                })
             })
         })
     }
@@ -179,6 +188,7 @@ impl DataStore {
                         inner,
                     } = bucket;
 
+                    let inner = &*inner.read();
                     let IndexedBucketInner {
                         is_sorted: _,
                         time_range,
@@ -187,9 +197,10 @@ impl DataStore {
                         col_row_id,
                         max_row_id: _,
                         col_num_instances,
-                        columns,
+                        columns: _,
                         size_bytes: _,
-                    } = &*inner.read();
+                        spilled: _,
+                    } = inner;
 
                     if !time_range.intersects(time_filter) {
                         return None;
@@ -220,8 +231,14 @@ impl DataStore {
                     let col_num_instances =
                         filter_column(col_time, col_num_instances.iter(), time_filter).collect();
 
+                    let columns = inner
+                        .load_columns()
+                        .map_err(|err| {
+                            re_log::error_once!("Failed to read back spilled data: {err}");
+                        })
+                        .ok()?;
                     let mut columns2 = BTreeMap::default();
-                    for (component, column) in columns {
+                    for (component, column) in columns.iter() {
                         let column = filter_column(col_time, column.iter(), time_filter).collect();
                         columns2.insert(*component, DataCellColumn(column));
                     }
@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use arrow2::{
+    array::Array,
+    chunk::Chunk,
+    datatypes::{Field, Schema},
+};
+use re_log_types::{DataReadError, DataTable, DataTableError, EntityPath, TableId};
+
+use crate::DataStore;
+
+// ---
+
+/// File format used by [`DataStore::export_to_dir`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Arrow IPC files, a.k.a. Feather v2.
+    ArrowIpc,
+
+    /// Apache Parquet.
+    ///
+    /// Fixed-size lists are written as regular lists, with their original datatype kept in the
+    /// field metadata (see [`re_log_types::METADATA_ORIGINAL_DATATYPE`]).
+    /// Columns with datatypes Parquet cannot represent (e.g. unions) are skipped with a warning.
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Self::ArrowIpc => "arrow",
+            #[cfg(feature = "parquet")]
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error("Failed to write {0:?}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("Entities {0} and {1} would be written to the same file")]
+    FileNameCollision(EntityPath, EntityPath),
+
+    #[error(transparent)]
+    DataRead(#[from] DataReadError),
+
+    #[error(transparent)]
+    DataTable(#[from] DataTableError),
+
+    #[error(transparent)]
+    Arrow(#[from] arrow2::error::Error),
+}
+
+pub type ExportResult<T> = ::std::result::Result<T, ExportError>;
+
+impl DataStore {
+    /// Serializes the entire datastore into one [`DataTable`] per entity.
+    ///
+    /// Every timeline becomes its own column, and each component its own column.
+    /// Timeless rows have no value in any of the time columns.
+    ///
+    /// Beware: this is extremely costly, don't use this in hot paths.
+    pub fn to_entity_tables(
+        &self,
+    ) -> re_log_types::DataReadResult<BTreeMap<EntityPath, DataTable>> {
+        re_tracing::profile_function!();
+
+        let mut rows_per_entity: BTreeMap<EntityPath, Vec<_>> = BTreeMap::new();
+        for row in self.to_rows()? {
+            rows_per_entity
+                .entry(row.entity_path().clone())
+                .or_default()
+                .push(row);
+        }
+
+        Ok(rows_per_entity
+            .into_iter()
+            .map(|(entity_path, rows)| (entity_path, DataTable::from_rows(TableId::new(), rows)))
+            .collect())
+    }
+
+    /// Writes the contents of the datastore to `dir`, one file per entity.
+    ///
+    /// See [`Self::to_entity_tables`] for the layout of each file.
+    /// The directory is created if needed.
+    ///
+    /// Returns the paths of all written files.
+    pub fn export_to_dir(&self, dir: &Path, format: ExportFormat) -> ExportResult<Vec<PathBuf>> {
+        re_tracing::profile_function!();
+
+        std::fs::create_dir_all(dir).map_err(|err| ExportError::Io(dir.to_owned(), err))?;
+
+        // Lowercase file names, to catch collisions on case-insensitive file systems.
+        let mut taken: BTreeMap<String, EntityPath> = BTreeMap::new();
+
+        let mut paths = Vec::new();
+        for (entity_path, table) in self.to_entity_tables()? {
+            let file_name = format!(
+                "{}.{}",
+                entity_file_stem(&entity_path),
+                format.file_extension()
+            );
+            if let Some(other) = taken.insert(file_name.to_lowercase(), entity_path.clone()) {
+                return Err(ExportError::FileNameCollision(other, entity_path));
+            }
+
+            // NOTE: not `with_extension`, the stem itself contains dots.
+            let path = dir.join(file_name);
+
+            let file =
+                std::fs::File::create(&path).map_err(|err| ExportError::Io(path.clone(), err))?;
+            write_table(&table, format, std::io::BufWriter::new(file))?;
+
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+}
+
+/// `/world/points` becomes `world.points`, the root entity `@root`.
+///
+/// Anything but ASCII letters, digits, `-` and `_` is percent-encoded, so that distinct entities
+/// never share a file name: `/a.b` becomes `a%2Eb` and `/a/b` becomes `a.b`.
+fn entity_file_stem(entity_path: &EntityPath) -> String {
+    if entity_path.is_root() {
+        // Can't collide: `@` is always percent-encoded in other entities.
+        return "@root".to_owned();
+    }
+
+    let mut stem = String::new();
+    for (i, part) in entity_path.iter().enumerate() {
+        if i != 0 {
+            stem.push('.');
+        }
+        for byte in part.unescaped_str().bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+                stem.push(byte as char);
+            } else {
+                stem.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+    stem
+}
+
+/// Writes a single [`DataTable`] in the given format.
+///
+/// Columns keep Rerun's field metadata, so the file can be loaded back as a [`DataTable`].
+pub fn write_table(
+    table: &DataTable,
+    format: ExportFormat,
+    write: impl std::io::Write,
+) -> ExportResult<()> {
+    re_tracing::profile_function!();
+
+    let (mut schema, chunk) = table.serialize()?;
+
+    // Time columns are sparse once timeless rows and rows from other timelines are mixed in.
+    for field in &mut schema.fields {
+        if is_time_field(field) {
+            field.is_nullable = true;
+        }
+    }
+
+    match format {
+        ExportFormat::ArrowIpc => write_ipc(schema, &chunk, write),
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet => write_parquet(schema, chunk, write),
+    }
+}
+
+fn is_time_field(field: &Field) -> bool {
+    field
+        .metadata
+        .get(re_log_types::METADATA_KIND)
+        .map(String::as_str)
+        == Some(re_log_types::METADATA_KIND_TIME)
+}
+
+fn write_ipc(
+    schema: Schema,
+    chunk: &Chunk<Box<dyn Array>>,
+    write: impl std::io::Write,
+) -> ExportResult<()> {
+    use arrow2::io::ipc::write::{FileWriter, WriteOptions};
+
+    let options = WriteOptions { compression: None };
+    let mut writer = FileWriter::try_new(write, schema, None, options)?;
+    writer.write(chunk, None)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(
+    schema: Schema,
+    chunk: Chunk<Box<dyn Array>>,
+    write: impl std::io::Write,
+) -> ExportResult<()> {
+    use arrow2::io::parquet::write::{
+        to_parquet_type, transverse, CompressionOptions, Encoding, FileWriter, RowGroupIterator,
+        Version, WriteOptions,
+    };
+
+    // Convert the columns that Parquet can't represent as-is, and drop those it can't represent at all.
+    let (fields, columns): (Vec<_>, Vec<_>) = schema
+        .fields
+        .into_iter()
+        .zip(chunk.into_arrays())
+        .filter_map(|(field, column)| {
+            let converted = to_parquet_compatible(column.as_ref())
+                .map(|column| {
+                    let mut converted_field = Field {
+                        data_type: column.data_type().clone(),
+                        ..field.clone()
+                    };
+                    // So that loaders can convert it back, see `ArrowTableLoader`.
+                    if converted_field.data_type != field.data_type {
+                        converted_field.metadata.insert(
+                            re_log_types::METADATA_ORIGINAL_DATATYPE.to_owned(),
+                            DataTable::encode_datatype(&field.data_type),
+                        );
+                    }
+                    (converted_field, column)
+                })
+                .filter(|(field, _)| to_parquet_type(field).is_ok());
+            if converted.is_none() {
+                re_log::warn_once!(
+                    "Column {:?} of type {:?} can't be written to Parquet, skipping it",
+                    field.name,
+                    field.data_type
+                );
+            }
+            converted
+        })
+        .unzip();
+    let schema = Schema {
+        fields,
+        metadata: schema.metadata,
+    };
+    let chunk = Chunk::new(columns);
+
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Uncompressed,
+        version: Version::V2,
+        data_pagesize_limit: None,
+    };
+    let encodings = schema
+        .fields
+        .iter()
+        .map(|field| transverse(&field.data_type, |_| Encoding::Plain))
+        .collect();
+
+    let row_groups =
+        RowGroupIterator::try_new(std::iter::once(Ok(chunk)), &schema, options, encodings)?;
+
+    let mut writer = FileWriter::try_new(write, schema, options)?;
+    for group in row_groups {
+        writer.write(group?)?;
+    }
+    writer.end(None)?;
+
+    Ok(())
+}
+
+/// Rewrites an array into something `arrow2` can write to Parquet: fixed-size lists, e.g. for
+/// `Vec2D` and `Vec3D`, become regular lists, at any depth.
+///
+/// Returns `None` for datatypes Parquet can't represent at all, such as unions.
+/// `arrow2` panics when writing those, so we must check ahead of time.
+#[cfg(feature = "parquet")]
+fn to_parquet_compatible(array: &dyn Array) -> Option<Box<dyn Array>> {
+    use arrow2::{
+        array::{FixedSizeListArray, ListArray, StructArray},
+        datatypes::DataType,
+        offset::Offsets,
+    };
+
+    fn with_type(field: &Field, data_type: &DataType) -> Field {
+        Field {
+            data_type: data_type.clone(),
+            ..field.clone()
+        }
+    }
+
+    match array.data_type().to_logical_type() {
+        DataType::Union(..) | DataType::Map(..) => None,
+
+        DataType::FixedSizeList(field, size) => {
+            let array = array.as_any().downcast_ref::<FixedSizeListArray>()?;
+            let values = to_parquet_compatible(array.values().as_ref())?;
+            let offsets =
+                Offsets::<i32>::try_from_lengths(std::iter::repeat(*size).take(array.len()))
+                    .ok()?;
+            let data_type = DataType::List(Box::new(with_type(field, values.data_type())));
+            Some(
+                ListArray::new(data_type, offsets.into(), values, array.validity().cloned())
+                    .boxed(),
+            )
+        }
+
+        DataType::List(field) => {
+            let array = array.as_any().downcast_ref::<ListArray<i32>>()?;
+            let values = to_parquet_compatible(array.values().as_ref())?;
+            let data_type = DataType::List(Box::new(with_type(field, values.data_type())));
+            Some(
+                ListArray::new(
+                    data_type,
+                    array.offsets().clone(),
+                    values,
+                    array.validity().cloned(),
+                )
+                .boxed(),
+            )
+        }
+
+        DataType::LargeList(field) => {
+            let array = array.as_any().downcast_ref::<ListArray<i64>>()?;
+            let values = to_parquet_compatible(array.values().as_ref())?;
+            let data_type = DataType::LargeList(Box::new(with_type(field, values.data_type())));
+            Some(
+                ListArray::new(
+                    data_type,
+                    array.offsets().clone(),
+                    values,
+                    array.validity().cloned(),
+                )
+                .boxed(),
+            )
+        }
+
+        DataType::Struct(fields) => {
+            let array = array.as_any().downcast_ref::<StructArray>()?;
+            let values = array
+                .values()
+                .iter()
+                .map(|values| to_parquet_compatible(values.as_ref()))
+                .collect::<Option<Vec<_>>>()?;
+            let fields = fields
+                .iter()
+                .zip(&values)
+                .map(|(field, values)| with_type(field, values.data_type()))
+                .collect();
+            Some(
+                StructArray::new(DataType::Struct(fields), values, array.validity().cloned())
+                    .boxed(),
+            )
+        }
+
+        _ => Some(array.to_boxed()),
+    }
+}
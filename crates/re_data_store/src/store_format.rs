@@ -21,6 +21,7 @@ impl std::fmt::Display for DataStore {
             query_id: _,
             gc_id: _,
             event_id: _,
+            spill_cache: _,
         } = self;
 
         f.write_str("DataStore {\n")?;
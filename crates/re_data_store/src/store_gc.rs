@@ -5,7 +5,8 @@ use web_time::Instant;
 
 use nohash_hasher::IntMap;
 use re_log_types::{
-    EntityPath, EntityPathHash, RowId, TimePoint, TimeRange, Timeline, VecDequeRemovalExt as _,
+    EntityPath, EntityPathHash, RowId, TimeInt, TimePoint, TimeRange, TimeType, Timeline,
+    TimelineName, VecDequeRemovalExt as _,
 };
 use re_types_core::{ComponentName, SizeBytes as _};
 
@@ -28,6 +29,119 @@ pub enum GarbageCollectionTarget {
 
     /// GC Everything that isn't protected
     Everything,
+
+    /// Drop all rows that fall outside of the retention window.
+    ///
+    /// Rows without a time on the policy's timeline are left untouched.
+    Retention(RetentionPolicy),
+}
+
+/// Keep only the latest `window` worth of data on a given timeline.
+///
+/// The window is relative to the latest time on that timeline across the whole store, not to
+/// the wall-clock.
+///
+/// Parsed from strings such as `60s@log_time` (temporal) or `1000@frame_nr` (sequence).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// The timeline is looked up by name in the store, whatever its type.
+    pub timeline: TimelineName,
+
+    pub window: RetentionWindow,
+}
+
+/// How much of a timeline a [`RetentionPolicy`] retains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionWindow {
+    /// A number of raw time units: steps for sequence timelines, nanoseconds for temporal ones.
+    Steps(i64),
+
+    /// A duration in nanoseconds, only valid for temporal timelines.
+    Duration(i64),
+}
+
+impl RetentionPolicy {
+    /// The window in the units of a timeline of the given type, if it applies to that type.
+    pub fn window_for(&self, typ: TimeType) -> Option<i64> {
+        match (self.window, typ) {
+            (RetentionWindow::Steps(window), _)
+            | (RetentionWindow::Duration(window), TimeType::Time) => Some(window),
+            (RetentionWindow::Duration(_), TimeType::Sequence) => None,
+        }
+    }
+}
+
+/// Units accepted in a [`RetentionWindow::Duration`], from largest to smallest.
+const DURATION_UNITS: [(&str, i64); 6] = [
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+impl std::str::FromStr for RetentionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((window, timeline)) = s.split_once('@') else {
+            return Err(format!(
+                "Expected a retention policy such as '60s@log_time' or '1000@frame_nr', got {s:?}"
+            ));
+        };
+
+        if timeline.is_empty() {
+            return Err(format!("Missing timeline name in retention policy {s:?}"));
+        }
+
+        let digits_end = window
+            .find(|c: char| !c.is_ascii_digit() && c != '-')
+            .unwrap_or(window.len());
+        let (number, unit) = window.split_at(digits_end);
+        let number: i64 = number
+            .parse()
+            .map_err(|_ignored| format!("Expected an integer retention window, got {s:?}"))?;
+        if number <= 0 {
+            return Err(format!("Retention window must be positive, got {s:?}"));
+        }
+
+        let window = if unit.is_empty() {
+            RetentionWindow::Steps(number)
+        } else {
+            let Some((_, nanos)) = DURATION_UNITS.iter().find(|(name, _)| *name == unit) else {
+                return Err(format!(
+                    "Unknown unit {unit:?} in retention policy {s:?}, expected one of h, m, s, ms, us or ns"
+                ));
+            };
+            RetentionWindow::Duration(
+                number
+                    .checked_mul(*nanos)
+                    .ok_or_else(|| format!("Retention window is too large: {s:?}"))?,
+            )
+        };
+
+        Ok(Self {
+            timeline: timeline.into(),
+            window,
+        })
+    }
+}
+
+impl std::fmt::Display for RetentionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.window {
+            RetentionWindow::Steps(steps) => write!(f, "{steps}@{}", self.timeline),
+            RetentionWindow::Duration(nanos) => {
+                // The largest unit that represents the window exactly, so that this round-trips.
+                let (unit, unit_nanos) = DURATION_UNITS
+                    .iter()
+                    .find(|(_, unit_nanos)| nanos % unit_nanos == 0)
+                    .unwrap_or(&("ns", 1));
+                write!(f, "{}{unit}@{}", nanos / unit_nanos, self.timeline)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -76,6 +190,20 @@ impl GarbageCollectionOptions {
             enable_batching: false,
         }
     }
+
+    /// Drop everything outside of the given retention window, but keep the latest value of
+    /// every component so that latest-at queries at the end of time are unaffected.
+    pub fn retention(policy: RetentionPolicy) -> Self {
+        GarbageCollectionOptions {
+            target: GarbageCollectionTarget::Retention(policy),
+            time_budget: std::time::Duration::MAX,
+            gc_timeless: false,
+            protect_latest: 1,
+            purge_empty_tables: false,
+            dont_protect: Default::default(),
+            enable_batching: false,
+        }
+    }
 }
 
 impl std::fmt::Display for GarbageCollectionTarget {
@@ -85,6 +213,7 @@ impl std::fmt::Display for GarbageCollectionTarget {
                 write!(f, "DropAtLeast({:.3}%)", re_format::format_f64(*p * 100.0))
             }
             GarbageCollectionTarget::Everything => write!(f, "Everything"),
+            GarbageCollectionTarget::Retention(policy) => write!(f, "Retention({policy})"),
         }
     }
 }
@@ -101,6 +230,8 @@ impl DataStore {
     ///
     /// Returns the list of `RowId`s that were purged from the store.
     ///
+    /// Whatever the target, the data outside of [`crate::DataStoreConfig::retention`] is dropped too.
+    ///
     /// ## Semantics
     ///
     /// Garbage collection works on a row-level basis and is driven by [`RowId`] order,
@@ -150,7 +281,7 @@ impl DataStore {
                     "starting GC"
                 );
 
-                self.gc_drop_at_least_num_bytes(options, num_bytes_to_drop, &protected_rows)
+                self.gc_drop_at_least_num_bytes(options, num_bytes_to_drop, &protected_rows, None)
             }
             GarbageCollectionTarget::Everything => {
                 re_log::trace!(
@@ -162,10 +293,30 @@ impl DataStore {
                     "starting GC"
                 );
 
-                self.gc_drop_at_least_num_bytes(options, f64::INFINITY, &protected_rows)
+                self.gc_drop_at_least_num_bytes(options, f64::INFINITY, &protected_rows, None)
+            }
+            GarbageCollectionTarget::Retention(policy) => {
+                re_log::trace!(
+                    kind = "gc",
+                    id = self.gc_id,
+                    %options.target,
+                    initial_num_rows = re_format::format_number(initial_num_rows as _),
+                    initial_num_bytes = re_format::format_bytes(initial_num_bytes),
+                    "starting GC"
+                );
+
+                self.gc_outside_retention_window(options, policy, &protected_rows)
             }
         };
 
+        // The configured retention applies whatever the target.
+        if let Some(policy) = self.config.retention {
+            if !matches!(options.target, GarbageCollectionTarget::Retention(target) if target == policy)
+            {
+                diffs.extend(self.gc_outside_retention_window(options, policy, &protected_rows));
+            }
+        }
+
         if options.purge_empty_tables {
             diffs.extend(self.purge_empty_tables());
         }
@@ -216,12 +367,56 @@ impl DataStore {
         (events, stats_diff)
     }
 
+    /// Drops the rows that are older than `policy`'s window on its timeline.
+    fn gc_outside_retention_window(
+        &mut self,
+        options: &GarbageCollectionOptions,
+        policy: RetentionPolicy,
+        protected_rows: &HashSet<RowId>,
+    ) -> Vec<StoreDiff> {
+        re_tracing::profile_function!();
+
+        // There's no registry of timelines: find it, and its latest time, in the tables.
+        let latest = self
+            .tables
+            .values()
+            .filter(|table| *table.timeline.name() == policy.timeline)
+            .map(|table| (table.timeline, table.time_range().max))
+            .max_by_key(|(_, max)| *max);
+
+        re_log::trace!(kind = "gc", id = self.gc_id, %policy, latest = ?latest, "retention");
+
+        let Some((timeline, latest)) = latest else {
+            return Vec::new();
+        };
+        let Some(window) = policy.window_for(timeline.typ()) else {
+            re_log::warn_once!(
+                "Retention policy {policy} gives a duration, but {:?} is a sequence timeline: ignoring it",
+                timeline.name()
+            );
+            return Vec::new();
+        };
+
+        // Exactly `window` worth of time is retained, `latest` included.
+        let cutoff = latest.as_i64().saturating_sub(window - 1).into();
+        self.gc_drop_at_least_num_bytes(
+            options,
+            f64::INFINITY,
+            protected_rows,
+            Some((timeline, cutoff)),
+        )
+    }
+
     /// Tries to drop _at least_ `num_bytes_to_drop` bytes of data from the store.
+    ///
+    /// If `time_cutoff` is set, only rows that are strictly older than the cutoff time on its
+    /// timeline are considered.
     fn gc_drop_at_least_num_bytes(
         &mut self,
         options: &GarbageCollectionOptions,
         mut num_bytes_to_drop: f64,
         protected_rows: &HashSet<RowId>,
+        time_cutoff: Option<(Timeline, TimeInt)>,
     ) -> Vec<StoreDiff> {
         re_tracing::profile_function!();
 
@@ -240,6 +435,9 @@ impl DataStore {
         let mut batch: Vec<(TimePoint, (EntityPathHash, RowId))> = Vec::with_capacity(batch_size);
         let mut batch_is_protected = false;
 
+        // Once a row has been retained, every later batch could drop its bucket.
+        let mut any_row_retained = false;
+
         let Self {
             cluster_key,
             metadata_registry,
@@ -256,6 +454,16 @@ impl DataStore {
                 continue;
             }
 
+            if let Some((timeline, cutoff)) = time_cutoff {
+                if timepoint
+                    .get(&timeline)
+                    .map_or(true, |time| cutoff <= *time)
+                {
+                    any_row_retained = true;
+                    continue;
+                }
+            }
+
             batch.push((timepoint.clone(), (*entity_path_hash, row_id)));
             if batch.len() < batch_size {
                 continue;
@@ -269,7 +477,7 @@ impl DataStore {
                 *cluster_key,
                 &mut num_bytes_to_drop,
                 &batch,
-                batch_is_protected,
+                batch_is_protected || any_row_retained,
             );
 
             // Only decrement the metadata size trackers if we're actually certain that we'll drop
@@ -313,7 +521,7 @@ impl DataStore {
                 *cluster_key,
                 &mut num_bytes_to_drop,
                 &batch,
-                batch_is_protected,
+                batch_is_protected || any_row_retained,
             );
 
             // Only decrement the metadata size trackers if we're actually certain that we'll drop
@@ -499,15 +707,27 @@ impl DataStore {
                 .collect();
 
             for bucket in table.buckets.values().rev() {
+                if components_to_find.values().all(|count| *count == 0) {
+                    break;
+                }
+
+                let inner = bucket.inner.read();
+                let columns = match inner.load_columns() {
+                    Ok(columns) => columns,
+                    Err(err) => {
+                        // Its rows can't be dropped either, for the same reason.
+                        re_log::error_once!("Failed to read back spilled data: {err}");
+                        continue;
+                    }
+                };
                 for (component, count) in &mut components_to_find {
                     if *count == 0 {
                         continue;
                     }
-                    let inner = bucket.inner.read();
                     // TODO(jleibs): If the entire column for a component is empty, we should
                     // make sure the column is dropped so we don't have to iterate over a
                     // bunch of Nones.
-                    if let Some(column) = inner.columns.get(component) {
+                    if let Some(column) = columns.get(component) {
                         for row in column
                             .iter()
                             .enumerate()
@@ -626,7 +846,14 @@ impl DataStore {
                 }
             }
 
-            // …otherwise we can drop it.
+            // …otherwise we can drop it, once we know what's in it.
+            let bucket_times = table.buckets.keys().copied().collect::<Vec<_>>();
+            for bucket_time in bucket_times {
+                if let Err(err) = table.page_in_bucket(bucket_time) {
+                    re_log::error_once!("Failed to read back spilled data: {err}");
+                    return true;
+                }
+            }
 
             let entity_path = table.ent_path.clone();
 
@@ -687,6 +914,16 @@ impl IndexedTable {
                 continue;
             }
 
+            // What's dropped is reported, so spilled data must be read back first.
+            let spilled_columns = match inner.spilled.as_ref().map(|spilled| spilled.load()) {
+                Some(Err(err)) => {
+                    re_log::error_once!("Failed to read back spilled data: {err}");
+                    continue;
+                }
+                Some(Ok(columns)) => Some(columns),
+                None => None,
+            };
+
             let IndexedBucketInner {
                 mut col_time,
                 mut col_row_id,
@@ -694,8 +931,10 @@ impl IndexedTable {
                 size_bytes,
                 ..
             } = std::mem::take(inner);
+            columns.extend(spilled_columns.into_iter().flatten());
 
             dropped_bucket_times.insert(*bucket_time);
+            dropped_num_rows += col_row_id.len() as u64;
 
             while let Some(row_id) = col_row_id.pop_front() {
                 let mut diff = StoreDiff::deletion(row_id, ent_path.clone());
@@ -723,18 +962,19 @@ impl IndexedTable {
                 diffs.push(diff);
             }
 
-            dropped_num_bytes += size_bytes;
-            dropped_num_rows += col_time.len() as u64;
+            // NOTE: the whole bucket, as accounted for in `buckets_size_bytes`.
+            dropped_num_bytes += bucket.stack_size_bytes() + size_bytes;
         }
 
         self.buckets
             .retain(|bucket_time, _| !dropped_bucket_times.contains(bucket_time));
 
-        self.uphold_indexing_invariants();
-
+        // NOTE: before upholding the invariants, which resets the counters if no bucket is left.
         self.buckets_num_rows -= dropped_num_rows;
         self.buckets_size_bytes -= dropped_num_bytes;
 
+        self.uphold_indexing_invariants();
+
         (diffs, dropped_num_bytes)
     }
 
@@ -756,6 +996,12 @@ impl IndexedTable {
 
         let table_has_more_than_one_bucket = self.buckets.len() > 1;
 
+        // Spilled buckets must be brought back into memory before they can be modified.
+        if let Err(err) = self.page_in_bucket(time.into()) {
+            re_log::error_once!("Failed to read back spilled data: {err}");
+            return (None, 0);
+        }
+
         let (bucket_key, bucket) = self.find_bucket_mut(time.into());
         let bucket_num_bytes = bucket.total_size_bytes();
 
@@ -819,7 +1065,12 @@ impl IndexedBucketInner {
             col_num_instances,
             columns,
             size_bytes,
+            spilled,
         } = self;
+        debug_assert!(
+            spilled.is_none(),
+            "spilled buckets must be paged in before being modified"
+        );
 
         let mut diff: Option<StoreDiff> = None;
         let mut dropped_num_bytes = 0u64;
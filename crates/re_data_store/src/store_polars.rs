@@ -13,8 +13,8 @@ use re_log_types::{DataCell, DataTable};
 use re_types_core::ComponentName;
 
 use crate::{
-    store::InsertIdVec, ArrayExt, DataStore, DataStoreConfig, IndexedBucket, IndexedBucketInner,
-    PersistentIndexedTable, PersistentIndexedTableInner,
+    store::InsertIdVec, ArrayExt, BucketColumns, DataStore, DataStoreConfig, IndexedBucket,
+    IndexedBucketInner, PersistentIndexedTable, PersistentIndexedTableInner,
 };
 
 // TODO(#1692): all of this stuff should be defined by Data{Cell,Row,Table}, not the store.
@@ -215,6 +215,7 @@ impl IndexedBucket {
     pub fn to_dataframe(&self, store: &DataStore, config: &DataStoreConfig) -> DataFrame {
         re_tracing::profile_function!();
 
+        let inner = &*self.inner.read();
         let IndexedBucketInner {
             is_sorted: _,
             time_range: _,
@@ -223,9 +224,15 @@ impl IndexedBucket {
             col_row_id,
             max_row_id: _,
             col_num_instances,
-            columns,
+            columns: _,
             size_bytes: _,
-        } = &*self.inner.read();
+            spilled: _,
+        } = inner;
+        // Whatever is still in memory is better than nothing.
+        let columns = inner.load_columns().unwrap_or_else(|err| {
+            re_log::error_once!("Failed to read back spilled data: {err}");
+            BucketColumns::InMemory(&inner.columns)
+        });
 
         let (_, times) = DataTable::serialize_primitive_column(
             self.timeline.name(),
@@ -9,7 +9,7 @@ use re_types_core::{ComponentName, ComponentNameSet};
 
 use crate::{
     store::PersistentIndexedTableInner, DataStore, IndexedBucket, IndexedBucketInner, IndexedTable,
-    PersistentIndexedTable,
+    PersistentIndexedTable, SpillResult,
 };
 
 // --- Queries ---
@@ -264,6 +264,12 @@ impl DataStore {
     /// This is what our `latest_components` polars helper does.
     ///
     /// For more information about working with dataframes, see the `polars` feature.
+    ///
+    /// # Spilled data
+    ///
+    /// If the data has been spilled to disk (see [`Self::spill_to_disk`]) and can't be read back,
+    /// this logs the error and returns `None` rather than older data.
+    /// Use [`Self::try_latest_at`] to handle that failure.
     pub fn latest_at<const N: usize>(
         &self,
         query: &LatestAtQuery,
@@ -271,6 +277,24 @@ impl DataStore {
         primary: ComponentName,
         components: &[ComponentName; N],
     ) -> Option<(Option<TimeInt>, RowId, [Option<DataCell>; N])> {
+        self.try_latest_at(query, ent_path, primary, components)
+            .unwrap_or_else(|err| {
+                re_log::error_once!("Failed to read back spilled data: {err}");
+                None
+            })
+    }
+
+    /// Same as [`Self::latest_at`], but fails if spilled data can't be read back from disk.
+    ///
+    /// The older data is never returned in that case, as it might not be the latest.
+    #[allow(clippy::type_complexity)]
+    pub fn try_latest_at<const N: usize>(
+        &self,
+        query: &LatestAtQuery,
+        ent_path: &EntityPath,
+        primary: ComponentName,
+        components: &[ComponentName; N],
+    ) -> SpillResult<Option<(Option<TimeInt>, RowId, [Option<DataCell>; N])>> {
         // TODO(cmc): kind & query_id need to somehow propagate through the span system.
         self.query_id.fetch_add(1, Ordering::Relaxed);
 
@@ -286,11 +310,9 @@ impl DataStore {
             "query started…"
         );
 
-        let results = self
-            .tables
-            .get(&(ent_path_hash, query.timeline))
-            .and_then(|table| {
-                let cells = table.latest_at(query.at, primary, components);
+        let results = match self.tables.get(&(ent_path_hash, query.timeline)) {
+            Some(table) => {
+                let cells = table.latest_at(query.at, primary, components)?;
                 trace!(
                     kind = "latest_at",
                     query = ?query,
@@ -301,7 +323,9 @@ impl DataStore {
                     "row cells fetched"
                 );
                 cells
-            });
+            }
+            None => None,
+        };
 
         // If we've found everything we were looking for in the temporal table, then we can
         // return the results immediately.
@@ -309,7 +333,7 @@ impl DataStore {
             .as_ref()
             .map_or(false, |(_, _, cells)| cells.iter().all(Option::is_some))
         {
-            return results.map(|(data_time, row_id, cells)| (Some(data_time), row_id, cells));
+            return Ok(results.map(|(data_time, row_id, cells)| (Some(data_time), row_id, cells)));
         }
 
         let cells_timeless = self.timeless_tables.get(&ent_path_hash).and_then(|table| {
@@ -331,12 +355,14 @@ impl DataStore {
         match (results, cells_timeless) {
             // nothing in the timeless table: return those partial cells we got.
             (results @ Some(_), None) => {
-                return results.map(|(data_time, row_id, cells)| (Some(data_time), row_id, cells))
+                return Ok(
+                    results.map(|(data_time, row_id, cells)| (Some(data_time), row_id, cells))
+                )
             }
 
             // no temporal cells, but some timeless ones: return those as-is.
             (None, results @ Some(_)) => {
-                return results.map(|(row_id, cells)| (None, row_id, cells))
+                return Ok(results.map(|(row_id, cells)| (None, row_id, cells)))
             }
 
             // we have both temporal & timeless cells: let's merge the two when it makes sense
@@ -347,7 +373,7 @@ impl DataStore {
                         cells[i] = row_idx;
                     }
                 }
-                return Some((Some(data_time), row_id, cells));
+                return Ok(Some((Some(data_time), row_id, cells)));
             }
 
             // no cells at all.
@@ -363,7 +389,7 @@ impl DataStore {
             "primary component not found"
         );
 
-        None
+        Ok(None)
     }
 
     /// Iterates the datastore in order to return the cells of the specified `components`,
@@ -375,7 +401,7 @@ impl DataStore {
     /// said component is not available in that row.
     /// A row is considered iff it contains data for the `primary` component.
     ///
-    /// This method cannot fail! If there's no data to return, an empty iterator is returned.
+    /// If there's no data to return, an empty iterator is returned.
     ///
     /// ⚠ Contrary to latest-at queries, range queries can and will yield multiple rows for a
     /// single timestamp if that timestamp happens to hold multiple entries for the `primary`
@@ -462,12 +488,36 @@ impl DataStore {
     /// This is what our `range_components` polars helper does.
     ///
     /// For more information about working with dataframes, see the `polars` feature.
+    ///
+    /// # Spilled data
+    ///
+    /// If the data has been spilled to disk (see [`Self::spill_to_disk`]) and can't be read back,
+    /// this logs the error and stops there, without skipping over the missing rows.
+    /// Use [`Self::try_range`] to handle that failure.
     pub fn range<'a, const N: usize>(
         &'a self,
         query: &RangeQuery,
         ent_path: &EntityPath,
         components: [ComponentName; N],
     ) -> impl Iterator<Item = (Option<TimeInt>, RowId, [Option<DataCell>; N])> + 'a {
+        self.try_range(query, ent_path, components)
+            .map_while(|result| {
+                result
+                    .map_err(|err| re_log::error_once!("Failed to read back spilled data: {err}"))
+                    .ok()
+            })
+    }
+
+    /// Same as [`Self::range`], but yields an error if spilled data can't be read back from disk.
+    ///
+    /// The error is yielded in place of the rows that couldn't be read.
+    pub fn try_range<'a, const N: usize>(
+        &'a self,
+        query: &RangeQuery,
+        ent_path: &EntityPath,
+        components: [ComponentName; N],
+    ) -> impl Iterator<Item = SpillResult<(Option<TimeInt>, RowId, [Option<DataCell>; N])>> + 'a
+    {
         // Beware! This merely measures the time it takes to gather all the necessary metadata
         // for building the returned iterator.
         re_tracing::profile_function!();
@@ -492,7 +542,7 @@ impl DataStore {
             .map(|index| index.range(query.range, components))
             .into_iter()
             .flatten()
-            .map(|(time, row_id, cells)| (Some(time), row_id, cells));
+            .map(|result| result.map(|(time, row_id, cells)| (Some(time), row_id, cells)));
 
         if query.range.min == TimeInt::MIN {
             let timeless = self
@@ -501,7 +551,7 @@ impl DataStore {
                 .map(|index| {
                     index
                         .range(components)
-                        .map(|(row_id, cells)| (None, row_id, cells))
+                        .map(|(row_id, cells)| Ok((None, row_id, cells)))
                 })
                 .into_iter()
                 .flatten();
@@ -535,15 +585,19 @@ impl IndexedTable {
     ///
     /// Returns an array of [`DataCell`]s (as well as the associated _data_ time and `RowId`) on
     /// success, or `None` iff no cell could be found for the `primary` component.
+    ///
+    /// Fails if a spilled bucket can't be read back, without looking any further back in time:
+    /// the older buckets might not hold the latest data.
+    #[allow(clippy::type_complexity)]
     pub fn latest_at<const N: usize>(
         &self,
         query_time: TimeInt,
         primary: ComponentName,
         components: &[ComponentName; N],
-    ) -> Option<(TimeInt, RowId, [Option<DataCell>; N])> {
+    ) -> SpillResult<Option<(TimeInt, RowId, [Option<DataCell>; N])>> {
         // Early-exit if this entire table is unaware of this component.
         if !self.all_components.contains(&primary) {
-            return None;
+            return Ok(None);
         }
 
         let timeline = self.timeline;
@@ -570,12 +624,12 @@ impl IndexedTable {
                 bucket_time_range = timeline.typ().format_range_utc(bucket.inner.read().time_range),
                 "found candidate bucket"
             );
-            if let ret @ Some(_) = bucket.latest_at(query_time, primary, components) {
-                return ret; // found at least the primary component!
+            if let ret @ Some(_) = bucket.latest_at(query_time, primary, components)? {
+                return Ok(ret); // found at least the primary component!
             }
         }
 
-        None // primary component not found
+        Ok(None) // primary component not found
     }
 
     /// Iterates the table in order to return the cells of the specified `components`,
@@ -587,12 +641,13 @@ impl IndexedTable {
     /// said component is not available in that row.
     /// A row is considered iff it contains data for the `primary` component.
     ///
-    /// This method cannot fail! If there's no data to return, an empty iterator is returned.
+    /// If there's no data to return, an empty iterator is returned.
+    /// If a spilled bucket can't be read back, an error is yielded in place of its rows.
     pub fn range<const N: usize>(
         &self,
         time_range: TimeRange,
         components: [ComponentName; N],
-    ) -> impl Iterator<Item = (TimeInt, RowId, [Option<DataCell>; N])> + '_ {
+    ) -> impl Iterator<Item = SpillResult<(TimeInt, RowId, [Option<DataCell>; N])>> + '_ {
         // Beware! This merely measures the time it takes to gather all the necessary metadata
         // for building the returned iterator.
         re_tracing::profile_function!();
@@ -733,14 +788,18 @@ impl IndexedBucket {
     ///
     /// Returns an array of [`DataCell`]s (as well as the associated _data_ time and `RowId`) on
     /// success, or `None` iff no cell could be found for the `primary` component.
+    ///
+    /// Fails if the bucket has been spilled to disk and can't be read back.
+    #[allow(clippy::type_complexity)]
     pub fn latest_at<const N: usize>(
         &self,
         query_time: TimeInt,
         primary: ComponentName,
         components: &[ComponentName; N],
-    ) -> Option<(TimeInt, RowId, [Option<DataCell>; N])> {
+    ) -> SpillResult<Option<(TimeInt, RowId, [Option<DataCell>; N])>> {
         self.sort_indices_if_needed();
 
+        let inner = &*self.inner.read();
         let IndexedBucketInner {
             is_sorted,
             time_range: _,
@@ -749,13 +808,19 @@ impl IndexedBucket {
             col_row_id,
             max_row_id: _,
             col_num_instances: _,
-            columns,
+            columns: _,
             size_bytes: _,
-        } = &*self.inner.read();
+            spilled: _,
+        } = inner;
         debug_assert!(is_sorted);
 
+        // Reads spilled data back from disk, if needed.
+        let columns = inner.load_columns()?;
+
         // Early-exit if this bucket is unaware of this component.
-        let column = columns.get(&primary)?;
+        let Some(column) = columns.get(&primary) else {
+            return Ok(None);
+        };
 
         trace!(
             kind = "latest_at",
@@ -773,7 +838,7 @@ impl IndexedBucket {
         // A partition point of 0 thus means that we're trying to query for data that lives
         // _before_ the beginning of time… there's nothing to be found there.
         if time_row_nr == 0 {
-            return None;
+            return Ok(None);
         }
 
         // The partition point is always _beyond_ the index that we're looking for; we need
@@ -802,7 +867,7 @@ impl IndexedBucket {
                     %primary_row_nr,
                     "no secondary row number found",
                 );
-                return None;
+                return Ok(None);
             }
             secondary_row_nr -= 1;
         }
@@ -836,11 +901,11 @@ impl IndexedBucket {
             }
         }
 
-        Some((
+        Ok(Some((
             col_time[secondary_row_nr as usize].into(),
             col_row_id[secondary_row_nr as usize],
             cells,
-        ))
+        )))
     }
 
     /// Iterates the bucket in order to return the cells of the specified `components`,
@@ -852,14 +917,16 @@ impl IndexedBucket {
     /// said component is not available in that row.
     /// A row is considered iff it contains data for the `primary` component.
     ///
-    /// This method cannot fail! If there's no data to return, an empty iterator is returned.
+    /// If there's no data to return, an empty iterator is returned.
+    /// If the bucket has been spilled to disk and can't be read back, only that error is yielded.
     pub fn range<const N: usize>(
         &self,
         time_range: TimeRange,
         components: [ComponentName; N],
-    ) -> impl Iterator<Item = (TimeInt, RowId, [Option<DataCell>; N])> + '_ {
+    ) -> impl Iterator<Item = SpillResult<(TimeInt, RowId, [Option<DataCell>; N])>> + '_ {
         self.sort_indices_if_needed();
 
+        let inner = &*self.inner.read();
         let IndexedBucketInner {
             is_sorted,
             time_range: bucket_time_range,
@@ -868,11 +935,18 @@ impl IndexedBucket {
             col_row_id,
             max_row_id: _,
             col_num_instances: _,
-            columns,
+            columns: _,
             size_bytes: _,
-        } = &*self.inner.read();
+            spilled: _,
+        } = inner;
         debug_assert!(is_sorted);
 
+        // Reads spilled data back from disk, if needed.
+        let columns = match inner.load_columns() {
+            Ok(columns) => columns,
+            Err(err) => return itertools::Either::Right(Some(Err(err)).into_iter()),
+        };
+
         let bucket_time_range = *bucket_time_range;
 
         // Early-exit if this bucket is unaware of any of our components of interest.
@@ -880,7 +954,7 @@ impl IndexedBucket {
             .iter()
             .all(|component| columns.get(component).is_none())
         {
-            return itertools::Either::Right(std::iter::empty());
+            return itertools::Either::Right(None.into_iter());
         }
 
         // Beware! This merely measures the time it takes to gather all the necessary metadata
@@ -915,7 +989,7 @@ impl IndexedBucket {
         // of components).
         let col_time = col_time.clone();
         let col_row_id = col_row_id.clone();
-        let mut columns = columns.clone(); // shallow
+        let mut columns = columns.into_owned(); // shallow
 
         // We have found the index of the first row that possibly contains data for any single one
         // of the components we're interested in.
@@ -959,7 +1033,7 @@ impl IndexedBucket {
                     "yielding cells",
                 );
 
-                Some((time.into(), row_id, cells))
+                Some(Ok((time.into(), row_id, cells)))
             });
 
         itertools::Either::Left(cells)
@@ -983,12 +1057,16 @@ impl IndexedBucketInner {
             col_num_instances,
             columns,
             size_bytes: _,
+            spilled,
         } = self;
 
         if *is_sorted {
             return;
         }
 
+        // NOTE: Spilled buckets are always sorted, see `IndexedBucketInner::spill`.
+        debug_assert!(spilled.is_none());
+
         re_tracing::profile_function!();
 
         let swaps = {
@@ -172,6 +172,7 @@ impl IndexedBucket {
                 col_num_instances,
                 columns,
                 size_bytes: _,
+                spilled: _, // NOTE: the cluster column is never spilled
             } = &*inner.read();
 
             // Time ranges are eagerly maintained.
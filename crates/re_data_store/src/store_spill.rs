@@ -0,0 +1,522 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Weak,
+};
+
+use arrow2::{chunk::Chunk, datatypes::Schema};
+use nohash_hasher::IntMap;
+use re_log_types::{DataCellColumn, DataTable, DataTableError, TimeInt};
+use re_types_core::{ComponentName, SizeBytes};
+
+use crate::{DataStore, DataStoreRowStats, IndexedBucketInner, IndexedTable};
+
+// ---
+
+#[derive(thiserror::Error, Debug)]
+pub enum SpillError {
+    #[error("Failed to access spill file {0:?}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error(transparent)]
+    DataTable(#[from] DataTableError),
+
+    #[error(transparent)]
+    Arrow(#[from] arrow2::error::Error),
+}
+
+pub type SpillResult<T> = ::std::result::Result<T, SpillError>;
+
+/// Used to give every spill file of this process a unique name, across all stores.
+static NEXT_SPILL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The [`SpillCache`] of a store, shared with its [`SpilledColumns`].
+pub(crate) type SharedSpillCache = Arc<parking_lot::Mutex<SpillCache>>;
+
+/// The columns of a spilled bucket, as read back from disk.
+struct CachedColumns {
+    /// See [`SpilledColumns::id`].
+    id: u64,
+    num_rows: u64,
+    num_bytes: u64,
+    columns: Arc<IntMap<ComponentName, DataCellColumn>>,
+}
+
+/// The spilled buckets of a store read back most recently, so that repeated queries of the same
+/// buckets don't hit the disk every time.
+///
+/// Least-recently-used first out, once it holds more than
+/// [`crate::DataStoreConfig::spill_cache_max_bytes`].
+/// Counted in [`crate::DataStoreStats::spill_cache`].
+pub(crate) struct SpillCache {
+    max_bytes: u64,
+
+    /// Least recently used first.
+    entries: VecDeque<CachedColumns>,
+
+    num_rows: u64,
+    num_bytes: u64,
+}
+
+impl SpillCache {
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            entries: VecDeque::new(),
+            num_rows: 0,
+            num_bytes: 0,
+        }
+    }
+
+    pub fn stats(&self) -> DataStoreRowStats {
+        DataStoreRowStats {
+            num_rows: self.num_rows,
+            num_bytes: self.num_bytes,
+        }
+    }
+
+    fn get(&mut self, id: u64) -> Option<Arc<IntMap<ComponentName, DataCellColumn>>> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        let entry = self.entries.remove(index)?;
+        let columns = entry.columns.clone();
+        self.entries.push_back(entry);
+        Some(columns)
+    }
+
+    /// Entries larger than the whole cache aren't kept at all.
+    fn insert(
+        &mut self,
+        id: u64,
+        num_rows: u64,
+        columns: Arc<IntMap<ComponentName, DataCellColumn>>,
+    ) {
+        let num_bytes = columns.total_size_bytes();
+        if self.max_bytes < num_bytes {
+            return;
+        }
+
+        self.evict((self.num_bytes + num_bytes).saturating_sub(self.max_bytes) as f64);
+        self.num_rows += num_rows;
+        self.num_bytes += num_bytes;
+        self.entries.push_back(CachedColumns {
+            id,
+            num_rows,
+            num_bytes,
+            columns,
+        });
+    }
+
+    fn remove(&mut self, id: u64) {
+        if let Some(index) = self.entries.iter().position(|entry| entry.id == id) {
+            if let Some(removed) = self.entries.remove(index) {
+                self.num_rows -= removed.num_rows;
+                self.num_bytes -= removed.num_bytes;
+            }
+        }
+    }
+
+    /// Drops the least recently used entries until at least `num_bytes_to_evict` bytes have been
+    /// freed, or the cache is empty.
+    ///
+    /// Returns how many bytes were freed.
+    fn evict(&mut self, num_bytes_to_evict: f64) -> u64 {
+        let mut num_bytes_evicted = 0;
+        while (num_bytes_evicted as f64) < num_bytes_to_evict {
+            let Some(evicted) = self.entries.pop_front() else {
+                break;
+            };
+            self.num_rows -= evicted.num_rows;
+            self.num_bytes -= evicted.num_bytes;
+            num_bytes_evicted += evicted.num_bytes;
+        }
+        num_bytes_evicted
+    }
+}
+
+/// The component columns of an `IndexedBucket` that have been written to disk, as an
+/// Arrow IPC file.
+///
+/// The file is deleted once this is dropped.
+#[derive(Debug)]
+pub struct SpilledColumns {
+    /// Unique across all stores.
+    id: u64,
+
+    path: PathBuf,
+
+    /// Where the columns are kept once read back, gone with the store.
+    cache: Weak<parking_lot::Mutex<SpillCache>>,
+
+    /// How many rows each column has.
+    num_rows: usize,
+
+    /// All spilled components, including the ones without a single cell (which aren't written).
+    components: Vec<ComponentName>,
+}
+
+impl Drop for SpilledColumns {
+    fn drop(&mut self) {
+        if let Some(cache) = self.cache.upgrade() {
+            cache.lock().remove(self.id);
+        }
+
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                re_log::warn!("Failed to remove spill file {:?}: {err}", self.path);
+            }
+        }
+    }
+}
+
+impl SizeBytes for SpilledColumns {
+    #[inline]
+    fn heap_size_bytes(&self) -> u64 {
+        let Self {
+            id: _,
+            path,
+            cache: _,
+            num_rows: _,
+            components,
+        } = self;
+
+        path.as_os_str().len() as u64 + components.heap_size_bytes()
+    }
+}
+
+impl SpilledColumns {
+    fn write(
+        dir: &Path,
+        columns: &IntMap<ComponentName, DataCellColumn>,
+        num_rows: usize,
+        cache: &SharedSpillCache,
+    ) -> SpillResult<Self> {
+        re_tracing::profile_function!();
+
+        let mut schema = Schema::default();
+        let mut arrays = Vec::new();
+        let mut components = Vec::with_capacity(columns.len());
+        for (component, column) in columns {
+            components.push(*component);
+
+            // There's no datatype to write down for a column without a single cell.
+            if column.iter().all(Option::is_none) {
+                continue;
+            }
+
+            let (field, array) = DataTable::serialize_data_column(component, column)?;
+            schema.fields.push(field);
+            arrays.push(array);
+        }
+
+        let id = NEXT_SPILL_ID.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("rerun-spill-{}-{id}.arrow", std::process::id()));
+
+        // NOTE: created first, so that the file gets cleaned up on failure.
+        let spilled = Self {
+            id,
+            path,
+            cache: Arc::downgrade(cache),
+            num_rows,
+            components,
+        };
+
+        let file = std::fs::File::create(&spilled.path)
+            .map_err(|err| SpillError::Io(spilled.path.clone(), err))?;
+        let options = arrow2::io::ipc::write::WriteOptions { compression: None };
+        let mut writer = arrow2::io::ipc::write::FileWriter::try_new(
+            std::io::BufWriter::new(file),
+            schema,
+            None,
+            options,
+        )?;
+        if !arrays.is_empty() {
+            writer.write(&Chunk::new(arrays), None)?;
+        }
+        writer.finish()?;
+
+        Ok(spilled)
+    }
+
+    /// Reads the columns back from disk.
+    pub fn load(&self) -> SpillResult<IntMap<ComponentName, DataCellColumn>> {
+        re_tracing::profile_function!();
+
+        let mut columns: IntMap<ComponentName, DataCellColumn> = self
+            .components
+            .iter()
+            .map(|component| (*component, self.empty_column()))
+            .collect();
+
+        let mut file = std::fs::File::open(&self.path)
+            .map_err(|err| SpillError::Io(self.path.clone(), err))?;
+        let metadata = arrow2::io::ipc::read::read_file_metadata(&mut file)?;
+        let fields = metadata.schema.fields.clone();
+
+        for chunk in arrow2::io::ipc::read::FileReader::new(file, metadata, None, None) {
+            for (field, array) in fields.iter().zip(chunk?.into_arrays()) {
+                let component = ComponentName::from(field.name.clone());
+                let mut column = DataTable::deserialize_data_column(component, array.as_ref())?;
+                for cell in column.0.iter_mut().flatten() {
+                    cell.compute_size_bytes();
+                }
+                columns.insert(component, column);
+            }
+        }
+
+        Ok(columns)
+    }
+
+    fn empty_column(&self) -> DataCellColumn {
+        DataCellColumn(std::iter::repeat(None).take(self.num_rows).collect())
+    }
+}
+
+/// The component columns of a bucket, see `IndexedBucketInner::load_columns`.
+pub enum BucketColumns<'a> {
+    /// The bucket is in memory.
+    InMemory(&'a IntMap<ComponentName, DataCellColumn>),
+
+    /// The bucket has been spilled, and its columns read back.
+    Loaded(Arc<IntMap<ComponentName, DataCellColumn>>),
+}
+
+impl BucketColumns<'_> {
+    /// A shallow copy of the columns.
+    pub fn into_owned(self) -> IntMap<ComponentName, DataCellColumn> {
+        match self {
+            Self::InMemory(columns) => columns.clone(),
+            Self::Loaded(columns) => Arc::try_unwrap(columns).unwrap_or_else(|arc| (*arc).clone()),
+        }
+    }
+}
+
+impl std::ops::Deref for BucketColumns<'_> {
+    type Target = IntMap<ComponentName, DataCellColumn>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::InMemory(columns) => columns,
+            Self::Loaded(columns) => columns,
+        }
+    }
+}
+
+impl IndexedBucketInner {
+    /// All component columns, read back from disk if this bucket has been spilled.
+    ///
+    /// The most recently read spilled buckets are kept in memory for a while (see
+    /// [`crate::DataStoreConfig::spill_cache_max_bytes`]), so that repeated queries don't read
+    /// them back every time.
+    /// Use this for reading only, see [`Self::page_in`] otherwise.
+    ///
+    /// On failure, the spill file is left untouched: the next call tries again.
+    pub fn load_columns(&self) -> SpillResult<BucketColumns<'_>> {
+        let Some(spilled) = &self.spilled else {
+            return Ok(BucketColumns::InMemory(&self.columns));
+        };
+
+        let cache = spilled.cache.upgrade();
+        if let Some(columns) = cache
+            .as_ref()
+            .and_then(|cache| cache.lock().get(spilled.id))
+        {
+            return Ok(BucketColumns::Loaded(columns));
+        }
+
+        // NOTE: not holding the lock while reading, other threads may be querying too.
+        let mut columns = self.columns.clone(); // shallow, only the cluster key's
+        columns.extend(spilled.load()?);
+        let columns = Arc::new(columns);
+        if let Some(cache) = cache {
+            cache
+                .lock()
+                .insert(spilled.id, spilled.num_rows as u64, columns.clone());
+        }
+
+        Ok(BucketColumns::Loaded(columns))
+    }
+
+    /// Brings the columns of a spilled bucket back into memory.
+    ///
+    /// On failure, the bucket stays spilled and its spill file untouched.
+    ///
+    /// Doesn't update the size stats of the parent table, see [`IndexedTable::page_in_bucket`].
+    pub(crate) fn page_in(&mut self) -> SpillResult<()> {
+        let Some(spilled) = &self.spilled else {
+            return Ok(());
+        };
+
+        re_tracing::profile_function!();
+
+        // NOTE: shallow, if it has been read back recently.
+        let cached = spilled
+            .cache
+            .upgrade()
+            .and_then(|cache| cache.lock().get(spilled.id));
+        let columns = match cached {
+            Some(columns) => (*columns).clone(),
+            None => spilled.load()?,
+        };
+        self.spilled = None;
+        self.columns.extend(columns);
+        self.compute_size_bytes();
+
+        Ok(())
+    }
+
+    /// Writes all component columns but the cluster key's to a new file in `dir`.
+    ///
+    /// Doesn't update the size stats of the parent table, see [`DataStore::spill_to_disk`].
+    fn spill(
+        &mut self,
+        dir: &Path,
+        cluster_key: ComponentName,
+        cache: &SharedSpillCache,
+    ) -> SpillResult<()> {
+        // The cluster key stays in memory: it's tiny and its cells are shared with the
+        // `ClusterCellCache`, which the GC relies on.
+        let nothing_to_spill = self
+            .columns
+            .keys()
+            .all(|component| *component == cluster_key);
+        if self.spilled.is_some() || nothing_to_spill {
+            return Ok(());
+        }
+
+        // Spilled buckets can't be sorted without paging them back in.
+        self.sort();
+
+        let cluster_column = self.columns.remove(&cluster_key);
+        let result =
+            SpilledColumns::write(dir, &self.columns, self.col_time.len(), cache).map(|spilled| {
+                self.columns.clear();
+                self.spilled = Some(Arc::new(spilled));
+            });
+        if let Some(cluster_column) = cluster_column {
+            self.columns.insert(cluster_key, cluster_column);
+        }
+
+        self.compute_size_bytes();
+
+        result
+    }
+}
+
+impl IndexedTable {
+    /// Brings the bucket responsible for `time` back into memory, if it has been spilled.
+    pub(crate) fn page_in_bucket(&mut self, time: TimeInt) -> SpillResult<()> {
+        let (_, bucket) = self.find_bucket_mut(time);
+
+        let inner = bucket.inner.get_mut();
+        if inner.spilled.is_none() {
+            return Ok(());
+        }
+
+        let size_bytes_before = inner.size_bytes;
+        inner.page_in()?;
+        let size_bytes_after = inner.size_bytes;
+
+        self.buckets_size_bytes = self.buckets_size_bytes - size_bytes_before + size_bytes_after;
+
+        Ok(())
+    }
+
+    /// Spills the bucket starting at `bucket_time`, see [`DataStore::spill_to_disk`].
+    ///
+    /// Returns how many bytes were freed.
+    fn spill_bucket(
+        &mut self,
+        dir: &Path,
+        bucket_time: TimeInt,
+        cache: &SharedSpillCache,
+    ) -> SpillResult<u64> {
+        let Some(bucket) = self.buckets.get_mut(&bucket_time) else {
+            return Ok(0);
+        };
+
+        let inner = bucket.inner.get_mut();
+        let size_bytes_before = inner.size_bytes;
+        inner.spill(dir, self.cluster_key, cache)?;
+        let size_bytes_after = inner.size_bytes;
+
+        self.buckets_size_bytes = self.buckets_size_bytes - size_bytes_before + size_bytes_after;
+
+        Ok(size_bytes_before.saturating_sub(size_bytes_after))
+    }
+}
+
+impl DataStore {
+    /// Writes the component data of the oldest buckets to files in `dir`, until at least
+    /// `num_bytes_to_spill` bytes have been freed or there's nothing left to spill.
+    ///
+    /// The spilled data that was read back from disk and cached (see
+    /// [`crate::DataStoreConfig::spill_cache_max_bytes`]) is dropped first, least recently used
+    /// first, as it can always be read back again.
+    ///
+    /// This is an alternative to [`DataStore::gc`] that doesn't lose any data: queries
+    /// transparently read spilled data back from disk, and buckets are paged back into memory
+    /// whenever they need to be modified (insertions, garbage collection).
+    /// The most recent bucket of each table, where new data usually goes, is never spilled.
+    ///
+    /// If a file can't be read back, the queries that need its data fail (see
+    /// [`DataStore::try_latest_at`] and [`DataStore::try_range`]) and insertions into its bucket
+    /// fail with [`crate::WriteError::Spill`], but the file is kept, and each later attempt
+    /// tries reading it again.
+    ///
+    /// The files are deleted once they aren't needed anymore, including when the store is
+    /// dropped.
+    ///
+    /// Returns how many bytes were freed.
+    pub fn spill_to_disk(&mut self, dir: &Path, num_bytes_to_spill: f64) -> SpillResult<u64> {
+        re_tracing::profile_function!();
+
+        let num_bytes_evicted = self.spill_cache.lock().evict(num_bytes_to_spill);
+        if num_bytes_to_spill <= num_bytes_evicted as f64 {
+            return Ok(num_bytes_evicted);
+        }
+
+        std::fs::create_dir_all(dir).map_err(|err| SpillError::Io(dir.to_owned(), err))?;
+
+        // Oldest buckets first, as seen from their most recent row.
+        let mut candidates: Vec<_> = self
+            .tables
+            .iter()
+            .flat_map(|(key, table)| {
+                table
+                    .buckets
+                    .iter()
+                    .rev()
+                    .skip(1)
+                    .filter_map(move |(bucket_time, bucket)| {
+                        let inner = bucket.inner.read();
+                        (inner.spilled.is_none() && !inner.col_time.is_empty())
+                            .then(|| (inner.max_row_id, *key, *bucket_time))
+                    })
+            })
+            .collect();
+        candidates.sort();
+
+        let mut num_bytes_spilled = num_bytes_evicted;
+        for (_, key, bucket_time) in candidates {
+            if num_bytes_to_spill <= num_bytes_spilled as f64 {
+                break;
+            }
+
+            let Some(table) = self.tables.get_mut(&key) else {
+                continue;
+            };
+            num_bytes_spilled += table.spill_bucket(dir, bucket_time, &self.spill_cache)?;
+        }
+
+        re_log::trace!(
+            kind = "spill",
+            num_bytes_to_spill = re_format::format_bytes(num_bytes_to_spill),
+            num_bytes_spilled = re_format::format_bytes(num_bytes_spilled as _),
+            "spilled to disk"
+        );
+
+        Ok(num_bytes_spilled)
+    }
+}
@@ -48,6 +48,13 @@ pub struct DataStoreStats {
     pub timeless: DataStoreRowStats,
     pub temporal: DataStoreRowStats,
     pub temporal_buckets: u64,
+
+    /// The spilled data read back from disk and kept around, see
+    /// [`crate::DataStoreConfig::spill_cache_max_bytes`].
+    ///
+    /// Its rows are already counted in [`Self::temporal`], but not its bytes.
+    pub spill_cache: DataStoreRowStats,
+
     pub total: DataStoreRowStats,
 }
 
@@ -62,6 +69,7 @@ impl std::ops::Sub for DataStoreStats {
             timeless: self.timeless - rhs.timeless,
             temporal: self.temporal - rhs.temporal,
             temporal_buckets: self.temporal_buckets - rhs.temporal_buckets,
+            spill_cache: self.spill_cache - rhs.spill_cache,
             total: self.total - rhs.total,
         }
     }
@@ -78,6 +86,7 @@ impl std::ops::Add for DataStoreStats {
             timeless: self.timeless + rhs.timeless,
             temporal: self.temporal + rhs.temporal,
             temporal_buckets: self.temporal_buckets + rhs.temporal_buckets,
+            spill_cache: self.spill_cache + rhs.spill_cache,
             total: self.total + rhs.total,
         }
     }
@@ -130,13 +139,19 @@ impl DataStoreStats {
             )
         };
 
+        let spill_cache = {
+            re_tracing::profile_scope!("spill_cache");
+            store.spill_cache.lock().stats()
+        };
+
         let total = DataStoreRowStats {
             num_rows: timeless.num_rows + temporal.num_rows,
             num_bytes: type_registry.num_bytes
                 + metadata_registry.num_bytes
                 + autogenerated.num_bytes
                 + timeless.num_bytes
-                + temporal.num_bytes,
+                + temporal.num_bytes
+                + spill_cache.num_bytes,
         };
 
         Self {
@@ -146,6 +161,7 @@ impl DataStoreStats {
             timeless,
             temporal,
             temporal_buckets,
+            spill_cache,
             total,
         }
     }
@@ -221,6 +237,13 @@ impl DataStore {
             .sum()
     }
 
+    /// Returns the size of the spilled data that has been read back from disk and kept in memory,
+    /// see [`crate::DataStoreConfig::spill_cache_max_bytes`].
+    #[inline]
+    pub fn spill_cache_size_bytes(&self) -> u64 {
+        self.spill_cache.lock().stats().num_bytes
+    }
+
     /// Returns the number of temporal indexed buckets stored across this entire store.
     #[inline]
     pub fn num_temporal_buckets(&self) -> u64 {
@@ -380,6 +403,7 @@ impl IndexedBucketInner {
             col_num_instances,
             columns,
             size_bytes,
+            spilled,
         } = self;
 
         *size_bytes = is_sorted.total_size_bytes()
@@ -390,7 +414,11 @@ impl IndexedBucketInner {
             + max_row_id.total_size_bytes()
             + col_num_instances.total_size_bytes()
             + columns.total_size_bytes()
-            + size_bytes.total_size_bytes();
+            + size_bytes.total_size_bytes()
+            + std::mem::size_of_val(spilled) as u64
+            + spilled
+                .as_ref()
+                .map_or(0, |spilled| spilled.heap_size_bytes());
 
         *size_bytes
     }
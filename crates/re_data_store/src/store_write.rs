@@ -14,8 +14,8 @@ use re_types_core::{
 
 use crate::{
     store::PersistentIndexedTableInner, DataStore, DataStoreConfig, IndexedBucket,
-    IndexedBucketInner, IndexedTable, MetadataRegistry, PersistentIndexedTable, StoreDiff,
-    StoreDiffKind, StoreEvent,
+    IndexedBucketInner, IndexedTable, MetadataRegistry, PersistentIndexedTable, SpillError,
+    StoreDiff, StoreDiffKind, StoreEvent,
 };
 
 // --- Data store ---
@@ -51,6 +51,9 @@ pub enum WriteError {
 
     #[error("Attempted to re-use already taken RowId:{0}")]
     ReusedRowId(RowId),
+
+    #[error("Failed to read back the spilled data the row would be inserted into")]
+    Spill(#[from] SpillError),
 }
 
 pub type WriteResult<T> = ::std::result::Result<T, WriteError>;
@@ -77,6 +80,14 @@ impl DataStore {
             cells,
         } = row;
 
+        // Spilled buckets must be brought back into memory before they can be modified, and
+        // this must happen before anything else is: if it fails, the store is left untouched.
+        for (timeline, time) in timepoint.iter() {
+            if let Some(table) = self.tables.get_mut(&(entity_path.hash(), *timeline)) {
+                table.page_in_bucket(*time)?;
+            }
+        }
+
         self.metadata_registry
             .upsert(*row_id, (timepoint.clone(), entity_path.hash()))?;
 
@@ -291,6 +302,10 @@ impl IndexedTable {
         let ent_path = self.ent_path.clone(); // shallow
 
         let (_, bucket) = self.find_bucket_mut(time);
+        debug_assert!(
+            bucket.inner.read().spilled.is_none(),
+            "spilled buckets must be paged in before inserting into them"
+        );
 
         let len = bucket.num_rows();
         let len_overflow = len > config.indexed_bucket_num_rows;
@@ -444,7 +459,12 @@ impl IndexedBucket {
             col_num_instances,
             columns,
             size_bytes,
+            spilled,
         } = &mut *inner;
+        debug_assert!(
+            spilled.is_none(),
+            "spilled buckets must be paged in before being modified"
+        );
 
         // append time to primary column and update time range appropriately
 
@@ -579,6 +599,7 @@ impl IndexedBucket {
             col_num_instances: col_num_instances1,
             columns: columns1,
             size_bytes: _, // NOTE: recomputed below
+            spilled: _,    // NOTE: sorting checks that this is unset
         } = &mut *inner1;
 
         let timeline = *timeline;
@@ -639,6 +660,7 @@ impl IndexedBucket {
                     col_num_instances: col_num_instances2,
                     columns: columns2,
                     size_bytes: 0, // NOTE: computed below
+                    spilled: None,
                 };
                 inner2.compute_size_bytes();
                 inner2
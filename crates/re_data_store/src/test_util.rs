@@ -51,6 +51,8 @@ pub fn all_configs() -> impl Iterator<Item = DataStoreConfig> {
         indexed_bucket_num_rows: idx.indexed_bucket_num_rows,
         store_insert_ids: idx.store_insert_ids,
         enable_typecheck: idx.enable_typecheck,
+        retention: idx.retention,
+        spill_cache_max_bytes: idx.spill_cache_max_bytes,
     })
 }
 
@@ -14,10 +14,11 @@ use re_data_store::WriteError;
 use re_data_store::{
     polars_util, test_row, test_util::sanity_unwrap, ArrayExt as _, DataStore, DataStoreConfig,
     DataStoreStats, GarbageCollectionOptions, GarbageCollectionTarget, LatestAtQuery, RangeQuery,
-    TimeInt, TimeRange,
+    RetentionPolicy, RetentionWindow, TimeInt, TimeRange,
 };
 use re_log_types::{
-    build_frame_nr, DataCell, DataRow, DataTable, EntityPath, TableId, TimeType, Timeline,
+    build_frame_nr, build_log_time, DataCell, DataRow, DataTable, EntityPath, TableId, Time,
+    TimeType, Timeline,
 };
 use re_types::datagen::{
     build_some_colors, build_some_instances, build_some_instances_from, build_some_positions2d,
@@ -892,6 +893,196 @@ fn gc_impl(store: &mut DataStore) {
     }
 }
 
+#[test]
+fn retention_gc() {
+    init_logs();
+
+    for config in re_data_store::test_util::all_configs() {
+        for enable_batching in [false, true] {
+            let mut store = DataStore::new(
+                re_log_types::StoreId::random(re_log_types::StoreKind::Recording),
+                InstanceKey::name(),
+                config.clone(),
+            );
+            retention_gc_impl(&mut store, enable_batching);
+        }
+    }
+}
+
+fn retention_gc_impl(store: &mut DataStore, enable_batching: bool) {
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+
+    let mut kept = Vec::new();
+    let mut dropped = Vec::new();
+
+    for i in 0..3 {
+        let ent_path = EntityPath::from(format!("this/that/{i}"));
+        for frame_nr in 0..100 {
+            let row = test_row!(ent_path @ [
+                build_frame_nr(frame_nr.into())
+            ] => 3; [
+                build_some_colors(3),
+            ]);
+            store.insert_row(&row).unwrap();
+
+            if frame_nr < 90 {
+                dropped.push(row.row_id());
+            } else {
+                kept.push(row.row_id());
+            }
+        }
+    }
+
+    // Data without a time on the retention timeline is never dropped.
+    let ent_path = EntityPath::from("other");
+    let row = test_row!(ent_path @ [build_log_time(Time::now())] => 3; [build_some_colors(3)]);
+    store.insert_row(&row).unwrap();
+    kept.push(row.row_id());
+
+    let policy: RetentionPolicy = "10@frame_nr".parse().unwrap();
+
+    let (store_events, _) = store.gc(&GarbageCollectionOptions {
+        protect_latest: 0,
+        enable_batching,
+        ..GarbageCollectionOptions::retention(policy)
+    });
+    sanity_unwrap(store);
+
+    for event in &store_events {
+        assert_eq!(event.kind, re_data_store::StoreDiffKind::Deletion);
+        assert!(event
+            .times
+            .iter()
+            .any(|(timeline, time)| *timeline == timeline_frame_nr && time.as_i64() < 90));
+    }
+    assert_eq!(dropped.len(), store_events.len());
+
+    for row_id in &dropped {
+        assert!(store.get_msg_metadata(row_id).is_none());
+    }
+    for row_id in &kept {
+        assert!(store.get_msg_metadata(row_id).is_some());
+    }
+
+    // Each kept row is on a single timeline.
+    let stats = DataStoreStats::from_store(store);
+    assert_eq!(kept.len() as u64, stats.temporal.num_rows);
+}
+
+#[test]
+fn retention_gc_temporal() {
+    init_logs();
+
+    let mut store = DataStore::new(
+        re_log_types::StoreId::random(re_log_types::StoreKind::Recording),
+        InstanceKey::name(),
+        DataStoreConfig::default(),
+    );
+
+    let ent_path = EntityPath::from("this/that");
+    let mut row_ids = Vec::new();
+    for secs in 0..10 {
+        let row = test_row!(ent_path @ [
+            build_log_time(Time::from_seconds_since_epoch(secs as f64)),
+            build_frame_nr(secs.into())
+        ] => 3; [build_some_colors(3)]);
+        store.insert_row(&row).unwrap();
+        row_ids.push(row.row_id());
+    }
+
+    let gc = |store: &mut DataStore, policy: &str| {
+        let (store_events, _) = store.gc(&GarbageCollectionOptions {
+            protect_latest: 0,
+            ..GarbageCollectionOptions::retention(policy.parse().unwrap())
+        });
+        sanity_unwrap(store);
+        store_events.len()
+    };
+
+    // A duration doesn't apply to a sequence timeline.
+    assert_eq!(0, gc(&mut store, "1s@frame_nr"));
+
+    // Without a unit, the window is in the timeline's own units, i.e. nanoseconds.
+    assert_eq!(1, gc(&mut store, "9000000000@log_time"));
+
+    assert_eq!(7, gc(&mut store, "2s@log_time"));
+    for row_id in &row_ids[..8] {
+        assert!(store.get_msg_metadata(row_id).is_none());
+    }
+    for row_id in &row_ids[8..] {
+        assert!(store.get_msg_metadata(row_id).is_some());
+    }
+}
+
+#[test]
+fn retention_gc_from_config() {
+    init_logs();
+
+    let mut store = DataStore::new(
+        re_log_types::StoreId::random(re_log_types::StoreKind::Recording),
+        InstanceKey::name(),
+        DataStoreConfig {
+            retention: Some("5@frame_nr".parse().unwrap()),
+            ..Default::default()
+        },
+    );
+
+    let ent_path = EntityPath::from("this/that");
+    let mut row_ids = Vec::new();
+    for frame_nr in 0..10 {
+        let row =
+            test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 3; [build_some_colors(3)]);
+        store.insert_row(&row).unwrap();
+        row_ids.push(row.row_id());
+    }
+
+    // Whatever the target, the configured retention window is enforced.
+    let (store_events, _) = store.gc(&GarbageCollectionOptions {
+        target: GarbageCollectionTarget::DropAtLeastFraction(0.0),
+        protect_latest: 0,
+        ..GarbageCollectionOptions::gc_everything()
+    });
+    sanity_unwrap(&store);
+
+    assert_eq!(5, store_events.len());
+    for row_id in &row_ids[..5] {
+        assert!(store.get_msg_metadata(row_id).is_none());
+    }
+    for row_id in &row_ids[5..] {
+        assert!(store.get_msg_metadata(row_id).is_some());
+    }
+}
+
+#[test]
+fn retention_policy_parse() {
+    let policy: RetentionPolicy = "60s@log_time".parse().unwrap();
+    assert_eq!(policy.timeline, "log_time");
+    assert_eq!(policy.window, RetentionWindow::Duration(60_000_000_000));
+    assert_eq!(policy.to_string(), "1m@log_time");
+
+    let policy: RetentionPolicy = "1500ms@sensor_time".parse().unwrap();
+    assert_eq!(policy.window, RetentionWindow::Duration(1_500_000_000));
+    assert_eq!(policy.to_string(), "1500ms@sensor_time");
+
+    let policy: RetentionPolicy = "1000@frame_nr".parse().unwrap();
+    assert_eq!(policy.timeline, "frame_nr");
+    assert_eq!(policy.window, RetentionWindow::Steps(1000));
+    assert_eq!(policy.to_string(), "1000@frame_nr");
+
+    for s in ["90s@log_time", "3h@t", "7ns@t", "100@log_time"] {
+        let policy: RetentionPolicy = s.parse().unwrap();
+        assert_eq!(policy, policy.to_string().parse().unwrap());
+    }
+
+    assert!("60s".parse::<RetentionPolicy>().is_err());
+    assert!("60s@".parse::<RetentionPolicy>().is_err());
+    assert!("0@frame_nr".parse::<RetentionPolicy>().is_err());
+    assert!("-5@frame_nr".parse::<RetentionPolicy>().is_err());
+    assert!("1.5s@log_time".parse::<RetentionPolicy>().is_err());
+    assert!("5d@log_time".parse::<RetentionPolicy>().is_err());
+    assert!("lots@frame_nr".parse::<RetentionPolicy>().is_err());
+}
+
 #[test]
 fn protected_gc() {
     init_logs();
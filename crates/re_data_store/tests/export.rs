@@ -0,0 +1,181 @@
+//! Exporting a datastore to Arrow IPC and Parquet files.
+
+use re_data_store::{test_row, DataStore, DataStoreConfig, ExportFormat};
+use re_log_types::{build_frame_nr, build_log_time, EntityPath, EntityPathPart, Time, TimePoint};
+use re_types::components::{Color, InstanceKey, Position2D};
+use re_types_core::Loggable as _;
+
+fn create_store() -> DataStore {
+    let mut store = DataStore::new(
+        re_log_types::StoreId::random(re_log_types::StoreKind::Recording),
+        InstanceKey::name(),
+        DataStoreConfig::default(),
+    );
+
+    let points = EntityPath::from("world/points");
+    let colors = EntityPath::from("world/colors");
+
+    for frame_nr in 0..3 {
+        let positions = [Position2D::new(1.0, 2.0), Position2D::new(3.0, 4.0)];
+        let row = test_row!(points @
+            [build_frame_nr(frame_nr.into()), build_log_time(Time::now())] => 2; [
+                positions.as_slice()
+        ]);
+        store.insert_row(&row).unwrap();
+    }
+
+    // Only on one of the timelines, and some timeless data.
+    let row = test_row!(colors @ [build_frame_nr(10.into())] => 1; [[Color::from_rgb(255, 0, 0)].as_slice()]);
+    store.insert_row(&row).unwrap();
+    let timeless = TimePoint::timeless();
+    let row = test_row!(colors @ timeless => 1; [[Color::from_rgb(0, 255, 0)].as_slice()]);
+    store.insert_row(&row).unwrap();
+
+    store
+}
+
+#[test]
+fn export_arrow_ipc() {
+    let store = create_store();
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut paths = store
+        .export_to_dir(dir.path(), ExportFormat::ArrowIpc)
+        .unwrap();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            dir.path().join("world.colors.arrow"),
+            dir.path().join("world.points.arrow"),
+        ]
+    );
+
+    let mut file = std::fs::File::open(&paths[0]).unwrap();
+    let metadata = arrow2::io::ipc::read::read_file_metadata(&mut file).unwrap();
+    let field_names = metadata
+        .schema
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect::<Vec<_>>();
+
+    // Only the timelines this entity has data on, even if only some of its rows do.
+    assert!(field_names.contains(&"frame_nr"));
+    assert!(!field_names.contains(&"log_time"));
+
+    // Component columns are named after their component, and keep Rerun's metadata.
+    let color_field = metadata
+        .schema
+        .fields
+        .iter()
+        .find(|field| field.name == Color::name().as_str())
+        .unwrap();
+    assert_eq!(
+        color_field
+            .metadata
+            .get(re_log_types::METADATA_KIND)
+            .map(String::as_str),
+        Some(re_log_types::METADATA_KIND_DATA)
+    );
+
+    let chunks = arrow2::io::ipc::read::FileReader::new(file, metadata, None, None)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(chunks.iter().map(|chunk| chunk.len()).sum::<usize>(), 2);
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn export_parquet() {
+    let store = create_store();
+    let dir = tempfile::tempdir().unwrap();
+
+    let paths = store
+        .export_to_dir(dir.path(), ExportFormat::Parquet)
+        .unwrap();
+    assert_eq!(paths.len(), 2);
+
+    for path in &paths {
+        let mut file = std::fs::File::open(path).unwrap();
+        let metadata = arrow2::io::parquet::read::read_metadata(&mut file).unwrap();
+        let schema = arrow2::io::parquet::read::infer_schema(&metadata).unwrap();
+        assert!(schema.fields.iter().any(|field| field.name == "frame_nr"));
+        assert!(0 < metadata.num_rows);
+    }
+
+    // Fixed-size lists (`Vec2D`) can't be written to Parquet as-is, but must not be dropped.
+    let path = dir.path().join("world.points.parquet");
+    let mut file = std::fs::File::open(path).unwrap();
+    let metadata = arrow2::io::parquet::read::read_metadata(&mut file).unwrap();
+    let schema = arrow2::io::parquet::read::infer_schema(&metadata).unwrap();
+    let position_field = schema
+        .fields
+        .iter()
+        .find(|field| field.name == Position2D::name().as_str())
+        .unwrap();
+
+    // The original datatype is kept, so the column can be converted back when loading.
+    let original = position_field
+        .metadata
+        .get(re_log_types::METADATA_ORIGINAL_DATATYPE)
+        .and_then(|datatype| re_log_types::DataTable::decode_datatype(datatype));
+    let Some(arrow2::datatypes::DataType::List(item)) = original else {
+        panic!("expected a list, got {original:?}");
+    };
+    assert_eq!(
+        item.data_type.to_logical_type(),
+        &Position2D::arrow_datatype()
+    );
+}
+
+#[test]
+fn export_file_names() {
+    let mut store = DataStore::new(
+        re_log_types::StoreId::random(re_log_types::StoreKind::Recording),
+        InstanceKey::name(),
+        DataStoreConfig::default(),
+    );
+
+    let entity_paths = [
+        EntityPath::root(),
+        EntityPath::from("root"),
+        EntityPath::from("a/b"),
+        EntityPath::new(vec![EntityPathPart::from("a.b")]),
+        EntityPath::new(vec![EntityPathPart::from("back\\slash")]),
+    ];
+    for entity_path in &entity_paths {
+        let timeless = TimePoint::timeless();
+        let row = test_row!(entity_path @ timeless => 1; [[Color::from_rgb(255, 0, 0)].as_slice()]);
+        store.insert_row(&row).unwrap();
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut file_names = store
+        .export_to_dir(dir.path(), ExportFormat::ArrowIpc)
+        .unwrap()
+        .into_iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    file_names.sort();
+    assert_eq!(
+        file_names,
+        vec![
+            "@root.arrow",
+            "a%2Eb.arrow",
+            "a.b.arrow",
+            "back%5Cslash.arrow",
+            "root.arrow",
+        ]
+    );
+
+    // Only differing in case: these would overwrite each other on some file systems.
+    let entity_path = EntityPath::from("Root");
+    let timeless = TimePoint::timeless();
+    let row = test_row!(entity_path @ timeless => 1; [[Color::from_rgb(255, 0, 0)].as_slice()]);
+    store.insert_row(&row).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    assert!(store
+        .export_to_dir(dir.path(), ExportFormat::ArrowIpc)
+        .is_err());
+}
@@ -0,0 +1,325 @@
+//! Spilling the datastore to disk and reading it back.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use re_data_store::{
+    test_row, test_util::sanity_unwrap, DataStore, DataStoreConfig, DataStoreRowStats,
+    DataStoreStats, GarbageCollectionOptions, LatestAtQuery, RangeQuery, TimeInt, TimeRange,
+    WriteError,
+};
+use re_log_types::{build_frame_nr, DataCell, EntityPath, RowId, TimeType, Timeline};
+use re_types::components::{Color, InstanceKey, Position2D};
+use re_types::datagen::{build_some_colors, build_some_positions2d};
+use re_types_core::Loggable as _;
+
+// ---
+
+const NUM_FRAMES: i64 = 100;
+
+fn create_store() -> DataStore {
+    create_store_with_config(Default::default())
+}
+
+fn create_store_with_config(config: DataStoreConfig) -> DataStore {
+    let mut store = DataStore::new(
+        re_log_types::StoreId::random(re_log_types::StoreKind::Recording),
+        InstanceKey::name(),
+        DataStoreConfig {
+            indexed_bucket_num_rows: 8,
+            ..config
+        },
+    );
+
+    let ent_path = EntityPath::from("this/that");
+    for frame_nr in 0..NUM_FRAMES {
+        // Colors are sparse, so that some spilled columns are partially (or entirely) empty.
+        let row = if frame_nr < 50 && frame_nr % 3 == 0 {
+            test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 2; [
+                build_some_positions2d(2), build_some_colors(2)
+            ])
+        } else {
+            test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 2; [
+                build_some_positions2d(2)
+            ])
+        };
+        store.insert_row(&row).unwrap();
+    }
+
+    store
+}
+
+type QueryResults = Vec<(Option<TimeInt>, RowId, [Option<DataCell>; 2])>;
+
+fn query_everything(store: &DataStore) -> (QueryResults, QueryResults) {
+    let timeline = Timeline::new("frame_nr", TimeType::Sequence);
+    let ent_path = EntityPath::from("this/that");
+    let components = [Position2D::name(), Color::name()];
+
+    let latest_at = (0..NUM_FRAMES)
+        .flat_map(|frame_nr| {
+            let query = LatestAtQuery::new(timeline, frame_nr.into());
+            [Position2D::name(), Color::name()]
+                .into_iter()
+                .filter_map(|primary| store.latest_at(&query, &ent_path, primary, &components))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let query = RangeQuery::new(timeline, TimeRange::new(TimeInt::MIN, TimeInt::MAX));
+    let range = store.range(&query, &ent_path, components).collect();
+
+    (latest_at, range)
+}
+
+fn num_files(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir).unwrap().count()
+}
+
+#[test]
+fn spill_and_query() {
+    let mut store = create_store();
+    let expected = query_everything(&store);
+    let stats_before = DataStoreStats::from_store(&store);
+
+    let dir = tempfile::tempdir().unwrap();
+    let num_bytes_spilled = store.spill_to_disk(dir.path(), f64::INFINITY).unwrap();
+    sanity_unwrap(&store);
+
+    assert!(0 < num_bytes_spilled);
+    assert!(0 < num_files(dir.path()));
+    let stats_after = DataStoreStats::from_store(&store);
+    assert_eq!(
+        stats_before.temporal.num_bytes - num_bytes_spilled,
+        stats_after.temporal.num_bytes
+    );
+    assert_eq!(
+        stats_before.temporal.num_rows,
+        stats_after.temporal.num_rows
+    );
+
+    // Nothing left to spill.
+    assert_eq!(0, store.spill_to_disk(dir.path(), f64::INFINITY).unwrap());
+
+    assert_eq!(expected, query_everything(&store));
+
+    // Dumping reads spilled data back too.
+    let num_cells = |store: &DataStore| {
+        store
+            .to_data_tables(None)
+            .flat_map(|table| table.to_rows().collect::<Vec<_>>())
+            .map(|row| row.unwrap().cells().len())
+            .sum::<usize>()
+    };
+    assert_eq!(num_cells(&create_store()), num_cells(&store));
+
+    drop(store);
+    assert_eq!(0, num_files(dir.path()));
+}
+
+#[test]
+fn spill_partially() {
+    let mut store = create_store();
+    let expected = query_everything(&store);
+
+    let dir = tempfile::tempdir().unwrap();
+    let num_bytes_spilled = store.spill_to_disk(dir.path(), 1.0).unwrap();
+    sanity_unwrap(&store);
+
+    // A single bucket is enough.
+    assert!(0 < num_bytes_spilled);
+    assert_eq!(1, num_files(dir.path()));
+
+    assert_eq!(expected, query_everything(&store));
+}
+
+#[test]
+fn spill_then_write_and_gc() {
+    init_logs();
+
+    let mut store = create_store();
+
+    let dir = tempfile::tempdir().unwrap();
+    store.spill_to_disk(dir.path(), f64::INFINITY).unwrap();
+    let num_spilled = num_files(dir.path());
+
+    // Writing into a spilled bucket brings it back into memory.
+    let ent_path = EntityPath::from("this/that");
+    let row = test_row!(ent_path @ [build_frame_nr(1.into())] => 2; [build_some_colors(2)]);
+    store.insert_row(&row).unwrap();
+    sanity_unwrap(&store);
+    assert_eq!(num_spilled - 1, num_files(dir.path()));
+
+    let query = LatestAtQuery::new(Timeline::new("frame_nr", TimeType::Sequence), 1.into());
+    let (_, row_id, _) = store
+        .latest_at(&query, &ent_path, Color::name(), &[Color::name()])
+        .unwrap();
+    assert_eq!(row.row_id(), row_id);
+
+    // The GC reports the spilled data as deleted, like any other.
+    let (store_events, _) = store.gc(&GarbageCollectionOptions::gc_everything());
+    sanity_unwrap(&store);
+    assert_eq!(NUM_FRAMES as usize + 1, store_events.len());
+    for event in &store_events {
+        assert!(event.cells.contains_key(&Position2D::name()) || event.row_id == row.row_id());
+    }
+    assert_eq!(0, num_files(dir.path()));
+}
+
+#[test]
+fn spill_and_query_from_cache() {
+    let mut store = create_store();
+    let expected = query_everything(&store);
+
+    let dir = tempfile::tempdir().unwrap();
+    store.spill_to_disk(dir.path(), f64::INFINITY).unwrap();
+    assert_eq!(expected, query_everything(&store));
+
+    // What was read back is still around.
+    for entry in std::fs::read_dir(dir.path()).unwrap() {
+        std::fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+    assert_eq!(expected, query_everything(&store));
+}
+
+#[test]
+fn spill_cache_is_counted_and_bounded() {
+    let mut store = create_store();
+    let dir = tempfile::tempdir().unwrap();
+    store.spill_to_disk(dir.path(), f64::INFINITY).unwrap();
+    assert_eq!(
+        DataStoreRowStats::default(),
+        DataStoreStats::from_store(&store).spill_cache
+    );
+
+    // What is read back is counted…
+    let stats_before = DataStoreStats::from_store(&store);
+    query_everything(&store);
+    let stats_after = DataStoreStats::from_store(&store);
+    let num_bytes_cached = stats_after.spill_cache.num_bytes;
+    assert!(0 < num_bytes_cached);
+    assert_eq!(
+        stats_before.total.num_bytes + num_bytes_cached,
+        stats_after.total.num_bytes
+    );
+
+    // …and goes first when spilling, as there's no need to write it down again.
+    assert_eq!(
+        num_bytes_cached,
+        store
+            .spill_to_disk(dir.path(), num_bytes_cached as f64)
+            .unwrap()
+    );
+    assert_eq!(
+        DataStoreRowStats::default(),
+        DataStoreStats::from_store(&store).spill_cache
+    );
+
+    // Nothing larger than the whole cache is kept.
+    let mut store = create_store_with_config(DataStoreConfig {
+        spill_cache_max_bytes: 1,
+        ..Default::default()
+    });
+    let expected = query_everything(&store);
+    store.spill_to_disk(dir.path(), f64::INFINITY).unwrap();
+    assert_eq!(expected, query_everything(&store));
+    assert_eq!(
+        DataStoreRowStats::default(),
+        DataStoreStats::from_store(&store).spill_cache
+    );
+}
+
+#[test]
+fn spill_and_fail_to_read_back() {
+    init_logs();
+
+    let mut store = create_store();
+    let expected = query_everything(&store);
+    let stats_before = DataStoreStats::from_store(&store);
+
+    let dir = tempfile::tempdir().unwrap();
+    store.spill_to_disk(dir.path(), f64::INFINITY).unwrap();
+
+    // Hide the spill files for a while…
+    let elsewhere = tempfile::tempdir().unwrap();
+    let move_files = |from: &std::path::Path, to: &std::path::Path| {
+        for entry in std::fs::read_dir(from).unwrap() {
+            let path = entry.unwrap().path();
+            std::fs::rename(&path, to.join(path.file_name().unwrap())).unwrap();
+        }
+    };
+    move_files(dir.path(), elsewhere.path());
+
+    // …so that writing into a spilled bucket fails, without touching the store…
+    let ent_path = EntityPath::from("this/that");
+    let row = test_row!(ent_path @ [build_frame_nr(1.into())] => 2; [build_some_colors(2)]);
+    assert!(matches!(store.insert_row(&row), Err(WriteError::Spill(_))));
+    sanity_unwrap(&store);
+    assert_eq!(
+        stats_before.temporal.num_rows,
+        DataStoreStats::from_store(&store).temporal.num_rows
+    );
+
+    // …and the same write succeeds once they're back, with nothing lost.
+    move_files(elsewhere.path(), dir.path());
+    assert_eq!(expected, query_everything(&store));
+    store.insert_row(&row).unwrap();
+    sanity_unwrap(&store);
+}
+
+#[test]
+fn spill_and_fail_to_query() {
+    init_logs();
+
+    let mut store = create_store();
+    let expected = query_everything(&store);
+
+    // Only the oldest bucket can be read back.
+    let dir = tempfile::tempdir().unwrap();
+    let oldest = tempfile::tempdir().unwrap();
+    store.spill_to_disk(oldest.path(), 1.0).unwrap();
+    store.spill_to_disk(dir.path(), f64::INFINITY).unwrap();
+    let elsewhere = tempfile::tempdir().unwrap();
+    for entry in std::fs::read_dir(dir.path()).unwrap() {
+        let path = entry.unwrap().path();
+        std::fs::rename(&path, elsewhere.path().join(path.file_name().unwrap())).unwrap();
+    }
+
+    let timeline = Timeline::new("frame_nr", TimeType::Sequence);
+    let ent_path = EntityPath::from("this/that");
+    let components = [Position2D::name(), Color::name()];
+
+    // The latest data can't be read: the older data mustn't be returned instead.
+    let query = LatestAtQuery::new(timeline, (NUM_FRAMES - 20).into());
+    assert!(store
+        .try_latest_at(&query, &ent_path, Position2D::name(), &components)
+        .is_err());
+    assert!(store
+        .latest_at(&query, &ent_path, Position2D::name(), &components)
+        .is_none());
+
+    // The range gets as far as the oldest bucket, and no further.
+    let query = RangeQuery::new(timeline, TimeRange::new(TimeInt::MIN, TimeInt::MAX));
+    let results: Vec<_> = store.try_range(&query, &ent_path, components).collect();
+    let num_read = results.iter().take_while(|result| result.is_ok()).count();
+    assert!(0 < num_read && num_read < results.len());
+    assert!(results[num_read].is_err());
+    let range: QueryResults = store.range(&query, &ent_path, components).collect();
+    assert_eq!(expected.1[..num_read], range[..]);
+
+    for entry in std::fs::read_dir(elsewhere.path()).unwrap() {
+        let path = entry.unwrap().path();
+        std::fs::rename(&path, dir.path().join(path.file_name().unwrap())).unwrap();
+    }
+    assert_eq!(expected, query_everything(&store));
+}
+
+pub fn init_logs() {
+    static INIT: AtomicBool = AtomicBool::new(false);
+
+    if INIT
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+    {
+        re_log::setup_native_logging();
+    }
+}
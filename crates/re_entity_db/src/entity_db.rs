@@ -115,6 +115,11 @@ pub struct EntityDb {
 
 impl EntityDb {
     pub fn new(store_id: StoreId) -> Self {
+        Self::with_store_config(store_id, DataStoreConfig::default())
+    }
+
+    /// Like [`Self::new`], with a [`DataStore`] configured by `store_config`.
+    pub fn with_store_config(store_id: StoreId, store_config: DataStoreConfig) -> Self {
         Self {
             store_id: store_id.clone(),
             data_source: None,
@@ -126,7 +131,7 @@ impl EntityDb {
             data_store: re_data_store::DataStore::new(
                 store_id.clone(),
                 InstanceKey::name(),
-                DataStoreConfig::default(),
+                store_config,
             ),
             stats: IngestionStatistics::new(store_id),
         }
@@ -450,6 +455,43 @@ impl EntityDb {
         });
     }
 
+    /// Write roughly `fraction_to_spill` of the temporal data to files in `dir`, oldest first.
+    ///
+    /// Unlike [`Self::purge_fraction_of_ram`], nothing is lost: see [`DataStore::spill_to_disk`].
+    /// The spilled data that was read back from disk counts as temporal data here, and goes first.
+    /// Returns how many bytes were freed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spill_fraction_to_disk(&mut self, dir: &std::path::Path, fraction_to_spill: f32) -> u64 {
+        re_tracing::profile_function!();
+
+        assert!((0.0..=1.0).contains(&fraction_to_spill));
+        let num_bytes_to_spill = (self.data_store.temporal_size_bytes()
+            + self.data_store.spill_cache_size_bytes()) as f64
+            * fraction_to_spill as f64;
+        match self.data_store.spill_to_disk(dir, num_bytes_to_spill) {
+            Ok(num_bytes_spilled) => num_bytes_spilled,
+            Err(err) => {
+                re_log::error_once!("Failed to spill data to disk: {err}");
+                0
+            }
+        }
+    }
+
+    /// Forget everything outside of the retention window of the store, if it has one.
+    ///
+    /// See [`DataStoreConfig::retention`].
+    pub fn enforce_retention(&mut self) {
+        re_tracing::profile_function!();
+
+        let Some(policy) = self.data_store.config().retention else {
+            return;
+        };
+        self.gc(&GarbageCollectionOptions {
+            time_budget: DEFAULT_GC_TIME_BUDGET,
+            ..GarbageCollectionOptions::retention(policy)
+        });
+    }
+
     pub fn gc(&mut self, gc_options: &GarbageCollectionOptions) {
         re_tracing::profile_function!();
 
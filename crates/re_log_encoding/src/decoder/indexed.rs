@@ -0,0 +1,312 @@
+//! Random access into `.rrd` files, using the index at the end of the file.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use re_log_types::LogMsg;
+
+use crate::decoder::{read_options, DecodeError, MessageReader, VersionPolicy};
+use crate::index::{IndexQuery, MessageIndexEntry, RrdIndex, INDEX_MAGIC, TRAILER_SIZE};
+use crate::{FileHeader, MessageHeader};
+
+/// Decodes only the messages you ask for from a seekable `.rrd` file.
+///
+/// If the file was written with an index (see [`crate::index`]), opening it only reads the
+/// index. Otherwise the whole file is scanned once to build the same index in memory.
+pub struct IndexedDecoder<R: Read + Seek> {
+    read: R,
+    reader: MessageReader,
+    index: RrdIndex,
+
+    /// Was the index read from the file, rather than built by scanning it?
+    has_footer: bool,
+}
+
+impl<R: Read + Seek> IndexedDecoder<R> {
+    pub fn new(version_policy: VersionPolicy, mut read: R) -> Result<Self, DecodeError> {
+        re_tracing::profile_function!();
+
+        read.seek(SeekFrom::Start(0)).map_err(DecodeError::Read)?;
+        let mut data = [0_u8; FileHeader::SIZE];
+        read.read_exact(&mut data).map_err(DecodeError::Read)?;
+        let compression = read_options(version_policy, &data)?.compression;
+
+        let mut reader = MessageReader::new(compression);
+
+        let footer = match read_footer(&mut read) {
+            Ok(footer) => footer,
+            Err(err) => {
+                re_log::warn!(
+                    "Ignoring invalid .rrd index, scanning the whole file instead: {err}"
+                );
+                None
+            }
+        };
+
+        let (index, has_footer) = if let Some(index) = footer {
+            (index, true)
+        } else {
+            (scan_for_index(&mut read, &mut reader)?, false)
+        };
+
+        Ok(Self {
+            read,
+            reader,
+            index,
+            has_footer,
+        })
+    }
+
+    /// Did the file contain an index?
+    ///
+    /// If not, it was built by decoding the whole file when opening it.
+    pub fn has_footer(&self) -> bool {
+        self.has_footer
+    }
+
+    pub fn index(&self) -> &RrdIndex {
+        &self.index
+    }
+
+    /// Decode the message whose header starts at the given offset.
+    pub fn read_message_at(&mut self, byte_offset: u64) -> Result<LogMsg, DecodeError> {
+        self.read
+            .seek(SeekFrom::Start(byte_offset))
+            .map_err(DecodeError::Read)?;
+        let header = MessageHeader::decode(&mut self.read)?;
+        self.reader.read_message(&mut self.read, header)
+    }
+
+    /// Decode all messages matching the query, in file order.
+    ///
+    /// [`LogMsg::SetStoreInfo`] messages of the matching stores are always included.
+    pub fn query(
+        &mut self,
+        query: &IndexQuery,
+    ) -> impl Iterator<Item = Result<LogMsg, DecodeError>> + '_ {
+        let offsets = self.index.offsets(query);
+        offsets
+            .into_iter()
+            .map(move |byte_offset| self.read_message_at(byte_offset))
+    }
+}
+
+/// Returns `None` if the file has no index.
+fn read_footer(read: &mut (impl Read + Seek)) -> Result<Option<RrdIndex>, DecodeError> {
+    re_tracing::profile_function!();
+
+    let file_len = read.seek(SeekFrom::End(0)).map_err(DecodeError::Read)?;
+    let min_len = (FileHeader::SIZE + MessageHeader::SIZE + TRAILER_SIZE) as u64;
+    if file_len < min_len {
+        return Ok(None);
+    }
+
+    let mut trailer = [0_u8; TRAILER_SIZE];
+    read.seek(SeekFrom::Start(file_len - TRAILER_SIZE as u64))
+        .map_err(DecodeError::Read)?;
+    read.read_exact(&mut trailer).map_err(DecodeError::Read)?;
+
+    if &trailer[8..] != INDEX_MAGIC {
+        return Ok(None);
+    }
+
+    let index_len = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+    let index_start = (file_len - min_len)
+        .checked_sub(index_len)
+        .map(|offset| offset + (FileHeader::SIZE + MessageHeader::SIZE) as u64)
+        .ok_or(DecodeError::InvalidIndex)?;
+
+    read.seek(SeekFrom::Start(index_start - MessageHeader::SIZE as u64))
+        .map_err(DecodeError::Read)?;
+    if !MessageHeader::decode(read)?.is_end_of_messages() {
+        return Err(DecodeError::InvalidIndex);
+    }
+
+    let mut index_bytes = vec![0_u8; index_len as usize];
+    read.read_exact(&mut index_bytes)
+        .map_err(DecodeError::Read)?;
+
+    Ok(Some(rmp_serde::from_slice(&index_bytes)?))
+}
+
+/// Build the index of a file that doesn't have one, by decoding every message.
+fn scan_for_index(
+    read: &mut (impl Read + Seek),
+    reader: &mut MessageReader,
+) -> Result<RrdIndex, DecodeError> {
+    re_tracing::profile_function!();
+
+    let mut index = RrdIndex::default();
+
+    let mut byte_offset = FileHeader::SIZE as u64;
+    read.seek(SeekFrom::Start(byte_offset))
+        .map_err(DecodeError::Read)?;
+
+    loop {
+        let header = match MessageHeader::decode(read) {
+            Ok(header) => header,
+            Err(DecodeError::Read(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break;
+            }
+            Err(err) => return Err(err),
+        };
+        if header.is_end_of_messages() {
+            break;
+        }
+
+        let msg = reader.read_message(read, header)?;
+        index
+            .entries
+            .push(MessageIndexEntry::from_msg(byte_offset, &msg));
+
+        byte_offset = read.stream_position().map_err(DecodeError::Read)?;
+    }
+
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use re_log_types::{
+        ApplicationId, DataRow, DataTable, EntityPath, EntityPathFilter, RowId, SetStoreInfo,
+        StoreId, StoreInfo, StoreKind, StoreSource, TableId, Time, TimeInt, TimePoint, TimeRange,
+        TimeType, Timeline,
+    };
+    use re_types::components::Text;
+
+    use crate::encoder::Encoder;
+    use crate::EncodingOptions;
+
+    use super::*;
+
+    fn test_messages(store_id: &StoreId) -> Vec<LogMsg> {
+        let timeline = Timeline::new("frame", TimeType::Sequence);
+
+        let mut messages = vec![LogMsg::SetStoreInfo(SetStoreInfo {
+            row_id: RowId::new(),
+            info: StoreInfo {
+                application_id: ApplicationId("test".to_owned()),
+                store_id: store_id.clone(),
+                is_official_example: false,
+                started: Time::from_ns_since_epoch(0),
+                store_source: StoreSource::Unknown,
+                store_kind: StoreKind::Recording,
+            },
+        })];
+
+        for frame in 0..10_i64 {
+            for entity_path in ["a", "b"] {
+                let row = DataRow::from_cells1_sized(
+                    RowId::new(),
+                    EntityPath::from(entity_path),
+                    TimePoint::from_iter([(timeline, TimeInt::from(frame))]),
+                    1,
+                    [Text::from("hello")].as_slice(),
+                )
+                .unwrap();
+                let table = DataTable::from_rows(TableId::new(), [row]);
+                messages.push(LogMsg::ArrowMsg(
+                    store_id.clone(),
+                    table.to_arrow_msg().unwrap(),
+                ));
+            }
+        }
+
+        messages
+    }
+
+    /// Arrow metadata doesn't survive a roundtrip bit-for-bit, so we compare ids instead.
+    fn msg_ids(messages: &[LogMsg]) -> Vec<String> {
+        messages
+            .iter()
+            .map(|msg| match msg {
+                LogMsg::SetStoreInfo(msg) => msg.row_id.to_string(),
+                LogMsg::ArrowMsg(_, msg) => msg.table_id.to_string(),
+            })
+            .collect()
+    }
+
+    fn encode(messages: &[LogMsg], indexed: bool) -> Vec<u8> {
+        let mut bytes = vec![];
+        let write = std::io::Cursor::new(&mut bytes);
+        let mut encoder = if indexed {
+            Encoder::new_indexed(EncodingOptions::COMPRESSED, write).unwrap()
+        } else {
+            Encoder::new(EncodingOptions::COMPRESSED, write).unwrap()
+        };
+        for msg in messages {
+            encoder.append(msg).unwrap();
+        }
+        encoder.finish().unwrap();
+        drop(encoder);
+        bytes
+    }
+
+    #[test]
+    fn linear_decoding_stops_at_index() {
+        let store_id = StoreId::random(StoreKind::Recording);
+        let messages = test_messages(&store_id);
+        let bytes = encode(&messages, true);
+
+        let decoded = crate::decoder::decode_bytes(VersionPolicy::Error, &bytes).unwrap();
+        assert_eq!(msg_ids(&messages), msg_ids(&decoded));
+    }
+
+    #[test]
+    fn query_with_and_without_footer() {
+        let store_id = StoreId::random(StoreKind::Recording);
+        let messages = test_messages(&store_id);
+        let timeline = Timeline::new("frame", TimeType::Sequence);
+
+        for indexed in [true, false] {
+            let bytes = encode(&messages, indexed);
+            let mut decoder =
+                IndexedDecoder::new(VersionPolicy::Error, std::io::Cursor::new(bytes)).unwrap();
+            assert_eq!(decoder.has_footer(), indexed);
+            assert_eq!(decoder.index().entries.len(), messages.len());
+
+            let all = decoder
+                .query(&IndexQuery::default())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(msg_ids(&messages), msg_ids(&all));
+
+            let query = IndexQuery {
+                store_id: Some(store_id.clone()),
+                entity_path_filter: Some(EntityPathFilter::parse_forgiving("+ a")),
+                time_range: Some((timeline, TimeRange::new(TimeInt::from(2), TimeInt::from(4)))),
+            };
+            let selected = decoder
+                .query(&query)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            // The store info, then "a" at frames 2, 3 and 4.
+            let expected = [0, 5, 7, 9].map(|i| messages[i].clone());
+            assert_eq!(msg_ids(&expected), msg_ids(&selected));
+        }
+    }
+
+    #[test]
+    fn invalid_footer_falls_back_to_scanning() {
+        let store_id = StoreId::random(StoreKind::Recording);
+        let messages = test_messages(&store_id);
+        let mut bytes = encode(&messages, true);
+
+        // Garble the index itself, keeping the trailer intact.
+        let index_end = bytes.len() - TRAILER_SIZE;
+        for byte in &mut bytes[index_end - 16..index_end] {
+            *byte = 0xff;
+        }
+
+        let mut decoder =
+            IndexedDecoder::new(VersionPolicy::Error, std::io::Cursor::new(bytes)).unwrap();
+        assert!(!decoder.has_footer());
+        assert_eq!(decoder.index().entries.len(), messages.len());
+
+        let all = decoder
+            .query(&IndexQuery::default())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(msg_ids(&messages), msg_ids(&all));
+    }
+}
@@ -1,5 +1,6 @@
 //! Decoding [`LogMsg`]:es from `.rrd` files/streams.
 
+pub mod indexed;
 pub mod stream;
 
 use re_build_info::CrateVersion;
@@ -85,8 +86,14 @@ pub enum DecodeError {
     #[error("lz4 error: {0}")]
     Lz4(lz4_flex::block::DecompressError),
 
+    #[error("zstd error: {0}")]
+    Zstd(std::io::Error),
+
     #[error("MsgPack error: {0}")]
     MsgPack(#[from] rmp_serde::decode::Error),
+
+    #[error("The index at the end of the file is corrupt")]
+    InvalidIndex,
 }
 
 // ----------------------------------------------------------------------------
@@ -133,13 +140,108 @@ pub fn read_options(
     Ok(options)
 }
 
-pub struct Decoder<R: std::io::Read> {
+/// Decompress a single message into `uncompressed`, which must be exactly the size of the message.
+pub(crate) fn decompress_into(
+    compression: Compression,
+    compressed: &[u8],
+    uncompressed: &mut [u8],
+) -> Result<(), DecodeError> {
+    match compression {
+        Compression::Off => {
+            uncompressed.copy_from_slice(compressed);
+        }
+        Compression::LZ4 => {
+            re_tracing::profile_scope!("lz4");
+            lz4_flex::block::decompress_into(compressed, uncompressed).map_err(DecodeError::Lz4)?;
+        }
+        Compression::Zstd { .. } => {
+            re_tracing::profile_scope!("zstd");
+            use std::io::Read as _;
+
+            // NOTE: `ruzstd` rather than `zstd`, so that decoding doesn't need a C toolchain
+            // and works on the web too.
+            let mut compressed = compressed;
+            let mut decoder = ruzstd::StreamingDecoder::new(&mut compressed).map_err(|err| {
+                DecodeError::Zstd(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    err.to_string(),
+                ))
+            })?;
+            decoder
+                .read_exact(uncompressed)
+                .map_err(DecodeError::Zstd)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the body of a message, given its already decoded [`MessageHeader`].
+///
+/// Keeps scratch space around so it can be reused between messages.
+pub(crate) struct MessageReader {
     compression: Compression,
-    read: R,
     uncompressed: Vec<u8>, // scratch space
     compressed: Vec<u8>,   // scratch space
 }
 
+impl MessageReader {
+    pub(crate) fn new(compression: Compression) -> Self {
+        Self {
+            compression,
+            uncompressed: vec![],
+            compressed: vec![],
+        }
+    }
+
+    pub(crate) fn read_message(
+        &mut self,
+        read: &mut impl std::io::Read,
+        header: MessageHeader,
+    ) -> Result<LogMsg, DecodeError> {
+        let uncompressed_len = header.uncompressed_len as usize;
+        self.uncompressed
+            .resize(self.uncompressed.len().max(uncompressed_len), 0);
+
+        match self.compression {
+            Compression::Off => {
+                re_tracing::profile_scope!("read uncompressed");
+                read.read_exact(&mut self.uncompressed[..uncompressed_len])
+                    .map_err(DecodeError::Read)?;
+            }
+            Compression::LZ4 | Compression::Zstd { .. } => {
+                let compressed_len = header.compressed_len as usize;
+                self.compressed
+                    .resize(self.compressed.len().max(compressed_len), 0);
+
+                {
+                    re_tracing::profile_scope!("read compressed");
+                    read.read_exact(&mut self.compressed[..compressed_len])
+                        .map_err(DecodeError::Read)?;
+                }
+
+                decompress_into(
+                    self.compression,
+                    &self.compressed[..compressed_len],
+                    &mut self.uncompressed[..uncompressed_len],
+                )?;
+            }
+        }
+
+        re_tracing::profile_scope!("MsgPack deser");
+        Ok(rmp_serde::from_slice(
+            &self.uncompressed[..uncompressed_len],
+        )?)
+    }
+}
+
+pub struct Decoder<R: std::io::Read> {
+    read: R,
+    reader: MessageReader,
+
+    /// Set once we've seen the end-of-messages marker of an indexed file.
+    reached_end: bool,
+}
+
 impl<R: std::io::Read> Decoder<R> {
     pub fn new(version_policy: VersionPolicy, mut read: R) -> Result<Self, DecodeError> {
         re_tracing::profile_function!();
@@ -149,10 +251,9 @@ impl<R: std::io::Read> Decoder<R> {
         let compression = read_options(version_policy, &data)?.compression;
 
         Ok(Self {
-            compression,
             read,
-            uncompressed: vec![],
-            compressed: vec![],
+            reader: MessageReader::new(compression),
+            reached_end: false,
         })
     }
 }
@@ -163,6 +264,10 @@ impl<R: std::io::Read> Iterator for Decoder<R> {
     fn next(&mut self) -> Option<Self::Item> {
         re_tracing::profile_function!();
 
+        if self.reached_end {
+            return None;
+        }
+
         let header = match MessageHeader::decode(&mut self.read) {
             Ok(header) => header,
             Err(err) => match err {
@@ -173,47 +278,13 @@ impl<R: std::io::Read> Iterator for Decoder<R> {
             },
         };
 
-        let uncompressed_len = header.uncompressed_len as usize;
-        self.uncompressed
-            .resize(self.uncompressed.len().max(uncompressed_len), 0);
-
-        match self.compression {
-            Compression::Off => {
-                re_tracing::profile_scope!("read uncompressed");
-                if let Err(err) = self
-                    .read
-                    .read_exact(&mut self.uncompressed[..uncompressed_len])
-                {
-                    return Some(Err(DecodeError::Read(err)));
-                }
-            }
-            Compression::LZ4 => {
-                let compressed_len = header.compressed_len as usize;
-                self.compressed
-                    .resize(self.compressed.len().max(compressed_len), 0);
-
-                {
-                    re_tracing::profile_scope!("read compressed");
-                    if let Err(err) = self.read.read_exact(&mut self.compressed[..compressed_len]) {
-                        return Some(Err(DecodeError::Read(err)));
-                    }
-                }
-
-                re_tracing::profile_scope!("lz4");
-                if let Err(err) = lz4_flex::block::decompress_into(
-                    &self.compressed[..compressed_len],
-                    &mut self.uncompressed[..uncompressed_len],
-                ) {
-                    return Some(Err(DecodeError::Lz4(err)));
-                }
-            }
+        if header.is_end_of_messages() {
+            // Whatever follows is the index, which we don't need for linear decoding.
+            self.reached_end = true;
+            return None;
         }
 
-        re_tracing::profile_scope!("MsgPack deser");
-        match rmp_serde::from_slice(&self.uncompressed[..uncompressed_len]) {
-            Ok(msg) => Some(Ok(msg)),
-            Err(err) => Some(Err(err.into())),
-        }
+        Some(self.reader.read_message(&mut self.read, header))
     }
 }
 
@@ -251,6 +322,14 @@ fn test_encode_decode() {
             compression: Compression::LZ4,
             serializer: Serializer::MsgPack,
         },
+        EncodingOptions {
+            compression: Compression::Zstd { level: 3 },
+            serializer: Serializer::MsgPack,
+        },
+        EncodingOptions {
+            compression: Compression::Zstd { level: -5 },
+            serializer: Serializer::MsgPack,
+        },
     ];
 
     for options in options {
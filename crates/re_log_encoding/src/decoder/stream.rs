@@ -4,7 +4,7 @@ use std::io::Read;
 
 use re_log_types::LogMsg;
 
-use crate::decoder::read_options;
+use crate::decoder::{decompress_into, read_options};
 use crate::Compression;
 use crate::FileHeader;
 use crate::MessageHeader;
@@ -38,7 +38,7 @@ pub struct StreamDecoder {
 /// StreamHeader
 ///      |
 ///      v
-/// MessageHeader
+/// MessageHeader ----> Done
 /// ^           |
 /// |           |
 /// ---Message<--
@@ -67,6 +67,11 @@ enum State {
     /// to read it, otherwise the call to `decompress_into` or the
     /// MessagePack deserialization may block or even fail.
     Message(MessageHeader),
+
+    /// We've seen the end-of-messages marker of an indexed file.
+    ///
+    /// Anything after it is the index, which we ignore.
+    Done,
 }
 
 impl StreamDecoder {
@@ -81,9 +86,23 @@ impl StreamDecoder {
     }
 
     pub fn push_chunk(&mut self, chunk: Vec<u8>) {
+        if matches!(self.state, State::Done) {
+            return;
+        }
         self.chunks.push(chunk);
     }
 
+    /// Is there a message that was only partially pushed?
+    ///
+    /// If the stream ends now, then it was truncated.
+    pub fn is_mid_message(&self) -> bool {
+        match self.state {
+            State::StreamHeader | State::MessageHeader => !self.chunks.is_empty(),
+            State::Message(_) => true,
+            State::Done => false,
+        }
+    }
+
     pub fn try_read(&mut self) -> Result<Option<LogMsg>, DecodeError> {
         match self.state {
             State::StreamHeader => {
@@ -100,6 +119,10 @@ impl StreamDecoder {
             State::MessageHeader => {
                 if let Some(mut len) = self.chunks.try_read(MessageHeader::SIZE) {
                     let header = MessageHeader::decode(&mut len)?;
+                    if header.is_end_of_messages() {
+                        self.state = State::Done;
+                        return Ok(None);
+                    }
                     self.state = State::Message(header);
                     // we might have data left in the current chunk,
                     // immediately try to read the message content
@@ -110,11 +133,10 @@ impl StreamDecoder {
                 if let Some(bytes) = self.chunks.try_read(header.compressed_len as usize) {
                     let bytes = match self.compression {
                         Compression::Off => bytes,
-                        Compression::LZ4 => {
+                        Compression::LZ4 | Compression::Zstd { .. } => {
                             self.uncompressed
                                 .resize(header.uncompressed_len as usize, 0);
-                            lz4_flex::block::decompress_into(bytes, &mut self.uncompressed)
-                                .map_err(DecodeError::Lz4)?;
+                            decompress_into(self.compression, bytes, &mut self.uncompressed)?;
                             &self.uncompressed
                         }
                     };
@@ -126,6 +148,7 @@ impl StreamDecoder {
                     return Ok(Some(message));
                 }
             }
+            State::Done => {}
         }
 
         Ok(None)
@@ -162,6 +185,11 @@ impl ChunkBuffer {
         self.queue.push_back(Chunk::new(chunk));
     }
 
+    /// No bytes are left to read, nor partially read.
+    fn is_empty(&self) -> bool {
+        self.buffer_fill == 0 && self.queue.is_empty()
+    }
+
     /// Attempt to read exactly `n` bytes out of the queued chunks.
     ///
     /// Returns `None` if there is not enough data to return a slice of `n` bytes.
@@ -358,6 +386,25 @@ mod tests {
         assert_eq!(input, decoded_messages);
     }
 
+    #[test]
+    fn stream_byte_chunks_compressed_zstd() {
+        let (input, data) = test_data(EncodingOptions::COMPRESSED_ZSTD, 16);
+
+        let mut decoder = StreamDecoder::new(VersionPolicy::Error);
+
+        assert_message_incomplete!(decoder.try_read());
+
+        for chunk in data.chunks(1) {
+            decoder.push_chunk(chunk.to_vec());
+        }
+
+        let decoded_messages: Vec<_> = (0..16)
+            .map(|_| assert_message_ok!(decoder.try_read()))
+            .collect();
+
+        assert_eq!(input, decoded_messages);
+    }
+
     #[test]
     fn stream_3x16_chunks() {
         let (input, data) = test_data(EncodingOptions::COMPRESSED, 16);
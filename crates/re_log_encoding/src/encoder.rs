@@ -3,6 +3,7 @@
 use re_build_info::CrateVersion;
 use re_log_types::LogMsg;
 
+use crate::index::{MessageIndexEntry, RrdIndex, MAX_INDEX_ENTRIES};
 use crate::FileHeader;
 use crate::MessageHeader;
 use crate::{Compression, EncodingOptions};
@@ -18,6 +19,9 @@ pub enum EncodeError {
     #[error("lz4 error: {0}")]
     Lz4(lz4_flex::block::CompressError),
 
+    #[error("zstd error: {0}")]
+    Zstd(std::io::Error),
+
     #[error("MsgPack error: {0}")]
     MsgPack(#[from] rmp_serde::encode::Error),
 
@@ -49,6 +53,14 @@ pub struct Encoder<W: std::io::Write> {
     write: W,
     uncompressed: Vec<u8>,
     compressed: Vec<u8>,
+
+    /// Number of bytes written so far, i.e. the offset of the next message.
+    num_bytes_written: u64,
+
+    /// Only built for encoders created with [`Self::new_indexed`].
+    index: Option<RrdIndex>,
+
+    is_finished: bool,
 }
 
 impl<W: std::io::Write> Encoder<W> {
@@ -71,10 +83,41 @@ impl<W: std::io::Write> Encoder<W> {
             write,
             uncompressed: vec![],
             compressed: vec![],
+            num_bytes_written: FileHeader::SIZE as u64,
+            index: None,
+            is_finished: false,
         })
     }
 
+    /// Like [`Self::new`], but keeps track of where every message is written,
+    /// and writes an index at the end of the stream when [`Self::finish`] is called.
+    ///
+    /// Files written this way can't be read by decoders older than the index.
+    /// See [`crate::index`] for details.
+    pub fn new_indexed(options: EncodingOptions, write: W) -> Result<Self, EncodeError> {
+        let mut encoder = Self::new(options, write)?;
+        encoder.index = Some(RrdIndex::default());
+        Ok(encoder)
+    }
+
     pub fn append(&mut self, message: &LogMsg) -> Result<(), EncodeError> {
+        if self.is_finished {
+            return Err(EncodeError::AlreadyFinished);
+        }
+
+        if let Some(index) = &mut self.index {
+            if index.entries.len() < MAX_INDEX_ENTRIES {
+                index
+                    .entries
+                    .push(MessageIndexEntry::from_msg(self.num_bytes_written, message));
+            } else {
+                re_log::warn!(
+                    "More than {MAX_INDEX_ENTRIES} messages, the .rrd file will be written without an index"
+                );
+                self.index = None;
+            }
+        }
+
         self.uncompressed.clear();
         rmp_serde::encode::write_named(&mut self.uncompressed, message)?;
 
@@ -88,6 +131,7 @@ impl<W: std::io::Write> Encoder<W> {
                 self.write
                     .write_all(&self.uncompressed)
                     .map_err(EncodeError::Write)?;
+                self.num_bytes_written += (MessageHeader::SIZE + self.uncompressed.len()) as u64;
             }
             Compression::LZ4 => {
                 let max_len = lz4_flex::block::get_maximum_output_size(self.uncompressed.len());
@@ -103,12 +147,63 @@ impl<W: std::io::Write> Encoder<W> {
                 self.write
                     .write_all(&self.compressed[..compressed_len])
                     .map_err(EncodeError::Write)?;
+                self.num_bytes_written += (MessageHeader::SIZE + compressed_len) as u64;
+            }
+            Compression::Zstd { level } => {
+                let max_len = zstd::zstd_safe::compress_bound(self.uncompressed.len());
+                self.compressed.resize(max_len, 0);
+                let compressed_len = zstd::bulk::compress_to_buffer(
+                    &self.uncompressed,
+                    &mut self.compressed[..],
+                    i32::from(level),
+                )
+                .map_err(EncodeError::Zstd)?;
+                MessageHeader {
+                    uncompressed_len: self.uncompressed.len() as u32,
+                    compressed_len: compressed_len as u32,
+                }
+                .encode(&mut self.write)?;
+                self.write
+                    .write_all(&self.compressed[..compressed_len])
+                    .map_err(EncodeError::Write)?;
+                self.num_bytes_written += (MessageHeader::SIZE + compressed_len) as u64;
             }
         }
 
         Ok(())
     }
 
+    /// Writes the index, if this encoder keeps one, and flushes.
+    ///
+    /// No more messages can be appended afterwards.
+    pub fn finish(&mut self) -> Result<(), EncodeError> {
+        if self.is_finished {
+            return Err(EncodeError::AlreadyFinished);
+        }
+        self.is_finished = true;
+
+        if let Some(index) = self.index.take() {
+            re_tracing::profile_function!();
+
+            MessageHeader::END_OF_MESSAGES.encode(&mut self.write)?;
+
+            let index_bytes = rmp_serde::to_vec_named(&index)?;
+            let index_len = index_bytes.len() as u64;
+
+            self.write
+                .write_all(&index_bytes)
+                .map_err(EncodeError::Write)?;
+            self.write
+                .write_all(&index_len.to_le_bytes())
+                .map_err(EncodeError::Write)?;
+            self.write
+                .write_all(crate::index::INDEX_MAGIC)
+                .map_err(EncodeError::Write)?;
+        }
+
+        self.write.flush().map_err(EncodeError::Write)
+    }
+
     pub fn flush_blocking(&mut self) -> std::io::Result<()> {
         self.write.flush()
     }
@@ -24,13 +24,13 @@ pub enum FileSinkError {
     LogMsgEncode(#[from] crate::encoder::EncodeError),
 }
 
-enum Command {
+pub(crate) enum Command {
     Send(LogMsg),
     Flush(SyncSender<()>),
 }
 
 impl Command {
-    fn flush() -> (Self, Receiver<()>) {
+    pub(crate) fn flush() -> (Self, Receiver<()>) {
         let (tx, rx) = std::sync::mpsc::sync_channel(0); // oneshot
         (Self::Flush(tx), rx)
     }
@@ -61,11 +61,36 @@ impl FileSink {
     /// Start writing log messages to a file at the given path.
     pub fn new(path: impl Into<std::path::PathBuf>) -> Result<Self, FileSinkError> {
         // We always compress on disk
-        let encoding_options = crate::EncodingOptions::COMPRESSED;
+        Self::with_options(path, crate::EncodingOptions::COMPRESSED)
+    }
 
-        let (tx, rx) = std::sync::mpsc::channel();
+    /// Start writing log messages to a file at the given path, using the given encoding.
+    ///
+    /// Use e.g. [`crate::EncodingOptions::COMPRESSED_ZSTD`] to trade some CPU for smaller files.
+    pub fn with_options(
+        path: impl Into<std::path::PathBuf>,
+        encoding_options: crate::EncodingOptions,
+    ) -> Result<Self, FileSinkError> {
+        Self::create(path.into(), encoding_options, false)
+    }
 
-        let path = path.into();
+    /// Like [`Self::with_options`], but writes an index at the end of the file when it is closed,
+    /// for random access with `decoder::indexed::IndexedDecoder`.
+    ///
+    /// Such files can't be read by Rerun versions older than the index, see [`crate::index`].
+    pub fn with_index(
+        path: impl Into<std::path::PathBuf>,
+        encoding_options: crate::EncodingOptions,
+    ) -> Result<Self, FileSinkError> {
+        Self::create(path.into(), encoding_options, true)
+    }
+
+    fn create(
+        path: std::path::PathBuf,
+        encoding_options: crate::EncodingOptions,
+        indexed: bool,
+    ) -> Result<Self, FileSinkError> {
+        let (tx, rx) = std::sync::mpsc::channel();
 
         re_log::debug!("Saving file to {path:?}…");
 
@@ -75,7 +100,11 @@ impl FileSink {
 
         let file = std::fs::File::create(&path)
             .map_err(|err| FileSinkError::CreateFile(path.clone(), err))?;
-        let encoder = crate::encoder::Encoder::new(encoding_options, file)?;
+        let encoder = if indexed {
+            crate::encoder::Encoder::new_indexed(encoding_options, file)?
+        } else {
+            crate::encoder::Encoder::new(encoding_options, file)?
+        };
         let join_handle = spawn_and_stream(Some(&path), encoder, rx)?;
 
         Ok(Self {
@@ -149,6 +178,10 @@ fn spawn_and_stream<W: std::io::Write + Send + 'static>(
                         }
                     }
                 }
+                if let Err(err) = encoder.finish() {
+                    re_log::error!("Failed to finish log stream to {target}: {err}");
+                    return;
+                }
                 re_log::debug!("Log stream written to {target}");
             }
         })
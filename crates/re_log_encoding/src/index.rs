@@ -0,0 +1,225 @@
+//! Optional index stored at the end of an `.rrd` file, for seeking without decoding everything.
+//!
+//! An indexed file looks like this:
+//!
+//! ```text,ignore
+//! FileHeader
+//! MessageHeader, Message
+//! …
+//! MessageHeader { compressed_len: 0, uncompressed_len: 0 }  <- end-of-messages marker
+//! RrdIndex (MsgPack, uncompressed)
+//! index length (u64 little endian)
+//! INDEX_MAGIC
+//! ```
+//!
+//! No valid message is ever zero bytes long, so the end-of-messages marker tells
+//! a linear decoder to stop before it reaches the index.
+//! Files without an index still decode as before.
+//!
+//! Decoders from before the index was introduced stop with an error at the end-of-messages
+//! marker, so indexing is opt-in: see [`crate::encoder::Encoder::new_indexed`] and
+//! [`crate::FileSink::with_index`].
+
+use std::collections::BTreeMap;
+
+use re_log_types::external::arrow2::{
+    array::{Array, PrimitiveArray},
+    datatypes::{DataType, TimeUnit},
+};
+use re_log_types::external::re_types_core::Loggable as _;
+use re_log_types::{
+    EntityPath, EntityPathFilter, LogMsg, StoreId, TimeRange, Timeline, METADATA_KIND,
+    METADATA_KIND_CONTROL, METADATA_KIND_TIME,
+};
+
+/// Magic bytes at the very end of an indexed `.rrd` file.
+pub(crate) const INDEX_MAGIC: &[u8; 4] = b"RRIX";
+
+/// An encoder stops indexing once it has this many entries, and writes no index at all.
+///
+/// The index is kept in memory until the file is closed, so this bounds its size for very long
+/// recordings. Readers then fall back to scanning the file, like for any other unindexed file.
+pub const MAX_INDEX_ENTRIES: usize = 1_000_000;
+
+/// Size of the trailer: the index length followed by [`INDEX_MAGIC`].
+#[cfg(feature = "decoder")]
+pub(crate) const TRAILER_SIZE: usize = 8 + 4;
+
+/// Where each message of an `.rrd` file lives, and what it contains.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RrdIndex {
+    /// One entry per message, in file order.
+    pub entries: Vec<MessageIndexEntry>,
+}
+
+/// Index entry for a single [`LogMsg`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MessageIndexEntry {
+    /// Offset of the message header from the start of the file.
+    pub byte_offset: u64,
+
+    /// The store the message belongs to.
+    pub store_id: StoreId,
+
+    /// Is this a [`LogMsg::SetStoreInfo`]?
+    ///
+    /// These are always returned by queries, so the resulting stream can be loaded on its own.
+    pub is_store_info: bool,
+
+    /// All entities with data in this message.
+    pub entity_paths: Vec<EntityPath>,
+
+    /// The time span covered by the message on each timeline.
+    ///
+    /// Rows without a time on a timeline don't contribute to its range.
+    pub time_ranges: BTreeMap<Timeline, TimeRange>,
+}
+
+impl MessageIndexEntry {
+    /// Only looks at the entity path and time columns of the message, the component data is
+    /// never deserialized.
+    pub fn from_msg(byte_offset: u64, msg: &LogMsg) -> Self {
+        re_tracing::profile_function!();
+
+        let mut entry = Self {
+            byte_offset,
+            store_id: msg.store_id().clone(),
+            is_store_info: matches!(msg, LogMsg::SetStoreInfo(_)),
+            entity_paths: Vec::new(),
+            time_ranges: BTreeMap::new(),
+        };
+
+        if let LogMsg::ArrowMsg(_, arrow_msg) = msg {
+            for (field, column) in arrow_msg.schema.fields.iter().zip(arrow_msg.chunk.iter()) {
+                match field.metadata.get(METADATA_KIND).map(String::as_str) {
+                    Some(METADATA_KIND_TIME) => {
+                        if let Some((timeline, range)) = time_range(&field.name, &**column) {
+                            entry.time_ranges.insert(timeline, range);
+                        }
+                    }
+                    Some(METADATA_KIND_CONTROL) if field.name == EntityPath::name().as_str() => {
+                        match EntityPath::from_arrow(&**column) {
+                            Ok(mut entity_paths) => {
+                                entity_paths.sort();
+                                entity_paths.dedup();
+                                entry.entity_paths = entity_paths;
+                            }
+                            Err(err) => {
+                                // Still index it: an entry without paths is only returned by
+                                // queries without an entity filter.
+                                re_log::warn_once!("Failed to index message: {err}");
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        entry
+    }
+
+    /// Does this message contain data matching the query?
+    pub fn matches(&self, query: &IndexQuery) -> bool {
+        if let Some(store_id) = &query.store_id {
+            if &self.store_id != store_id {
+                return false;
+            }
+        }
+
+        if self.is_store_info {
+            return true;
+        }
+
+        if let Some(filter) = &query.entity_path_filter {
+            if !self
+                .entity_paths
+                .iter()
+                .any(|path| filter.is_included(path))
+            {
+                return false;
+            }
+        }
+
+        if let Some((timeline, range)) = &query.time_range {
+            // Messages without data on the timeline are timeless as far as it is concerned,
+            // so they are relevant to every time window.
+            if let Some(msg_range) = self.time_ranges.get(timeline) {
+                if !msg_range.intersects(*range) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// The span of a serialized time column, see [`Timeline::datatype`].
+///
+/// Returns `None` if the column is empty or isn't a time column.
+fn time_range(name: &str, column: &dyn Array) -> Option<(Timeline, TimeRange)> {
+    let timeline = match column.data_type().to_logical_type() {
+        DataType::Int64 => Timeline::new_sequence(name),
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => Timeline::new_temporal(name),
+        _ => return None,
+    };
+
+    let times = column.as_any().downcast_ref::<PrimitiveArray<i64>>()?;
+    let mut times = times.iter().flatten().copied();
+    let first = times.next()?;
+    let (min, max) = times.fold((first, first), |(min, max), t| (min.min(t), max.max(t)));
+
+    Some((timeline, TimeRange::new(min.into(), max.into())))
+}
+
+/// Selects messages from an indexed `.rrd` file.
+///
+/// An empty query returns everything.
+#[derive(Clone, Default)]
+pub struct IndexQuery {
+    /// Only messages for this store.
+    pub store_id: Option<StoreId>,
+
+    /// Only messages with at least one matching entity.
+    pub entity_path_filter: Option<EntityPathFilter>,
+
+    /// Only messages with data overlapping this time range on the given timeline.
+    pub time_range: Option<(Timeline, TimeRange)>,
+}
+
+impl RrdIndex {
+    /// Byte offsets of all messages matching the query, in file order.
+    pub fn offsets(&self, query: &IndexQuery) -> Vec<u64> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.matches(query))
+            .map(|entry| entry.byte_offset)
+            .collect()
+    }
+
+    /// All stores in the file.
+    pub fn store_ids(&self) -> Vec<StoreId> {
+        let mut store_ids: Vec<StoreId> = Vec::new();
+        for entry in &self.entries {
+            if !store_ids.contains(&entry.store_id) {
+                store_ids.push(entry.store_id.clone());
+            }
+        }
+        store_ids
+    }
+
+    /// The full time span of the file on every timeline.
+    pub fn time_ranges(&self) -> BTreeMap<Timeline, TimeRange> {
+        let mut ranges: BTreeMap<Timeline, TimeRange> = BTreeMap::new();
+        for entry in &self.entries {
+            for (timeline, range) in &entry.time_ranges {
+                ranges
+                    .entry(*timeline)
+                    .and_modify(|r| *r = r.union(*range))
+                    .or_insert(*range);
+            }
+        }
+        ranges
+    }
+}
@@ -10,6 +10,13 @@ pub mod encoder;
 #[cfg(not(target_arch = "wasm32"))]
 mod file_sink;
 
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+mod rotating_file_sink;
+
+#[cfg(any(feature = "encoder", feature = "decoder"))]
+pub mod index;
+
 #[cfg(feature = "stream_from_http")]
 pub mod stream_rrd_from_http;
 
@@ -19,6 +26,10 @@ pub mod stream_rrd_from_http;
 #[cfg(not(target_arch = "wasm32"))]
 pub use file_sink::{FileSink, FileSinkError};
 
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+pub use rotating_file_sink::{RotatingFileSink, RotationOptions};
+
 // ----------------------------------------------------------------------------
 
 #[cfg(any(feature = "encoder", feature = "decoder"))]
@@ -31,12 +42,42 @@ const OLD_RRD_HEADERS: &[[u8; 4]] = &[*b"RRF0", *b"RRF1"];
 
 /// Compression format used.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[repr(u8)]
 pub enum Compression {
-    Off = 0,
+    Off,
 
     /// Very fast compression and decompression, but not very good compression ratio.
-    LZ4 = 1,
+    LZ4,
+
+    /// Slower than [`Self::LZ4`], but with a much better compression ratio.
+    ///
+    /// The `level` is handed straight to zstd: higher is smaller but slower,
+    /// negative levels trade ratio for speed.
+    /// It is stored in the options header, but is not needed for decoding.
+    Zstd {
+        level: i8,
+    },
+}
+
+impl Compression {
+    /// The zstd level we use unless told otherwise; zstd's own default.
+    pub const ZSTD_DEFAULT_LEVEL: i8 = 3;
+
+    /// The byte identifying the codec in the options header.
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Off => 0,
+            Self::LZ4 => 1,
+            Self::Zstd { .. } => 2,
+        }
+    }
+
+    /// The codec-specific parameter stored next to the codec in the options header.
+    fn level_byte(self) -> u8 {
+        match self {
+            Self::Off | Self::LZ4 => 0,
+            Self::Zstd { level } => level.to_le_bytes()[0],
+        }
+    }
 }
 
 /// How we serialize the data
@@ -61,13 +102,26 @@ impl EncodingOptions {
         compression: Compression::LZ4,
         serializer: Serializer::MsgPack,
     };
+    pub const COMPRESSED_ZSTD: Self = Self::zstd(Compression::ZSTD_DEFAULT_LEVEL);
+
+    /// zstd compression with the given level.
+    pub const fn zstd(level: i8) -> Self {
+        Self {
+            compression: Compression::Zstd { level },
+            serializer: Serializer::MsgPack,
+        }
+    }
 
     pub fn from_bytes(bytes: [u8; 4]) -> Result<Self, OptionsError> {
         match bytes {
-            [compression, serializer, 0, 0] => {
-                let compression = match compression {
-                    0 => Compression::Off,
-                    1 => Compression::LZ4,
+            [compression, serializer, level, 0] => {
+                let compression = match (compression, level) {
+                    (0, 0) => Compression::Off,
+                    (1, 0) => Compression::LZ4,
+                    (2, level) => Compression::Zstd {
+                        level: i8::from_le_bytes([level]),
+                    },
+                    (0 | 1, _) => return Err(OptionsError::UnknownReservedBytes),
                     _ => return Err(OptionsError::UnknownCompression(compression)),
                 };
                 let serializer = match serializer {
@@ -85,9 +139,9 @@ impl EncodingOptions {
 
     pub fn to_bytes(self) -> [u8; 4] {
         [
-            self.compression as u8,
+            self.compression.to_byte(),
             self.serializer as u8,
-            0, // reserved
+            self.compression.level_byte(),
             0, // reserved
         ]
     }
@@ -117,7 +171,6 @@ pub(crate) struct FileHeader {
 
 #[cfg(any(feature = "encoder", feature = "decoder"))]
 impl FileHeader {
-    #[cfg(feature = "decoder")]
     pub const SIZE: usize = 12;
 
     #[cfg(feature = "encoder")]
@@ -161,9 +214,21 @@ pub(crate) struct MessageHeader {
 
 #[cfg(any(feature = "encoder", feature = "decoder"))]
 impl MessageHeader {
-    #[cfg(feature = "decoder")]
     pub const SIZE: usize = 8;
 
+    /// Marks the end of the messages in an indexed file, see [`index`].
+    #[cfg(feature = "encoder")]
+    #[cfg(not(target_arch = "wasm32"))] // we do no yet support encoding LogMsgs in the browser
+    pub const END_OF_MESSAGES: Self = Self {
+        compressed_len: 0,
+        uncompressed_len: 0,
+    };
+
+    #[cfg(feature = "decoder")]
+    pub fn is_end_of_messages(&self) -> bool {
+        self.compressed_len == 0 && self.uncompressed_len == 0
+    }
+
     #[cfg(feature = "encoder")]
     #[cfg(not(target_arch = "wasm32"))] // we do no yet support encoding LogMsgs in the browser
     pub fn encode(&self, write: &mut impl std::io::Write) -> Result<(), encoder::EncodeError> {
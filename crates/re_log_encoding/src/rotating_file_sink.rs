@@ -0,0 +1,502 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::Receiver,
+    Arc,
+};
+
+use parking_lot::Mutex;
+
+use re_log_types::{LogMsg, StoreId};
+
+use crate::encoder::Encoder;
+use crate::file_sink::{Command, FileSinkError};
+
+/// When a [`RotatingFileSink`] starts a new segment, and what happens to the old ones.
+#[derive(Clone, Debug)]
+pub struct RotationOptions {
+    /// Start a new segment once the current one has grown to this many bytes.
+    pub max_segment_bytes: Option<u64>,
+
+    /// Start a new segment once the current one has been open for this long.
+    ///
+    /// Only checked when a new message comes in.
+    pub max_segment_duration: Option<std::time::Duration>,
+
+    /// Delete the oldest segments written by this sink, so that at most this many are kept.
+    ///
+    /// `None` keeps all of them.
+    pub max_segments: Option<usize>,
+
+    pub encoding_options: crate::EncodingOptions,
+}
+
+impl Default for RotationOptions {
+    fn default() -> Self {
+        Self {
+            max_segment_bytes: Some(1024 * 1024 * 1024),
+            max_segment_duration: None,
+            max_segments: None,
+            // We always compress on disk
+            encoding_options: crate::EncodingOptions::COMPRESSED,
+        }
+    }
+}
+
+/// Stream log messages to a series of `.rrd` files, starting a new one whenever the current one
+/// gets too big or too old.
+///
+/// Segments are named after the given path: `recording.rrd` results in `recording-00000.rrd`,
+/// `recording-00001.rrd`, …
+/// Numbering picks up after any segments already in the directory.
+///
+/// Every segment starts with the latest [`LogMsg::SetStoreInfo`] of each recording seen so far,
+/// so each of them can be opened on its own.
+pub struct RotatingFileSink {
+    // None = quit
+    tx: Mutex<std::sync::mpsc::Sender<Option<Command>>>,
+    join_handle: Option<std::thread::JoinHandle<()>>,
+
+    /// Only used for diagnostics, not for access after `new()`.
+    path: PathBuf,
+}
+
+impl Drop for RotatingFileSink {
+    fn drop(&mut self) {
+        self.tx.lock().send(None).ok();
+        if let Some(join_handle) = self.join_handle.take() {
+            join_handle.join().ok();
+        }
+    }
+}
+
+impl RotatingFileSink {
+    /// Start writing log messages to segments named after the given path.
+    pub fn new(
+        path: impl Into<std::path::PathBuf>,
+        options: RotationOptions,
+    ) -> Result<Self, FileSinkError> {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let path = path.into();
+
+        re_log::debug!("Saving rotating files to {path:?}…");
+
+        let writer = SegmentWriter::new(path.clone(), options)?;
+        let join_handle = std::thread::Builder::new()
+            .name("rotating_file_writer".into())
+            .spawn(move || writer.run(rx))
+            .map_err(FileSinkError::SpawnThread)?;
+
+        Ok(Self {
+            tx: tx.into(),
+            join_handle: Some(join_handle),
+            path,
+        })
+    }
+
+    #[inline]
+    pub fn flush_blocking(&self) {
+        let (cmd, oneshot) = Command::flush();
+        self.tx.lock().send(Some(cmd)).ok();
+        oneshot.recv().ok();
+    }
+
+    #[inline]
+    pub fn send(&self, log_msg: LogMsg) {
+        self.tx.lock().send(Some(Command::Send(log_msg))).ok();
+    }
+}
+
+impl fmt::Debug for RotatingFileSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RotatingFileSink")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+// ---
+
+/// Counts the bytes going through, so we know how big the current segment is.
+struct CountingWrite<W> {
+    write: W,
+    num_bytes: Arc<AtomicU64>,
+}
+
+impl<W: std::io::Write> std::io::Write for CountingWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.write.write(buf)?;
+        self.num_bytes.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write.flush()
+    }
+}
+
+struct Segment {
+    path: PathBuf,
+    encoder: Encoder<CountingWrite<std::fs::File>>,
+    num_bytes: Arc<AtomicU64>,
+    opened: std::time::Instant,
+
+    /// Has anything but the repeated store infos been written to it yet?
+    has_data: bool,
+}
+
+/// Lives on the writer thread.
+struct SegmentWriter {
+    path: PathBuf,
+    options: RotationOptions,
+    next_index: u64,
+
+    /// The latest store info of each recording, repeated at the start of each segment.
+    store_infos: Vec<(StoreId, LogMsg)>,
+
+    /// All segments written so far, oldest first, including the current one.
+    segment_paths: VecDeque<PathBuf>,
+
+    current: Segment,
+}
+
+impl SegmentWriter {
+    fn new(path: PathBuf, options: RotationOptions) -> Result<Self, FileSinkError> {
+        let next_index = first_free_index(&path);
+        let current = Self::open_segment(&path, next_index, &options)?;
+
+        Ok(Self {
+            path,
+            options,
+            next_index: next_index + 1,
+            store_infos: Vec::new(),
+            segment_paths: std::iter::once(current.path.clone()).collect(),
+            current,
+        })
+    }
+
+    fn open_segment(
+        path: &Path,
+        index: u64,
+        options: &RotationOptions,
+    ) -> Result<Segment, FileSinkError> {
+        let path = segment_path(path, index);
+
+        let file = std::fs::File::create(&path)
+            .map_err(|err| FileSinkError::CreateFile(path.clone(), err))?;
+        let num_bytes = Arc::new(AtomicU64::new(0));
+        let write = CountingWrite {
+            write: file,
+            num_bytes: num_bytes.clone(),
+        };
+        let encoder = Encoder::new(options.encoding_options, write)?;
+
+        Ok(Segment {
+            path,
+            encoder,
+            num_bytes,
+            opened: std::time::Instant::now(),
+            has_data: false,
+        })
+    }
+
+    fn run(mut self, rx: Receiver<Option<Command>>) {
+        while let Ok(Some(cmd)) = rx.recv() {
+            match cmd {
+                Command::Send(log_msg) => {
+                    if let Err(err) = self.append(log_msg) {
+                        re_log::error!(
+                            "Failed to write log stream to {:?}: {err}",
+                            self.current.path
+                        );
+                        return;
+                    }
+                }
+                Command::Flush(oneshot) => {
+                    re_log::trace!("Flushing…");
+                    if let Err(err) = self.current.encoder.flush_blocking() {
+                        re_log::error!(
+                            "Failed to flush log stream to {:?}: {err}",
+                            self.current.path
+                        );
+                        return;
+                    }
+                    drop(oneshot); // signals the oneshot
+                }
+            }
+        }
+        if let Err(err) = self.current.encoder.finish() {
+            re_log::error!(
+                "Failed to finish log stream to {:?}: {err}",
+                self.current.path
+            );
+            return;
+        }
+        re_log::debug!("Log stream written to {:?}", self.current.path);
+    }
+
+    fn append(&mut self, log_msg: LogMsg) -> Result<(), FileSinkError> {
+        if self.should_rotate() {
+            self.rotate()?;
+        }
+
+        if let LogMsg::SetStoreInfo(_) = &log_msg {
+            let store_id = log_msg.store_id().clone();
+            self.store_infos.retain(|(id, _)| *id != store_id);
+            self.store_infos.push((store_id, log_msg.clone()));
+        }
+
+        self.current.encoder.append(&log_msg)?;
+        self.current.has_data = true;
+
+        Ok(())
+    }
+
+    fn should_rotate(&self) -> bool {
+        let Segment {
+            num_bytes,
+            opened,
+            has_data,
+            ..
+        } = &self.current;
+
+        // Never leave a segment without any data.
+        if !has_data {
+            return false;
+        }
+
+        let too_big = self
+            .options
+            .max_segment_bytes
+            .map_or(false, |max| max <= num_bytes.load(Ordering::Relaxed));
+        let too_old = self
+            .options
+            .max_segment_duration
+            .map_or(false, |max| max <= opened.elapsed());
+
+        too_big || too_old
+    }
+
+    fn rotate(&mut self) -> Result<(), FileSinkError> {
+        re_tracing::profile_function!();
+
+        let next = Self::open_segment(&self.path, self.next_index, &self.options)?;
+        self.next_index += 1;
+
+        let mut previous = std::mem::replace(&mut self.current, next);
+        previous.encoder.finish()?;
+        re_log::debug!("Log stream written to {:?}", previous.path);
+        drop(previous);
+
+        for (_, store_info) in &self.store_infos {
+            self.current.encoder.append(store_info)?;
+        }
+
+        self.segment_paths.push_back(self.current.path.clone());
+        if let Some(max_segments) = self.options.max_segments {
+            while self.segment_paths.len() > max_segments.max(1) {
+                if let Some(oldest) = self.segment_paths.pop_front() {
+                    re_log::debug!("Removing old segment {oldest:?}");
+                    if let Err(err) = std::fs::remove_file(&oldest) {
+                        re_log::warn!("Failed to remove old segment {oldest:?}: {err}");
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `dir/recording.rrd` -> `dir/recording-00042.rrd`
+fn segment_path(path: &Path, index: u64) -> PathBuf {
+    let (prefix, suffix) = segment_name_parts(path);
+    path.with_file_name(format!("{prefix}{index:05}{suffix}"))
+}
+
+/// `dir/recording.rrd` -> (`recording-`, `.rrd`): what comes before and after the index in the
+/// name of a segment.
+fn segment_name_parts(path: &Path) -> (String, String) {
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "recording".into(), |stem| stem.to_string_lossy());
+    let extension = path
+        .extension()
+        .map_or_else(|| "rrd".into(), |ext| ext.to_string_lossy());
+    (format!("{stem}-"), format!(".{extension}"))
+}
+
+/// The index after the highest one already on disk for this path.
+fn first_free_index(path: &Path) -> u64 {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+
+    let (prefix, suffix) = segment_name_parts(path);
+    entries
+        .filter_map(|entry| {
+            let file_name = entry.ok()?.file_name();
+            let index = file_name
+                .to_str()?
+                .strip_prefix(prefix.as_str())?
+                .strip_suffix(suffix.as_str())?;
+            if index.is_empty() || !index.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None;
+            }
+            index.parse::<u64>().ok()
+        })
+        .max()
+        .map_or(0, |index| index + 1)
+}
+
+#[cfg(all(test, feature = "decoder"))]
+mod tests {
+    use re_log_types::{
+        ApplicationId, RowId, SetStoreInfo, StoreInfo, StoreKind, StoreSource, Time,
+    };
+
+    use super::*;
+    use crate::decoder::{decode_bytes, VersionPolicy};
+
+    fn store_info(store_id: &str) -> LogMsg {
+        LogMsg::SetStoreInfo(SetStoreInfo {
+            row_id: RowId::new(),
+            info: StoreInfo {
+                application_id: ApplicationId::unknown(),
+                store_id: StoreId::from_string(StoreKind::Recording, store_id.to_owned()),
+                is_official_example: false,
+                started: Time::from_ns_since_epoch(0),
+                store_source: StoreSource::Unknown,
+                store_kind: StoreKind::Recording,
+            },
+        })
+    }
+
+    fn read_segments(dir: &Path) -> Vec<(PathBuf, Vec<LogMsg>)> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| {
+                let bytes = std::fs::read(&path).unwrap();
+                let msgs = decode_bytes(VersionPolicy::Error, &bytes).unwrap();
+                (path, msgs)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rotate_on_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("robot.rrd");
+
+        let msgs: Vec<_> = (0..10).map(|i| store_info(&format!("rec-{i}"))).collect();
+        {
+            let sink = RotatingFileSink::new(
+                path,
+                RotationOptions {
+                    max_segment_bytes: Some(1),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            for msg in &msgs {
+                sink.send(msg.clone());
+            }
+        }
+
+        // One message per segment, each repeating the store infos seen before it.
+        let segments = read_segments(dir.path());
+        assert_eq!(msgs.len(), segments.len());
+        for (i, (segment_path, segment)) in segments.iter().enumerate() {
+            assert_eq!(dir.path().join(format!("robot-{i:05}.rrd")), *segment_path);
+            assert_eq!(&msgs[..=i], segment.as_slice());
+        }
+    }
+
+    #[test]
+    fn keep_latest_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("robot.rrd");
+
+        // Left over from a previous run: numbering continues after it, and it is left alone.
+        std::fs::write(dir.path().join("robot-00003.rrd"), b"").unwrap();
+
+        {
+            let sink = RotatingFileSink::new(
+                path,
+                RotationOptions {
+                    max_segment_bytes: Some(1),
+                    max_segments: Some(2),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            for _ in 0..5 {
+                sink.send(store_info("rec"));
+            }
+            sink.flush_blocking();
+        }
+
+        let mut file_names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        file_names.sort();
+        assert_eq!(
+            vec!["robot-00003.rrd", "robot-00007.rrd", "robot-00008.rrd"],
+            file_names
+        );
+    }
+
+    #[test]
+    fn rotate_on_duration() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("robot.rrd");
+
+        {
+            let sink = RotatingFileSink::new(
+                path,
+                RotationOptions {
+                    max_segment_bytes: None,
+                    max_segment_duration: Some(std::time::Duration::ZERO),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            sink.send(store_info("a"));
+            sink.send(store_info("b"));
+        }
+
+        let segments = read_segments(dir.path());
+        assert_eq!(2, segments.len());
+        assert_eq!(2, segments[1].1.len());
+    }
+
+    #[test]
+    fn first_free_index_of_odd_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run-00000.rrd");
+        assert_eq!(0, first_free_index(&path));
+
+        for name in [
+            "run-00000-00003.rrd",
+            "run-00000-00007.rrd",
+            "run-00000-x.rrd",
+        ] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        assert_eq!(8, first_free_index(&path));
+        assert_eq!(0, first_free_index(&dir.path().join("run.rrd")));
+    }
+}
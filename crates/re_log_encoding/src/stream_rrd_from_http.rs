@@ -1,7 +1,9 @@
-use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::ops::ControlFlow;
 use std::sync::Arc;
 
+use parking_lot::Mutex;
+
 use re_log::ResultExt as _;
 use re_log_types::LogMsg;
 
@@ -10,9 +12,13 @@ pub fn stream_rrd_from_http_to_channel(
     url: String,
     on_msg: Option<Box<dyn Fn() + Send + Sync>>,
 ) -> re_smart_channel::Receiver<LogMsg> {
+    let progress = re_smart_channel::LoadProgress::new();
     let (tx, rx) = re_smart_channel::smart_channel(
         re_smart_channel::SmartMessageSource::RrdHttpStream { url: url.clone() },
-        re_smart_channel::SmartChannelSource::RrdHttpStream { url: url.clone() },
+        re_smart_channel::SmartChannelSource::RrdHttpStream {
+            url: url.clone(),
+            progress: progress.clone(),
+        },
     );
     stream_rrd_from_http(
         url,
@@ -29,6 +35,13 @@ pub fn stream_rrd_from_http_to_channel(
                         ControlFlow::Break(())
                     }
                 }
+                HttpMessage::Progress {
+                    loaded_bytes,
+                    total_bytes,
+                } => {
+                    progress.set(loaded_bytes, total_bytes);
+                    ControlFlow::Continue(())
+                }
                 HttpMessage::Success => {
                     tx.quit(None).warn_on_err_once("failed to send quit marker");
                     ControlFlow::Break(())
@@ -49,6 +62,15 @@ pub enum HttpMessage {
     /// The next [`LogMsg`] in the decoding stream.
     LogMsg(LogMsg),
 
+    /// How much of the file has been downloaded so far,
+    /// or decoded for files posted to the web viewer.
+    Progress {
+        loaded_bytes: u64,
+
+        /// `None` if the server didn't tell us.
+        total_bytes: Option<u64>,
+    },
+
     /// Everything has been successfully decoded. End of stream.
     Success,
 
@@ -58,63 +80,428 @@ pub enum HttpMessage {
 
 pub type HttpMessageCallback = dyn Fn(HttpMessage) -> ControlFlow<()> + Send + Sync;
 
+/// How to download an `.rrd` file, see [`stream_rrd_from_http_with_options`].
+#[derive(Clone, Debug)]
+pub struct HttpOptions {
+    /// How many bytes to ask for per request, if the server supports range requests.
+    pub chunk_size: u64,
+
+    /// How many range requests can be in flight at once.
+    ///
+    /// Chunks that arrive out of order are kept around until everything before them
+    /// has been decoded.
+    pub max_parallel_requests: usize,
+
+    /// How many times a request is retried after the connection dropped, before giving up.
+    ///
+    /// A retry picks up where the failed request left off: nothing is decoded twice.
+    pub max_retries: usize,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 4 * 1024 * 1024,
+            max_parallel_requests: 4,
+            max_retries: 5,
+        }
+    }
+}
+
 pub fn stream_rrd_from_http(url: String, on_msg: Arc<HttpMessageCallback>) {
+    stream_rrd_from_http_with_options(url, HttpOptions::default(), on_msg);
+}
+
+/// Downloads and decodes the `.rrd` file at `url`, calling `on_msg` for every decoded message
+/// and progress update.
+///
+/// If the server supports range requests, the file is downloaded in chunks of
+/// [`HttpOptions::chunk_size`], several at a time. Otherwise it is streamed in one go.
+/// Either way, a dropped connection is resumed from where it left off.
+pub fn stream_rrd_from_http_with_options(
+    url: String,
+    options: HttpOptions,
+    on_msg: Arc<HttpMessageCallback>,
+) {
     re_log::debug!("Downloading .rrd file from {url:?}…");
 
-    ehttp::streaming::fetch(ehttp::Request::get(&url), {
-        let version_policy = crate::decoder::VersionPolicy::Warn;
-        let decoder = RefCell::new(StreamDecoder::new(version_policy));
-        move |part| match part {
-            Ok(part) => match part {
-                ehttp::streaming::Part::Response(ehttp::PartialResponse {
-                    ok,
-                    status,
-                    status_text,
-                    ..
-                }) => {
-                    if ok {
-                        re_log::debug!("Decoding .rrd file from {url:?}…");
-                        ControlFlow::Continue(())
-                    } else {
-                        on_msg(HttpMessage::Failure(
-                            format!("Failed to fetch .rrd file from {url}: {status} {status_text}")
-                                .into(),
-                        ))
-                    }
-                }
-                ehttp::streaming::Part::Chunk(chunk) => {
-                    if chunk.is_empty() {
-                        re_log::debug!("Finished decoding .rrd file from {url:?}…");
-                        return on_msg(HttpMessage::Success);
-                    }
+    let version_policy = crate::decoder::VersionPolicy::Warn;
+    let probe = RangeRequest::new(0, Some(options.chunk_size.max(1)));
+    let download = Arc::new(Mutex::new(Download {
+        url,
+        options,
+        on_msg,
+        decoder: StreamDecoder::new(version_policy),
+        stopped: false,
+        supports_ranges: None,
+        total_bytes: None,
+        eof_at: None,
+        next_request_start: probe.end.unwrap_or(0),
+        decoded_until: 0,
+        pending: BTreeMap::new(),
+        loaded_bytes: 0,
+        in_flight: 1,
+    }));
 
-                    re_tracing::profile_scope!("decoding_rrd_stream");
-                    decoder.borrow_mut().push_chunk(chunk);
-                    loop {
-                        match decoder.borrow_mut().try_read() {
-                            Ok(message) => match message {
-                                Some(message) => {
-                                    // only return if the callback asks us to
-                                    if on_msg(HttpMessage::LogMsg(message)).is_break() {
-                                        return ControlFlow::Break(());
-                                    }
-                                }
-                                None => return ControlFlow::Continue(()),
-                            },
-                            Err(err) => {
-                                return on_msg(HttpMessage::Failure(
-                                    format!("Failed to fetch .rrd file from {url}: {err}").into(),
-                                ))
-                            }
-                        }
+    fetch_range(download, probe);
+}
+
+/// One HTTP request, for the bytes `start..end` of the file.
+struct RangeRequest {
+    start: u64,
+
+    /// `None` means until the end of the file.
+    end: Option<u64>,
+
+    /// How many bytes of the range we already have, from this request or previous attempts.
+    received: u64,
+
+    /// How many bytes to drop from the start of the body, for servers that ignore our
+    /// `Range` header.
+    skip: u64,
+
+    /// How many times this range has been retried.
+    retries: usize,
+}
+
+impl RangeRequest {
+    fn new(start: u64, end: Option<u64>) -> Self {
+        Self {
+            start,
+            end,
+            received: 0,
+            skip: 0,
+            retries: 0,
+        }
+    }
+
+    /// Where the next byte we're waiting on lives in the file.
+    fn offset(&self) -> u64 {
+        self.start + self.received
+    }
+
+    fn is_complete(&self) -> bool {
+        self.end.map_or(false, |end| end <= self.offset())
+    }
+
+    fn to_http_request(&self, url: &str) -> ehttp::Request {
+        let mut request = ehttp::Request::get(url);
+        let range = match self.end {
+            Some(end) => format!("bytes={}-{}", self.offset(), end - 1),
+            None => format!("bytes={}-", self.offset()),
+        };
+        request.headers.insert("Range", range);
+        request
+    }
+}
+
+/// The state shared by all requests of a single download.
+struct Download {
+    url: String,
+    options: HttpOptions,
+    on_msg: Arc<HttpMessageCallback>,
+    decoder: StreamDecoder,
+
+    /// Set once we're done, successfully or not. Requests still in flight are then abandoned.
+    stopped: bool,
+
+    /// `None` until the first response comes in.
+    supports_ranges: Option<bool>,
+
+    /// The size of the file, if the server told us.
+    total_bytes: Option<u64>,
+
+    /// Where the file ended, for servers that don't tell us its size upfront.
+    eof_at: Option<u64>,
+
+    /// Where the next range request starts.
+    next_request_start: u64,
+
+    /// Everything before this offset has been handed to the decoder.
+    decoded_until: u64,
+
+    /// Bytes that arrived ahead of [`Self::decoded_until`], by offset.
+    pending: BTreeMap<u64, Vec<u8>>,
+
+    /// For progress reporting.
+    loaded_bytes: u64,
+
+    in_flight: usize,
+}
+
+impl Download {
+    fn fail(&mut self, err: String) -> ControlFlow<()> {
+        if !self.stopped {
+            self.stopped = true;
+            let url = &self.url;
+            (self.on_msg)(HttpMessage::Failure(
+                format!("Failed to fetch .rrd file from {url}: {err}").into(),
+            ));
+        }
+        ControlFlow::Break(())
+    }
+
+    fn on_response(
+        &mut self,
+        request: &mut RangeRequest,
+        response: &ehttp::PartialResponse,
+    ) -> ControlFlow<()> {
+        const PARTIAL_CONTENT: u16 = 206;
+        const RANGE_NOT_SATISFIABLE: u16 = 416;
+
+        if response.status == RANGE_NOT_SATISFIABLE && self.total_bytes.is_none() {
+            // We asked for bytes past the end of a file of unknown size.
+            self.eof_at = Some(
+                self.eof_at
+                    .map_or(request.start, |eof| eof.min(request.start)),
+            );
+            request.end = Some(request.start);
+            return ControlFlow::Break(());
+        }
+
+        if !response.ok {
+            let (status, status_text) = (response.status, &response.status_text);
+            return self.fail(format!("{status} {status_text}"));
+        }
+
+        let supports_ranges = response.status == PARTIAL_CONTENT;
+        if self.supports_ranges.is_none() {
+            re_log::debug!(
+                "Decoding .rrd file from {:?} (range requests supported: {supports_ranges})…",
+                self.url
+            );
+            self.supports_ranges = Some(supports_ranges);
+        }
+
+        if supports_ranges {
+            // E.g. `Content-Range: bytes 0-1023/4096`.
+            let total_bytes = response
+                .headers
+                .get("content-range")
+                .and_then(|range| range.rsplit_once('/'))
+                .and_then(|(_, total)| total.trim().parse().ok());
+            if let Some(total_bytes) = total_bytes {
+                self.total_bytes = Some(total_bytes);
+                request.end = Some(request.end.map_or(total_bytes, |end| end.min(total_bytes)));
+            }
+        } else {
+            // We get the whole file, whatever we asked for.
+            request.skip = request.offset();
+            if self.supports_ranges == Some(false) {
+                request.end = None;
+                self.next_request_start = u64::MAX;
+                self.total_bytes = response
+                    .headers
+                    .get("content-length")
+                    .and_then(|len| len.trim().parse().ok());
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn on_chunk(&mut self, request: &mut RangeRequest, mut chunk: Vec<u8>) -> ControlFlow<()> {
+        let skipped = (request.skip as usize).min(chunk.len());
+        chunk.drain(..skipped);
+        request.skip -= skipped as u64;
+
+        if let Some(end) = request.end {
+            chunk.truncate(end.saturating_sub(request.offset()) as usize);
+        }
+
+        if !chunk.is_empty() {
+            let offset = request.offset();
+            request.received += chunk.len() as u64;
+            self.receive(offset, chunk)?;
+        }
+
+        if request.is_complete() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    /// The body of a response ended without error.
+    fn on_body_end(&mut self, request: &mut RangeRequest) {
+        if request.skip > 0 || request.is_complete() {
+            return;
+        }
+
+        // A short range means we've reached the end of the file.
+        let end_of_file = request.offset();
+        if self.total_bytes.map_or(true, |total| end_of_file >= total) {
+            self.eof_at = Some(self.eof_at.map_or(end_of_file, |eof| eof.min(end_of_file)));
+            request.end = Some(end_of_file);
+        }
+    }
+
+    /// Hands the bytes at `offset` to the decoder, or keeps them around until everything before
+    /// them has arrived.
+    fn receive(&mut self, offset: u64, bytes: Vec<u8>) -> ControlFlow<()> {
+        self.loaded_bytes += bytes.len() as u64;
+        (self.on_msg)(HttpMessage::Progress {
+            loaded_bytes: self.loaded_bytes,
+            total_bytes: self.total_bytes,
+        })?;
+
+        self.pending.insert(offset, bytes);
+        while let Some(entry) = self.pending.first_entry() {
+            if *entry.key() != self.decoded_until {
+                break;
+            }
+            let bytes = entry.remove();
+            self.decoded_until += bytes.len() as u64;
+            self.decoder.push_chunk(bytes);
+        }
+
+        re_tracing::profile_scope!("decoding_rrd_stream");
+        loop {
+            match self.decoder.try_read() {
+                Ok(Some(msg)) => {
+                    // only return if the callback asks us to
+                    if (self.on_msg)(HttpMessage::LogMsg(msg)).is_break() {
+                        self.stopped = true;
+                        return ControlFlow::Break(());
                     }
                 }
-            },
-            Err(err) => on_msg(HttpMessage::Failure(
-                format!("Failed to fetch .rrd file from {url}: {err}").into(),
-            )),
+                Ok(None) => return ControlFlow::Continue(()),
+                Err(err) => return self.fail(err.to_string()),
+            }
+        }
+    }
+
+    /// A request is done with: either retry it, or move on to the next ranges.
+    fn on_request_done(
+        &mut self,
+        mut request: RangeRequest,
+        err: Option<String>,
+    ) -> Vec<RangeRequest> {
+        if self.stopped {
+            return Vec::new();
         }
-    });
+
+        if let Some(err) = err {
+            if request.retries >= self.options.max_retries {
+                self.fail(err);
+                return Vec::new();
+            }
+
+            re_log::debug!(
+                "Fetching {:?} failed at byte {}, resuming: {err}",
+                self.url,
+                request.offset()
+            );
+            request.retries += 1;
+            request.skip = 0;
+            return vec![request];
+        }
+
+        if !request.is_complete() {
+            // The server closed the connection early without telling us.
+            return self.on_request_done(request, Some("connection closed early".to_owned()));
+        }
+
+        self.in_flight -= 1;
+        let requests = self.next_requests();
+
+        if self.in_flight == 0 && requests.is_empty() {
+            if !self.pending.is_empty() || self.decoder.is_mid_message() {
+                self.fail(format!(
+                    "the file ends in the middle of a message after {} bytes: it is truncated",
+                    self.decoded_until
+                ));
+                return Vec::new();
+            }
+
+            let (loaded_bytes, total_bytes) = (self.loaded_bytes, Some(self.loaded_bytes));
+            _ = (self.on_msg)(HttpMessage::Progress {
+                loaded_bytes,
+                total_bytes,
+            });
+
+            re_log::debug!("Finished decoding .rrd file from {:?}…", self.url);
+            self.stopped = true;
+            (self.on_msg)(HttpMessage::Success);
+        }
+
+        requests
+    }
+
+    /// The range requests to start now, if any.
+    fn next_requests(&mut self) -> Vec<RangeRequest> {
+        if self.supports_ranges != Some(true) {
+            return Vec::new();
+        }
+
+        // Without knowing the size of the file, we can only go one chunk at a time until we
+        // run into its end.
+        let max_in_flight = if self.total_bytes.is_some() {
+            self.options.max_parallel_requests.max(1)
+        } else {
+            1
+        };
+        let end_of_file = self.total_bytes.or(self.eof_at).unwrap_or(u64::MAX);
+
+        let mut requests = Vec::new();
+        while self.in_flight < max_in_flight && self.next_request_start < end_of_file {
+            let start = self.next_request_start;
+            let end = start
+                .saturating_add(self.options.chunk_size.max(1))
+                .min(end_of_file);
+            requests.push(RangeRequest::new(start, Some(end)));
+            self.next_request_start = end;
+            self.in_flight += 1;
+        }
+        requests
+    }
+}
+
+fn fetch_range(download: Arc<Mutex<Download>>, request: RangeRequest) {
+    let http_request = request.to_http_request(&download.lock().url);
+
+    // NOTE: `on_data` has to be `Fn`, the request lives in a mutex as well.
+    let request = Mutex::new(Some(request));
+    ehttp::streaming::fetch(http_request, move |part| {
+        let mut guard = request.lock();
+        let Some(range) = guard.as_mut() else {
+            return ControlFlow::Break(());
+        };
+
+        let mut state = download.lock();
+        if state.stopped {
+            return ControlFlow::Break(());
+        }
+
+        let (flow, done) = match part {
+            Ok(ehttp::streaming::Part::Response(response)) => {
+                let flow = state.on_response(range, &response);
+                (flow, flow.is_break().then_some(None))
+            }
+            Ok(ehttp::streaming::Part::Chunk(chunk)) if chunk.is_empty() => {
+                state.on_body_end(range);
+                (ControlFlow::Break(()), Some(None))
+            }
+            Ok(ehttp::streaming::Part::Chunk(chunk)) => {
+                let flow = state.on_chunk(range, chunk);
+                (flow, flow.is_break().then_some(None))
+            }
+            Err(err) => (ControlFlow::Break(()), Some(Some(err))),
+        };
+
+        if let Some(err) = done {
+            let Some(range) = guard.take() else {
+                return ControlFlow::Break(());
+            };
+            let requests = state.on_request_done(range, err);
+            drop(state);
+            for request in requests {
+                fetch_range(download.clone(), request);
+            }
+        }
+
+        flow
+    })
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -159,20 +546,43 @@ pub use web_event_listener::stream_rrd_from_event_listener;
 #[cfg(target_arch = "wasm32")]
 mod web_decode {
     use super::{HttpMessage, HttpMessageCallback};
+    use std::cell::Cell;
+    use std::rc::Rc;
     use std::sync::Arc;
 
     pub fn decode_rrd(rrd_bytes: Vec<u8>, on_msg: Arc<HttpMessageCallback>) {
         wasm_bindgen_futures::spawn_local(decode_rrd_async(rrd_bytes, on_msg));
     }
 
+    /// Counts the bytes the decoder has read, for progress reports.
+    struct CountingReader<'a> {
+        bytes: &'a [u8],
+        num_read: Rc<Cell<u64>>,
+    }
+
+    impl std::io::Read for CountingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let num_read = self.bytes.read(buf)?;
+            self.num_read.set(self.num_read.get() + num_read as u64);
+            Ok(num_read)
+        }
+    }
+
     /// Decodes the file in chunks, with an yield between each chunk.
     ///
     /// This is cooperative multi-tasking.
     async fn decode_rrd_async(rrd_bytes: Vec<u8>, on_msg: Arc<HttpMessageCallback>) {
         let mut last_yield = web_time::Instant::now();
 
+        let total_bytes = Some(rrd_bytes.len() as u64);
+        let num_read = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            bytes: rrd_bytes.as_slice(),
+            num_read: num_read.clone(),
+        };
+
         let version_policy = crate::decoder::VersionPolicy::Warn;
-        match crate::decoder::Decoder::new(version_policy, rrd_bytes.as_slice()) {
+        match crate::decoder::Decoder::new(version_policy, reader) {
             Ok(decoder) => {
                 for msg in decoder {
                     match msg {
@@ -184,14 +594,23 @@ mod web_decode {
                         }
                     }
 
-                    on_msg(HttpMessage::Success);
-
                     if last_yield.elapsed() > web_time::Duration::from_millis(10) {
+                        on_msg(HttpMessage::Progress {
+                            loaded_bytes: num_read.get(),
+                            total_bytes,
+                        });
+
                         // yield to the ui task
                         yield_().await;
                         last_yield = web_time::Instant::now();
                     }
                 }
+
+                on_msg(HttpMessage::Progress {
+                    loaded_bytes: num_read.get(),
+                    total_bytes,
+                });
+                on_msg(HttpMessage::Success);
             }
             Err(err) => {
                 on_msg(HttpMessage::Failure(
@@ -224,3 +643,243 @@ mod web_decode {
 use web_decode::decode_rrd;
 
 use crate::decoder::stream::StreamDecoder;
+
+#[cfg(all(test, feature = "encoder", not(target_arch = "wasm32")))]
+mod tests {
+    use std::io::{BufRead as _, BufReader, Write as _};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use re_log_types::{
+        ApplicationId, RowId, SetStoreInfo, StoreId, StoreInfo, StoreKind, StoreSource, Time,
+    };
+
+    use super::*;
+    use crate::EncodingOptions;
+
+    fn fake_log_msg(i: usize) -> LogMsg {
+        LogMsg::SetStoreInfo(SetStoreInfo {
+            row_id: RowId::ZERO,
+            info: StoreInfo {
+                application_id: ApplicationId::unknown(),
+                store_id: StoreId::from_string(StoreKind::Recording, format!("test-{i}")),
+                is_official_example: false,
+                started: Time::from_ns_since_epoch(0),
+                store_source: StoreSource::Unknown,
+                store_kind: StoreKind::Recording,
+            },
+        })
+    }
+
+    /// Serves `data` on a local port, optionally cutting the first few responses short.
+    ///
+    /// Returns the url to fetch.
+    fn serve(data: Vec<u8>, supports_ranges: bool, num_drops: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/test.rrd", listener.local_addr().unwrap());
+        let num_drops = Arc::new(AtomicUsize::new(num_drops));
+
+        let server = move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let data = data.clone();
+                let num_drops = num_drops.clone();
+                let respond = move || {
+                    let mut range = None;
+                    for line in BufReader::new(&stream).lines() {
+                        let line = line.unwrap();
+                        if line.is_empty() {
+                            break;
+                        }
+                        let line = line.to_lowercase();
+                        if let Some(value) = line.strip_prefix("range: bytes=") {
+                            let (start, end) = value.split_once('-').unwrap();
+                            let start: usize = start.parse().unwrap();
+                            let end = end.parse::<usize>().map_or(data.len(), |end| end + 1);
+                            range = Some((start, end.min(data.len())));
+                        }
+                    }
+
+                    let (status, headers, body) = match range {
+                        Some((start, _)) if supports_ranges && data.len() <= start => {
+                            ("416 Range Not Satisfiable", String::new(), &data[..0])
+                        }
+                        Some((start, end)) if supports_ranges => (
+                            "206 Partial Content",
+                            format!(
+                                "Content-Range: bytes {start}-{}/{}\r\n",
+                                end - 1,
+                                data.len()
+                            ),
+                            &data[start..end],
+                        ),
+                        _ => ("200 OK", String::new(), &data[..]),
+                    };
+
+                    let drop_connection = num_drops
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                        .is_ok();
+                    let body_sent = if drop_connection {
+                        &body[..body.len() / 2]
+                    } else {
+                        body
+                    };
+
+                    let header = format!(
+                        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n{headers}\r\n",
+                        body.len()
+                    );
+                    // The client may hang up early on its own, that's fine.
+                    _ = stream
+                        .write_all(header.as_bytes())
+                        .and_then(|()| stream.write_all(body_sent));
+                };
+                std::thread::Builder::new()
+                    .name("test_http_response".to_owned())
+                    .spawn(respond)
+                    .unwrap();
+            }
+        };
+        std::thread::Builder::new()
+            .name("test_http_server".to_owned())
+            .spawn(server)
+            .unwrap();
+
+        url
+    }
+
+    /// The decoded messages, and the last progress report.
+    type Downloaded = (Vec<LogMsg>, (u64, Option<u64>));
+
+    /// Downloads everything, returning the decoded messages and the last progress report.
+    fn download(url: String, options: HttpOptions) -> Downloaded {
+        try_download(url, options).unwrap()
+    }
+
+    /// Like [`download`], but returns the failure reported, if any.
+    fn try_download(url: String, options: HttpOptions) -> Result<Downloaded, String> {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let progress = Arc::new(Mutex::new((0, None)));
+        let (done_tx, done_rx) = std::sync::mpsc::sync_channel(1);
+
+        stream_rrd_from_http_with_options(url, options, {
+            let messages = messages.clone();
+            let progress = progress.clone();
+            let done_tx = Mutex::new(done_tx);
+            Arc::new(move |msg| match msg {
+                HttpMessage::LogMsg(msg) => {
+                    messages.lock().push(msg);
+                    ControlFlow::Continue(())
+                }
+                HttpMessage::Progress {
+                    loaded_bytes,
+                    total_bytes,
+                } => {
+                    *progress.lock() = (loaded_bytes, total_bytes);
+                    ControlFlow::Continue(())
+                }
+                HttpMessage::Success => {
+                    done_tx.lock().send(Ok(())).ok();
+                    ControlFlow::Break(())
+                }
+                HttpMessage::Failure(err) => {
+                    done_tx.lock().send(Err(err.to_string())).ok();
+                    ControlFlow::Break(())
+                }
+            })
+        });
+
+        done_rx
+            .recv_timeout(std::time::Duration::from_secs(30))
+            .unwrap()?;
+
+        let messages = std::mem::take(&mut *messages.lock());
+        let progress = *progress.lock();
+        Ok((messages, progress))
+    }
+
+    fn test_data() -> (Vec<LogMsg>, Vec<u8>) {
+        let messages: Vec<_> = (0..200).map(fake_log_msg).collect();
+        let data = crate::encoder::encode_to_bytes(EncodingOptions::COMPRESSED, &messages).unwrap();
+        (messages, data)
+    }
+
+    #[test]
+    fn stream_in_parallel_ranges() {
+        let (messages, data) = test_data();
+        let num_bytes = data.len() as u64;
+        let url = serve(data, true, 3);
+
+        let options = HttpOptions {
+            chunk_size: num_bytes / 7,
+            max_parallel_requests: 3,
+            max_retries: 3,
+        };
+        let (decoded, progress) = download(url, options);
+
+        assert_eq!(messages, decoded);
+        assert_eq!((num_bytes, Some(num_bytes)), progress);
+    }
+
+    #[test]
+    fn stream_without_range_support() {
+        let (messages, data) = test_data();
+        let num_bytes = data.len() as u64;
+        let url = serve(data, false, 2);
+
+        let options = HttpOptions {
+            chunk_size: num_bytes / 7,
+            ..Default::default()
+        };
+        let (decoded, progress) = download(url, options);
+
+        assert_eq!(messages, decoded);
+        assert_eq!((num_bytes, Some(num_bytes)), progress);
+    }
+
+    #[test]
+    fn truncated_file() {
+        let (_, mut data) = test_data();
+        data.truncate(data.len() - 3);
+        let num_bytes = data.len() as u64;
+
+        for supports_ranges in [true, false] {
+            let url = serve(data.clone(), supports_ranges, 0);
+            let options = HttpOptions {
+                chunk_size: num_bytes / 7,
+                ..Default::default()
+            };
+            let err = try_download(url, options).unwrap_err();
+            assert!(err.contains("truncated"), "{err}");
+        }
+    }
+
+    #[test]
+    fn give_up_after_retries() {
+        let (_, data) = test_data();
+        let url = serve(data, true, usize::MAX);
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        let tx = Mutex::new(tx);
+        stream_rrd_from_http_with_options(
+            url,
+            HttpOptions {
+                max_retries: 2,
+                ..Default::default()
+            },
+            Arc::new(move |msg| match msg {
+                HttpMessage::LogMsg(_) | HttpMessage::Progress { .. } => ControlFlow::Continue(()),
+                HttpMessage::Success => {
+                    tx.lock().send(false).ok();
+                    ControlFlow::Break(())
+                }
+                HttpMessage::Failure(_) => {
+                    tx.lock().send(true).ok();
+                    ControlFlow::Break(())
+                }
+            }),
+        );
+
+        assert!(rx.recv_timeout(std::time::Duration::from_secs(30)).unwrap());
+    }
+}
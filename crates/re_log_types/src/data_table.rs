@@ -576,6 +576,12 @@ pub const METADATA_KIND_DATA: &str = "data";
 pub const METADATA_KIND_CONTROL: &str = "control";
 pub const METADATA_KIND_TIME: &str = "time";
 
+/// Field metadata holding the original datatype of a column that had to be converted to be
+/// written to a format that can't represent it as-is, e.g. fixed-size lists in Parquet.
+///
+/// See [`DataTable::encode_datatype`].
+pub const METADATA_ORIGINAL_DATATYPE: &str = "rerun.original_datatype";
+
 impl DataTable {
     /// Serializes the entire table into an arrow payload and schema.
     ///
@@ -1018,7 +1024,7 @@ impl DataTable {
     }
 
     /// Deserializes a sparse data column.
-    fn deserialize_data_column(
+    pub fn deserialize_data_column(
         component: ComponentName,
         column: &dyn Array,
     ) -> DataTableResult<DataCellColumn> {
@@ -1072,6 +1078,34 @@ impl DataTable {
     }
 }
 
+impl DataTable {
+    /// Encodes a datatype as a string, to be stored as [`METADATA_ORIGINAL_DATATYPE`].
+    pub fn encode_datatype(datatype: &DataType) -> String {
+        let fields = vec![Field::new("", datatype.clone(), true)];
+        let ipc_fields = arrow2::io::ipc::write::default_ipc_fields(&fields);
+        let bytes = arrow2::io::ipc::write::schema_to_bytes(&Schema::from(fields), &ipc_fields);
+        bytes.iter().fold(String::new(), |mut encoded, byte| {
+            use std::fmt::Write as _;
+            write!(encoded, "{byte:02x}").ok();
+            encoded
+        })
+    }
+
+    /// Inverse of [`Self::encode_datatype`].
+    pub fn decode_datatype(encoded: &str) -> Option<DataType> {
+        let bytes = (0..encoded.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(encoded.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        let (schema, _) = arrow2::io::ipc::read::deserialize_schema(&bytes).ok()?;
+        schema
+            .fields
+            .into_iter()
+            .next()
+            .map(|field| field.data_type)
+    }
+}
+
 // ---
 
 impl std::fmt::Display for DataTable {
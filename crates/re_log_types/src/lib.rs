@@ -46,7 +46,7 @@ pub use self::data_row::{
 pub use self::data_table::{
     DataCellColumn, DataCellOptVec, DataTable, DataTableError, DataTableResult, EntityPathVec,
     ErasedTimeVec, NumInstancesVec, RowIdVec, TableId, TimePointVec, METADATA_KIND,
-    METADATA_KIND_CONTROL, METADATA_KIND_DATA,
+    METADATA_KIND_CONTROL, METADATA_KIND_DATA, METADATA_KIND_TIME, METADATA_ORIGINAL_DATATYPE,
 };
 pub use self::num_instances::NumInstances;
 pub use self::path::*;
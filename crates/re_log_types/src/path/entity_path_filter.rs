@@ -114,6 +114,11 @@ impl EntityPathFilter {
         self.rules.insert(rule, effect);
     }
 
+    /// The rules, most specific last.
+    pub fn iter(&self) -> impl Iterator<Item = (&EntityPathRule, RuleEffect)> + '_ {
+        self.rules.iter().map(|(rule, effect)| (rule, *effect))
+    }
+
     pub fn formatted(&self) -> String {
         let mut s = String::new();
         for (rule, effect) in &self.rules {
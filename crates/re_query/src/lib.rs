@@ -63,6 +63,9 @@ pub enum QueryError {
     #[error("Error converting arrow data: {0}")]
     ArrowError(#[from] arrow2::error::Error),
 
+    #[error("Failed to read back spilled data: {0}")]
+    Spill(#[from] re_data_store::SpillError),
+
     #[cfg(feature = "polars")]
     #[error("Error from within Polars")]
     PolarsError(#[from] polars_core::prelude::PolarsError),
@@ -1,4 +1,4 @@
-use re_data_store::{DataStore, LatestAtQuery};
+use re_data_store::{DataStore, LatestAtQuery, SpillResult};
 use re_log_types::{EntityPath, RowId, TimeInt};
 use re_types_core::{components::InstanceKey, Archetype, ComponentName, Loggable};
 
@@ -6,7 +6,8 @@ use crate::{ArchetypeView, ComponentWithInstances, QueryError};
 
 /// Retrieves a [`ComponentWithInstances`] from the [`DataStore`].
 ///
-/// Returns `None` if the component is not found.
+/// Returns `None` if the component is not found, or if its data has been spilled to disk and
+/// can't be read back (which is logged).
 ///
 #[cfg_attr(
     feature = "testing",
@@ -54,23 +55,41 @@ pub fn get_component_with_instances(
     ent_path: &EntityPath,
     component: ComponentName,
 ) -> Option<(Option<TimeInt>, RowId, ComponentWithInstances)> {
+    try_get_component_with_instances(store, query, ent_path, component).unwrap_or_else(|err| {
+        re_log::error_once!("Failed to read back spilled data: {err}");
+        None
+    })
+}
+
+/// Same as [`get_component_with_instances`], but fails if spilled data can't be read back.
+fn try_get_component_with_instances(
+    store: &DataStore,
+    query: &LatestAtQuery,
+    ent_path: &EntityPath,
+    component: ComponentName,
+) -> SpillResult<Option<(Option<TimeInt>, RowId, ComponentWithInstances)>> {
     debug_assert_eq!(store.cluster_key(), InstanceKey::name());
 
     let components = [InstanceKey::name(), component];
 
-    let (data_time, row_id, mut cells) =
-        store.latest_at(query, ent_path, component, &components)?;
-
-    Some((
-        data_time,
-        row_id,
-        ComponentWithInstances {
-            // NOTE: The unwrap cannot fail, the cluster key's presence is guaranteed
-            // by the store.
-            instance_keys: cells[0].take().unwrap(),
-            values: cells[1].take()?,
-        },
-    ))
+    let Some((data_time, row_id, mut cells)) =
+        store.try_latest_at(query, ent_path, component, &components)?
+    else {
+        return Ok(None);
+    };
+
+    Ok(cells[1].take().map(|values| {
+        (
+            data_time,
+            row_id,
+            ComponentWithInstances {
+                // NOTE: The unwrap cannot fail, the cluster key's presence is guaranteed
+                // by the store.
+                instance_keys: cells[0].take().unwrap(),
+                values,
+            },
+        )
+    }))
 }
 
 /// Retrieve an [`ArchetypeView`] from the `DataStore`, as well as the associated _data_ time of
@@ -127,8 +146,8 @@ pub fn query_archetype<A: Archetype>(
 
     let required_components: Vec<_> = A::required_components()
         .iter()
-        .map(|component| get_component_with_instances(store, query, ent_path, *component))
-        .collect();
+        .map(|component| try_get_component_with_instances(store, query, ent_path, *component))
+        .collect::<SpillResult<_>>()?;
 
     // NOTE: It's important to use `PrimaryNotFound` here. Any other error will be
     // reported to the user.
@@ -153,23 +172,18 @@ pub fn query_archetype<A: Archetype>(
     let recommended_components = A::recommended_components();
     let optional_components = A::optional_components();
 
-    let other_components = recommended_components
+    let mut cwis = required_components;
+    for component in recommended_components
         .iter()
         .chain(optional_components.iter())
-        .filter_map(|component| {
-            get_component_with_instances(store, query, ent_path, *component).map(
-                |(data_time, _, component_result)| {
-                    max_data_time = Option::max(max_data_time, data_time);
-                    component_result
-                },
-            )
-        });
-
-    // NOTE: Need to collect so we can compute `max_data_time`.
-    let cwis: Vec<_> = required_components
-        .into_iter()
-        .chain(other_components)
-        .collect();
+    {
+        if let Some((data_time, _, component_result)) =
+            try_get_component_with_instances(store, query, ent_path, *component)?
+        {
+            max_data_time = Option::max(max_data_time, data_time);
+            cwis.push(component_result);
+        }
+    }
     let arch_view = ArchetypeView::from_components(max_data_time, *row_id, cwis);
 
     Ok(arch_view)
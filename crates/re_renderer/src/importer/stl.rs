@@ -0,0 +1,314 @@
+use std::sync::Arc;
+
+use smallvec::smallvec;
+
+use crate::{
+    mesh::{Material, Mesh, MeshError},
+    renderer::MeshInstance,
+    resource_managers::{ResourceLifeTime, ResourceManagerError},
+    RenderContext, Rgba32Unmul,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum StlImportError {
+    #[error(
+        "Binary STL is {actual} bytes long, but its {num_triangles} triangles need {expected}"
+    )]
+    Truncated {
+        num_triangles: u32,
+        expected: u64,
+        actual: usize,
+    },
+
+    #[error("Invalid ASCII STL on line {line}: {reason}")]
+    Ascii { line: usize, reason: String },
+
+    #[error(transparent)]
+    Mesh(#[from] MeshError),
+
+    #[error(transparent)]
+    ResourceManager(#[from] ResourceManagerError),
+}
+
+/// Load a binary or ASCII [STL file](https://en.wikipedia.org/wiki/STL_(file_format))
+/// into the mesh & texture manager.
+///
+/// Binary files may color their triangles the way VisCAM and SolidView do:
+/// the attribute of a triangle holds a 15 bit RGB color, with its highest bit set.
+pub fn load_stl_from_buffer(
+    buffer: &[u8],
+    lifetime: ResourceLifeTime,
+    ctx: &RenderContext,
+) -> Result<Vec<MeshInstance>, StlImportError> {
+    re_tracing::profile_function!();
+
+    let triangles = if is_binary(buffer) {
+        read_binary(buffer)?
+    } else {
+        read_ascii(buffer)?
+    };
+
+    let vertex_positions: Vec<glam::Vec3> = triangles
+        .iter()
+        .flat_map(|triangle| triangle.vertices)
+        .collect();
+    let triangle_indices = (0..triangles.len() as u32)
+        .map(|i| glam::uvec3(3 * i, 3 * i + 1, 3 * i + 2))
+        .collect();
+    let vertex_normals = triangles
+        .iter()
+        .flat_map(|triangle| [triangle.normal(); 3])
+        .collect();
+    let vertex_colors = triangles
+        .iter()
+        .flat_map(|triangle| [triangle.color.unwrap_or(Rgba32Unmul::WHITE); 3])
+        .collect();
+    let vertex_texcoords = vec![glam::Vec2::ZERO; vertex_positions.len()];
+
+    let texture = ctx.texture_manager_2d.white_texture_unorm_handle();
+
+    let mesh = Mesh {
+        label: "stl mesh".into(),
+        triangle_indices,
+        vertex_positions,
+        vertex_colors,
+        vertex_normals,
+        vertex_texcoords,
+
+        materials: smallvec![Material {
+            label: "default material".into(),
+            index_range: 0..3 * triangles.len() as u32,
+            albedo: texture.clone(),
+            albedo_multiplier: crate::Rgba::WHITE,
+        }],
+    };
+
+    mesh.sanity_check()?;
+
+    let gpu_mesh = ctx.mesh_manager.write().create(ctx, &mesh, lifetime)?;
+
+    Ok(vec![MeshInstance {
+        gpu_mesh,
+        mesh: Some(Arc::new(mesh)),
+        ..Default::default()
+    }])
+}
+
+#[derive(Debug, PartialEq)]
+struct Triangle {
+    /// As stored in the file, which may be zero.
+    normal: glam::Vec3,
+    vertices: [glam::Vec3; 3],
+    color: Option<Rgba32Unmul>,
+}
+
+impl Triangle {
+    /// The stored normal, or the one given by the winding order if none was stored.
+    fn normal(&self) -> glam::Vec3 {
+        let normal = self.normal.normalize_or_zero();
+        if normal != glam::Vec3::ZERO {
+            return normal;
+        }
+        let [a, b, c] = self.vertices;
+        (b - a).cross(c - a).normalize_or_zero()
+    }
+}
+
+const BINARY_HEADER_SIZE: usize = 80 + 4;
+const BINARY_TRIANGLE_SIZE: usize = 12 * 4 + 2;
+
+/// Binary files may start with "solid" too, but their size gives them away.
+fn is_binary(buffer: &[u8]) -> bool {
+    let starts_with_solid = buffer
+        .iter()
+        .skip_while(|c| c.is_ascii_whitespace())
+        .take(5)
+        .eq(b"solid");
+    !starts_with_solid
+        || (BINARY_HEADER_SIZE <= buffer.len()
+            && binary_size(binary_num_triangles(buffer)) == buffer.len() as u64)
+}
+
+fn binary_num_triangles(buffer: &[u8]) -> u32 {
+    u32::from_le_bytes([buffer[80], buffer[81], buffer[82], buffer[83]])
+}
+
+/// In bytes. Doesn't fit in a `usize` on 32-bit platforms for the largest triangle counts.
+fn binary_size(num_triangles: u32) -> u64 {
+    BINARY_HEADER_SIZE as u64 + num_triangles as u64 * BINARY_TRIANGLE_SIZE as u64
+}
+
+fn read_binary(buffer: &[u8]) -> Result<Vec<Triangle>, StlImportError> {
+    re_tracing::profile_function!();
+
+    let num_triangles = if BINARY_HEADER_SIZE <= buffer.len() {
+        binary_num_triangles(buffer)
+    } else {
+        0
+    };
+    let expected = binary_size(num_triangles);
+    if (buffer.len() as u64) < expected {
+        return Err(StlImportError::Truncated {
+            num_triangles,
+            expected,
+            actual: buffer.len(),
+        });
+    }
+
+    let vec3 = |bytes: &[u8]| {
+        let f32 =
+            |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        glam::vec3(f32(0), f32(4), f32(8))
+    };
+
+    Ok(buffer[BINARY_HEADER_SIZE..expected as usize]
+        .chunks_exact(BINARY_TRIANGLE_SIZE)
+        .map(|triangle| {
+            let attribute = u16::from_le_bytes([triangle[48], triangle[49]]);
+            let color = (attribute & 0x8000 != 0).then(|| {
+                let channel = |shift: u16| {
+                    let five_bits = ((attribute >> shift) & 0x1f) as u8;
+                    (five_bits << 3) | (five_bits >> 2)
+                };
+                Rgba32Unmul::from_rgb(channel(10), channel(5), channel(0))
+            });
+
+            Triangle {
+                normal: vec3(&triangle[0..12]),
+                vertices: [
+                    vec3(&triangle[12..24]),
+                    vec3(&triangle[24..36]),
+                    vec3(&triangle[36..48]),
+                ],
+                color,
+            }
+        })
+        .collect())
+}
+
+fn read_ascii(buffer: &[u8]) -> Result<Vec<Triangle>, StlImportError> {
+    re_tracing::profile_function!();
+
+    let text = String::from_utf8_lossy(buffer);
+
+    let mut triangles = Vec::new();
+    let mut normal = glam::Vec3::ZERO;
+    let mut vertices = Vec::with_capacity(3);
+
+    for (line_index, line) in text.lines().enumerate() {
+        let error = |reason: &str| StlImportError::Ascii {
+            line: line_index + 1,
+            reason: reason.to_owned(),
+        };
+        let vec3 = |words: &[&str]| -> Result<glam::Vec3, StlImportError> {
+            let [x, y, z] = words else {
+                return Err(error("expected 3 coordinates"));
+            };
+            let parse = |word: &str| {
+                word.parse::<f32>()
+                    .map_err(|_err| error(&format!("{word:?} is not a number")))
+            };
+            Ok(glam::vec3(parse(x)?, parse(y)?, parse(z)?))
+        };
+
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["facet", "normal", coordinates @ ..] => {
+                normal = vec3(coordinates)?;
+                vertices.clear();
+            }
+            ["facet"] => {
+                normal = glam::Vec3::ZERO;
+                vertices.clear();
+            }
+            ["vertex", coordinates @ ..] => vertices.push(vec3(coordinates)?),
+            ["endfacet"] => {
+                if vertices.len() < 3 {
+                    return Err(error("a facet needs at least 3 vertices"));
+                }
+                // Split polygons into triangles, as a fan around their first vertex.
+                for i in 2..vertices.len() {
+                    triangles.push(Triangle {
+                        normal,
+                        vertices: [vertices[0], vertices[i - 1], vertices[i]],
+                        color: None,
+                    });
+                }
+                vertices.clear();
+            }
+            // `solid`, `outer loop`, `endloop`, `endsolid`, and empty lines.
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii() {
+        let stl = b"solid cube_corner
+  facet normal 0 0 -1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 0 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 0 1
+    endloop
+  endfacet
+endsolid cube_corner
+";
+        assert!(!is_binary(stl));
+
+        let triangles = read_ascii(stl).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangles[0].normal(), glam::Vec3::NEG_Z);
+        assert_eq!(triangles[0].vertices[1], glam::Vec3::X);
+        // Missing normals are computed from the winding order.
+        assert_eq!(triangles[1].normal(), glam::Vec3::NEG_Y);
+
+        assert!(matches!(
+            read_ascii(b"solid\nfacet normal 0 0 1\nvertex 0 0 zero\n"),
+            Err(StlImportError::Ascii { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn binary() {
+        // Binary files starting with "solid" are still binary.
+        let mut stl = b"solid but actually binary".to_vec();
+        stl.resize(80, 0);
+        stl.extend(2_u32.to_le_bytes());
+        for (z, attribute) in [(0.0_f32, 0_u16), (1.0, 0x8000 | 0x1f << 10)] {
+            for v in [[0.0, 0.0, 1.0], [0.0, 0.0, z], [1.0, 0.0, z], [0.0, 1.0, z]] {
+                stl.extend(v.iter().flat_map(|c: &f32| c.to_le_bytes()));
+            }
+            stl.extend(attribute.to_le_bytes());
+        }
+        assert!(is_binary(&stl));
+
+        let triangles = read_binary(&stl).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangles[1].vertices[2], glam::vec3(0.0, 1.0, 1.0));
+        assert_eq!(triangles[0].color, None);
+        assert_eq!(triangles[1].color, Some(Rgba32Unmul::from_rgb(255, 0, 0)));
+
+        stl.pop();
+        assert!(matches!(
+            read_binary(&stl),
+            Err(StlImportError::Truncated {
+                num_triangles: 2,
+                ..
+            })
+        ));
+    }
+}
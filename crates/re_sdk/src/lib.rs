@@ -51,6 +51,18 @@ impl crate::sink::LogSink for re_log_encoding::FileSink {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl crate::sink::LogSink for re_log_encoding::RotatingFileSink {
+    fn send(&self, msg: re_log_types::LogMsg) {
+        re_log_encoding::RotatingFileSink::send(self, msg);
+    }
+
+    #[inline]
+    fn flush_blocking(&self) {
+        re_log_encoding::RotatingFileSink::flush_blocking(self);
+    }
+}
+
 // ---------------
 // Public modules:
 
@@ -59,10 +71,17 @@ impl crate::sink::LogSink for re_log_encoding::FileSink {
 /// This is how you select whether the log stream ends up
 /// sent over TCP, written to file, etc.
 pub mod sink {
-    pub use crate::log_sink::{BufferedSink, LogSink, MemorySink, MemorySinkStorage, TcpSink};
+    pub use crate::log_sink::{
+        BufferedSink, LogSink, MemorySink, MemorySinkStorage, TcpSink, TeeSink, TeeSinkOptions,
+    };
+
+    pub use re_sdk_comms::{BackpressurePolicy, ClientOptions, ServerAddr, SpoolOptions};
+
+    #[cfg(feature = "tls")]
+    pub use re_sdk_comms::{TlsClientConfig, TlsError};
 
     #[cfg(not(target_arch = "wasm32"))]
-    pub use re_log_encoding::{FileSink, FileSinkError};
+    pub use re_log_encoding::{FileSink, FileSinkError, RotatingFileSink, RotationOptions};
 }
 
 /// Things directly related to logging.
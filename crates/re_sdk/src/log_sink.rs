@@ -30,6 +30,15 @@ pub trait LogSink: Send + Sync + 'static {
     /// See also [`LogSink::drop_if_disconnected`].
     fn flush_blocking(&self);
 
+    /// Blocks until all data sent so far has been ingested at its destination,
+    /// e.g. by the viewer at the other end of a [`TcpSink`].
+    ///
+    /// Defaults to [`LogSink::flush_blocking`], which is as far as most sinks can tell.
+    #[inline]
+    fn flush_ingested_blocking(&self) {
+        self.flush_blocking();
+    }
+
     /// Drops all pending data currently sitting in the sink's send buffers if it is unable to
     /// flush it for any reason (e.g. a broken TCP connection for a [`TcpSink`]).
     #[inline]
@@ -228,6 +237,33 @@ impl TcpSink {
             client: re_sdk_comms::Client::new(addr, flush_timeout),
         }
     }
+
+    /// Like [`Self::new`], but with a limit on how many messages may wait for the viewer
+    /// to acknowledge them.
+    #[inline]
+    pub fn with_backpressure(
+        addr: std::net::SocketAddr,
+        flush_timeout: Option<std::time::Duration>,
+        backpressure: re_sdk_comms::BackpressurePolicy,
+    ) -> Self {
+        Self {
+            client: re_sdk_comms::Client::with_backpressure(addr, flush_timeout, backpressure),
+        }
+    }
+
+    /// Like [`Self::new`], but with control over backpressure, authentication and encryption.
+    ///
+    /// `addr` can also be a Unix domain socket, see [`re_sdk_comms::ServerAddr`].
+    #[inline]
+    pub fn with_options(
+        addr: impl Into<re_sdk_comms::ServerAddr>,
+        flush_timeout: Option<std::time::Duration>,
+        options: re_sdk_comms::ClientOptions,
+    ) -> Self {
+        Self {
+            client: re_sdk_comms::Client::with_options(addr, flush_timeout, options),
+        }
+    }
 }
 
 impl LogSink for TcpSink {
@@ -241,8 +277,217 @@ impl LogSink for TcpSink {
         self.client.flush();
     }
 
+    #[inline]
+    fn flush_ingested_blocking(&self) {
+        self.client.flush_ingested();
+    }
+
     #[inline]
     fn drop_if_disconnected(&self) {
         self.client.drop_if_disconnected();
     }
 }
+
+// ----------------------------------------------------------------------------
+
+/// Forward log messages to several sinks at once.
+///
+/// E.g. stream to a live viewer over TCP while also saving everything to disk.
+///
+/// Each sink is fed from its own queue, on its own thread, so a sink that blocks (like a
+/// [`TcpSink`] with [`re_sdk_comms::BackpressurePolicy::Block`] that can't reach its viewer)
+/// doesn't hold back the others, nor the caller: its messages queue up in RAM instead,
+/// up to [`TeeSinkOptions::max_queued`].
+/// Likewise, the sinks are flushed in parallel.
+pub struct TeeSink {
+    branches: Vec<TeeBranch>,
+    drop_timeout: std::time::Duration,
+}
+
+/// See [`TeeSink::with_options`].
+#[derive(Clone, Copy, Debug)]
+pub struct TeeSinkOptions {
+    /// How many messages, or batches of messages, may wait for each sink.
+    ///
+    /// Once a sink has this many waiting, it misses the new ones.
+    pub max_queued: usize,
+
+    /// How long dropping the [`TeeSink`] waits for its sinks to get through their queues.
+    ///
+    /// The sinks still busy after that are left to finish in the background.
+    pub drop_timeout: std::time::Duration,
+}
+
+impl Default for TeeSinkOptions {
+    fn default() -> Self {
+        Self {
+            max_queued: 1024,
+            drop_timeout: std::time::Duration::from_secs(10),
+        }
+    }
+}
+
+/// One of the sinks of a [`TeeSink`], and the queue feeding it.
+struct TeeBranch {
+    sink: Arc<dyn LogSink>,
+    tx: Option<crossbeam::channel::Sender<TeeCmd>>,
+    join_handle: Option<std::thread::JoinHandle<()>>,
+
+    /// Disconnected once the thread of the branch is done.
+    done_rx: crossbeam::channel::Receiver<()>,
+}
+
+enum TeeCmd {
+    Send(LogMsg),
+    SendAll(Vec<LogMsg>),
+    Flush {
+        ingested: bool,
+        done_tx: crossbeam::channel::Sender<()>,
+    },
+}
+
+impl TeeSink {
+    /// Forward to all of these sinks, in this order.
+    pub fn new(sinks: Vec<Box<dyn LogSink>>) -> Self {
+        Self::with_options(sinks, TeeSinkOptions::default())
+    }
+
+    /// Forward to all of these sinks, in this order, with a custom queue size and drop timeout.
+    pub fn with_options(sinks: Vec<Box<dyn LogSink>>, options: TeeSinkOptions) -> Self {
+        let TeeSinkOptions {
+            max_queued,
+            drop_timeout,
+        } = options;
+
+        let branches = sinks
+            .into_iter()
+            .map(|sink| {
+                let sink: Arc<dyn LogSink> = sink.into();
+                let (tx, rx) = crossbeam::channel::bounded(max_queued);
+                let (done_tx, done_rx) = crossbeam::channel::bounded::<()>(0);
+                let join_handle = std::thread::Builder::new()
+                    .name("tee_sink".into())
+                    .spawn({
+                        let sink = sink.clone();
+                        move || {
+                            let _done_tx = done_tx;
+                            for cmd in rx {
+                                match cmd {
+                                    TeeCmd::Send(msg) => sink.send(msg),
+                                    TeeCmd::SendAll(messages) => sink.send_all(messages),
+                                    TeeCmd::Flush { ingested, done_tx } => {
+                                        if ingested {
+                                            sink.flush_ingested_blocking();
+                                        } else {
+                                            sink.flush_blocking();
+                                        }
+                                        done_tx.send(()).ok();
+                                    }
+                                }
+                            }
+                        }
+                    })
+                    .expect("Failed to spawn thread");
+                TeeBranch {
+                    sink,
+                    tx: Some(tx),
+                    join_handle: Some(join_handle),
+                    done_rx,
+                }
+            })
+            .collect();
+
+        Self {
+            branches,
+            drop_timeout,
+        }
+    }
+
+    /// Queues a message for every sink, except those whose queue is full.
+    fn send_msgs(&self, mut cmd: impl FnMut() -> TeeCmd) {
+        for branch in &self.branches {
+            if let Some(tx) = &branch.tx {
+                // Only fails if the queue is full: the thread only quits once we're dropped.
+                if tx.try_send(cmd()).is_err() {
+                    re_log::warn_once!(
+                        "A sink of a TeeSink can't keep up: dropping the messages it has no room for"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Waits for every sink to get through its queue, and then to flush.
+    fn flush_all(&self, ingested: bool) {
+        let (done_tx, done_rx) = crossbeam::channel::unbounded();
+        for branch in &self.branches {
+            if let Some(tx) = &branch.tx {
+                tx.send(TeeCmd::Flush {
+                    ingested,
+                    done_tx: done_tx.clone(),
+                })
+                .ok(); // The thread only quits once we're dropped.
+            }
+        }
+        drop(done_tx);
+        for () in done_rx {}
+    }
+}
+
+impl LogSink for TeeSink {
+    fn send(&self, msg: LogMsg) {
+        self.send_msgs(|| TeeCmd::Send(msg.clone()));
+    }
+
+    fn send_all(&self, messages: Vec<LogMsg>) {
+        self.send_msgs(|| TeeCmd::SendAll(messages.clone()));
+    }
+
+    fn flush_blocking(&self) {
+        self.flush_all(false);
+    }
+
+    fn flush_ingested_blocking(&self) {
+        self.flush_all(true);
+    }
+
+    fn drop_if_disconnected(&self) {
+        // Not queued: it must reach the sinks that are stuck on a message.
+        for branch in &self.branches {
+            branch.sink.drop_if_disconnected();
+        }
+    }
+}
+
+impl Drop for TeeSink {
+    fn drop(&mut self) {
+        // Let every sink get through its queue first, so that nothing is lost…
+        for branch in &mut self.branches {
+            branch.tx = None;
+        }
+
+        // …but don't wait forever on a sink that is stuck, e.g. on a viewer that is gone.
+        let deadline = std::time::Instant::now() + self.drop_timeout;
+        for branch in &mut self.branches {
+            match branch.done_rx.recv_deadline(deadline) {
+                Ok(()) | Err(crossbeam::channel::RecvTimeoutError::Disconnected) => {
+                    if let Some(join_handle) = branch.join_handle.take() {
+                        join_handle.join().ok();
+                    }
+                }
+                Err(crossbeam::channel::RecvTimeoutError::Timeout) => {
+                    re_log::warn!(
+                        "A sink of a TeeSink is still busy after {:?}: leaving it to finish in the background",
+                        self.drop_timeout
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for TeeSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TeeSink {{ {} sinks }}", self.branches.len())
+    }
+}
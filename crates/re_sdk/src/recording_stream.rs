@@ -105,6 +105,8 @@ pub struct RecordingStreamBuilder {
 
     batcher_config: Option<DataTableBatcherConfig>,
 
+    client_options: re_sdk_comms::ClientOptions,
+
     is_official_example: bool,
 }
 
@@ -135,6 +137,7 @@ impl RecordingStreamBuilder {
             enabled: None,
 
             batcher_config: None,
+            client_options: Default::default(),
             is_official_example,
         }
     }
@@ -203,6 +206,51 @@ impl RecordingStreamBuilder {
         self
     }
 
+    /// Specifies what to do when the viewer can't keep up with a TCP stream.
+    ///
+    /// Only applies to the sinks connecting to a remote viewer, e.g. [`Self::connect`].
+    /// The default is to never block nor drop any data.
+    #[inline]
+    pub fn backpressure(mut self, backpressure: re_sdk_comms::BackpressurePolicy) -> Self {
+        self.client_options.backpressure = backpressure;
+        self
+    }
+
+    /// The shared secret to present to a remote viewer that was started with a `--token`.
+    ///
+    /// Only applies to the sinks connecting to a remote viewer, e.g. [`Self::connect`].
+    #[inline]
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.client_options.token = Some(token.into());
+        self
+    }
+
+    /// Encrypt the connection to a remote viewer that was started with a TLS certificate.
+    ///
+    /// Only applies to the sinks connecting to a remote viewer, e.g. [`Self::connect`].
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn tls(mut self, tls: re_sdk_comms::TlsClientConfig) -> Self {
+        self.client_options.tls = Some(tls);
+        self
+    }
+
+    /// While the remote viewer can't be reached, write the messages to disk in `dir`
+    /// instead of keeping them in RAM, and send them once it is back.
+    ///
+    /// At most `max_bytes` are spooled; messages beyond that are dropped.
+    /// Messages still spooled when the process exits are sent by the next one using the same `dir`.
+    ///
+    /// Only applies to the sinks connecting to a remote viewer, e.g. [`Self::connect`].
+    #[inline]
+    pub fn spool(mut self, dir: impl Into<std::path::PathBuf>, max_bytes: u64) -> Self {
+        self.client_options.spool = Some(re_sdk_comms::SpoolOptions {
+            dir: dir.into(),
+            max_bytes,
+        });
+        self
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn store_source(mut self, store_source: StoreSource) -> Self {
@@ -315,12 +363,17 @@ impl RecordingStreamBuilder {
         addr: std::net::SocketAddr,
         flush_timeout: Option<std::time::Duration>,
     ) -> RecordingStreamResult<RecordingStream> {
+        let client_options = self.client_options.clone();
         let (enabled, store_info, batcher_config) = self.into_args();
         if enabled {
             RecordingStream::new(
                 store_info,
                 batcher_config,
-                Box::new(crate::log_sink::TcpSink::new(addr, flush_timeout)),
+                Box::new(crate::log_sink::TcpSink::with_options(
+                    addr,
+                    flush_timeout,
+                    client_options,
+                )),
             )
         } else {
             re_log::debug!("Rerun disabled - call to connect() ignored");
@@ -328,6 +381,115 @@ impl RecordingStreamBuilder {
         }
     }
 
+    /// Creates a new [`RecordingStream`] that is pre-configured to stream the data through to a
+    /// Rerun instance on the same machine, over the Unix domain socket at `path`.
+    ///
+    /// The viewer must listen on that socket, e.g. `rerun --bind unix:///tmp/rerun.sock`.
+    /// This is cheaper than going through TCP, which matters for high rate images and tensors.
+    ///
+    /// See [`Self::connect_opts`] for `flush_timeout`.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let rec = re_sdk::RecordingStreamBuilder::new("rerun_example_app")
+    ///     .connect_unix("/tmp/rerun.sock", re_sdk::default_flush_timeout())?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(unix)]
+    pub fn connect_unix(
+        self,
+        path: impl Into<std::path::PathBuf>,
+        flush_timeout: Option<std::time::Duration>,
+    ) -> RecordingStreamResult<RecordingStream> {
+        let client_options = self.client_options.clone();
+        let (enabled, store_info, batcher_config) = self.into_args();
+        if enabled {
+            RecordingStream::new(
+                store_info,
+                batcher_config,
+                Box::new(crate::log_sink::TcpSink::with_options(
+                    re_sdk_comms::ServerAddr::Unix(path.into()),
+                    flush_timeout,
+                    client_options,
+                )),
+            )
+        } else {
+            re_log::debug!("Rerun disabled - call to connect_unix() ignored");
+            Ok(RecordingStream::disabled())
+        }
+    }
+
+    /// Creates a new [`RecordingStream`] that is pre-configured to stream the data through to a
+    /// remote Rerun instance, and to also save it to an RRD file on disk.
+    ///
+    /// See also [`Self::connect_opts_and_save`] if you wish to configure the TCP connection.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let rec = re_sdk::RecordingStreamBuilder::new("rerun_example_app")
+    ///     .connect_and_save("my_recording.rrd")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn connect_and_save(
+        self,
+        path: impl Into<std::path::PathBuf>,
+    ) -> RecordingStreamResult<RecordingStream> {
+        self.connect_opts_and_save(
+            crate::default_server_addr(),
+            crate::default_flush_timeout(),
+            path,
+        )
+    }
+
+    /// Creates a new [`RecordingStream`] that is pre-configured to stream the data through to a
+    /// remote Rerun instance, and to also save it to an RRD file on disk.
+    ///
+    /// The file doesn't depend on the connection: everything is saved even if the viewer can't
+    /// be reached.
+    ///
+    /// `flush_timeout` is the minimum time the [`TcpSink`][`crate::log_sink::TcpSink`] will
+    /// wait during a flush before potentially dropping data. Note: Passing `None` here can cause a
+    /// call to `flush` to block indefinitely if a connection cannot be established.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let rec = re_sdk::RecordingStreamBuilder::new("rerun_example_app").connect_opts_and_save(
+    ///     re_sdk::default_server_addr(),
+    ///     re_sdk::default_flush_timeout(),
+    ///     "my_recording.rrd",
+    /// )?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn connect_opts_and_save(
+        self,
+        addr: std::net::SocketAddr,
+        flush_timeout: Option<std::time::Duration>,
+        path: impl Into<std::path::PathBuf>,
+    ) -> RecordingStreamResult<RecordingStream> {
+        let client_options = self.client_options.clone();
+        let (enabled, store_info, batcher_config) = self.into_args();
+
+        if enabled {
+            let sink = crate::log_sink::TeeSink::new(vec![
+                Box::new(crate::sink::FileSink::new(path)?),
+                Box::new(crate::log_sink::TcpSink::with_options(
+                    addr,
+                    flush_timeout,
+                    client_options,
+                )),
+            ]);
+            RecordingStream::new(store_info, batcher_config, Box::new(sink))
+        } else {
+            re_log::debug!("Rerun disabled - call to connect_and_save() ignored");
+            Ok(RecordingStream::disabled())
+        }
+    }
+
     /// Creates a new [`RecordingStream`] that is pre-configured to stream the data through to an
     /// RRD file on disk.
     ///
@@ -356,6 +518,45 @@ impl RecordingStreamBuilder {
         }
     }
 
+    /// Creates a new [`RecordingStream`] that is pre-configured to stream the data through to a
+    /// series of RRD files on disk, starting a new one whenever the current one gets too big or
+    /// too old.
+    ///
+    /// See [`crate::sink::RotatingFileSink`] for how the files are named.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let rec = re_sdk::RecordingStreamBuilder::new("rerun_example_app").save_rotating(
+    ///     "my_recording.rrd",
+    ///     re_sdk::sink::RotationOptions {
+    ///         max_segment_bytes: Some(100 * 1024 * 1024),
+    ///         max_segments: Some(10),
+    ///         ..Default::default()
+    ///     },
+    /// )?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_rotating(
+        self,
+        path: impl Into<std::path::PathBuf>,
+        options: crate::sink::RotationOptions,
+    ) -> RecordingStreamResult<RecordingStream> {
+        let (enabled, store_info, batcher_config) = self.into_args();
+
+        if enabled {
+            RecordingStream::new(
+                store_info,
+                batcher_config,
+                Box::new(crate::sink::RotatingFileSink::new(path, options)?),
+            )
+        } else {
+            re_log::debug!("Rerun disabled - call to save_rotating() ignored");
+            Ok(RecordingStream::disabled())
+        }
+    }
+
     /// Creates a new [`RecordingStream`] that is pre-configured to stream the data through to stdout.
     ///
     /// If there isn't any listener at the other end of the pipe, the [`RecordingStream`] will
@@ -525,6 +726,7 @@ impl RecordingStreamBuilder {
             default_enabled,
             enabled,
             batcher_config,
+            client_options: _,
             is_official_example,
         } = self;
 
@@ -684,6 +886,7 @@ enum Command {
     RecordMsg(LogMsg),
     SwapSink(Box<dyn LogSink>),
     Flush(Sender<()>),
+    FlushIngested(Sender<()>),
     PopPendingTables,
     Shutdown,
 }
@@ -1027,6 +1230,12 @@ fn forwarding_thread(
                 sink.flush_blocking();
                 drop(oneshot); // signals the oneshot
             }
+            Command::FlushIngested(oneshot) => {
+                re_log::trace!("Flushing until ingested…");
+                sink.drop_if_disconnected();
+                sink.flush_ingested_blocking();
+                drop(oneshot); // signals the oneshot
+            }
             Command::PopPendingTables => {
                 // Wake up and skip the current iteration so that we can drain all pending tables
                 // before handling the next command.
@@ -1267,6 +1476,31 @@ impl RecordingStream {
         this.cmds_tx.send(cmd).ok();
         oneshot.recv().ok();
     }
+
+    /// Like [`Self::flush_blocking`], but also waits for the data to be ingested at its
+    /// destination, e.g. by the viewer at the other end of a [`crate::log_sink::TcpSink`].
+    ///
+    /// Data that can't reach its destination, e.g. because the connection broke, is not waited for.
+    ///
+    /// See [`RecordingStream`] docs for ordering semantics and multithreading guarantees.
+    pub fn flush_ingested_blocking(&self) {
+        if self.is_forked_child() {
+            re_log::error_once!("Fork detected during flush. cleanup_if_forked() should always be called after forking. This is likely a bug in the SDK.");
+            return;
+        }
+
+        let Some(this) = &*self.inner else {
+            re_log::warn_once!("Recording disabled - call to flush_ingested_blocking() ignored");
+            return;
+        };
+
+        this.batcher.flush_blocking();
+        this.cmds_tx.send(Command::PopPendingTables).ok();
+
+        let (tx, oneshot) = crossbeam::channel::bounded(0); // oneshot
+        this.cmds_tx.send(Command::FlushIngested(tx)).ok();
+        oneshot.recv().ok();
+    }
 }
 
 impl RecordingStream {
@@ -1396,6 +1630,79 @@ impl RecordingStream {
         Ok(())
     }
 
+    /// Swaps the underlying sink for a [`crate::log_sink::TeeSink`] that both streams to a
+    /// [`crate::log_sink::TcpSink`] pre-configured to use the default address, and saves to a
+    /// [`crate::sink::FileSink`] at the specified `path`.
+    ///
+    /// See also [`Self::connect_opts_and_save`] if you wish to configure the TCP connection.
+    ///
+    /// This is a convenience wrapper for [`Self::set_sink`] that upholds the same guarantees in
+    /// terms of data durability and ordering.
+    /// See [`Self::set_sink`] for more information.
+    pub fn connect_and_save(
+        &self,
+        path: impl Into<std::path::PathBuf>,
+    ) -> Result<(), crate::sink::FileSinkError> {
+        self.connect_opts_and_save(
+            crate::default_server_addr(),
+            crate::default_flush_timeout(),
+            path,
+        )
+    }
+
+    /// Swaps the underlying sink for a [`crate::log_sink::TeeSink`] that both streams to a
+    /// [`crate::log_sink::TcpSink`] at the specified address and saves to a
+    /// [`crate::sink::FileSink`] at the specified `path`.
+    ///
+    /// `flush_timeout` is the minimum time the [`TcpSink`][`crate::log_sink::TcpSink`] will
+    /// wait during a flush before potentially dropping data. Note: Passing `None` here can cause a
+    /// call to `flush` to block indefinitely if a connection cannot be established.
+    ///
+    /// This is a convenience wrapper for [`Self::set_sink`] that upholds the same guarantees in
+    /// terms of data durability and ordering.
+    /// See [`Self::set_sink`] for more information.
+    pub fn connect_opts_and_save(
+        &self,
+        addr: std::net::SocketAddr,
+        flush_timeout: Option<std::time::Duration>,
+        path: impl Into<std::path::PathBuf>,
+    ) -> Result<(), crate::sink::FileSinkError> {
+        if forced_sink_path().is_some() {
+            re_log::debug!("Ignored setting new file since _RERUN_FORCE_SINK is set");
+            return Ok(());
+        }
+
+        let sink = crate::log_sink::TeeSink::new(vec![
+            Box::new(crate::sink::FileSink::new(path)?),
+            Box::new(crate::log_sink::TcpSink::new(addr, flush_timeout)),
+        ]);
+        self.set_sink(Box::new(sink));
+
+        Ok(())
+    }
+
+    /// Swaps the underlying sink for a [`crate::sink::RotatingFileSink`] writing segments named
+    /// after the specified `path`.
+    ///
+    /// This is a convenience wrapper for [`Self::set_sink`] that upholds the same guarantees in
+    /// terms of data durability and ordering.
+    /// See [`Self::set_sink`] for more information.
+    pub fn save_rotating(
+        &self,
+        path: impl Into<std::path::PathBuf>,
+        options: crate::sink::RotationOptions,
+    ) -> Result<(), crate::sink::FileSinkError> {
+        if forced_sink_path().is_some() {
+            re_log::debug!("Ignored setting new file since _RERUN_FORCE_SINK is set");
+            return Ok(());
+        }
+
+        let sink = crate::sink::RotatingFileSink::new(path, options)?;
+        self.set_sink(Box::new(sink));
+
+        Ok(())
+    }
+
     /// Swaps the underlying sink for a [`crate::sink::FileSink`] pointed at stdout.
     ///
     /// If there isn't any listener at the other end of the pipe, the [`RecordingStream`] will
@@ -1962,6 +2269,126 @@ mod tests {
         assert!(msgs.pop().is_none());
     }
 
+    #[test]
+    fn tee_sink() {
+        let rec = RecordingStreamBuilder::new("rerun_example_tee_sink")
+            .enabled(true)
+            .batcher_config(DataTableBatcherConfig::ALWAYS)
+            .buffered()
+            .unwrap();
+
+        let memory = crate::log_sink::MemorySink::default();
+        let storage = memory.buffer();
+        // Nobody's listening on the other end: flushing it times out, but must not get in
+        // the way of the other sink.
+        let unreachable = crate::log_sink::TcpSink::new(
+            "127.0.0.1:1".parse().unwrap(),
+            Some(std::time::Duration::from_millis(100)),
+        );
+        let other_memory = crate::log_sink::MemorySink::default();
+        let other_storage = other_memory.buffer();
+        rec.set_sink(Box::new(crate::log_sink::TeeSink::new(vec![
+            Box::new(memory),
+            Box::new(unreachable),
+            Box::new(other_memory),
+        ])));
+
+        let mut table = DataTable::example(false);
+        table.compute_all_size_bytes();
+        for row in table.to_rows() {
+            rec.record_row(row.unwrap(), false);
+        }
+        rec.flush_blocking();
+
+        let msgs = storage.take();
+        // The store info, and then at least one table.
+        assert!(msgs.len() >= 2);
+        assert_eq!(msgs, other_storage.take());
+    }
+
+    #[test]
+    fn tee_sink_blocked() {
+        let (rec, storage) = RecordingStreamBuilder::new("rerun_example_tee_sink_blocked")
+            .enabled(true)
+            .batcher_config(DataTableBatcherConfig::ALWAYS)
+            .memory()
+            .unwrap();
+        let mut table = DataTable::example(false);
+        table.compute_all_size_bytes();
+        for row in table.to_rows() {
+            rec.record_row(row.unwrap(), false);
+        }
+        rec.flush_blocking();
+        let msgs = storage.take();
+        assert!(msgs.len() >= 2);
+
+        let memory = crate::log_sink::MemorySink::default();
+        let memory_storage = memory.buffer();
+        // Nobody's listening on the other end: it blocks on its second message, until told to
+        // drop what it can't send.
+        let blocked = crate::log_sink::TcpSink::with_backpressure(
+            "127.0.0.1:1".parse().unwrap(),
+            Some(std::time::Duration::from_millis(100)),
+            re_sdk_comms::BackpressurePolicy::Block { max_in_flight: 1 },
+        );
+        let tee = crate::log_sink::TeeSink::with_options(
+            vec![Box::new(blocked), Box::new(memory)],
+            crate::log_sink::TeeSinkOptions {
+                max_queued: msgs.len(),
+                drop_timeout: std::time::Duration::from_millis(100),
+            },
+        );
+
+        // Neither the caller, nor the other sink, are held back.
+        for msg in msgs.clone() {
+            tee.send(msg);
+        }
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while memory_storage.num_msgs() < msgs.len() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "the memory sink is stuck"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(msgs, memory_storage.take());
+
+        // Once its queue is full, the blocked sink misses messages instead of holding back
+        // the caller…
+        for _ in 0..3 {
+            for msg in msgs.clone() {
+                tee.send(msg);
+            }
+        }
+
+        // …nor is the caller held back when it is done with the sinks.
+        let start = std::time::Instant::now();
+        drop(tee);
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn flush_ingested_unreachable() {
+        let rec = RecordingStreamBuilder::new("rerun_example_flush_ingested")
+            .enabled(true)
+            .batcher_config(DataTableBatcherConfig::ALWAYS)
+            .backpressure(re_sdk_comms::BackpressurePolicy::Drop { max_in_flight: 1 })
+            .connect_opts(
+                "127.0.0.1:1".parse().unwrap(),
+                Some(std::time::Duration::from_millis(100)),
+            )
+            .unwrap();
+
+        let mut table = DataTable::example(false);
+        table.compute_all_size_bytes();
+        for row in table.to_rows() {
+            rec.record_row(row.unwrap(), false);
+        }
+
+        // Nothing will ever be acknowledged, but the dropped data must not be waited for.
+        rec.flush_ingested_blocking();
+    }
+
     #[test]
     fn test_set_thread_local() {
         // Regression-test for https://github.com/rerun-io/rerun/issues/2889
@@ -88,7 +88,10 @@ pub async fn host_web_viewer(
 
     let viewer_url = format!("{http_web_viewer_url}?url={source_url}");
 
-    re_log::info!("Hosting a web-viewer at {viewer_url}");
+    re_log::info!(
+        "Hosting a web-viewer at {}",
+        re_ws_comms::redact_token(&viewer_url)
+    );
     if open_browser {
         webbrowser::open(&viewer_url).ok();
     }
@@ -1,9 +1,53 @@
-use std::{fmt, net::SocketAddr, thread::JoinHandle};
+use std::{fmt, net::SocketAddr, sync::Arc, thread::JoinHandle};
 
-use crossbeam::channel::{select, Receiver, Sender};
+use crossbeam::{
+    atomic::AtomicCell,
+    channel::{select, Receiver, Sender},
+};
 
+use re_log_encoding::EncodingOptions;
 use re_log_types::LogMsg;
 
+use crate::{spool::Spool, tcp_client::InFlight, ServerAddr, SpoolOptions};
+
+/// What [`Client::send`] does when too many messages are waiting to be acknowledged by the server.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Never block nor drop: messages queue up in RAM until they can be sent.
+    #[default]
+    Unbounded,
+
+    /// Block until fewer than `max_in_flight` messages are waiting.
+    ///
+    /// Note that this blocks for as long as the server can't be reached.
+    Block { max_in_flight: u64 },
+
+    /// Drop new messages while `max_in_flight` messages are waiting.
+    ///
+    /// Store infos are never dropped.
+    Drop { max_in_flight: u64 },
+}
+
+/// How a [`Client`] talks to the server.
+#[derive(Clone, Debug, Default)]
+pub struct ClientOptions {
+    pub backpressure: BackpressurePolicy,
+
+    /// Presented to servers that only accept clients knowing this shared secret.
+    pub token: Option<String>,
+
+    /// Encrypt the connection. The server must use TLS too.
+    #[cfg(feature = "tls")]
+    pub tls: Option<crate::TlsClientConfig>,
+
+    /// Write the messages to disk while the server is unreachable, instead of keeping them in RAM.
+    ///
+    /// Messages are then never dropped because of [`Client::drop_if_disconnected`]:
+    /// whatever the server didn't acknowledge stays on disk for the next client using the same
+    /// spool.
+    pub spool: Option<SpoolOptions>,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct FlushedMsg;
 
@@ -46,8 +90,12 @@ pub struct Client {
     encode_join: Option<JoinHandle<()>>,
     send_join: Option<JoinHandle<()>>,
 
+    /// The messages sent but not yet acknowledged by the server.
+    in_flight: Arc<InFlight>,
+    backpressure: BackpressurePolicy,
+
     /// Only used for diagnostics, not for communication after `new()`.
-    addr: SocketAddr,
+    addr: ServerAddr,
 }
 
 impl Client {
@@ -58,7 +106,38 @@ impl Client {
     /// cause a call to `flush` to block indefinitely if a connection cannot be
     /// established.
     pub fn new(addr: SocketAddr, flush_timeout: Option<std::time::Duration>) -> Self {
+        Self::with_backpressure(addr, flush_timeout, BackpressurePolicy::default())
+    }
+
+    /// Connect via TCP to this log server, limiting how many messages may wait for the server.
+    ///
+    /// See [`Self::new`] for `flush_timeout`.
+    pub fn with_backpressure(
+        addr: SocketAddr,
+        flush_timeout: Option<std::time::Duration>,
+        backpressure: BackpressurePolicy,
+    ) -> Self {
+        Self::with_options(
+            addr,
+            flush_timeout,
+            ClientOptions {
+                backpressure,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Connect via TCP, or a Unix domain socket, to this log server.
+    ///
+    /// See [`Self::new`] for `flush_timeout`.
+    pub fn with_options(
+        addr: impl Into<ServerAddr>,
+        flush_timeout: Option<std::time::Duration>,
+        options: ClientOptions,
+    ) -> Self {
+        let addr = addr.into();
         re_log::debug!("Connecting to remote {addr}…");
+        let backpressure = options.backpressure;
 
         // TODO(emilk): keep track of how much memory is in each pipe
         // and apply back-pressure to not use too much RAM.
@@ -68,22 +147,72 @@ impl Client {
         let (encode_quit_tx, encode_quit_rx) = crossbeam::channel::unbounded();
         let (send_quit_tx, send_quit_rx) = crossbeam::channel::unbounded();
 
-        // We don't compress the stream because we assume the SDK
-        // and server are on the same machine and compression
-        // can be expensive, see https://github.com/rerun-io/rerun/issues/2216
-        let encoding_options = re_log_encoding::EncodingOptions::UNCOMPRESSED;
+        // Messages are encoded ahead of the connection, so until the handshake tells us otherwise
+        // we use the encoding every server understands.
+        // Each packet carries its own encoding, so switching later is fine.
+        let encoding_options = Arc::new(AtomicCell::new(EncodingOptions::UNCOMPRESSED));
+
+        let in_flight = Arc::new(InFlight::default());
 
         let encode_join = std::thread::Builder::new()
             .name("msg_encoder".into())
-            .spawn(move || {
-                msg_encode(encoding_options, &msg_rx, &encode_quit_rx, &packet_tx);
+            .spawn({
+                let encoding_options = encoding_options.clone();
+                let in_flight = in_flight.clone();
+                move || {
+                    msg_encode(
+                        &encoding_options,
+                        &in_flight,
+                        &msg_rx,
+                        &encode_quit_rx,
+                        &packet_tx,
+                    );
+                }
             })
             .expect("Failed to spawn thread");
 
         let send_join = std::thread::Builder::new()
             .name("tcp_sender".into())
-            .spawn(move || {
-                tcp_sender(addr, flush_timeout, &packet_rx, &send_quit_rx, &flushed_tx);
+            .spawn({
+                let addr = addr.clone();
+                let in_flight = in_flight.clone();
+                move || {
+                    let spool = options.spool.as_ref().and_then(|spool_options| {
+                        Spool::open(spool_options)
+                            .map_err(|err| {
+                                re_log::error!(
+                                    "Failed to open the spool in {:?}, keeping unsent messages in RAM instead: {err}",
+                                    spool_options.dir
+                                );
+                            })
+                            .ok()
+                    });
+
+                    if let Some(spool) = spool {
+                        spooling_tcp_sender(
+                            addr,
+                            flush_timeout,
+                            options,
+                            spool,
+                            &encoding_options,
+                            &in_flight,
+                            &packet_rx,
+                            &send_quit_rx,
+                            &flushed_tx,
+                        );
+                    } else {
+                        tcp_sender(
+                            addr,
+                            flush_timeout,
+                            options,
+                            &encoding_options,
+                            in_flight,
+                            &packet_rx,
+                            &send_quit_rx,
+                            &flushed_tx,
+                        );
+                    }
+                }
             })
             .expect("Failed to spawn thread");
 
@@ -94,29 +223,77 @@ impl Client {
             send_quit_tx,
             encode_join: Some(encode_join),
             send_join: Some(send_join),
+            in_flight,
+            backpressure,
             addr,
         }
     }
 
+    /// Sends the message, unless the [`BackpressurePolicy`] says to drop it.
+    ///
+    /// Blocks if the [`BackpressurePolicy`] says so.
     pub fn send(&self, log_msg: LogMsg) {
-        self.send_msg_msg(MsgMsg::LogMsg(log_msg));
+        let started = match self.backpressure {
+            BackpressurePolicy::Unbounded => self.in_flight.start(u64::MAX, None),
+            BackpressurePolicy::Block { max_in_flight } => {
+                self.in_flight.start(max_in_flight.max(1), None)
+            }
+            BackpressurePolicy::Drop { max_in_flight } => {
+                if matches!(log_msg, LogMsg::SetStoreInfo(_)) {
+                    self.in_flight.start(u64::MAX, None)
+                } else {
+                    self.in_flight
+                        .start(max_in_flight, Some(std::time::Duration::ZERO))
+                }
+            }
+        };
+
+        if started {
+            self.send_msg_msg(MsgMsg::LogMsg(log_msg));
+        } else {
+            re_log::warn_once!(
+                "Dropping messages: {} messages are still waiting for the Rerun Viewer at {}",
+                self.in_flight.len(),
+                self.addr
+            );
+        }
     }
 
     /// Stall until all messages so far has been sent.
     pub fn flush(&self) {
+        self.flush_sent();
+    }
+
+    /// Stall until all messages so far have been ingested by the server,
+    /// as acknowledged by it.
+    ///
+    /// Messages that were dropped, or lost on a broken connection, are not waited for.
+    pub fn flush_ingested(&self) {
+        let num_messages = self.in_flight.num_started();
+        if self.flush_sent() {
+            re_log::debug!("Waiting for the server to ingest everything…");
+            self.in_flight.wait_until_done(num_messages);
+            re_log::debug!("Everything was ingested.");
+        }
+    }
+
+    /// Returns `false` if the pipeline has shut down.
+    fn flush_sent(&self) -> bool {
         re_log::debug!("Flushing message queue…");
         if self.msg_tx.send(MsgMsg::Flush).is_err() {
             re_log::debug!("Flush failed: already shut down.");
-            return;
+            return false;
         }
 
         match self.flushed_rx.recv() {
             Ok(FlushedMsg) => {
                 re_log::debug!("Flush complete.");
+                true
             }
             Err(_) => {
                 // This can happen on Ctrl-C
                 re_log::warn!("Failed to flush pipeline - not all messages were sent.");
+                false
             }
         }
     }
@@ -162,7 +339,8 @@ impl fmt::Debug for Client {
 }
 
 fn msg_encode(
-    encoding_options: re_log_encoding::EncodingOptions,
+    encoding_options: &AtomicCell<EncodingOptions>,
+    in_flight: &InFlight,
     msg_rx: &Receiver<MsgMsg>,
     quit_rx: &Receiver<QuitMsg>,
     packet_tx: &Sender<PacketMsg>,
@@ -177,13 +355,14 @@ fn msg_encode(
 
                 let packet_msg = match &msg_msg {
                     MsgMsg::LogMsg(log_msg) => {
-                        match re_log_encoding::encoder::encode_to_bytes(encoding_options, std::iter::once(log_msg)) {
+                        match re_log_encoding::encoder::encode_to_bytes(encoding_options.load(), std::iter::once(log_msg)) {
                             Ok(packet) => {
                                 re_log::trace!("Encoded message of size {}", packet.len());
                                 Some(PacketMsg::Packet(packet))
                             }
                             Err(err) => {
                                 re_log::error_once!("Failed to encode log message: {err}");
+                                in_flight.done(1);
                                 None
                             }
                         }
@@ -206,14 +385,19 @@ fn msg_encode(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn tcp_sender(
-    addr: SocketAddr,
+    addr: ServerAddr,
     flush_timeout: Option<std::time::Duration>,
+    options: ClientOptions,
+    encoding_options: &AtomicCell<EncodingOptions>,
+    in_flight: Arc<InFlight>,
     packet_rx: &Receiver<PacketMsg>,
     quit_rx: &Receiver<InterruptMsg>,
     flushed_tx: &Sender<FlushedMsg>,
 ) {
-    let mut tcp_client = crate::tcp_client::TcpClient::new(addr, flush_timeout);
+    let mut tcp_client =
+        crate::tcp_client::TcpClient::new(addr, flush_timeout, in_flight.clone(), options);
     // Once this flag has been set, we will drop all messages if the tcp_client is
     // no longer connected.
     let mut drop_if_disconnected = false;
@@ -224,13 +408,17 @@ fn tcp_sender(
                 if let Ok(packet_msg) = packet_msg {
                     match packet_msg {
                         PacketMsg::Packet(packet) => {
-                            match send_until_success(&mut tcp_client, drop_if_disconnected, &packet, quit_rx) {
+                            match send_until_success(&mut tcp_client, &in_flight, drop_if_disconnected, &packet, quit_rx) {
                                 Some(InterruptMsg::Quit) => {return;}
                                 Some(InterruptMsg::DropIfDisconnected) => {
                                     drop_if_disconnected = true;
                                 }
                                 None => {}
                             }
+                            if let Some(encoding) = tcp_client.encoding() {
+                                // Use whatever the server negotiated for the upcoming messages.
+                                encoding_options.store(encoding);
+                            }
                         }
                         PacketMsg::Flush => {
                             tcp_client.flush();
@@ -263,8 +451,236 @@ fn tcp_sender(
     }
 }
 
+/// Like [`tcp_sender`], but writes whatever it can't send to the spool, and replays it from there.
+#[allow(clippy::too_many_arguments)]
+fn spooling_tcp_sender(
+    addr: ServerAddr,
+    flush_timeout: Option<std::time::Duration>,
+    options: ClientOptions,
+    mut spool: Spool,
+    encoding_options: &AtomicCell<EncodingOptions>,
+    in_flight: &Arc<InFlight>,
+    packet_rx: &Receiver<PacketMsg>,
+    quit_rx: &Receiver<InterruptMsg>,
+    flushed_tx: &Sender<FlushedMsg>,
+) {
+    let mut tcp_client =
+        crate::tcp_client::TcpClient::new(addr, flush_timeout, in_flight.clone(), options);
+    let mut reconnect = Reconnect::default();
+    let mut replay = SpoolReplay::default();
+
+    loop {
+        if let Some(encoding) = tcp_client.encoding() {
+            // Use whatever the server negotiated for the upcoming messages.
+            encoding_options.store(encoding);
+        }
+
+        // Wake up to replay the spool even if there is nothing new to send, and to find out
+        // what the server acknowledged of it.
+        let replay_rx = if spool.has_unsent() {
+            crossbeam::channel::at(reconnect.next_attempt)
+        } else if !spool.is_empty() {
+            crossbeam::channel::after(Reconnect::MIN_INTERVAL)
+        } else {
+            crossbeam::channel::never()
+        };
+
+        select! {
+            recv(packet_rx) -> packet_msg => {
+                let Ok(packet_msg) = packet_msg else {
+                    re_log::debug!("Shutting down tcp_sender thread: packet_rx channel has closed");
+                    return; // channel has closed
+                };
+
+                match packet_msg {
+                    PacketMsg::Packet(packet) => {
+                        // Until the server has acknowledged all of the spool, new packets go
+                        // behind it, so that none is lost if the connection breaks meanwhile.
+                        if spool.is_empty() && reconnect.is_due() {
+                            if send_once(&mut tcp_client, &packet) {
+                                reconnect.succeeded();
+                                continue;
+                            }
+                            reconnect.failed();
+                        }
+
+                        match spool.push(&packet) {
+                            Ok(true) => re_log::info_once!("The server can't be reached: spooling messages to disk"),
+                            Ok(false) => re_log::warn_once!("Dropping messages: the spool is full"),
+                            Err(err) => re_log::error_once!("Dropping messages: failed to write to the spool: {err}"),
+                        }
+                        in_flight.done(1);
+                    }
+                    PacketMsg::Flush => {
+                        // Whatever is still spooled afterwards is safe on disk.
+                        if replay.run(&mut tcp_client, &mut spool, in_flight) {
+                            reconnect.succeeded();
+                            tcp_client.flush();
+                        } else {
+                            reconnect.failed();
+                        }
+                        flushed_tx
+                            .send(FlushedMsg)
+                            .expect("Main thread should still be alive");
+                    }
+                }
+            },
+            recv(replay_rx) -> _ => {
+                if replay.run(&mut tcp_client, &mut spool, in_flight) {
+                    if spool.is_empty() {
+                        re_log::debug!("Sent all spooled messages");
+                    }
+                    reconnect.succeeded();
+                } else {
+                    reconnect.failed();
+                }
+            },
+            recv(quit_rx) -> quit_msg => { match quit_msg {
+                // Nothing gets stuck waiting for the server: unsent messages go to the spool.
+                Ok(InterruptMsg::DropIfDisconnected) => {}
+                Ok(InterruptMsg::Quit) => {
+                    re_log::debug!("Shutting down tcp_sender thread: received Quit message");
+                    return;
+                }
+                Err(_) => {
+                    re_log::debug!("Shutting down tcp_sender thread: quit_rx channel has closed");
+                    return;
+                }
+            }}
+        }
+    }
+}
+
+fn send_once(tcp_client: &mut crate::tcp_client::TcpClient, packet: &[u8]) -> bool {
+    if let Err(err) = tcp_client.send(packet) {
+        re_log::debug!("Failed to send message, spooling it: {err}");
+        false
+    } else {
+        true
+    }
+}
+
+/// Which connection the spooled packets awaiting acknowledgement were sent on.
+#[derive(Default)]
+struct SpoolReplay {
+    /// The id of the connection, and how many packets had been sent on it before the oldest
+    /// spooled packet not yet acknowledged.
+    sent_on: Option<(u64, u64)>,
+}
+
+impl SpoolReplay {
+    /// Removes the spooled packets the server acknowledged, then sends those not sent yet,
+    /// oldest first.
+    ///
+    /// Returns `false` if some are left unsent, because the server couldn't be reached.
+    fn run(
+        &mut self,
+        tcp_client: &mut crate::tcp_client::TcpClient,
+        spool: &mut Spool,
+        in_flight: &InFlight,
+    ) -> bool {
+        self.collect_acks(tcp_client, spool);
+
+        loop {
+            let packet = match spool.next_unsent() {
+                Ok(Some(packet)) => packet,
+                Ok(None) => return true,
+                Err(err) => {
+                    re_log::error_once!("Failed to read from the spool: {err}");
+                    spool.rewind();
+                    self.sent_on = None;
+                    return false;
+                }
+            };
+
+            // Spooled packets were marked as done, and may come from a previous client.
+            in_flight.start(u64::MAX, None);
+            if let Err(err) = tcp_client.send(&packet) {
+                re_log::debug!("Failed to send spooled message: {err}");
+                in_flight.done(1);
+                // The connection is gone, and with it the packets it didn't acknowledge.
+                spool.rewind();
+                self.sent_on = None;
+                return false;
+            }
+
+            if self.sent_on.is_none() {
+                self.sent_on = tcp_client
+                    .acks()
+                    .map(|acks| (acks.connection_id, acks.num_sent.saturating_sub(1)));
+            }
+        }
+    }
+
+    /// Removes the spooled packets the server acknowledged, and rewinds the spool to send the
+    /// others again if the connection they were sent on is gone.
+    fn collect_acks(&mut self, tcp_client: &mut crate::tcp_client::TcpClient, spool: &mut Spool) {
+        let Some((connection_id, num_sent_before)) = self.sent_on else {
+            return;
+        };
+
+        let acks = tcp_client
+            .acks()
+            .filter(|acks| acks.connection_id == connection_id);
+        if let Some(acks) = acks {
+            let num_acked = acks.num_acked.saturating_sub(num_sent_before);
+            if let Err(err) = spool.acknowledge(num_acked) {
+                re_log::error_once!("Failed to remove sent messages from the spool: {err}");
+            }
+            self.sent_on = Some((connection_id, num_sent_before + num_acked));
+
+            if !acks.closed {
+                if spool.is_empty() {
+                    self.sent_on = None;
+                }
+                return;
+            }
+
+            // The server won't acknowledge anything more on this connection.
+            tcp_client.disconnect();
+        }
+
+        spool.rewind();
+        self.sent_on = None;
+    }
+}
+
+/// When to next try reaching the server, backing off while it can't be reached.
+struct Reconnect {
+    next_attempt: std::time::Instant,
+    interval: std::time::Duration,
+}
+
+impl Reconnect {
+    const MIN_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+    const MAX_INTERVAL: std::time::Duration = std::time::Duration::from_millis(3000);
+
+    fn is_due(&self) -> bool {
+        self.next_attempt <= std::time::Instant::now()
+    }
+
+    fn succeeded(&mut self) {
+        *self = Self::default();
+    }
+
+    fn failed(&mut self) {
+        self.next_attempt = std::time::Instant::now() + self.interval;
+        self.interval = (2 * self.interval).min(Self::MAX_INTERVAL);
+    }
+}
+
+impl Default for Reconnect {
+    fn default() -> Self {
+        Self {
+            next_attempt: std::time::Instant::now(),
+            interval: Self::MIN_INTERVAL,
+        }
+    }
+}
+
 fn send_until_success(
     tcp_client: &mut crate::tcp_client::TcpClient,
+    in_flight: &InFlight,
     drop_if_disconnected: bool,
     packet: &[u8],
     quit_rx: &Receiver<InterruptMsg>,
@@ -272,12 +688,14 @@ fn send_until_success(
     // Early exit if tcp_client is disconnected
     if drop_if_disconnected && tcp_client.has_timed_out_for_flush() {
         re_log::warn_once!("Dropping messages because tcp client has timed out.");
+        in_flight.done(1);
         return None;
     }
 
     if let Err(err) = tcp_client.send(packet) {
         if drop_if_disconnected && tcp_client.has_timed_out_for_flush() {
             re_log::warn_once!("Dropping messages because tcp client has timed out.");
+            in_flight.done(1);
             return None;
         }
         // If this is the first time we fail to send the message, produce a warning.
@@ -290,6 +708,7 @@ fn send_until_success(
             select! {
                 recv(quit_rx) -> _quit_msg => {
                     re_log::warn_once!("Dropping messages because tcp client has timed out or quitting.");
+                    in_flight.done(1);
                     return Some(_quit_msg.unwrap_or(InterruptMsg::Quit));
                 }
                 default(std::time::Duration::from_millis(sleep_ms)) => {
@@ -301,6 +720,7 @@ fn send_until_success(
 
                         if drop_if_disconnected && tcp_client.has_timed_out_for_flush() {
                             re_log::warn_once!("Dropping messages because tcp client has timed out.");
+                            in_flight.done(1);
                             return None;
                         }
 
@@ -1,9 +1,12 @@
-//! TCP communications between a Rerun logging SDK and server/viewer.
+//! TCP (or Unix domain socket) communications between a Rerun logging SDK and server/viewer.
 //!
 //! ## Feature flags
 #![doc = document_features::document_features!()]
 //!
 
+#[cfg(any(feature = "client", feature = "server"))]
+mod protocol;
+
 #[cfg(feature = "client")]
 pub(crate) mod tcp_client;
 
@@ -11,7 +14,20 @@ pub(crate) mod tcp_client;
 mod buffered_client;
 
 #[cfg(feature = "client")]
-pub use {buffered_client::Client, tcp_client::ClientError};
+mod spool;
+
+#[cfg(feature = "client")]
+pub use {
+    buffered_client::{BackpressurePolicy, Client, ClientOptions},
+    spool::SpoolOptions,
+    tcp_client::ClientError,
+};
+
+#[cfg(feature = "tls")]
+mod tls;
+
+#[cfg(feature = "tls")]
+pub use tls::{load_server_config, TlsClientConfig, TlsError};
 
 #[cfg(feature = "server")]
 mod server;
@@ -19,10 +35,78 @@ mod server;
 #[cfg(feature = "server")]
 pub use server::{serve, ServerError, ServerOptions};
 
-pub const PROTOCOL_VERSION: u16 = 0;
+#[cfg(all(feature = "server", unix))]
+pub use server::serve_unix;
+
+/// Version of the protocol spoken between the SDK and the server.
+///
+/// * 0: no handshake, packets are sent right away.
+/// * 1: the client and server exchange hellos first, negotiating the encoding of the packets.
+/// * 2: the server acknowledges the packets it has ingested.
+pub const PROTOCOL_VERSION: u16 = 2;
 
 pub const DEFAULT_SERVER_PORT: u16 = 9876;
 
+/// Prefix of the address of a server listening on a Unix domain socket, e.g. `unix:///tmp/rerun.sock`.
+pub const UNIX_SOCKET_SCHEME: &str = "unix://";
+
+/// Removes the socket file left at `path` by a server that is no longer running, so that a new
+/// server can bind there.
+///
+/// Anything else found at `path` is left alone and reported as an error: it's most likely a
+/// typo, not a socket.
+#[cfg(unix)]
+pub fn remove_stale_socket(path: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt as _;
+
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{path:?} already exists, and is not a socket"),
+        ));
+    }
+
+    // A server is still listening there: binding will fail and say so.
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Ok(());
+    }
+    std::fs::remove_file(path)
+}
+
+/// Where a Rerun server listens for SDKs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServerAddr {
+    Tcp(std::net::SocketAddr),
+
+    /// A Unix domain socket, for SDKs running on the same machine as the server.
+    ///
+    /// Cheaper than TCP over the loopback interface.
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl From<std::net::SocketAddr> for ServerAddr {
+    #[inline]
+    fn from(addr: std::net::SocketAddr) -> Self {
+        Self::Tcp(addr)
+    }
+}
+
+impl std::fmt::Display for ServerAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => addr.fmt(f),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "{UNIX_SOCKET_SCHEME}{}", path.display()),
+        }
+    }
+}
+
 /// The default address of a Rerun TCP server which an SDK connects to.
 pub fn default_server_addr() -> std::net::SocketAddr {
     std::net::SocketAddr::from(([127, 0, 0, 1], DEFAULT_SERVER_PORT))
@@ -0,0 +1,244 @@
+//! The control messages exchanged over an SDK connection, next to the log packets.
+//!
+//! Right after connecting, the client sends [`crate::PROTOCOL_VERSION`] as a little-endian `u16`,
+//! followed by a [`ClientHello`].
+//! The server answers with a [`ServerHello`], which either accepts the client
+//! along with the negotiated options, or rejects it with a human-readable reason.
+//! Only once accepted does the client start sending packets.
+//! From then on, the server sends [`ServerMsg`]s back, e.g. to acknowledge packets.
+//!
+//! Control messages are `MsgPack`, prefixed with their length as a little-endian `u32`.
+//!
+//! The leading version is all that protocol version 0 sent, so an older server
+//! rejects a newer client with a readable error instead of a decode error.
+//! For the same reason, [`ServerHello`] must stay readable across protocol versions:
+//! only ever add fields to it.
+
+use re_build_info::CrateVersion;
+use re_log_encoding::EncodingOptions;
+
+/// The encodings the client can produce, in order of preference.
+///
+/// We prefer sending uncompressed because we assume the SDK and server are on the same machine,
+/// and compression can be expensive, see <https://github.com/rerun-io/rerun/issues/2216>.
+#[cfg(feature = "client")]
+pub(crate) const SUPPORTED_ENCODINGS: [EncodingOptions; 3] = [
+    EncodingOptions::UNCOMPRESSED,
+    EncodingOptions::COMPRESSED,
+    EncodingOptions::COMPRESSED_ZSTD,
+];
+
+/// Larger control messages than this are refused, so a stray connection can't make us allocate gigabytes.
+pub(crate) const MAX_CONTROL_MSG_SIZE: u32 = 64 * 1024;
+
+/// Sent by the client right after [`crate::PROTOCOL_VERSION`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ClientHello {
+    /// The version of Rerun the client was built with, see [`CrateVersion::to_bytes`].
+    pub version: [u8; 4],
+
+    /// Human-readable build info, e.g. `re_sdk_comms 0.13.0 [rustc 1.74.0] x86_64-unknown-linux-gnu`.
+    pub build_info: String,
+
+    /// The encodings the client can send, most preferred first, see [`EncodingOptions::to_bytes`].
+    pub encodings: Vec<[u8; 4]>,
+
+    /// The shared secret of servers that only accept authenticated clients.
+    ///
+    /// Clients that predate it simply don't send it.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// The answer to a [`ClientHello`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ServerHello {
+    /// The protocol version spoken by the server.
+    pub protocol_version: u16,
+
+    /// The version of Rerun the server was built with, see [`CrateVersion::to_bytes`].
+    pub version: [u8; 4],
+
+    /// Human-readable build info of the server.
+    pub build_info: String,
+
+    /// The options the client must use, or the reason why it was rejected.
+    pub result: Result<Accepted, String>,
+}
+
+/// The options negotiated for an accepted client.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Accepted {
+    /// The encoding the client should use for its packets, see [`EncodingOptions::to_bytes`].
+    pub encoding: [u8; 4],
+}
+
+/// Sent by the server once it has accepted the client, for as long as the connection lasts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) enum ServerMsg {
+    /// The viewer has ingested this many of the packets sent on this connection so far.
+    ///
+    /// Packets are acknowledged in order. Packets dropped by the server to keep its latency down
+    /// count as ingested.
+    Ack { num_packets: u64 },
+}
+
+impl ClientHello {
+    #[cfg(feature = "client")]
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            version: local_version().to_bytes(),
+            build_info: local_build_info(),
+            encodings: SUPPORTED_ENCODINGS.iter().map(|e| e.to_bytes()).collect(),
+            token,
+        }
+    }
+
+    /// The version of Rerun the client was built with.
+    #[cfg(feature = "server")]
+    pub fn version(&self) -> CrateVersion {
+        CrateVersion::from_bytes(self.version)
+    }
+
+    /// Picks the encoding the client should use.
+    ///
+    /// That is `preferred` if the client offers it, otherwise the first of the client's
+    /// encodings that we understand.
+    #[cfg(feature = "server")]
+    pub fn negotiate_encoding(&self, preferred: EncodingOptions) -> Option<EncodingOptions> {
+        let offered = || {
+            self.encodings
+                .iter()
+                .filter_map(|bytes| EncodingOptions::from_bytes(*bytes).ok())
+        };
+        offered()
+            .find(|encoding| *encoding == preferred)
+            .or_else(|| offered().next())
+    }
+}
+
+#[cfg(feature = "server")]
+impl ServerHello {
+    pub fn accept(encoding: EncodingOptions) -> Self {
+        Self::new(Ok(Accepted {
+            encoding: encoding.to_bytes(),
+        }))
+    }
+
+    pub fn reject(reason: String) -> Self {
+        Self::new(Err(reason))
+    }
+
+    fn new(result: Result<Accepted, String>) -> Self {
+        Self {
+            protocol_version: crate::PROTOCOL_VERSION,
+            version: local_version().to_bytes(),
+            build_info: local_build_info(),
+            result,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl ServerHello {
+    /// The version of Rerun the server was built with.
+    pub fn version(&self) -> CrateVersion {
+        CrateVersion::from_bytes(self.version)
+    }
+}
+
+fn local_version() -> CrateVersion {
+    CrateVersion::parse(env!("CARGO_PKG_VERSION"))
+}
+
+fn local_build_info() -> String {
+    re_build_info::build_info!().to_string()
+}
+
+/// Serializes a control message, prefixed with its length as a little-endian `u32`.
+pub(crate) fn encode_control_msg(msg: &impl serde::Serialize) -> Vec<u8> {
+    let msg = rmp_serde::to_vec_named(msg).expect("control messages are always serializable");
+    let mut bytes = Vec::with_capacity(4 + msg.len());
+    bytes.extend_from_slice(&(msg.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&msg);
+    bytes
+}
+
+/// Reads the body of the next control message, i.e. without its length prefix.
+#[cfg(feature = "client")]
+pub(crate) fn read_control_msg(read: &mut impl std::io::Read) -> std::io::Result<Vec<u8>> {
+    let mut size = [0_u8; 4];
+    read.read_exact(&mut size)?;
+    let size = u32::from_le_bytes(size);
+    if MAX_CONTROL_MSG_SIZE < size {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("control message is too large ({size} B)"),
+        ));
+    }
+
+    let mut body = vec![0_u8; size as usize];
+    read.read_exact(&mut body)?;
+    Ok(body)
+}
+
+/// Deserializes a control message, without the length prefix written by [`encode_control_msg`].
+pub(crate) fn decode_control_msg<T: serde::de::DeserializeOwned>(
+    bytes: &[u8],
+) -> Result<T, rmp_serde::decode::Error> {
+    rmp_serde::from_slice(bytes)
+}
+
+#[cfg(all(test, feature = "client", feature = "server"))]
+mod tests {
+    use super::*;
+
+    fn roundtrip<T: serde::Serialize + serde::de::DeserializeOwned>(msg: &T) -> T {
+        let bytes = encode_control_msg(msg);
+        let body = read_control_msg(&mut bytes.as_slice()).unwrap();
+        decode_control_msg(&body).unwrap()
+    }
+
+    #[test]
+    fn control_msg_roundtrip() {
+        let client_hello = ClientHello::new(Some("secret".to_owned()));
+        assert_eq!(client_hello, roundtrip(&client_hello));
+        assert_eq!(local_version(), client_hello.version());
+
+        for server_hello in [
+            ServerHello::accept(EncodingOptions::COMPRESSED),
+            ServerHello::reject("go away".to_owned()),
+        ] {
+            assert_eq!(server_hello, roundtrip(&server_hello));
+        }
+
+        let ack = ServerMsg::Ack { num_packets: 42 };
+        assert_eq!(ack, roundtrip(&ack));
+    }
+
+    #[test]
+    fn negotiate_encoding() {
+        let mut hello = ClientHello::new(None);
+        assert_eq!(
+            Some(EncodingOptions::COMPRESSED),
+            hello.negotiate_encoding(EncodingOptions::COMPRESSED)
+        );
+
+        // The client's own preference is used when it doesn't offer ours.
+        hello.encodings = vec![
+            [42, 1, 0, 0], // unknown compression
+            EncodingOptions::COMPRESSED_ZSTD.to_bytes(),
+            EncodingOptions::UNCOMPRESSED.to_bytes(),
+        ];
+        assert_eq!(
+            Some(EncodingOptions::COMPRESSED_ZSTD),
+            hello.negotiate_encoding(EncodingOptions::COMPRESSED)
+        );
+
+        hello.encodings = vec![[42, 1, 0, 0]];
+        assert_eq!(
+            None,
+            hello.negotiate_encoding(EncodingOptions::UNCOMPRESSED)
+        );
+    }
+}
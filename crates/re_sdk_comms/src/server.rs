@@ -1,10 +1,28 @@
-use std::{io::ErrorKind, time::Instant};
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use parking_lot::Mutex;
 use rand::{Rng as _, SeedableRng};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf},
+    net::{TcpListener, TcpStream},
+};
 
+use re_log_encoding::{Compression, EncodingOptions, Serializer};
 use re_log_types::{LogMsg, TimePoint, TimeType, TimelineName};
-use re_smart_channel::{Receiver, Sender};
+use re_smart_channel::{ClientStatsHandle, ConnectedClients, Receiver, Sender, SmartMessageSource};
+
+use crate::protocol::{
+    decode_control_msg, encode_control_msg, ClientHello, ServerHello, ServerMsg,
+    MAX_CONTROL_MSG_SIZE,
+};
+
+/// How often we tell clients how far the viewer has come.
+const ACK_INTERVAL: Duration = Duration::from_millis(10);
 
 #[derive(thiserror::Error, Debug)]
 pub enum ServerError {
@@ -13,17 +31,23 @@ pub enum ServerError {
         bind_addr: String,
         err: std::io::Error,
     },
+
+    #[error("Failed to bind Unix domain socket {path:?}. Another Rerun instance is probably running. {err}")]
+    UnixBindError {
+        path: std::path::PathBuf,
+        err: std::io::Error,
+    },
 }
 
 #[derive(thiserror::Error, Debug)]
 enum VersionError {
-    #[error("SDK client is using an older protocol version ({client_version}) than the SDK server ({server_version})")]
+    #[error("SDK client is using an older protocol version ({client_version}) than the SDK server ({server_version}). Update the Rerun SDK to match the Rerun Viewer.")]
     ClientIsOlder {
         client_version: u16,
         server_version: u16,
     },
 
-    #[error("SDK client is using a newer protocol version ({client_version}) than the SDK server ({server_version})")]
+    #[error("SDK client is using a newer protocol version ({client_version}) than the SDK server ({server_version}). Update the Rerun Viewer to match the Rerun SDK.")]
     ClientIsNewer {
         client_version: u16,
         server_version: u16,
@@ -35,6 +59,19 @@ enum ConnectionError {
     #[error(transparent)]
     VersionError(#[from] VersionError),
 
+    #[error("Invalid hello from SDK client: {0}")]
+    InvalidHello(String),
+
+    #[error("None of the encodings offered by the SDK client are supported")]
+    NoCommonEncoding,
+
+    #[error("The SDK client did not present the right token")]
+    Unauthorized,
+
+    #[cfg(feature = "tls")]
+    #[error("TLS handshake failed: {0}")]
+    TlsError(std::io::Error),
+
     #[error(transparent)]
     SendError(#[from] std::io::Error),
 
@@ -45,7 +82,7 @@ enum ConnectionError {
     ChannelDisconnected(#[from] re_smart_channel::SendError<LogMsg>),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct ServerOptions {
     /// If the latency in the [`LogMsg`] channel is greater than this,
     /// then start dropping messages in order to keep up.
@@ -53,6 +90,22 @@ pub struct ServerOptions {
 
     /// Turns `info`-level logs into `debug`-level logs.
     pub quiet: bool,
+
+    /// The compression we ask SDK clients to use, if they support it.
+    pub compression: Compression,
+
+    /// Only accept SDK clients presenting this shared secret.
+    pub token: Option<String>,
+
+    /// Stop reading from any single client sending more than this many bytes per second,
+    /// so that one runaway client can't starve the others.
+    ///
+    /// Short bursts of up to a second's worth of data are let through.
+    pub max_bytes_per_sec: Option<u64>,
+
+    /// Only accept TLS connections, see [`crate::load_server_config`].
+    #[cfg(feature = "tls")]
+    pub tls: Option<Arc<rustls::ServerConfig>>,
 }
 
 impl Default for ServerOptions {
@@ -60,6 +113,11 @@ impl Default for ServerOptions {
         Self {
             max_latency_sec: f32::INFINITY,
             quiet: false,
+            compression: Compression::Off,
+            token: None,
+            max_bytes_per_sec: None,
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
 }
@@ -78,10 +136,14 @@ pub async fn serve(
     port: u16,
     options: ServerOptions,
 ) -> Result<Receiver<LogMsg>, ServerError> {
+    let clients = ConnectedClients::default();
     let (tx, rx) = re_smart_channel::smart_channel(
         // NOTE: We don't know until we start actually accepting clients!
-        re_smart_channel::SmartMessageSource::Unknown,
-        re_smart_channel::SmartChannelSource::TcpServer { port },
+        SmartMessageSource::Unknown,
+        re_smart_channel::SmartChannelSource::TcpServer {
+            port,
+            clients: clients.clone(),
+        },
     );
 
     let bind_addr = format!("{bind_ip}:{port}");
@@ -103,18 +165,71 @@ pub async fn serve(
         );
     }
 
-    tokio::spawn(listen_for_new_clients(listener, options, tx));
+    tokio::spawn(listen_for_new_clients(listener, options, tx, clients));
+
+    Ok(rx)
+}
+
+/// Listen to multiple SDK:s on the same machine connecting to us over a Unix domain socket.
+///
+/// A socket file left over at `path` by a server that is no longer running is replaced, but
+/// nothing else: see [`crate::remove_stale_socket`].
+/// TLS is not used, as the data never leaves the machine.
+#[cfg(unix)]
+pub async fn serve_unix(
+    path: &std::path::Path,
+    options: ServerOptions,
+) -> Result<Receiver<LogMsg>, ServerError> {
+    let clients = ConnectedClients::default();
+    let (tx, rx) = re_smart_channel::smart_channel(
+        SmartMessageSource::Unknown,
+        re_smart_channel::SmartChannelSource::UnixSocketServer {
+            path: path.to_owned(),
+            clients: clients.clone(),
+        },
+    );
+
+    let bind_err = |err| ServerError::UnixBindError {
+        path: path.to_owned(),
+        err,
+    };
+    crate::remove_stale_socket(path).map_err(bind_err)?;
+    let listener = tokio::net::UnixListener::bind(path).map_err(bind_err)?;
+
+    let bind_addr = crate::ServerAddr::Unix(path.to_owned());
+    if options.quiet {
+        re_log::debug!("Hosting a SDK server at {bind_addr}. Connect with the Rerun logging SDK.");
+    } else {
+        re_log::info!("Hosting a SDK server at {bind_addr}. Connect with the Rerun logging SDK.");
+    }
+
+    tokio::spawn(listen_for_new_unix_clients(listener, options, tx, clients));
 
     Ok(rx)
 }
 
-async fn listen_for_new_clients(listener: TcpListener, options: ServerOptions, tx: Sender<LogMsg>) {
+async fn listen_for_new_clients(
+    listener: TcpListener,
+    options: ServerOptions,
+    tx: Sender<LogMsg>,
+    clients: ConnectedClients,
+) {
     loop {
         match listener.accept().await {
             Ok((stream, _)) => {
                 let addr = stream.peer_addr().ok();
-                let tx = tx.clone_as(re_smart_channel::SmartMessageSource::TcpClient { addr });
-                spawn_client(stream, tx, options, addr);
+                let source = SmartMessageSource::TcpClient { addr };
+                let stats = clients.add(source.clone());
+                let tx = tx.clone_as(source);
+                let addr_string =
+                    addr.map_or_else(|| "(unknown ip)".to_owned(), |addr| addr.to_string());
+                spawn_client(
+                    IncomingStream::Tcp(stream),
+                    tx,
+                    options.clone(),
+                    addr_string,
+                    stats,
+                );
             }
             Err(err) => {
                 re_log::warn!("Failed to accept incoming SDK client: {err}");
@@ -123,23 +238,67 @@ async fn listen_for_new_clients(listener: TcpListener, options: ServerOptions, t
     }
 }
 
+#[cfg(unix)]
+async fn listen_for_new_unix_clients(
+    listener: tokio::net::UnixListener,
+    options: ServerOptions,
+    tx: Sender<LogMsg>,
+    clients: ConnectedClients,
+) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                // The peers of a Unix domain socket are usually unnamed, so we go by process id.
+                let pid = stream
+                    .peer_cred()
+                    .ok()
+                    .and_then(|cred| cred.pid())
+                    .and_then(|pid| u32::try_from(pid).ok());
+                let source = SmartMessageSource::UnixSocketClient { pid };
+                let stats = clients.add(source.clone());
+                let tx = tx.clone_as(source);
+                let addr_string = pid.map_or_else(
+                    || "(unknown process)".to_owned(),
+                    |pid| format!("process {pid}"),
+                );
+                spawn_client(
+                    IncomingStream::Unix(stream),
+                    tx,
+                    options.clone(),
+                    addr_string,
+                    stats,
+                );
+            }
+            Err(err) => {
+                re_log::warn!("Failed to accept incoming SDK client: {err}");
+            }
+        }
+    }
+}
+
+/// A freshly accepted connection from an SDK.
+enum IncomingStream {
+    Tcp(TcpStream),
+
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
 fn spawn_client(
-    stream: TcpStream,
+    stream: IncomingStream,
     tx: Sender<LogMsg>,
     options: ServerOptions,
-    peer_addr: Option<std::net::SocketAddr>,
+    addr_string: String,
+    stats: ClientStatsHandle,
 ) {
     tokio::spawn(async move {
-        let addr_string =
-            peer_addr.map_or_else(|| "(unknown ip)".to_owned(), |addr| addr.to_string());
-
         if options.quiet {
             re_log::debug!("New SDK client connected: {addr_string}");
         } else {
             re_log::info!("New SDK client connected: {addr_string}");
         }
 
-        if let Err(err) = run_client(stream, &tx, options).await {
+        if let Err(err) = run_client(stream, &tx, &options, &stats).await {
             if let ConnectionError::SendError(err) = &err {
                 if err.kind() == ErrorKind::UnexpectedEof {
                     // Client gracefully severed the connection.
@@ -155,35 +314,115 @@ fn spawn_client(
 }
 
 async fn run_client(
-    mut stream: TcpStream,
+    stream: IncomingStream,
     tx: &Sender<LogMsg>,
-    options: ServerOptions,
+    options: &ServerOptions,
+    stats: &ClientStatsHandle,
 ) -> Result<(), ConnectionError> {
-    #![allow(clippy::read_zero_byte_vec)] // false positive: https://github.com/rust-lang/rust-clippy/issues/9274
+    let stream = match stream {
+        IncomingStream::Tcp(stream) => stream,
+
+        #[cfg(unix)]
+        IncomingStream::Unix(stream) => return run_protocol(stream, tx, options, stats).await,
+    };
 
+    #[cfg(feature = "tls")]
+    if let Some(tls) = &options.tls {
+        let stream = tokio_rustls::TlsAcceptor::from(tls.clone())
+            .accept(stream)
+            .await
+            .map_err(ConnectionError::TlsError)?;
+        return run_protocol(stream, tx, options, stats).await;
+    }
+
+    run_protocol(stream, tx, options, stats).await
+}
+
+async fn run_protocol(
+    mut stream: impl AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    tx: &Sender<LogMsg>,
+    options: &ServerOptions,
+    stats: &ClientStatsHandle,
+) -> Result<(), ConnectionError> {
     use tokio::io::AsyncReadExt as _;
 
     let mut client_version = [0_u8; 2];
     stream.read_exact(&mut client_version).await?;
     let client_version = u16::from_le_bytes(client_version);
 
-    match client_version.cmp(&crate::PROTOCOL_VERSION) {
-        std::cmp::Ordering::Less => {
-            return Err(ConnectionError::VersionError(VersionError::ClientIsOlder {
-                client_version,
-                server_version: crate::PROTOCOL_VERSION,
-            }));
+    let version_error = match client_version.cmp(&crate::PROTOCOL_VERSION) {
+        std::cmp::Ordering::Less => Some(VersionError::ClientIsOlder {
+            client_version,
+            server_version: crate::PROTOCOL_VERSION,
+        }),
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Greater => Some(VersionError::ClientIsNewer {
+            client_version,
+            server_version: crate::PROTOCOL_VERSION,
+        }),
+    };
+    if let Some(err) = version_error {
+        // Since protocol version 1, clients wait for our hello, so we can tell them why.
+        if 1 <= client_version {
+            write_control_msg(&mut stream, &ServerHello::reject(err.to_string()))
+                .await
+                .ok(); // best-effort: we are closing the connection anyway
         }
-        std::cmp::Ordering::Equal => {}
-        std::cmp::Ordering::Greater => {
-            return Err(ConnectionError::VersionError(VersionError::ClientIsNewer {
-                client_version,
-                server_version: crate::PROTOCOL_VERSION,
-            }));
+        return Err(err.into());
+    }
+
+    let client_hello = read_hello(&mut stream).await?;
+    if let Some(token) = &options.token {
+        if !client_hello
+            .token
+            .as_deref()
+            .map_or(false, |client_token| tokens_match(client_token, token))
+        {
+            let err = ConnectionError::Unauthorized;
+            write_control_msg(&mut stream, &ServerHello::reject(err.to_string())).await?;
+            return Err(err);
         }
     }
 
+    let preferred = EncodingOptions {
+        compression: options.compression,
+        serializer: Serializer::MsgPack,
+    };
+    let Some(encoding) = client_hello.negotiate_encoding(preferred) else {
+        let err = ConnectionError::NoCommonEncoding;
+        write_control_msg(&mut stream, &ServerHello::reject(err.to_string())).await?;
+        return Err(err);
+    };
+    write_control_msg(&mut stream, &ServerHello::accept(encoding)).await?;
+
+    re_log::debug!(
+        "SDK client is {} (v{}), using {encoding:?}",
+        client_hello.build_info,
+        client_hello.version()
+    );
+
+    let (stream, ack_stream) = tokio::io::split(stream);
+    let acks = Arc::new(Mutex::new(PendingAcks::default()));
+    tokio::spawn(send_acks(ack_stream, tx.clone(), acks.clone()));
+
+    let result = receive_packets(stream, tx, options, &acks, stats).await;
+    acks.lock().closed = true;
+    result
+}
+
+async fn receive_packets(
+    mut stream: ReadHalf<impl AsyncRead>,
+    tx: &Sender<LogMsg>,
+    options: &ServerOptions,
+    acks: &Mutex<PendingAcks>,
+    stats: &ClientStatsHandle,
+) -> Result<(), ConnectionError> {
+    #![allow(clippy::read_zero_byte_vec)] // false positive: https://github.com/rust-lang/rust-clippy/issues/9274
+
+    use tokio::io::AsyncReadExt as _;
+
     let mut congestion_manager = CongestionManager::new(options.max_latency_sec);
+    let mut rate_limiter = options.max_bytes_per_sec.map(RateLimiter::new);
 
     let mut packet = Vec::new();
 
@@ -197,22 +436,179 @@ async fn run_client(
 
         re_log::trace!("Received packet of size {packet_size}.");
 
-        congestion_manager.register_latency(tx.latency_sec());
+        let latency_sec = tx.latency_sec();
+        congestion_manager.register_latency(latency_sec);
 
+        let mut num_sent = 0;
+        let mut num_dropped = 0;
         let version_policy = re_log_encoding::decoder::VersionPolicy::Warn;
         for msg in re_log_encoding::decoder::decode_bytes(version_policy, &packet)? {
             if congestion_manager.should_send(&msg) {
                 tx.send(msg)?;
+                num_sent += 1;
             } else {
                 re_log::warn_once!(
                     "Input latency is over the max ({} s) - dropping packets.",
                     options.max_latency_sec
                 );
+                num_dropped += 1;
             }
         }
+
+        acks.lock().pending.push_back(tx.num_sent());
+
+        let delay = rate_limiter
+            .as_mut()
+            .map_or(Duration::ZERO, |rate_limiter| {
+                rate_limiter.delay(4 + packet_size as u64)
+            });
+
+        stats.update(|stats| {
+            stats.bytes_received += 4 + packet_size as u64;
+            stats.messages_received += num_sent + num_dropped;
+            stats.messages_dropped += num_dropped;
+            stats.latency_sec = latency_sec;
+            stats.throttled_sec += delay.as_secs_f32();
+        });
+
+        if !delay.is_zero() {
+            re_log::debug_once!("Throttling SDK clients sending more than the rate limit");
+            tokio::time::sleep(delay).await;
+        }
     }
 }
 
+/// Keeps a client under a number of bytes per second, by telling how long to wait before reading on.
+struct RateLimiter {
+    max_bytes_per_sec: f64,
+
+    /// How many bytes may be read right now. Negative when over the limit.
+    allowance: f64,
+
+    last_update: Instant,
+}
+
+impl RateLimiter {
+    fn new(max_bytes_per_sec: u64) -> Self {
+        Self {
+            max_bytes_per_sec: max_bytes_per_sec as f64,
+            allowance: max_bytes_per_sec as f64,
+            last_update: Instant::now(),
+        }
+    }
+
+    /// Registers that many bytes as read, and returns how long to wait before reading more.
+    fn delay(&mut self, num_bytes: u64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_update).as_secs_f64();
+        self.last_update = now;
+
+        // At most a second's worth of bursting.
+        self.allowance = (self.allowance + elapsed * self.max_bytes_per_sec)
+            .min(self.max_bytes_per_sec)
+            - num_bytes as f64;
+
+        if self.allowance < 0.0 {
+            Duration::from_secs_f64(-self.allowance / self.max_bytes_per_sec)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// The packets of a client that the viewer has yet to ingest.
+#[derive(Default)]
+struct PendingAcks {
+    /// For each packet: [`Sender::num_sent`] right after sending its messages.
+    ///
+    /// Once [`Sender::num_received`] has caught up with that, the packet has been ingested.
+    pending: VecDeque<u64>,
+
+    /// Number of packets ingested so far.
+    num_ingested: u64,
+
+    /// The client has disconnected.
+    closed: bool,
+}
+
+/// Tells the client how many of its packets the viewer has ingested, until it disconnects.
+async fn send_acks(
+    mut stream: WriteHalf<impl AsyncWrite>,
+    tx: Sender<LogMsg>,
+    acks: Arc<Mutex<PendingAcks>>,
+) {
+    let mut interval = tokio::time::interval(ACK_INTERVAL);
+    let mut num_acked = 0;
+
+    loop {
+        interval.tick().await;
+
+        let (num_ingested, closed) = {
+            let mut acks = acks.lock();
+            let num_received = tx.num_received();
+            while acks
+                .pending
+                .front()
+                .map_or(false, |num_sent| *num_sent <= num_received)
+            {
+                acks.pending.pop_front();
+                acks.num_ingested += 1;
+            }
+            (acks.num_ingested, acks.closed)
+        };
+
+        if closed {
+            return;
+        }
+
+        if num_acked < num_ingested {
+            let ack = ServerMsg::Ack {
+                num_packets: num_ingested,
+            };
+            if let Err(err) = write_control_msg(&mut stream, &ack).await {
+                re_log::debug!("Failed to acknowledge packets: {err}");
+                return;
+            }
+            num_acked = num_ingested;
+        }
+    }
+}
+
+/// Compares in constant time, so that the time it takes doesn't tell how much of a guess was right.
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn read_hello(stream: &mut (impl AsyncRead + Unpin)) -> Result<ClientHello, ConnectionError> {
+    use tokio::io::AsyncReadExt as _;
+
+    let mut hello_size = [0_u8; 4];
+    stream.read_exact(&mut hello_size).await?;
+    let hello_size = u32::from_le_bytes(hello_size);
+    if MAX_CONTROL_MSG_SIZE < hello_size {
+        return Err(ConnectionError::InvalidHello(format!(
+            "too large ({hello_size} B)"
+        )));
+    }
+
+    let mut hello = vec![0_u8; hello_size as usize];
+    stream.read_exact(&mut hello).await?;
+    decode_control_msg(&hello).map_err(|err| ConnectionError::InvalidHello(err.to_string()))
+}
+
+async fn write_control_msg(
+    stream: &mut (impl tokio::io::AsyncWrite + Unpin),
+    msg: &impl serde::Serialize,
+) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt as _;
+
+    stream.write_all(&encode_control_msg(msg)).await
+}
+
 // ----------------------------------------------------------------------------
 
 /// Decides how many messages to drop so that we achieve a desired maximum latency.
@@ -367,3 +763,446 @@ impl Throttling {
         }
     }
 }
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use re_log_types::{
+        ApplicationId, RowId, SetStoreInfo, StoreId, StoreInfo, StoreKind, StoreSource, Time,
+    };
+
+    use super::*;
+    use crate::tcp_client::{InFlight, TcpClient};
+
+    fn store_info_msg() -> LogMsg {
+        LogMsg::SetStoreInfo(SetStoreInfo {
+            row_id: RowId::new(),
+            info: StoreInfo {
+                application_id: ApplicationId::unknown(),
+                store_id: StoreId::random(StoreKind::Recording),
+                is_official_example: false,
+                started: Time::from_ns_since_epoch(0),
+                store_source: StoreSource::Unknown,
+                store_kind: StoreKind::Recording,
+            },
+        })
+    }
+
+    fn store_info_packet() -> Vec<u8> {
+        re_log_encoding::encoder::encode_to_bytes(
+            EncodingOptions::UNCOMPRESSED,
+            std::iter::once(&store_info_msg()),
+        )
+        .unwrap()
+    }
+
+    /// Runs the server side of a single connection, while `client` runs on its own thread.
+    ///
+    /// The client is also handed what the viewer would receive.
+    fn serve_one<R: Send + 'static>(
+        options: ServerOptions,
+        client: impl FnOnce(std::net::SocketAddr, Receiver<LogMsg>) -> R + Send + 'static,
+    ) -> (Result<(), ConnectionError>, R) {
+        serve_one_with_clients(options, &ConnectedClients::default(), client)
+    }
+
+    /// Like [`serve_one`], registering the client's statistics in `clients`.
+    fn serve_one_with_clients<R: Send + 'static>(
+        options: ServerOptions,
+        clients: &ConnectedClients,
+        client: impl FnOnce(std::net::SocketAddr, Receiver<LogMsg>) -> R + Send + 'static,
+    ) -> (Result<(), ConnectionError>, R) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async move {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (tx, rx) = re_smart_channel::smart_channel(
+                SmartMessageSource::TcpClient { addr: None },
+                re_smart_channel::SmartChannelSource::TcpServer {
+                    port: addr.port(),
+                    clients: clients.clone(),
+                },
+            );
+            let client = std::thread::Builder::new()
+                .name("test_client".into())
+                .spawn(move || client(addr, rx))
+                .unwrap();
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let stats = clients.add(SmartMessageSource::TcpClient { addr: None });
+            let result = run_client(IncomingStream::Tcp(stream), &tx, &options, &stats).await;
+
+            (result, client.join().unwrap())
+        })
+    }
+
+    #[test]
+    fn handshake_negotiates_compression() {
+        let options = ServerOptions {
+            compression: Compression::LZ4,
+            ..Default::default()
+        };
+        let (result, encoding) = serve_one(options, |addr, _rx| {
+            let mut client =
+                TcpClient::new(addr.into(), None, Default::default(), Default::default());
+            client.connect().unwrap();
+            client.encoding()
+        });
+
+        // The client hangs up right after the handshake.
+        assert!(
+            matches!(&result, Err(ConnectionError::SendError(err)) if err.kind() == ErrorKind::UnexpectedEof),
+            "{result:?}"
+        );
+        assert_eq!(Some(EncodingOptions::COMPRESSED), encoding);
+    }
+
+    #[test]
+    fn handshake_rejects_newer_client() {
+        let (result, server_hello) = serve_one(ServerOptions::default(), |addr, _rx| {
+            use std::io::{Read as _, Write as _};
+
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(&(crate::PROTOCOL_VERSION + 1).to_le_bytes())
+                .unwrap();
+
+            let mut hello_size = [0_u8; 4];
+            stream.read_exact(&mut hello_size).unwrap();
+            let mut hello = vec![0_u8; u32::from_le_bytes(hello_size) as usize];
+            stream.read_exact(&mut hello).unwrap();
+            decode_control_msg::<ServerHello>(&hello).unwrap()
+        });
+
+        assert!(
+            matches!(
+                result,
+                Err(ConnectionError::VersionError(
+                    VersionError::ClientIsNewer { .. }
+                ))
+            ),
+            "{result:?}"
+        );
+        let reason = server_hello.result.unwrap_err();
+        assert!(reason.contains("Update the Rerun Viewer"), "{reason}");
+    }
+
+    #[test]
+    fn acknowledge_ingested_packets() {
+        let (result, ()) = serve_one(ServerOptions::default(), |addr, rx| {
+            let in_flight = Arc::new(InFlight::default());
+            let mut client =
+                TcpClient::new(addr.into(), None, in_flight.clone(), Default::default());
+
+            let packet = store_info_packet();
+            for _ in 0..3 {
+                assert!(in_flight.start(u64::MAX, None));
+                client.send(&packet).unwrap();
+            }
+
+            // Nothing is acknowledged before the viewer has received the messages.
+            std::thread::sleep(10 * ACK_INTERVAL);
+            assert_eq!(3, in_flight.len());
+
+            rx.recv().unwrap();
+            rx.recv().unwrap();
+            while in_flight.len() != 1 {
+                std::thread::sleep(ACK_INTERVAL);
+            }
+
+            rx.recv().unwrap();
+            in_flight.wait_until_done(3);
+        });
+
+        // The client hangs up once everything was acknowledged.
+        assert!(
+            matches!(&result, Err(ConnectionError::SendError(err)) if err.kind() == ErrorKind::UnexpectedEof),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn client_stats_and_rate_limit() {
+        let packet = store_info_packet();
+        let packet_bytes = 4 + packet.len() as u64;
+
+        // Ten packets go through right away, the next five take half a second.
+        let options = ServerOptions {
+            max_bytes_per_sec: Some(10 * packet_bytes),
+            ..Default::default()
+        };
+        let clients = ConnectedClients::default();
+
+        let (_result, ()) = serve_one_with_clients(options, &clients, move |addr, rx| {
+            let in_flight = Arc::new(InFlight::default());
+            let mut client =
+                TcpClient::new(addr.into(), None, in_flight.clone(), Default::default());
+            for _ in 0..15 {
+                assert!(in_flight.start(u64::MAX, None));
+                client.send(&packet).unwrap();
+            }
+            for _ in 0..15 {
+                rx.recv().unwrap();
+            }
+            in_flight.wait_until_done(15);
+        });
+
+        let stats = clients.snapshot();
+        assert_eq!(stats.len(), 1);
+        assert!(!stats[0].connected);
+        assert_eq!(stats[0].bytes_received, 15 * packet_bytes);
+        assert_eq!(stats[0].messages_received, 15);
+        assert_eq!(stats[0].messages_dropped, 0);
+        assert!(0.4 < stats[0].throttled_sec, "{:?}", stats[0]);
+    }
+
+    #[test]
+    fn handshake_checks_token() {
+        let options = ServerOptions {
+            token: Some("secret".to_owned()),
+            ..Default::default()
+        };
+
+        let (result, client_result) = serve_one(options.clone(), |addr, _rx| {
+            let client_options = crate::ClientOptions {
+                token: Some("guess".to_owned()),
+                ..Default::default()
+            };
+            TcpClient::new(addr.into(), None, Default::default(), client_options).connect()
+        });
+        assert!(
+            matches!(result, Err(ConnectionError::Unauthorized)),
+            "{result:?}"
+        );
+        assert!(
+            matches!(&client_result, Err(crate::ClientError::Rejected { reason, .. }) if reason.contains("token")),
+            "{client_result:?}"
+        );
+
+        let (_result, client_result) = serve_one(options, |addr, _rx| {
+            let client_options = crate::ClientOptions {
+                token: Some("secret".to_owned()),
+                ..Default::default()
+            };
+            TcpClient::new(addr.into(), None, Default::default(), client_options).connect()
+        });
+        assert!(client_result.is_ok(), "{client_result:?}");
+    }
+
+    fn spool_options(dir: &std::path::Path) -> crate::ClientOptions {
+        crate::ClientOptions {
+            spool: Some(crate::SpoolOptions {
+                dir: dir.to_owned(),
+                max_bytes: 1024 * 1024,
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Spools the messages while the server can't be reached.
+    fn spool_messages(client_options: &crate::ClientOptions, messages: &[LogMsg]) {
+        // Nothing listens there anymore.
+        let closed_addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let client = crate::Client::with_options(closed_addr, None, client_options.clone());
+        for msg in messages {
+            client.send(msg.clone());
+        }
+        // Dropping the client flushes it, which can only leave the messages in the spool.
+    }
+
+    #[test]
+    fn spooled_messages_are_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let client_options = spool_options(dir.path());
+
+        let messages = (0..3).map(|_| store_info_msg()).collect::<Vec<_>>();
+        spool_messages(&client_options, &messages);
+
+        let (_result, received) = serve_one(ServerOptions::default(), move |addr, rx| {
+            let _client = crate::Client::with_options(addr, None, client_options);
+            (0..3)
+                .map(|_| rx.recv().unwrap().into_data().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(received, messages);
+    }
+
+    /// Serves the next client on `listener` until `is_done` with what the viewer received, then
+    /// kills the server.
+    fn serve_until(
+        listener: &std::net::TcpListener,
+        is_done: impl Fn(&[LogMsg]) -> bool + Send + 'static,
+    ) -> Vec<LogMsg> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async move {
+            let listener = TcpListener::from_std(listener.try_clone().unwrap()).unwrap();
+            let (tx, rx) = re_smart_channel::smart_channel(
+                SmartMessageSource::TcpClient { addr: None },
+                re_smart_channel::SmartChannelSource::TcpServer {
+                    port: listener.local_addr().unwrap().port(),
+                    clients: Default::default(),
+                },
+            );
+
+            let viewer = tokio::task::spawn_blocking(move || {
+                let mut received = vec![];
+                while !is_done(&received) {
+                    let msg = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+                    received.push(msg.into_data().unwrap());
+                }
+                received
+            });
+
+            tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let stats =
+                    ConnectedClients::default().add(SmartMessageSource::TcpClient { addr: None });
+                let options = ServerOptions::default();
+                run_client(IncomingStream::Tcp(stream), &tx, &options, &stats).await
+            });
+
+            viewer.await.unwrap()
+        })
+        // Dropping the runtime drops the tasks serving the client, connection included.
+    }
+
+    #[test]
+    fn spool_replay_survives_server_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let client_options = spool_options(dir.path());
+
+        let messages = (0..3).map(|_| store_info_msg()).collect::<Vec<_>>();
+        spool_messages(&client_options, &messages);
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let _client =
+            crate::Client::with_options(listener.local_addr().unwrap(), None, client_options);
+
+        // The server dies mid-replay, after acknowledging the first message at best…
+        let received = serve_until(&listener, |received| {
+            let is_done = !received.is_empty();
+            if is_done {
+                // Leave time for the acknowledgement.
+                std::thread::sleep(10 * ACK_INTERVAL);
+            }
+            is_done
+        });
+        assert_eq!(received, messages[..1]);
+
+        // …so the others are sent again once it's back, maybe along with the first one.
+        let last = messages.last().cloned();
+        let received = serve_until(&listener, move |received| received.last() == last.as_ref());
+        assert!(messages[..].ends_with(&received), "{received:?}");
+        assert!(2 <= received.len(), "{received:?}");
+
+        // Rather than having the client wait for the handshake of a server that's gone when
+        // it flushes on drop.
+        drop(listener);
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn tls_connection() {
+        let data_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data");
+        let cert_path = data_dir.join("test_cert.pem");
+        let options = ServerOptions {
+            tls: Some(
+                crate::load_server_config(&cert_path, &data_dir.join("test_key.pem")).unwrap(),
+            ),
+            ..Default::default()
+        };
+
+        let (result, ()) = serve_one(options, move |addr, rx| {
+            let in_flight = Arc::new(InFlight::default());
+            let client_options = crate::ClientOptions {
+                tls: Some(crate::TlsClientConfig::new("localhost", Some(&cert_path)).unwrap()),
+                ..Default::default()
+            };
+            let mut client = TcpClient::new(addr.into(), None, in_flight.clone(), client_options);
+
+            assert!(in_flight.start(u64::MAX, None));
+            client.send(&store_info_packet()).unwrap();
+            rx.recv().unwrap();
+            in_flight.wait_until_done(1);
+        });
+
+        assert!(
+            matches!(&result, Err(ConnectionError::SendError(err)) if err.kind() == ErrorKind::UnexpectedEof),
+            "{result:?}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rerun.sock");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let rx = serve_unix(&path, ServerOptions::default()).await.unwrap();
+            let addr = crate::ServerAddr::Unix(path.clone());
+            tokio::task::spawn_blocking(move || {
+                let in_flight = Arc::new(InFlight::default());
+                let mut client = TcpClient::new(addr, None, in_flight.clone(), Default::default());
+
+                assert!(in_flight.start(u64::MAX, None));
+                client.send(&store_info_packet()).unwrap();
+                let msg = rx.recv().unwrap();
+                assert_eq!(
+                    *msg.source,
+                    SmartMessageSource::UnixSocketClient {
+                        pid: Some(std::process::id())
+                    }
+                );
+                in_flight.wait_until_done(1);
+            })
+            .await
+            .unwrap();
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_only_replaces_stale_sockets() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+
+        // A typo must never cost the user their data.
+        let file = dir.path().join("data.rrd");
+        std::fs::write(&file, b"precious").unwrap();
+        runtime.block_on(async {
+            assert!(serve_unix(&file, ServerOptions::default()).await.is_err());
+        });
+        assert_eq!(std::fs::read(&file).unwrap(), b"precious");
+
+        // The socket of a server that is gone is replaced.
+        let path = dir.path().join("rerun.sock");
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        runtime.block_on(async {
+            serve_unix(&path, ServerOptions::default()).await.unwrap();
+        });
+    }
+}
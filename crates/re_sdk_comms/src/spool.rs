@@ -0,0 +1,353 @@
+//! A file on disk holding the packets a client couldn't send, oldest first.
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read as _, Seek as _, SeekFrom, Write as _},
+    path::PathBuf,
+};
+
+const SPOOL_FILE_NAME: &str = "packets.spool";
+
+/// Where compaction writes the new spool before it replaces the old one.
+const SPOOL_TMP_FILE_NAME: &str = "packets.spool.tmp";
+
+/// Where a [`crate::Client`] keeps the messages it can't send, until it can.
+///
+/// While the server is unreachable, messages are written to disk instead of piling up in RAM.
+/// They are sent in order once the connection is back, even if that is only
+/// the next time a client is created with the same directory.
+///
+/// Each message stays on disk until the server has acknowledged it, and is sent again
+/// otherwise: a message may be received twice, but none is lost.
+#[derive(Clone, Debug)]
+pub struct SpoolOptions {
+    /// The directory holding the spool. Created if missing.
+    ///
+    /// Must not be shared by several clients at once.
+    pub dir: PathBuf,
+
+    /// Once the spool takes this much space on disk, new messages are dropped.
+    pub max_bytes: u64,
+}
+
+/// Each packet is stored as its size (`u32`, little endian) followed by its bytes.
+pub(crate) struct Spool {
+    path: PathBuf,
+    file: File,
+
+    /// Where the oldest packet not yet acknowledged starts.
+    read_pos: u64,
+
+    /// Where the oldest packet not yet sent starts.
+    send_pos: u64,
+
+    /// The sizes of the packets sent but not yet acknowledged, oldest first.
+    unacked: VecDeque<u64>,
+
+    /// The size of the file.
+    end_pos: u64,
+
+    max_bytes: u64,
+}
+
+impl Spool {
+    pub fn open(options: &SpoolOptions) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&options.dir)?;
+        let path = options.dir.join(SPOOL_FILE_NAME);
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let end_pos = file.metadata()?.len();
+        if 0 < end_pos {
+            re_log::info!(
+                "Found {end_pos} bytes of unsent messages in {path:?}, sending them first"
+            );
+        }
+
+        Ok(Self {
+            path,
+            file,
+            read_pos: 0,
+            send_pos: 0,
+            unacked: VecDeque::new(),
+            end_pos,
+            max_bytes: options.max_bytes,
+        })
+    }
+
+    /// Nothing is waiting to be sent or acknowledged.
+    pub fn is_empty(&self) -> bool {
+        self.read_pos == self.end_pos
+    }
+
+    /// Some packets are waiting to be sent.
+    pub fn has_unsent(&self) -> bool {
+        self.send_pos < self.end_pos
+    }
+
+    /// Appends a packet. Returns `false`, without writing it, if the spool is full.
+    pub fn push(&mut self, packet: &[u8]) -> std::io::Result<bool> {
+        let packet_size = 4 + packet.len() as u64;
+        if self.max_bytes < self.end_pos + packet_size && 0 < self.read_pos {
+            self.compact()?;
+        }
+        if self.max_bytes < self.end_pos + packet_size {
+            return Ok(false);
+        }
+
+        self.file.seek(SeekFrom::Start(self.end_pos))?;
+        self.file.write_all(&(packet.len() as u32).to_le_bytes())?;
+        self.file.write_all(packet)?;
+        self.end_pos += packet_size;
+        Ok(true)
+    }
+
+    /// The oldest packet not yet sent, left in the spool until [`Self::acknowledge`]d.
+    pub fn next_unsent(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        if !self.has_unsent() {
+            return Ok(None);
+        }
+
+        self.file.seek(SeekFrom::Start(self.send_pos))?;
+        let remaining = self.end_pos - self.send_pos;
+        let packet_size = if 4 <= remaining {
+            let mut packet_size = [0_u8; 4];
+            self.file.read_exact(&mut packet_size)?;
+            Some(4 + u32::from_le_bytes(packet_size) as u64)
+        } else {
+            None
+        };
+
+        // Don't trust a size that goes past the end of the file: it may be garbage.
+        if let Some(packet_size) = packet_size.filter(|&packet_size| packet_size <= remaining) {
+            let mut packet = vec![0_u8; (packet_size - 4) as usize];
+            self.file.read_exact(&mut packet)?;
+            self.send_pos += packet_size;
+            self.unacked.push_back(packet_size);
+            Ok(Some(packet))
+        } else {
+            // The last write was cut short, e.g. by a crash.
+            re_log::warn!(
+                "Discarding a truncated message at the end of {:?}",
+                self.path
+            );
+            self.end_pos = self.send_pos;
+            self.file.set_len(self.end_pos)?;
+            if self.is_empty() {
+                self.clear()?;
+            }
+            Ok(None)
+        }
+    }
+
+    /// Removes the oldest `num_packets` packets sent, now that the server has them.
+    pub fn acknowledge(&mut self, num_packets: u64) -> std::io::Result<()> {
+        for _ in 0..num_packets {
+            let Some(packet_size) = self.unacked.pop_front() else {
+                break;
+            };
+            self.read_pos += packet_size;
+        }
+        if self.is_empty() {
+            self.clear()?;
+        }
+        Ok(())
+    }
+
+    /// Sends the packets not yet acknowledged again, e.g. because the connection they were
+    /// sent on broke.
+    pub fn rewind(&mut self) {
+        self.send_pos = self.read_pos;
+        self.unacked.clear();
+    }
+
+    fn clear(&mut self) -> std::io::Result<()> {
+        self.file.set_len(0)?;
+        self.read_pos = 0;
+        self.send_pos = 0;
+        self.unacked.clear();
+        self.end_pos = 0;
+        Ok(())
+    }
+
+    /// Removes the packets already acknowledged from the start of the file.
+    ///
+    /// The packets left are copied to a new file which then replaces the old one,
+    /// so that a crash halfway through doesn't lose them.
+    fn compact(&mut self) -> std::io::Result<()> {
+        let tmp_path = self.path.with_file_name(SPOOL_TMP_FILE_NAME);
+        let mut tmp_file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+        self.file.seek(SeekFrom::Start(self.read_pos))?;
+        let num_bytes = std::io::copy(
+            &mut (&mut self.file).take(self.end_pos - self.read_pos),
+            &mut tmp_file,
+        )?;
+        tmp_file.sync_all()?;
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        self.file = tmp_file;
+        self.send_pos -= self.read_pos;
+        self.read_pos = 0;
+        self.end_pos = num_bytes;
+        Ok(())
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        // Don't send the acknowledged packets again next time.
+        if 0 < self.read_pos {
+            if let Err(err) = self.compact() {
+                re_log::warn!("Failed to compact {:?}: {err}", self.path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pop_all(spool: &mut Spool) -> Vec<Vec<u8>> {
+        let mut packets = vec![];
+        while let Some(packet) = spool.next_unsent().unwrap() {
+            spool.acknowledge(1).unwrap();
+            packets.push(packet);
+        }
+        packets
+    }
+
+    #[test]
+    fn packets_survive_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = SpoolOptions {
+            dir: dir.path().join("spool"),
+            max_bytes: 1024,
+        };
+
+        {
+            let mut spool = Spool::open(&options).unwrap();
+            assert!(spool.is_empty());
+            for packet in [b"one".as_slice(), b"two", b"three"] {
+                assert!(spool.push(packet).unwrap());
+            }
+
+            let packet = spool.next_unsent().unwrap().unwrap();
+            assert_eq!(packet, b"one");
+            spool.acknowledge(1).unwrap();
+        }
+
+        // Compacted when closed.
+        assert_eq!(
+            std::fs::metadata(options.dir.join(SPOOL_FILE_NAME))
+                .unwrap()
+                .len(),
+            (4 + 3) + (4 + 5)
+        );
+        assert!(!options.dir.join(SPOOL_TMP_FILE_NAME).exists());
+
+        let mut spool = Spool::open(&options).unwrap();
+        assert!(!spool.is_empty());
+        assert_eq!(
+            pop_all(&mut spool),
+            vec![b"two".to_vec(), b"three".to_vec()]
+        );
+        assert!(spool.is_empty());
+
+        // A write cut short is discarded, without losing what came before it.
+        assert!(spool.push(b"four").unwrap());
+        drop(spool);
+        let file = File::options()
+            .append(true)
+            .open(options.dir.join(SPOOL_FILE_NAME))
+            .unwrap();
+        (&file).write_all(&100_u32.to_le_bytes()).unwrap();
+        (&file).write_all(b"fi").unwrap();
+
+        let mut spool = Spool::open(&options).unwrap();
+        assert_eq!(pop_all(&mut spool), vec![b"four".to_vec()]);
+
+        // So is a size that can't be right, without trying to read that much.
+        assert!(spool.push(b"five").unwrap());
+        drop(spool);
+        let file = File::options()
+            .append(true)
+            .open(options.dir.join(SPOOL_FILE_NAME))
+            .unwrap();
+        (&file).write_all(&u32::MAX.to_le_bytes()).unwrap();
+        (&file).write_all(b"six").unwrap();
+
+        let mut spool = Spool::open(&options).unwrap();
+        assert_eq!(pop_all(&mut spool), vec![b"five".to_vec()]);
+        assert!(spool.is_empty());
+    }
+
+    #[test]
+    fn full_spool_refuses_packets() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut spool = Spool::open(&SpoolOptions {
+            dir: dir.path().to_owned(),
+            max_bytes: 20,
+        })
+        .unwrap();
+
+        assert!(spool.push(&[0; 6]).unwrap());
+        assert!(spool.push(&[1; 6]).unwrap());
+        assert!(!spool.push(&[2; 6]).unwrap());
+
+        // Sending a packet isn't enough to make room for another one…
+        spool.next_unsent().unwrap().unwrap();
+        assert!(!spool.push(&[2; 6]).unwrap());
+
+        // …it must be acknowledged too.
+        spool.acknowledge(1).unwrap();
+        assert!(spool.push(&[2; 6]).unwrap());
+
+        assert_eq!(pop_all(&mut spool), vec![vec![1; 6], vec![2; 6]]);
+    }
+
+    #[test]
+    fn unacknowledged_packets_are_sent_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = SpoolOptions {
+            dir: dir.path().to_owned(),
+            max_bytes: 1024,
+        };
+
+        {
+            let mut spool = Spool::open(&options).unwrap();
+            for packet in [b"one".as_slice(), b"two", b"three"] {
+                assert!(spool.push(packet).unwrap());
+            }
+
+            assert_eq!(spool.next_unsent().unwrap().unwrap(), b"one");
+            assert_eq!(spool.next_unsent().unwrap().unwrap(), b"two");
+            spool.acknowledge(1).unwrap();
+
+            // The connection broke before "two" was acknowledged.
+            spool.rewind();
+            assert_eq!(spool.next_unsent().unwrap().unwrap(), b"two");
+            assert_eq!(spool.next_unsent().unwrap().unwrap(), b"three");
+            assert!(spool.next_unsent().unwrap().is_none());
+            assert!(!spool.has_unsent());
+            assert!(!spool.is_empty());
+        }
+
+        // Neither was acknowledged before the spool was closed.
+        let mut spool = Spool::open(&options).unwrap();
+        assert_eq!(
+            pop_all(&mut spool),
+            vec![b"two".to_vec(), b"three".to_vec()]
+        );
+        assert!(spool.is_empty());
+    }
+}
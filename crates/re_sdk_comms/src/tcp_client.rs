@@ -1,22 +1,222 @@
 use std::{
-    io::Write,
-    net::{SocketAddr, TcpStream},
+    io::{Read, Write as _},
+    net::{Shutdown, TcpStream},
+    sync::Arc,
     time::{Duration, Instant},
 };
 
+use parking_lot::{Condvar, Mutex};
+
+use re_log_encoding::EncodingOptions;
+
+use crate::{
+    protocol::{
+        decode_control_msg, encode_control_msg, read_control_msg, ClientHello, ServerHello,
+        ServerMsg,
+    },
+    ClientOptions, ServerAddr,
+};
+
+/// How long we wait for the server to answer our hello.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(thiserror::Error, Debug)]
 pub enum ClientError {
-    #[error("Failed to connect to Rerun server at {addr:?}: {err}")]
+    #[error("Failed to connect to Rerun server at {addr}: {err}")]
     Connect {
-        addr: SocketAddr,
+        addr: ServerAddr,
         err: std::io::Error,
     },
 
-    #[error("Failed to send to Rerun server at {addr:?}: {err}")]
+    #[error("Failed to send to Rerun server at {addr}: {err}")]
     Send {
-        addr: SocketAddr,
+        addr: ServerAddr,
+        err: std::io::Error,
+    },
+
+    #[cfg(feature = "tls")]
+    #[error("Failed to establish TLS with Rerun server at {addr}: {err}")]
+    Tls {
+        addr: ServerAddr,
+        err: std::io::Error,
+    },
+
+    #[error("Failed to handshake with Rerun server at {addr}: {err}")]
+    Handshake {
+        addr: ServerAddr,
         err: std::io::Error,
     },
+
+    #[error("The server at {addr} closed the connection during the handshake. Is it running an older version of Rerun than this SDK ({sdk_version})?")]
+    HandshakeClosed {
+        addr: ServerAddr,
+        sdk_version: String,
+    },
+
+    #[error("Rerun server at {addr} ({server_build_info}) rejected the connection: {reason}")]
+    Rejected {
+        addr: ServerAddr,
+        server_build_info: String,
+        reason: String,
+    },
+}
+
+/// Counts the packets between their creation and their acknowledgement by the server.
+///
+/// A packet is done once the server has acknowledged it, or once it is lost: dropped
+/// because we could not send it, or sent on a connection that broke before it was acknowledged.
+#[derive(Default)]
+pub(crate) struct InFlight {
+    counts: Mutex<InFlightCounts>,
+    done_changed: Condvar,
+}
+
+#[derive(Default)]
+struct InFlightCounts {
+    num_started: u64,
+    num_done: u64,
+}
+
+impl InFlight {
+    /// Number of packets started so far.
+    pub fn num_started(&self) -> u64 {
+        self.counts.lock().num_started
+    }
+
+    /// Number of packets started but not yet done.
+    pub fn len(&self) -> u64 {
+        let counts = self.counts.lock();
+        counts.num_started - counts.num_done
+    }
+
+    /// Registers a new packet, once fewer than `max_len` packets are in flight.
+    ///
+    /// Returns `false` without registering anything if that takes longer than `timeout`.
+    pub fn start(&self, max_len: u64, timeout: Option<Duration>) -> bool {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut counts = self.counts.lock();
+        while max_len <= counts.num_started - counts.num_done {
+            match deadline {
+                Some(deadline) => {
+                    if self
+                        .done_changed
+                        .wait_until(&mut counts, deadline)
+                        .timed_out()
+                    {
+                        return false;
+                    }
+                }
+                None => self.done_changed.wait(&mut counts),
+            }
+        }
+        counts.num_started += 1;
+        true
+    }
+
+    /// Marks this many packets as done.
+    pub fn done(&self, num_packets: u64) {
+        if num_packets == 0 {
+            return;
+        }
+        let mut counts = self.counts.lock();
+        counts.num_done += num_packets;
+        debug_assert!(counts.num_done <= counts.num_started);
+        self.done_changed.notify_all();
+    }
+
+    /// Blocks until the first `num_packets` packets started are done.
+    pub fn wait_until_done(&self, num_packets: u64) {
+        let mut counts = self.counts.lock();
+        while counts.num_done < num_packets {
+            self.done_changed.wait(&mut counts);
+        }
+    }
+}
+
+/// The packets sent on a single connection, and how many of them the server has acknowledged.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ConnectionAcks {
+    /// Tells the connections of a [`TcpClient`] apart.
+    pub connection_id: u64,
+
+    pub num_sent: u64,
+    pub num_acked: u64,
+
+    /// Once closed, nothing more will be acknowledged.
+    pub closed: bool,
+}
+
+/// The connection to the server, encrypted or not.
+enum Transport {
+    Plain(TcpStream),
+
+    #[cfg(feature = "tls")]
+    Tls(crate::tls::TlsStream),
+
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+}
+
+impl Transport {
+    fn write_all(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Plain(stream) => stream.write_all(bytes),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.write_all(bytes),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.write_all(bytes),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(stream) => stream.flush(),
+            #[cfg(feature = "tls")]
+            Self::Tls(_) => Ok(()), // `write_all` already wrote everything to the socket
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        match self {
+            Self::Plain(stream) => stream.set_read_timeout(timeout),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.tcp().set_read_timeout(timeout),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+
+    fn shutdown(&self, how: Shutdown) -> std::io::Result<()> {
+        match self {
+            Self::Plain(stream) => stream.shutdown(how),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.tcp().shutdown(how),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.shutdown(how),
+        }
+    }
+
+    /// Tells the server we won't send anything more.
+    fn close(&self) {
+        #[cfg(feature = "tls")]
+        if let Self::Tls(stream) = self {
+            stream.send_close_notify();
+        }
+        self.shutdown(Shutdown::Write).ok();
+    }
+
+    /// Reads from the connection, independently of the writes.
+    fn reader(&self) -> std::io::Result<Box<dyn Read + Send>> {
+        Ok(match self {
+            Self::Plain(stream) => Box::new(stream.try_clone()?),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => Box::new(stream.reader()?),
+            #[cfg(unix)]
+            Self::Unix(stream) => Box::new(stream.try_clone()?),
+        })
+    }
 }
 
 /// State of the [`TcpStream`]
@@ -41,13 +241,21 @@ enum TcpStreamState {
         num_attempts: usize,
     },
 
-    /// A healthy [`TcpStream`] ready to send packets
+    /// A healthy [`TcpStream`] ready to send packets, after a successful handshake.
     ///
     /// Behavior: Send packets on [`TcpClient::send`]
     ///
     /// Transitions:
     ///  - Connected -> Pending on send error
-    Connected(TcpStream),
+    Connected {
+        stream: Transport,
+
+        /// The encoding negotiated with the server.
+        encoding: EncodingOptions,
+
+        /// Shared with the thread reading the acknowledgements from the server.
+        acks: Arc<Mutex<ConnectionAcks>>,
+    },
 }
 
 impl TcpStreamState {
@@ -61,49 +269,82 @@ impl TcpStreamState {
 
 /// Connect to a rerun server and send log messages.
 ///
-/// Blocking connection.
+/// Blocking connection, over TCP or a Unix domain socket.
 pub struct TcpClient {
-    addr: SocketAddr,
+    addr: ServerAddr,
     stream_state: TcpStreamState,
     flush_timeout: Option<Duration>,
+
+    /// Every packet we send is marked as done here once acknowledged (or lost).
+    in_flight: Arc<InFlight>,
+
+    /// How we authenticate, and whether we encrypt.
+    options: ClientOptions,
+
+    /// How many connections were established so far.
+    num_connections: u64,
 }
 
 impl TcpClient {
-    pub fn new(addr: SocketAddr, flush_timeout: Option<Duration>) -> Self {
+    pub fn new(
+        addr: ServerAddr,
+        flush_timeout: Option<Duration>,
+        in_flight: Arc<InFlight>,
+        options: ClientOptions,
+    ) -> Self {
         Self {
             addr,
             stream_state: TcpStreamState::reset(),
             flush_timeout,
+            in_flight,
+            options,
+            num_connections: 0,
         }
     }
 
-    /// Returns `false` on failure. Does nothing if already connected.
+    /// Connects and handshakes with the server. Does nothing if already connected.
     ///
     /// [`Self::send`] will call this.
     pub fn connect(&mut self) -> Result<(), ClientError> {
         match self.stream_state {
-            TcpStreamState::Connected(_) => Ok(()),
+            TcpStreamState::Connected { .. } => Ok(()),
             TcpStreamState::Pending {
                 start_time,
                 num_attempts,
             } => {
-                re_log::debug!("Connecting to {:?}…", self.addr);
-                let timeout = std::time::Duration::from_secs(5);
-                match TcpStream::connect_timeout(&self.addr, timeout) {
+                re_log::debug!("Connecting to {}…", self.addr);
+                match self.open() {
                     Ok(mut stream) => {
-                        re_log::debug!("Connected to {:?}.", self.addr);
-                        if let Err(err) = stream.write(&crate::PROTOCOL_VERSION.to_le_bytes()) {
-                            self.stream_state = TcpStreamState::Pending {
-                                start_time,
-                                num_attempts: num_attempts + 1,
-                            };
-                            Err(ClientError::Send {
-                                addr: self.addr,
-                                err,
-                            })
-                        } else {
-                            self.stream_state = TcpStreamState::Connected(stream);
-                            Ok(())
+                        re_log::debug!("Connected to {}.", self.addr);
+                        match self.handshake(&mut stream) {
+                            Ok(encoding) => {
+                                self.num_connections += 1;
+                                let acks = Arc::new(Mutex::new(ConnectionAcks {
+                                    connection_id: self.num_connections,
+                                    ..Default::default()
+                                }));
+                                self.spawn_ack_reader(&stream, &acks)?;
+                                self.stream_state = TcpStreamState::Connected {
+                                    stream,
+                                    encoding,
+                                    acks,
+                                };
+                                Ok(())
+                            }
+                            Err(err) => {
+                                if matches!(
+                                    err,
+                                    ClientError::Rejected { .. }
+                                        | ClientError::HandshakeClosed { .. }
+                                ) {
+                                    re_log::error_once!("{err}");
+                                }
+                                self.stream_state = TcpStreamState::Pending {
+                                    start_time,
+                                    num_attempts: num_attempts + 1,
+                                };
+                                Err(err)
+                            }
                         }
                     }
                     Err(err) => {
@@ -111,40 +352,217 @@ impl TcpClient {
                             start_time,
                             num_attempts: num_attempts + 1,
                         };
-                        Err(ClientError::Connect {
-                            addr: self.addr,
-                            err,
-                        })
+                        Err(err)
                     }
                 }
             }
         }
     }
 
+    /// Opens a new connection to the server.
+    fn open(&self) -> Result<Transport, ClientError> {
+        let connect_err = |err| ClientError::Connect {
+            addr: self.addr.clone(),
+            err,
+        };
+
+        match &self.addr {
+            ServerAddr::Tcp(addr) => {
+                let timeout = std::time::Duration::from_secs(5);
+                let stream = TcpStream::connect_timeout(addr, timeout).map_err(connect_err)?;
+                self.secure(stream)
+            }
+
+            // No point in encrypting what never leaves the machine.
+            #[cfg(unix)]
+            ServerAddr::Unix(path) => std::os::unix::net::UnixStream::connect(path)
+                .map(Transport::Unix)
+                .map_err(connect_err),
+        }
+    }
+
+    /// Wraps the stream in TLS if asked to.
+    fn secure(&self, stream: TcpStream) -> Result<Transport, ClientError> {
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.options.tls {
+            return crate::tls::TlsStream::connect(stream, tls)
+                .map(Transport::Tls)
+                .map_err(|err| ClientError::Tls {
+                    addr: self.addr.clone(),
+                    err,
+                });
+        }
+
+        Ok(Transport::Plain(stream))
+    }
+
+    /// Sends our hello, and waits for the server to accept it.
+    ///
+    /// Returns the encoding the server wants us to use.
+    fn handshake(&self, stream: &mut Transport) -> Result<EncodingOptions, ClientError> {
+        let addr = &self.addr;
+        let invalid_data = |err: String| ClientError::Handshake {
+            addr: addr.clone(),
+            err: std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+        };
+
+        let mut hello = crate::PROTOCOL_VERSION.to_le_bytes().to_vec();
+        hello.extend(encode_control_msg(&ClientHello::new(
+            self.options.token.clone(),
+        )));
+        stream.write_all(&hello).map_err(|err| ClientError::Send {
+            addr: addr.clone(),
+            err,
+        })?;
+
+        let handshake_err = |err| ClientError::Handshake {
+            addr: addr.clone(),
+            err,
+        };
+        stream
+            .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+            .map_err(handshake_err)?;
+        let mut reader = stream.reader().map_err(handshake_err)?;
+        let hello = read_control_msg(&mut reader).map_err(|err| match err.kind() {
+            std::io::ErrorKind::UnexpectedEof
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::ConnectionAborted => ClientError::HandshakeClosed {
+                addr: addr.clone(),
+                sdk_version: env!("CARGO_PKG_VERSION").to_owned(),
+            },
+            _ => handshake_err(err),
+        })?;
+        stream.set_read_timeout(None).map_err(handshake_err)?;
+
+        let server_hello: ServerHello =
+            decode_control_msg(&hello).map_err(|err| invalid_data(err.to_string()))?;
+        let accepted = server_hello
+            .result
+            .clone()
+            .map_err(|reason| ClientError::Rejected {
+                addr: addr.clone(),
+                server_build_info: server_hello.build_info.clone(),
+                reason,
+            })?;
+        let encoding = EncodingOptions::from_bytes(accepted.encoding)
+            .map_err(|err| invalid_data(err.to_string()))?;
+
+        re_log::debug!(
+            "Handshake with {addr} done: server is {} (v{}), sending {encoding:?}",
+            server_hello.build_info,
+            server_hello.version(),
+        );
+
+        Ok(encoding)
+    }
+
+    /// Reads the acknowledgements of the server on a thread of its own,
+    /// until the connection closes.
+    fn spawn_ack_reader(
+        &self,
+        stream: &Transport,
+        acks: &Arc<Mutex<ConnectionAcks>>,
+    ) -> Result<(), ClientError> {
+        let addr = self.addr.clone();
+        let mut stream = stream.reader().map_err(|err| ClientError::Connect {
+            addr: addr.clone(),
+            err,
+        })?;
+        let acks = acks.clone();
+        let in_flight = self.in_flight.clone();
+
+        std::thread::Builder::new()
+            .name("tcp_ack_reader".into())
+            .spawn(move || {
+                loop {
+                    let msg = read_control_msg(&mut stream).and_then(|msg| {
+                        decode_control_msg(&msg).map_err(|err| {
+                            std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+                        })
+                    });
+                    match msg {
+                        Ok(ServerMsg::Ack { num_packets }) => {
+                            let mut acks = acks.lock();
+                            let num_packets = num_packets.min(acks.num_sent);
+                            in_flight.done(num_packets.saturating_sub(acks.num_acked));
+                            acks.num_acked = acks.num_acked.max(num_packets);
+                        }
+                        Err(err) => {
+                            re_log::debug!("Stopped reading acknowledgements from {addr}: {err}");
+                            break;
+                        }
+                    }
+                }
+
+                // Whatever wasn't acknowledged by now is lost.
+                let mut acks = acks.lock();
+                acks.closed = true;
+                in_flight.done(acks.num_sent - acks.num_acked);
+            })
+            .map_err(|err| ClientError::Connect {
+                addr: self.addr.clone(),
+                err,
+            })?;
+
+        Ok(())
+    }
+
+    /// The encoding negotiated with the server, if connected.
+    pub fn encoding(&self) -> Option<EncodingOptions> {
+        match self.stream_state {
+            TcpStreamState::Pending { .. } => None,
+            TcpStreamState::Connected { encoding, .. } => Some(encoding),
+        }
+    }
+
+    /// What the server acknowledged of the packets sent on the current connection, if connected.
+    pub fn acks(&self) -> Option<ConnectionAcks> {
+        match &self.stream_state {
+            TcpStreamState::Pending { .. } => None,
+            TcpStreamState::Connected { acks, .. } => Some(*acks.lock()),
+        }
+    }
+
+    /// Drops the current connection, if any: the next [`Self::send`] connects again.
+    pub fn disconnect(&mut self) {
+        if let TcpStreamState::Connected { stream, .. } = &self.stream_state {
+            stream.shutdown(Shutdown::Both).ok(); // wakes up the ack reader
+            self.stream_state = TcpStreamState::reset();
+        }
+    }
+
     /// Blocks until it is sent.
     pub fn send(&mut self, packet: &[u8]) -> Result<(), ClientError> {
-        use std::io::Write as _;
-
         self.connect()?;
 
-        if let TcpStreamState::Connected(stream) = &mut self.stream_state {
+        if let TcpStreamState::Connected { stream, acks, .. } = &mut self.stream_state {
             re_log::trace!("Sending a packet of size {}…", packet.len());
-            if let Err(err) = stream.write(&(packet.len() as u32).to_le_bytes()) {
+            if let Err(err) = stream.write_all(&(packet.len() as u32).to_le_bytes()) {
+                stream.shutdown(Shutdown::Both).ok(); // wakes up the ack reader
                 self.stream_state = TcpStreamState::reset();
                 return Err(ClientError::Send {
-                    addr: self.addr,
+                    addr: self.addr.clone(),
                     err,
                 });
             }
 
-            if let Err(err) = stream.write(packet) {
+            if let Err(err) = stream.write_all(packet) {
+                stream.shutdown(Shutdown::Both).ok(); // wakes up the ack reader
                 self.stream_state = TcpStreamState::reset();
                 return Err(ClientError::Send {
-                    addr: self.addr,
+                    addr: self.addr.clone(),
                     err,
                 });
             }
 
+            let mut acks = acks.lock();
+            if acks.closed {
+                // The server won't tell us what became of this one.
+                self.in_flight.done(1);
+            } else {
+                acks.num_sent += 1;
+            }
+
             Ok(())
         } else {
             unreachable!("self.connect should have ensured this");
@@ -160,7 +578,7 @@ impl TcpClient {
                     "Tried to flush while TCP stream was still Pending. Data was possibly dropped."
                 );
             }
-            TcpStreamState::Connected(stream) => {
+            TcpStreamState::Connected { stream, .. } => {
                 if let Err(err) = stream.flush() {
                     re_log::warn!("Failed to flush TCP stream: {err}");
                     self.stream_state = TcpStreamState::reset();
@@ -186,7 +604,17 @@ impl TcpClient {
                     Instant::now().duration_since(start_time) > timeout && num_attempts > 0
                 })
             }
-            TcpStreamState::Connected(_) => false,
+            TcpStreamState::Connected { .. } => false,
+        }
+    }
+}
+
+impl Drop for TcpClient {
+    fn drop(&mut self) {
+        if let TcpStreamState::Connected { stream, .. } = &self.stream_state {
+            // Let the server know we are done, so that it closes the connection and with it
+            // the ack reader. Closing the stream ourselves isn't enough: the ack reader has a copy.
+            stream.close();
         }
     }
 }
@@ -0,0 +1,223 @@
+//! Optional TLS encryption of the connection between SDK and server.
+
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+#[derive(thiserror::Error, Debug)]
+pub enum TlsError {
+    #[error("Failed to read {path:?}: {err}")]
+    Read { path: PathBuf, err: std::io::Error },
+
+    #[error("No PEM certificate found in {0:?}")]
+    NoCertificate(PathBuf),
+
+    #[error("No PEM private key found in {0:?}")]
+    NoPrivateKey(PathBuf),
+
+    #[error("Invalid server name {0:?}")]
+    InvalidServerName(String),
+
+    #[error(transparent)]
+    Rustls(#[from] rustls::Error),
+}
+
+/// Loads the certificate chain and private key a server presents to its clients.
+///
+/// Both files are PEM.
+/// Clients must trust the certificate, see [`TlsClientConfig::new`].
+pub fn load_server_config(
+    cert_path: &Path,
+    key_path: &Path,
+) -> Result<Arc<rustls::ServerConfig>, TlsError> {
+    let certs = read_pem(cert_path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(der) => Some(rustls::Certificate(der)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificate(cert_path.to_owned()));
+    }
+
+    let key = read_pem(key_path)?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::RSAKey(der)
+            | rustls_pemfile::Item::PKCS8Key(der)
+            | rustls_pemfile::Item::ECKey(der) => Some(rustls::PrivateKey(der)),
+            _ => None,
+        })
+        .ok_or_else(|| TlsError::NoPrivateKey(key_path.to_owned()))?;
+
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(Arc::new(config))
+}
+
+fn read_pem(path: &Path) -> Result<Vec<rustls_pemfile::Item>, TlsError> {
+    let read_err = |err| TlsError::Read {
+        path: path.to_owned(),
+        err,
+    };
+    let file = std::fs::File::open(path).map_err(read_err)?;
+    rustls_pemfile::read_all(&mut std::io::BufReader::new(file)).map_err(read_err)
+}
+
+/// How an SDK client verifies the server it connects to over TLS.
+#[derive(Clone, Debug)]
+pub struct TlsClientConfig {
+    config: Arc<rustls::ClientConfig>,
+    server_name: rustls::ServerName,
+}
+
+impl TlsClientConfig {
+    /// The server certificate must be valid for the DNS name `server_name`,
+    /// e.g. `"localhost"` or `"viewer.lab.example.com"`.
+    ///
+    /// We trust the usual web certificate authorities, plus the PEM certificates in `ca_cert_path`.
+    /// Pass the server certificate itself as `ca_cert_path` if it is self-signed.
+    pub fn new(server_name: &str, ca_cert_path: Option<&Path>) -> Result<Self, TlsError> {
+        let server_name = rustls::ServerName::try_from(server_name)
+            .map_err(|_err| TlsError::InvalidServerName(server_name.to_owned()))?;
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+            rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+                anchor.subject,
+                anchor.spki,
+                anchor.name_constraints,
+            )
+        }));
+
+        if let Some(path) = ca_cert_path {
+            let mut num_certs = 0;
+            for item in read_pem(path)? {
+                if let rustls_pemfile::Item::X509Certificate(der) = item {
+                    roots.add(&rustls::Certificate(der)).map_err(|err| {
+                        TlsError::Rustls(rustls::Error::General(format!("{path:?}: {err}")))
+                    })?;
+                    num_certs += 1;
+                }
+            }
+            if num_certs == 0 {
+                return Err(TlsError::NoCertificate(path.to_owned()));
+            }
+        }
+
+        let config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Ok(Self {
+            config: Arc::new(config),
+            server_name,
+        })
+    }
+}
+
+/// A TLS stream that can be read from and written to on different threads.
+///
+/// The socket itself is read and written without locking,
+/// only the TLS state is shared.
+pub(crate) struct TlsStream {
+    tcp: TcpStream,
+    tls: Arc<Mutex<rustls::ClientConnection>>,
+}
+
+impl TlsStream {
+    /// Runs the TLS handshake on a freshly connected socket.
+    pub fn connect(mut tcp: TcpStream, config: &TlsClientConfig) -> std::io::Result<Self> {
+        let mut tls =
+            rustls::ClientConnection::new(config.config.clone(), config.server_name.clone())
+                .map_err(invalid_data)?;
+        while tls.is_handshaking() {
+            tls.complete_io(&mut tcp)?;
+        }
+
+        Ok(Self {
+            tcp,
+            tls: Arc::new(Mutex::new(tls)),
+        })
+    }
+
+    pub fn tcp(&self) -> &TcpStream {
+        &self.tcp
+    }
+
+    pub fn write_all(&self, bytes: &[u8]) -> std::io::Result<()> {
+        let mut tls = self.tls.lock();
+        tls.writer().write_all(bytes)?;
+        while tls.wants_write() {
+            tls.write_tls(&mut &self.tcp)?;
+        }
+        Ok(())
+    }
+
+    /// Best-effort, as we are closing the connection anyway.
+    pub fn send_close_notify(&self) {
+        let mut tls = self.tls.lock();
+        tls.send_close_notify();
+        while tls.wants_write() {
+            if tls.write_tls(&mut &self.tcp).is_err() {
+                break;
+            }
+        }
+    }
+
+    pub fn reader(&self) -> std::io::Result<TlsReader> {
+        Ok(TlsReader {
+            tcp: self.tcp.try_clone()?,
+            tls: self.tls.clone(),
+            encrypted: vec![0_u8; 16 * 1024],
+        })
+    }
+}
+
+/// Reads the decrypted data of a [`TlsStream`].
+pub(crate) struct TlsReader {
+    tcp: TcpStream,
+    tls: Arc<Mutex<rustls::ClientConnection>>,
+    encrypted: Vec<u8>,
+}
+
+impl Read for TlsReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            match self.tls.lock().reader().read(buf) {
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {}
+                result => return result,
+            }
+
+            // Wait for more data without holding the lock, so that we can still write meanwhile.
+            let num_read = self.tcp.read(&mut self.encrypted)?;
+
+            let mut tls = self.tls.lock();
+            let mut encrypted = &self.encrypted[..num_read];
+            loop {
+                // Reading nothing tells rustls about the end of the stream.
+                tls.read_tls(&mut encrypted)?;
+                tls.process_new_packets().map_err(invalid_data)?;
+                if encrypted.is_empty() {
+                    break;
+                }
+            }
+            while tls.wants_write() {
+                tls.write_tls(&mut &self.tcp)?;
+            }
+        }
+    }
+}
+
+fn invalid_data(err: rustls::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::SmartMessageSource;
+
+/// What a server knows about one of its clients.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientStats {
+    /// Who the client is, e.g. its address.
+    pub source: SmartMessageSource,
+
+    /// `false` once the client has disconnected.
+    pub connected: bool,
+
+    pub bytes_received: u64,
+    pub messages_received: u64,
+
+    /// Messages dropped to keep the latency down.
+    pub messages_dropped: u64,
+
+    /// The latency of the channel when the last message of this client was received.
+    pub latency_sec: f32,
+
+    /// How long we have stopped reading from this client to keep it under its rate limit.
+    pub throttled_sec: f32,
+}
+
+impl ClientStats {
+    pub fn new(source: SmartMessageSource) -> Self {
+        Self {
+            source,
+            connected: true,
+            bytes_received: 0,
+            messages_received: 0,
+            messages_dropped: 0,
+            latency_sec: 0.0,
+            throttled_sec: 0.0,
+        }
+    }
+}
+
+/// The clients of a server, shared between the server and whoever displays them.
+///
+/// Recently disconnected clients are kept around too.
+///
+/// This is not part of the identity of a [`crate::SmartChannelSource`]: all of them compare equal.
+#[derive(Clone, Default)]
+pub struct ConnectedClients(Arc<Mutex<Vec<Arc<Mutex<ClientStats>>>>>);
+
+impl ConnectedClients {
+    /// How many disconnected clients we remember.
+    const MAX_DISCONNECTED: usize = 16;
+
+    /// Registers a new client. It is marked as disconnected once the handle is dropped.
+    pub fn add(&self, source: SmartMessageSource) -> ClientStatsHandle {
+        let stats = Arc::new(Mutex::new(ClientStats::new(source)));
+
+        let mut clients = self.0.lock();
+        let num_disconnected = clients
+            .iter()
+            .filter(|client| !client.lock().connected)
+            .count();
+        let mut num_to_forget = num_disconnected.saturating_sub(Self::MAX_DISCONNECTED);
+        clients.retain(|client| {
+            let forget = 0 < num_to_forget && !client.lock().connected;
+            if forget {
+                num_to_forget -= 1;
+            }
+            !forget
+        });
+        clients.push(stats.clone());
+
+        ClientStatsHandle(stats)
+    }
+
+    /// The current statistics of every client, oldest first.
+    pub fn snapshot(&self) -> Vec<ClientStats> {
+        self.0
+            .lock()
+            .iter()
+            .map(|client| client.lock().clone())
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for ConnectedClients {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.snapshot()).finish()
+    }
+}
+
+impl PartialEq for ConnectedClients {
+    #[inline]
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ConnectedClients {}
+
+impl std::hash::Hash for ConnectedClients {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
+/// Updates the [`ClientStats`] of a single client, see [`ConnectedClients::add`].
+pub struct ClientStatsHandle(Arc<Mutex<ClientStats>>);
+
+impl ClientStatsHandle {
+    pub fn update(&self, update: impl FnOnce(&mut ClientStats)) {
+        update(&mut self.0.lock());
+    }
+}
+
+impl Drop for ClientStatsHandle {
+    fn drop(&mut self) {
+        self.0.lock().connected = false;
+    }
+}
+
+#[test]
+fn forget_old_disconnected_clients() {
+    let clients = ConnectedClients::default();
+
+    let first = clients.add(SmartMessageSource::UnixSocketClient { pid: Some(0) });
+    let mut handles = (1..=ConnectedClients::MAX_DISCONNECTED as u32 + 1)
+        .map(|pid| clients.add(SmartMessageSource::UnixSocketClient { pid: Some(pid) }))
+        .collect::<Vec<_>>();
+    first.update(|stats| stats.bytes_received += 42);
+    handles.clear();
+
+    let pids = |clients: &ConnectedClients| {
+        clients
+            .snapshot()
+            .iter()
+            .map(|stats| match stats.source {
+                SmartMessageSource::UnixSocketClient { pid } => pid.unwrap(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(pids(&clients).len(), ConnectedClients::MAX_DISCONNECTED + 2);
+
+    // Connecting a client forgets the oldest disconnected ones, but not the connected ones.
+    let _last = clients.add(SmartMessageSource::UnixSocketClient { pid: Some(100) });
+    let snapshot = clients.snapshot();
+    assert_eq!(snapshot.len(), ConnectedClients::MAX_DISCONNECTED + 2);
+    assert_eq!(snapshot[0].bytes_received, 42);
+    assert!(snapshot[0].connected);
+    assert_eq!(pids(&clients)[1], 2);
+    assert_eq!(pids(&clients).last(), Some(&100));
+}
@@ -6,10 +6,12 @@ use web_time::Instant;
 
 pub use crossbeam::channel::{RecvError, RecvTimeoutError, SendError, TryRecvError};
 
+mod client_stats;
 mod receive_set;
 mod receiver;
 mod sender;
 
+pub use client_stats::{ClientStats, ClientStatsHandle, ConnectedClients};
 pub use receive_set::ReceiveSet;
 pub use receiver::Receiver;
 pub use sender::Sender;
@@ -25,13 +27,21 @@ pub enum SmartChannelSource {
     File(std::path::PathBuf),
 
     /// The channel was created in the context of loading an `.rrd` file over http.
-    RrdHttpStream { url: String },
+    RrdHttpStream {
+        url: String,
+
+        /// How much of the file has been downloaded so far.
+        progress: LoadProgress,
+    },
 
     /// The channel was created in the context of loading an `.rrd` file from a `postMessage`
     /// js event.
     ///
     /// Only applicable to web browser iframes.
-    RrdWebEventListener,
+    RrdWebEventListener {
+        /// How much of the last posted file has been decoded so far.
+        progress: LoadProgress,
+    },
 
     /// The channel was created in the context of loading data using a Rerun SDK sharing the same
     /// process.
@@ -49,7 +59,23 @@ pub enum SmartChannelSource {
     /// over TCP.
     ///
     /// We are a TCP server listening on this port.
-    TcpServer { port: u16 },
+    TcpServer {
+        port: u16,
+
+        /// The SDKs connected to us.
+        clients: ConnectedClients,
+    },
+
+    /// The channel was created in the context of receiving data from one or more Rerun SDKs
+    /// on the same machine, over a Unix domain socket.
+    ///
+    /// We are a server listening on the socket at this path.
+    UnixSocketServer {
+        path: std::path::PathBuf,
+
+        /// The SDKs connected to us.
+        clients: ConnectedClients,
+    },
 
     /// The channel was created in the context of streaming in RRD data from standard input.
     Stdin,
@@ -59,11 +85,14 @@ impl std::fmt::Display for SmartChannelSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::File(path) => path.display().fmt(f),
-            Self::RrdHttpStream { url } => url.fmt(f),
-            Self::RrdWebEventListener => "Web Event Listener".fmt(f),
+            Self::RrdHttpStream { url, .. } => url.fmt(f),
+            Self::RrdWebEventListener { .. } => "Web Event Listener".fmt(f),
             Self::Sdk => "SDK".fmt(f),
             Self::WsClient { ws_server_url } => ws_server_url.fmt(f),
-            Self::TcpServer { port } => write!(f, "TCP Server, port {port}"),
+            Self::TcpServer { port, .. } => write!(f, "TCP Server, port {port}"),
+            Self::UnixSocketServer { path, .. } => {
+                write!(f, "Unix Socket Server, {}", path.display())
+            }
             Self::Stdin => "Standard Input".fmt(f),
         }
     }
@@ -72,12 +101,99 @@ impl std::fmt::Display for SmartChannelSource {
 impl SmartChannelSource {
     pub fn is_network(&self) -> bool {
         match self {
-            Self::File(_) | Self::Sdk | Self::RrdWebEventListener | Self::Stdin => false,
-            Self::RrdHttpStream { .. } | Self::WsClient { .. } | Self::TcpServer { .. } => true,
+            Self::File(_) | Self::Sdk | Self::RrdWebEventListener { .. } | Self::Stdin => false,
+            Self::RrdHttpStream { .. }
+            | Self::WsClient { .. }
+            | Self::TcpServer { .. }
+            | Self::UnixSocketServer { .. } => true,
+        }
+    }
+}
+
+/// How many bytes of a source have been loaded so far, shared between the loader and whoever
+/// displays it.
+///
+/// This is not part of the identity of a [`SmartChannelSource`]: all progresses compare equal.
+#[derive(Clone)]
+pub struct LoadProgress(Arc<LoadProgressInner>);
+
+struct LoadProgressInner {
+    loaded_bytes: AtomicU64,
+
+    /// `u64::MAX` if unknown.
+    total_bytes: AtomicU64,
+}
+
+impl Default for LoadProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadProgress {
+    pub fn new() -> Self {
+        Self(Arc::new(LoadProgressInner {
+            loaded_bytes: AtomicU64::new(0),
+            total_bytes: AtomicU64::new(u64::MAX),
+        }))
+    }
+
+    pub fn set(&self, loaded_bytes: u64, total_bytes: Option<u64>) {
+        use std::sync::atomic::Ordering::Relaxed;
+        self.0.loaded_bytes.store(loaded_bytes, Relaxed);
+        self.0
+            .total_bytes
+            .store(total_bytes.unwrap_or(u64::MAX), Relaxed);
+    }
+
+    pub fn loaded_bytes(&self) -> u64 {
+        self.0
+            .loaded_bytes
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// `None` if the total size isn't known (yet).
+    pub fn total_bytes(&self) -> Option<u64> {
+        let total_bytes = self
+            .0
+            .total_bytes
+            .load(std::sync::atomic::Ordering::Relaxed);
+        (total_bytes != u64::MAX).then_some(total_bytes)
+    }
+
+    /// In `0.0..=1.0`, `None` if the total size isn't known (yet).
+    pub fn fraction(&self) -> Option<f32> {
+        let total_bytes = self.total_bytes()?;
+        if total_bytes == 0 {
+            return Some(1.0);
         }
+        Some((self.loaded_bytes() as f64 / total_bytes as f64).min(1.0) as f32)
     }
 }
 
+impl std::fmt::Debug for LoadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadProgress")
+            .field("loaded_bytes", &self.loaded_bytes())
+            .field("total_bytes", &self.total_bytes())
+            .finish()
+    }
+}
+
+impl PartialEq for LoadProgress {
+    #[inline]
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for LoadProgress {}
+
+impl std::hash::Hash for LoadProgress {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
 /// Identifies who/what sent a particular message in a smart channel.
 ///
 /// Due to the multiplexed nature of the smart channel, every message coming in can originate
@@ -121,6 +237,12 @@ pub enum SmartMessageSource {
         addr: Option<std::net::SocketAddr>,
     },
 
+    /// The sender is a client of a Unix domain socket, i.e. a process on the same machine.
+    UnixSocketClient {
+        // NOTE: Optional as not all platforms tell who is on the other end.
+        pid: Option<u32>,
+    },
+
     /// The data is streaming in from standard input.
     Stdin,
 }
@@ -138,6 +260,13 @@ impl std::fmt::Display for SmartMessageSource {
                 "tcp://{}",
                 addr.map_or_else(|| "(unknown ip)".to_owned(), |addr| addr.to_string())
             ),
+            SmartMessageSource::UnixSocketClient { pid } => format!(
+                "unix://{}",
+                pid.map_or_else(
+                    || "(unknown process)".to_owned(),
+                    |pid| format!("pid {pid}")
+                )
+            ),
             SmartMessageSource::Stdin => "stdin".into(),
         })
     }
@@ -150,6 +279,14 @@ impl std::fmt::Display for SmartMessageSource {
 pub(crate) struct SharedStats {
     /// Latest known latency from sending a message to receiving it, it nanoseconds.
     latency_ns: AtomicU64,
+
+    /// Number of messages sent down the channel, not counting forwarding between chained channels.
+    ///
+    /// Incremented once a message is in the channel.
+    num_sent: AtomicU64,
+
+    /// Number of messages received at the end of the channel (chain).
+    num_received: AtomicU64,
 }
 
 pub fn smart_channel<T: Send>(
@@ -250,6 +387,33 @@ fn test_smart_channel() {
     assert!(tx.latency_ns() > 1_000_000);
 }
 
+#[test]
+fn test_smart_channel_counts() {
+    let (tx, rx) = smart_channel(SmartMessageSource::Sdk, SmartChannelSource::Sdk); // whatever source
+
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(tx.num_sent(), 2);
+    assert_eq!(tx.num_received(), 0);
+
+    // Forwarding through a chained channel doesn't count as sending or receiving.
+    let (chained_tx, chained_rx) = rx.chained_channel();
+    let msg = rx.recv_with_send_time().unwrap();
+    chained_tx
+        .send_at(msg.time, msg.source, msg.payload)
+        .unwrap();
+    assert_eq!(tx.num_sent(), 2);
+    assert_eq!(tx.num_received(), 0);
+
+    assert_eq!(
+        chained_rx.try_recv().map(|msg| msg.into_data()),
+        Ok(Some(1))
+    );
+    assert_eq!(tx.num_received(), 1);
+    assert_eq!(rx.try_recv().map(|msg| msg.into_data()), Ok(Some(2)));
+    assert_eq!(tx.num_received(), 2);
+}
+
 #[test]
 fn test_smart_channel_connected() {
     let (tx1, rx) = smart_channel(SmartMessageSource::Sdk, SmartChannelSource::Sdk); // whatever source
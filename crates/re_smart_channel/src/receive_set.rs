@@ -43,7 +43,7 @@ impl<T: Send> ReceiveSet<T> {
             // retain only sources which:
             // - aren't network sources
             // - don't point at the given `uri`
-            SmartChannelSource::RrdHttpStream { url } => url != uri,
+            SmartChannelSource::RrdHttpStream { url, .. } => url != uri,
             SmartChannelSource::WsClient { ws_server_url } => ws_server_url != uri,
             _ => true,
         });
@@ -66,12 +66,15 @@ impl<T: Send> ReceiveSet<T> {
         !self.is_empty()
     }
 
-    /// Does this viewer accept inbound TCP connections?
+    /// Does this viewer accept inbound TCP (or Unix domain socket) connections?
     pub fn accepts_tcp_connections(&self) -> bool {
         re_tracing::profile_function!();
-        self.sources()
-            .iter()
-            .any(|s| matches!(**s, SmartChannelSource::TcpServer { .. }))
+        self.sources().iter().any(|s| {
+            matches!(
+                **s,
+                SmartChannelSource::TcpServer { .. } | SmartChannelSource::UnixSocketServer { .. }
+            )
+        })
     }
 
     /// No connected receivers?
@@ -123,6 +126,7 @@ impl<T: Send> ReceiveSet<T> {
             let oper = sel.select();
             let index = oper.index();
             if let Ok(msg) = oper.recv(&rx[index].rx) {
+                rx[index].count_received();
                 return Ok(msg);
             }
         }
@@ -147,6 +151,7 @@ impl<T: Send> ReceiveSet<T> {
         let oper = sel.try_select().ok()?;
         let index = oper.index();
         if let Ok(msg) = oper.recv(&rx[index].rx) {
+            rx[index].count_received();
             return Some((rx[index].source.clone(), msg));
         }
 
@@ -182,6 +187,7 @@ impl<T: Send> ReceiveSet<T> {
         let oper = sel.select_timeout(timeout).ok()?;
         let index = oper.index();
         if let Ok(msg) = oper.recv(&rx[index].rx) {
+            rx[index].count_received();
             return Some((rx[index].source.clone(), msg));
         }
 
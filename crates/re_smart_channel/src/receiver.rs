@@ -47,6 +47,7 @@ impl<T: Send> Receiver<T> {
 
         let latency_ns = msg.time.elapsed().as_nanos() as u64;
         self.stats.latency_ns.store(latency_ns, Relaxed);
+        self.stats.num_received.fetch_add(1, Relaxed);
 
         Ok(msg)
     }
@@ -64,6 +65,7 @@ impl<T: Send> Receiver<T> {
 
         let latency_ns = msg.time.elapsed().as_nanos() as u64;
         self.stats.latency_ns.store(latency_ns, Relaxed);
+        self.stats.num_received.fetch_add(1, Relaxed);
 
         Ok(msg)
     }
@@ -85,11 +87,12 @@ impl<T: Send> Receiver<T> {
 
         let latency_ns = msg.time.elapsed().as_nanos() as u64;
         self.stats.latency_ns.store(latency_ns, Relaxed);
+        self.stats.num_received.fetch_add(1, Relaxed);
 
         Ok(msg)
     }
 
-    /// Receives without registering the latency.
+    /// Receives without registering the latency, nor counting the message as received.
     ///
     /// This is for use with [`crate::Sender::send_at`] when chaining to another channel
     /// created with [`Self::chained_channel`].
@@ -98,6 +101,11 @@ impl<T: Send> Receiver<T> {
         self.rx.recv()
     }
 
+    /// Counts a message received straight from [`Self::rx`], see [`crate::Sender::num_received`].
+    pub(crate) fn count_received(&self) {
+        self.stats.num_received.fetch_add(1, Relaxed);
+    }
+
     /// Where is the data coming from?
     #[inline]
     pub fn source(&self) -> &SmartChannelSource {
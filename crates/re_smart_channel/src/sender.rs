@@ -34,7 +34,9 @@ impl<T: Send> Sender<T> {
             Instant::now(),
             Arc::clone(&self.source),
             SmartMessagePayload::Msg(msg),
-        )
+        )?;
+        self.stats.num_sent.fetch_add(1, Relaxed);
+        Ok(())
     }
 
     /// Forwards a message as-is.
@@ -74,7 +76,9 @@ impl<T: Send> Sender<T> {
             time: Instant::now(),
             source: Arc::clone(&self.source),
             payload: SmartMessagePayload::Quit(err),
-        })
+        })?;
+        self.stats.num_sent.fetch_add(1, Relaxed);
+        Ok(())
     }
 
     /// Is the channel currently empty of messages?
@@ -89,6 +93,24 @@ impl<T: Send> Sender<T> {
         self.tx.len()
     }
 
+    /// Number of messages sent so far with [`Self::send`] and [`Self::quit`], by all senders.
+    ///
+    /// Messages forwarded with [`Self::send_at`] are not counted.
+    #[inline]
+    pub fn num_sent(&self) -> u64 {
+        self.stats.num_sent.load(Relaxed)
+    }
+
+    /// Number of messages received so far at the end of the channel (chain).
+    ///
+    /// Since the channel is first-in first-out, once this reaches the value [`Self::num_sent`]
+    /// had right after a send, that message has been received, unless other senders were still
+    /// in the middle of sending theirs at that time.
+    #[inline]
+    pub fn num_received(&self) -> u64 {
+        self.stats.num_received.load(Relaxed)
+    }
+
     /// Latest known latency from sending a message to receiving it, it nanoseconds.
     pub fn latency_ns(&self) -> u64 {
         self.stats.latency_ns.load(Relaxed)
@@ -50,6 +50,11 @@ impl LoadedMesh {
                 ResourceLifeTime::LongLived,
                 render_ctx,
             )?,
+            MediaType::STL => re_renderer::importer::stl::load_stl_from_buffer(
+                bytes,
+                ResourceLifeTime::LongLived,
+                render_ctx,
+            )?,
             _ => anyhow::bail!("{media_type} files are not supported"),
         };
 
@@ -21,7 +21,7 @@ use ::re_types_core::SerializationResult;
 use ::re_types_core::{ComponentBatch, MaybeOwnedComponentBatch};
 use ::re_types_core::{DeserializationError, DeserializationResult};
 
-/// **Archetype**: A prepacked 3D asset (`.gltf`, `.glb`, `.obj`, etc.).
+/// **Archetype**: A prepacked 3D asset (`.gltf`, `.glb`, `.obj`, `.stl`, etc.).
 ///
 /// See also [`Mesh3D`][crate::archetypes::Mesh3D].
 ///
@@ -34,7 +34,7 @@ use ::re_types_core::{DeserializationError, DeserializationResult};
 /// fn main() -> anyhow::Result<()> {
 ///     let args = std::env::args().collect::<Vec<_>>();
 ///     let Some(path) = args.get(1) else {
-///         anyhow::bail!("Usage: {} <path_to_asset.[gltf|glb|obj]>", args[0]);
+///         anyhow::bail!("Usage: {} <path_to_asset.[gltf|glb|obj|stl]>", args[0]);
 ///     };
 ///
 ///     let rec = rerun::RecordingStreamBuilder::new("rerun_example_asset3d_simple").spawn()?;
@@ -64,6 +64,7 @@ pub struct Asset3D {
     /// Supported values:
     /// * `model/gltf-binary`
     /// * `model/obj` (.mtl material files are not supported yet, references are silently ignored)
+    /// * `model/stl`
     ///
     /// If omitted, the viewer will try to guess from the data blob.
     /// If it cannot guess, it won't be able to render the asset.
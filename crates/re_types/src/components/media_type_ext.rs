@@ -25,6 +25,12 @@ impl MediaType {
     ///
     /// <https://www.iana.org/assignments/media-types/model/obj>
     pub const OBJ: &'static str = "model/obj";
+
+    /// [Stereolithography Model `stl`](https://en.wikipedia.org/wiki/STL_(file_format)): `model/stl`.
+    /// Either binary or ASCII.
+    ///
+    /// <https://www.iana.org/assignments/media-types/model/stl>
+    pub const STL: &'static str = "model/stl";
 }
 
 impl MediaType {
@@ -57,6 +63,12 @@ impl MediaType {
     pub fn obj() -> Self {
         Self(Self::OBJ.into())
     }
+
+    /// `model/stl`
+    #[inline]
+    pub fn stl() -> Self {
+        Self(Self::STL.into())
+    }
 }
 
 impl MediaType {
@@ -72,14 +84,15 @@ impl MediaType {
     pub fn guess_from_path(path: impl AsRef<std::path::Path>) -> Option<Self> {
         let path = path.as_ref();
 
-        // `mime_guess2` considers `.obj` to be a tgif… but really it's way more likely to be an obj.
-        if path
+        let extension = path
             .extension()
-            .and_then(|ext| ext.to_str().map(|s| s.to_lowercase()))
-            .as_deref()
-            == Some("obj")
-        {
-            return Some(Self::obj());
+            .and_then(|ext| ext.to_str().map(|s| s.to_lowercase()));
+        match extension.as_deref() {
+            // `mime_guess2` considers `.obj` to be a tgif… but really it's way more likely to be an obj.
+            Some("obj") => return Some(Self::obj()),
+            // …and `.stl` to be a certificate trust list.
+            Some("stl") => return Some(Self::stl()),
+            _ => {}
         }
 
         mime_guess2::from_path(path)
@@ -95,14 +108,27 @@ impl MediaType {
             buf.len() >= 4 && buf[0] == b'g' && buf[1] == b'l' && buf[2] == b'T' && buf[3] == b'F'
         }
 
+        fn stl_matcher(buf: &[u8]) -> bool {
+            // ASCII files start with `solid`, binary ones have a header of 80 bytes followed by
+            // the number of triangles, which must add up to the size of the file.
+            let is_ascii = buf.starts_with(b"solid") && buf.windows(5).any(|w| w == b"facet");
+            let is_binary = buf.len() >= 84 && {
+                let num_triangles = u32::from_le_bytes([buf[80], buf[81], buf[82], buf[83]]);
+                84 + num_triangles as u64 * 50 == buf.len() as u64
+            };
+            is_ascii || is_binary
+        }
+
         // NOTE:
         // - gltf is simply json, so no magic byte
         //   (also most gltf files contain file:// links, so not much point in sending that to
         //   Rerun for now…)
         // - obj is simply text, so no magic byte
+        // - stl has no magic byte either, but can be recognized from its structure
 
         let mut inferer = infer::Infer::new();
         inferer.add(Self::GLB, "", glb_matcher);
+        inferer.add(Self::STL, "", stl_matcher);
 
         inferer
             .get(data)
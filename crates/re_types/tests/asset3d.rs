@@ -65,4 +65,23 @@ fn roundtrip() {
     similar_asserts::assert_eq!(expected, deserialized);
 }
 
+#[test]
+fn guess_stl_media_type() {
+    assert_eq!(
+        MediaType::guess_from_path("robot/base_link.STL"),
+        Some(MediaType::stl())
+    );
+
+    let ascii = b"solid part\n  facet normal 0 0 1\n  endfacet\nendsolid part\n";
+    assert_eq!(MediaType::guess_from_data(ascii), Some(MediaType::stl()));
+
+    let mut binary = vec![0_u8; 80];
+    binary.extend(1_u32.to_le_bytes());
+    binary.extend([0_u8; 50]);
+    assert_eq!(MediaType::guess_from_data(&binary), Some(MediaType::stl()));
+
+    binary.pop();
+    assert_eq!(MediaType::guess_from_data(&binary), None);
+}
+
 mod util;
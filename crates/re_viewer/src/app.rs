@@ -39,6 +39,16 @@ pub struct StartupOptions {
     /// When the total process RAM reaches this limit, we GC old data.
     pub memory_limit: re_memory::MemoryLimit,
 
+    /// If set, recordings only keep the latest window of data on the given timeline.
+    ///
+    /// Sets [`re_data_store::DataStoreConfig::retention`] for every new recording.
+    pub retention: Option<re_data_store::RetentionPolicy>,
+
+    /// If set, old data is written to this directory when reaching [`Self::memory_limit`],
+    /// rather than being dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub spill_dir: Option<std::path::PathBuf>,
+
     pub persist_state: bool,
 
     /// Whether or not the app is running in the context of a Jupyter Notebook.
@@ -64,6 +74,11 @@ impl Default for StartupOptions {
     fn default() -> Self {
         Self {
             memory_limit: re_memory::MemoryLimit::from_fraction_of_total(0.75),
+            retention: None,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            spill_dir: None,
+
             persist_state: true,
             is_in_notebook: false,
 
@@ -93,6 +108,10 @@ pub struct App {
     build_info: re_build_info::BuildInfo,
     startup_options: StartupOptions,
     ram_limit_warner: re_memory::RamLimitWarner,
+
+    /// When we last enforced [`StartupOptions::retention`].
+    last_retention_gc: Instant,
+
     pub(crate) re_ui: re_ui::ReUi,
     screenshotter: crate::screenshotter::Screenshotter,
 
@@ -214,10 +233,28 @@ impl App {
             .checked_sub(web_time::Duration::from_secs(1_000_000_000))
             .unwrap_or(web_time::Instant::now());
 
+        let recording_config = {
+            let default = re_data_store::DataStoreConfig::DEFAULT;
+            re_data_store::DataStoreConfig {
+                retention: startup_options.retention,
+                // Only a small part of the memory budget goes to what was spilled and read back.
+                spill_cache_max_bytes: startup_options.memory_limit.max_bytes.map_or(
+                    default.spill_cache_max_bytes,
+                    |max_bytes| {
+                        default
+                            .spill_cache_max_bytes
+                            .min(max_bytes.max(0) as u64 / 10)
+                    },
+                ),
+                ..default
+            }
+        };
+
         Self {
             build_info,
             startup_options,
             ram_limit_warner: re_memory::RamLimitWarner::warn_at_fraction_of_max(0.75),
+            last_retention_gc: Instant::now(),
             re_ui,
             screenshotter,
 
@@ -231,7 +268,7 @@ impl App {
             open_files_promise: Default::default(),
             state,
             background_tasks: Default::default(),
-            store_hub: Some(StoreHub::new()),
+            store_hub: Some(StoreHub::new(recording_config)),
             toasts: toasts::Toasts::new(),
             memory_panel: Default::default(),
             memory_panel_open: false,
@@ -654,7 +691,7 @@ impl App {
             .and_then(|ctx| ctx.recording)
             .and_then(|rec| rec.data_source.as_ref())
         {
-            Some(SmartChannelSource::RrdHttpStream { url }) => format!("{href}/?url={url}"),
+            Some(SmartChannelSource::RrdHttpStream { url, .. }) => format!("{href}/?url={url}"),
             _ => href,
         };
         self.re_ui
@@ -907,6 +944,21 @@ impl App {
         let mem_use_before = MemoryUse::capture();
 
         if let Some(minimum_fraction_to_purge) = limit.is_exceeded_by(&mem_use_before) {
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(spill_dir) = &self.startup_options.spill_dir {
+                // Nothing is lost by spilling, so don't hold back.
+                let fraction_to_spill = (minimum_fraction_to_purge + 0.2).clamp(0.25, 1.0);
+                if store_hub.spill_fraction_to_disk(spill_dir, fraction_to_spill) > 0 {
+                    re_log::info_once!(
+                        "Reached memory limit of {}, spilling oldest data to {spill_dir:?}.",
+                        format_limit(limit.max_bytes)
+                    );
+                    self.memory_panel.note_memory_purge();
+                    return;
+                }
+                // Nothing left to spill: fall back to dropping data.
+            }
+
             re_log::info_once!(
                 "Reached memory limit of {}, dropping oldest data.",
                 format_limit(limit.max_bytes)
@@ -951,6 +1003,22 @@ impl App {
         }
     }
 
+    fn enforce_retention_if_needed(&mut self, store_hub: &mut StoreHub) {
+        re_tracing::profile_function!();
+
+        if self.startup_options.retention.is_none() {
+            return;
+        }
+
+        // Every GC has to go through all rows, so don't do it every frame.
+        if self.last_retention_gc.elapsed() < web_time::Duration::from_secs(1) {
+            return;
+        }
+        self.last_retention_gc = Instant::now();
+
+        store_hub.enforce_retention();
+    }
+
     /// Reset the viewer to how it looked the first time you ran it.
     fn reset(&mut self, store_hub: &mut StoreHub, egui_ctx: &egui::Context) {
         self.state = Default::default();
@@ -1038,7 +1106,7 @@ impl App {
 
                 // The workflows associated with these sources typically do not require showing the
                 // welcome screen until after some recording have been loaded and then closed.
-                SmartChannelSource::RrdWebEventListener
+                SmartChannelSource::RrdWebEventListener { .. }
                 | SmartChannelSource::Sdk
                 | SmartChannelSource::WsClient { .. } => {}
 
@@ -1046,7 +1114,8 @@ impl App {
                 // the welcome screen (default, "new user" workflow). There are other case using Tcp
                 // where it's not the case, including Python/C++ SDKs and possibly other, advanced used,
                 // scenarios. In this cases, `--skip-welcome-screen` should be used.
-                SmartChannelSource::TcpServer { .. } => {
+                SmartChannelSource::TcpServer { .. }
+                | SmartChannelSource::UnixSocketServer { .. } => {
                     return true;
                 }
             }
@@ -1143,6 +1212,7 @@ impl eframe::App for App {
         self.check_keyboard_shortcuts(egui_ctx);
 
         self.purge_memory_if_needed(&mut store_hub);
+        self.enforce_retention_if_needed(&mut store_hub);
 
         self.state.cache.begin_frame();
 
@@ -375,12 +375,15 @@ fn recording_config_entry<'cfgs>(
                 // We assume the `RrdHttpStream` is a done recording.
                 re_smart_channel::SmartChannelSource::File(_)
                 | re_smart_channel::SmartChannelSource::RrdHttpStream { .. }
-                | re_smart_channel::SmartChannelSource::RrdWebEventListener => PlayState::Playing,
+                | re_smart_channel::SmartChannelSource::RrdWebEventListener { .. } => {
+                    PlayState::Playing
+                }
 
                 // Live data - follow it!
                 re_smart_channel::SmartChannelSource::Sdk
                 | re_smart_channel::SmartChannelSource::WsClient { .. }
                 | re_smart_channel::SmartChannelSource::TcpServer { .. }
+                | re_smart_channel::SmartChannelSource::UnixSocketServer { .. }
                 | re_smart_channel::SmartChannelSource::Stdin => PlayState::Following,
             }
         } else {
@@ -56,10 +56,12 @@ impl StoreHub {
     /// The [`StoreHub`] will contain a single empty blueprint associated with the app ID returned
     /// by `[StoreHub::welcome_screen_app_id]`. It should be used as a marker to display the welcome
     /// screen.
-    pub fn new() -> Self {
+    ///
+    /// The stores of new recordings are configured by `recording_config`.
+    pub fn new(recording_config: DataStoreConfig) -> Self {
         re_tracing::profile_function!();
         let mut blueprint_by_app_id = HashMap::new();
-        let mut store_bundle = StoreBundle::default();
+        let mut store_bundle = StoreBundle::with_recording_config(recording_config);
 
         let welcome_screen_store_id = StoreId::from_string(
             StoreKind::Blueprint,
@@ -210,6 +212,31 @@ impl StoreHub {
         self.store_bundle.purge_empty();
     }
 
+    /// Call [`EntityDb::enforce_retention`] on every recording.
+    pub fn enforce_retention(&mut self) {
+        re_tracing::profile_function!();
+
+        for entity_db in self.store_bundle.entity_dbs_mut() {
+            if entity_db.store_kind() == StoreKind::Recording {
+                entity_db.enforce_retention();
+            }
+        }
+    }
+
+    /// Call [`EntityDb::spill_fraction_to_disk`] on every recording.
+    ///
+    /// Returns how many bytes were freed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spill_fraction_to_disk(&mut self, dir: &std::path::Path, fraction_to_spill: f32) -> u64 {
+        re_tracing::profile_function!();
+
+        self.store_bundle
+            .entity_dbs_mut()
+            .filter(|entity_db| entity_db.store_kind() == StoreKind::Recording)
+            .map(|entity_db| entity_db.spill_fraction_to_disk(dir, fraction_to_spill))
+            .sum()
+    }
+
     /// Call [`EntityDb::purge_fraction_of_ram`] on every recording
     //
     // NOTE: If you touch any of this, make sure to play around with our GC stress test scripts
@@ -288,7 +315,7 @@ impl StoreHub {
             // - aren't network sources
             // - don't point at the given `uri`
             match data_source {
-                re_smart_channel::SmartChannelSource::RrdHttpStream { url } => url != uri,
+                re_smart_channel::SmartChannelSource::RrdHttpStream { url, .. } => url != uri,
                 re_smart_channel::SmartChannelSource::WsClient { ws_server_url } => {
                     ws_server_url != uri
                 }
@@ -435,9 +462,19 @@ pub enum StoreLoadError {
 pub struct StoreBundle {
     // TODO(emilk): two separate maps per [`StoreKind`].
     entity_dbs: ahash::HashMap<StoreId, EntityDb>,
+
+    /// The config of the stores of the recordings created by this bundle.
+    recording_config: DataStoreConfig,
 }
 
 impl StoreBundle {
+    pub fn with_recording_config(recording_config: DataStoreConfig) -> Self {
+        Self {
+            entity_dbs: Default::default(),
+            recording_config,
+        }
+    }
+
     /// Decode an rrd stream.
     /// It can theoretically contain multiple recordings, and blueprints.
     pub fn from_rrd(
@@ -460,9 +497,10 @@ impl StoreBundle {
     /// Returns either a recording or blueprint [`EntityDb`].
     /// One is created if it doesn't already exist.
     pub fn entity_db_entry(&mut self, id: &StoreId) -> &mut EntityDb {
-        self.entity_dbs
-            .entry(id.clone())
-            .or_insert_with(|| EntityDb::new(id.clone()))
+        match id.kind {
+            StoreKind::Recording => self.recording_entry(id),
+            StoreKind::Blueprint => self.blueprint_entry(id),
+        }
     }
 
     /// All loaded [`EntityDb`], both recordings and blueprints, in arbitrary order.
@@ -542,9 +580,9 @@ impl StoreBundle {
     /// Creates one if it doesn't exist.
     pub fn recording_entry(&mut self, id: &StoreId) -> &mut EntityDb {
         debug_assert_eq!(id.kind, StoreKind::Recording);
-        self.entity_dbs
-            .entry(id.clone())
-            .or_insert_with(|| EntityDb::new(id.clone()))
+        self.entity_dbs.entry(id.clone()).or_insert_with(|| {
+            EntityDb::with_store_config(id.clone(), self.recording_config.clone())
+        })
     }
 
     pub fn insert_recording(&mut self, entity_db: EntityDb) {
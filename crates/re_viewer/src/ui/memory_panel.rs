@@ -275,6 +275,7 @@ impl MemoryPanel {
                     timeless,
                     temporal,
                     temporal_buckets,
+                    spill_cache,
                     total,
                 } = *store_stats;
 
@@ -319,6 +320,12 @@ impl MemoryPanel {
                 label_row_stats(ui, temporal);
                 ui.end_row();
 
+                ui.label("Spill cache:")
+                    .on_hover_text("Data spilled to disk, read back and kept around for a while");
+                ui.label("");
+                label_row_stats(ui, spill_cache);
+                ui.end_row();
+
                 ui.label("Total");
                 ui.label(re_format::format_number(temporal_buckets as _));
                 label_row_stats(ui, total);
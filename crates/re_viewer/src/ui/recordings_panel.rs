@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use re_data_ui::DataUi;
 use re_log_types::LogMsg;
-use re_smart_channel::{ReceiveSet, SmartChannelSource};
+use re_smart_channel::{ClientStats, ReceiveSet, SmartChannelSource};
 use re_viewer_context::{SystemCommand, SystemCommandSender, ViewerContext};
 
 /// Show the currently open Recordings in a selectable list.
@@ -38,6 +38,8 @@ pub fn recordings_panel_ui(
                 // They will likely end up here as recordings soon.
                 any_shown |= loading_receivers_ui(ctx, rx, ui);
 
+                any_shown |= connected_clients_ui(ctx, rx, ui);
+
                 any_shown
             })
         })
@@ -62,12 +64,13 @@ fn loading_receivers_ui(
         let string = match source.as_ref() {
             // We only show things we know are very-soon-to-be recordings:
             SmartChannelSource::File(path) => format!("Loading {}…", path.display()),
-            SmartChannelSource::RrdHttpStream { url } => format!("Loading {url}…"),
+            SmartChannelSource::RrdHttpStream { url, .. } => format!("Loading {url}…"),
 
-            SmartChannelSource::RrdWebEventListener
+            SmartChannelSource::RrdWebEventListener { .. }
             | SmartChannelSource::Sdk
             | SmartChannelSource::WsClient { .. }
             | SmartChannelSource::TcpServer { .. }
+            | SmartChannelSource::UnixSocketServer { .. }
             | SmartChannelSource::Stdin => {
                 // These show up in the top panel - see `top_panel.rs`.
                 continue;
@@ -93,15 +96,99 @@ fn loading_receivers_ui(
                     resp
                 })
                 .show(ui);
-            if let SmartChannelSource::TcpServer { .. } = source.as_ref() {
+            if let SmartChannelSource::TcpServer { .. }
+            | SmartChannelSource::UnixSocketServer { .. } = source.as_ref()
+            {
                 response.on_hover_text("You can connect to this viewer from a Rerun SDK");
             }
+            progress_bar_ui(ui, source.as_ref());
         }
     }
 
     any_shown
 }
 
+/// Show the SDKs that are, or recently were, connected to our servers.
+///
+/// Returns `true` if any were shown.
+fn connected_clients_ui(
+    ctx: &ViewerContext<'_>,
+    rx: &ReceiveSet<LogMsg>,
+    ui: &mut egui::Ui,
+) -> bool {
+    let clients = rx
+        .sources()
+        .iter()
+        .flat_map(|source| match source.as_ref() {
+            SmartChannelSource::TcpServer { clients, .. }
+            | SmartChannelSource::UnixSocketServer { clients, .. } => clients.snapshot(),
+            _ => vec![],
+        })
+        .collect::<Vec<_>>();
+    if clients.is_empty() {
+        return false;
+    }
+
+    ctx.re_ui
+        .list_item("SDK clients")
+        .active(false)
+        .show_collapsing(ui, ui.make_persistent_id("sdk_clients"), true, |_, ui| {
+            for stats in &clients {
+                let title = format!(
+                    "{} - {}",
+                    stats.source,
+                    re_format::format_bytes(stats.bytes_received as _)
+                );
+                ctx.re_ui
+                    .list_item(title)
+                    .weak(!stats.connected)
+                    .show(ui)
+                    .on_hover_ui(|ui| client_stats_ui(ui, stats));
+            }
+        });
+
+    true
+}
+
+fn client_stats_ui(ui: &mut egui::Ui, stats: &ClientStats) {
+    egui::Grid::new("client stats grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Status");
+            ui.label(if stats.connected {
+                "Connected"
+            } else {
+                "Disconnected"
+            });
+            ui.end_row();
+
+            ui.label("Received");
+            ui.label(format!(
+                "{} in {} messages",
+                re_format::format_bytes(stats.bytes_received as _),
+                re_format::format_number(stats.messages_received as _)
+            ));
+            ui.end_row();
+
+            ui.label("Dropped");
+            ui.label(format!(
+                "{} messages",
+                re_format::format_number(stats.messages_dropped as _)
+            ))
+            .on_hover_text("Dropped to keep the latency under --drop-at-latency");
+            ui.end_row();
+
+            ui.label("Latency");
+            ui.label(format!("{:.0} ms", 1e3 * stats.latency_sec));
+            ui.end_row();
+
+            ui.label("Throttled");
+            ui.label(format!("{:.1} s", stats.throttled_sec))
+                .on_hover_text("Time spent waiting to keep this SDK under --max-client-rate");
+            ui.end_row();
+        });
+}
+
 /// Draw the recording list.
 ///
 /// Returns `true` if any recordings were shown.
@@ -212,6 +299,10 @@ fn recording_ui(
         );
     });
 
+    if let Some(source) = &entity_db.data_source {
+        progress_bar_ui(ui, source);
+    }
+
     if response.hovered() {
         ctx.selection_state().set_hovered(item.clone());
     }
@@ -229,6 +320,25 @@ fn recording_ui(
     }
 }
 
+/// A progress bar while the source is still being loaded, nothing otherwise.
+fn progress_bar_ui(ui: &mut egui::Ui, source: &SmartChannelSource) {
+    let (SmartChannelSource::RrdHttpStream { progress, .. }
+    | SmartChannelSource::RrdWebEventListener { progress }) = source
+    else {
+        return;
+    };
+
+    let progress_bar = match (progress.fraction(), progress.loaded_bytes()) {
+        (Some(fraction), _) if fraction < 1.0 => egui::ProgressBar::new(fraction).show_percentage(),
+        // Without the total size, all we can tell is how much has been loaded so far.
+        (None, loaded_bytes) if 0 < loaded_bytes => egui::ProgressBar::new(0.0)
+            .animate(true)
+            .text(re_format::format_bytes(loaded_bytes as _)),
+        _ => return,
+    };
+    ui.add(progress_bar);
+}
+
 fn add_button_ui(ctx: &ViewerContext<'_>, ui: &mut egui::Ui) {
     use re_ui::UICommandSender;
 
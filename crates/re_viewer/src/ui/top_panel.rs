@@ -106,10 +106,11 @@ fn connection_status_ui(ui: &mut egui::Ui, rx: &ReceiveSet<re_log_types::LogMsg>
                     false // These show up in the recordings panel as a "Loading…" in `recordings_panel.rs`
                 }
 
-                re_smart_channel::SmartChannelSource::RrdWebEventListener
+                re_smart_channel::SmartChannelSource::RrdWebEventListener { .. }
                 | re_smart_channel::SmartChannelSource::Sdk
                 | re_smart_channel::SmartChannelSource::WsClient { .. }
-                | re_smart_channel::SmartChannelSource::TcpServer { .. } => true,
+                | re_smart_channel::SmartChannelSource::TcpServer { .. }
+                | re_smart_channel::SmartChannelSource::UnixSocketServer { .. } => true,
             }
         })
         .collect_vec();
@@ -139,11 +140,11 @@ fn connection_status_ui(ui: &mut egui::Ui, rx: &ReceiveSet<re_log_types::LogMsg>
             SmartChannelSource::File(_)
             | SmartChannelSource::Stdin
             | SmartChannelSource::RrdHttpStream { .. }
-            | SmartChannelSource::RrdWebEventListener
+            | SmartChannelSource::RrdWebEventListener { .. }
             | SmartChannelSource::Sdk
             | SmartChannelSource::WsClient { .. } => None,
 
-            SmartChannelSource::TcpServer { .. } => {
+            SmartChannelSource::TcpServer { .. } | SmartChannelSource::UnixSocketServer { .. } => {
                 Some("Waiting for an SDK to connect".to_owned())
             }
         };
@@ -161,10 +162,10 @@ fn connection_status_ui(ui: &mut egui::Ui, rx: &ReceiveSet<re_log_types::LogMsg>
                 format!("Loading {}…", path.display())
             }
             re_smart_channel::SmartChannelSource::Stdin => "Loading stdin…".to_owned(),
-            re_smart_channel::SmartChannelSource::RrdHttpStream { url } => {
+            re_smart_channel::SmartChannelSource::RrdHttpStream { url, .. } => {
                 format!("Loading {url}…")
             }
-            re_smart_channel::SmartChannelSource::RrdWebEventListener => {
+            re_smart_channel::SmartChannelSource::RrdWebEventListener { .. } => {
                 "Waiting for logging data…".to_owned()
             }
             re_smart_channel::SmartChannelSource::Sdk => {
@@ -174,9 +175,12 @@ fn connection_status_ui(ui: &mut egui::Ui, rx: &ReceiveSet<re_log_types::LogMsg>
                 // TODO(emilk): it would be even better to know whether or not we are connected, or are attempting to connect
                 format!("Waiting for data from {ws_server_url}")
             }
-            re_smart_channel::SmartChannelSource::TcpServer { port } => {
+            re_smart_channel::SmartChannelSource::TcpServer { port, .. } => {
                 format!("Listening on TCP port {port}")
             }
+            re_smart_channel::SmartChannelSource::UnixSocketServer { path, .. } => {
+                format!("Listening on {}", path.display())
+            }
         }
     }
 }
@@ -377,7 +377,7 @@ impl ExamplePage {
 
 fn is_loading(rx: &ReceiveSet<LogMsg>, example: &ExampleDesc) -> bool {
     rx.sources().iter().any(|s| {
-        if let re_smart_channel::SmartChannelSource::RrdHttpStream { url } = s.as_ref() {
+        if let re_smart_channel::SmartChannelSource::RrdHttpStream { url, .. } = s.as_ref() {
             url == &example.rrd_url
         } else {
             false
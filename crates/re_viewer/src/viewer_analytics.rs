@@ -233,6 +233,9 @@ impl ViewerAnalytics {
                 re_smart_channel::SmartChannelSource::Sdk => "sdk", // show()
                 re_smart_channel::SmartChannelSource::WsClient { .. } => "ws_client", // spawn()
                 re_smart_channel::SmartChannelSource::TcpServer { .. } => "tcp_server", // connect()
+                re_smart_channel::SmartChannelSource::UnixSocketServer { .. } => {
+                    "unix_socket_server"
+                } // connect_unix()
                 re_smart_channel::SmartChannelSource::Stdin => "stdin",
             };
             self.register("data_source", data_source);
@@ -96,9 +96,14 @@ impl WebHandle {
         let Some(mut app) = self.runner.app_mut::<crate::App>() else {
             return;
         };
-        app.msg_receive_set().remove_by_uri(url);
+        // WebSocket sources are known by their url without its token, see `connect_to_ws_url`.
+        let uri = match categorize_uri(url) {
+            EndpointCategory::WebSocket(url) => re_ws_comms::redact_token(&url),
+            EndpointCategory::HttpRrd(_) | EndpointCategory::WebEventListener => url.to_owned(),
+        };
+        app.msg_receive_set().remove_by_uri(&uri);
         if let Some(store_hub) = app.store_hub.as_mut() {
-            store_hub.remove_recording_by_uri(url);
+            store_hub.remove_recording_by_uri(&uri);
         }
     }
 }
@@ -115,6 +120,7 @@ fn create_app(
             // On wasm32 we only have 4GB of memory to play around with.
             max_bytes: Some(2_500_000_000),
         },
+        retention: None,
         location: Some(cc.integration_info.web_info.location.clone()),
         persist_state: get_persist_state(&cc.integration_info),
         is_in_notebook: is_in_notebook(&cc.integration_info),
@@ -167,9 +173,12 @@ fn url_to_receiver(
         }
         EndpointCategory::WebEventListener => {
             // Process an rrd when it's posted via `window.postMessage`
+            let progress = re_smart_channel::LoadProgress::new();
             let (tx, rx) = re_smart_channel::smart_channel(
                 re_smart_channel::SmartMessageSource::RrdWebEventCallback,
-                re_smart_channel::SmartChannelSource::RrdWebEventListener,
+                re_smart_channel::SmartChannelSource::RrdWebEventListener {
+                    progress: progress.clone(),
+                },
             );
             re_log_encoding::stream_rrd_from_http::stream_rrd_from_event_listener(Arc::new({
                 move |msg| {
@@ -184,6 +193,13 @@ fn url_to_receiver(
                                 ControlFlow::Break(())
                             }
                         }
+                        HttpMessage::Progress {
+                            loaded_bytes,
+                            total_bytes,
+                        } => {
+                            progress.set(loaded_bytes, total_bytes);
+                            ControlFlow::Continue(())
+                        }
                         HttpMessage::Success => {
                             tx.quit(None).warn_on_err_once("failed to send quit marker");
                             ControlFlow::Break(())
@@ -199,7 +215,12 @@ fn url_to_receiver(
             rx
         }
         EndpointCategory::WebSocket(url) => re_data_source::connect_to_ws_url(&url, Some(ui_waker))
-            .unwrap_or_else(|err| panic!("Failed to connect to WebSocket server at {url}: {err}")),
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Failed to connect to WebSocket server at {}: {err}",
+                    re_ws_comms::redact_token(&url)
+                )
+            }),
     }
 }
 
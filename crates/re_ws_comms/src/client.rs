@@ -6,15 +6,19 @@ use ewebsock::{WsEvent, WsMessage};
 pub type Result<T> = anyhow::Result<T>;
 
 /// Connect viewer to server
+///
+/// Servers that only accept authenticated viewers expect their token in the url,
+/// see [`crate::url_with_token`].
 pub fn viewer_to_server(
     url: String,
     on_binary_msg: impl Fn(Vec<u8>) -> ControlFlow<()> + Send + 'static,
 ) -> Result<()> {
+    let redacted_url = crate::redact_token(&url);
     ewebsock::ws_receive(
-        url.clone(),
+        url,
         Box::new(move |event: WsEvent| match event {
             WsEvent::Opened => {
-                re_log::info!("Connection to {url} established");
+                re_log::info!("Connection to {redacted_url} established");
                 ControlFlow::Continue(())
             }
             WsEvent::Message(message) => match message {
@@ -41,7 +45,7 @@ pub fn viewer_to_server(
                 ControlFlow::Break(())
             }
             WsEvent::Closed => {
-                re_log::info!("Connection to {url} closed.");
+                re_log::info!("Connection to {redacted_url} closed.");
                 ControlFlow::Break(())
             }
         }),
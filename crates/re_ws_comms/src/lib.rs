@@ -14,7 +14,10 @@ pub use client::viewer_to_server;
 #[cfg(feature = "server")]
 mod server;
 #[cfg(feature = "server")]
-pub use server::{RerunServer, RerunServerHandle};
+pub use server::{RerunServer, RerunServerHandle, RerunServerOptions};
+
+mod query;
+pub use query::{redact_token, url_with_token, HistoryQuery, HistoryWindow};
 
 use re_log_types::LogMsg;
 
@@ -79,11 +82,15 @@ impl FromStr for RerunServerPort {
 
 /// Add a protocol (`ws://` or `wss://`) to the given address.
 pub fn server_url(local_addr: &std::net::SocketAddr) -> String {
+    server_url_with_protocol(PROTOCOL, local_addr)
+}
+
+fn server_url_with_protocol(protocol: &str, local_addr: &std::net::SocketAddr) -> String {
     if local_addr.ip().is_unspecified() {
         // "0.0.0.0"
-        format!("{PROTOCOL}://localhost:{}", local_addr.port())
+        format!("{protocol}://localhost:{}", local_addr.port())
     } else {
-        format!("{PROTOCOL}://{local_addr}")
+        format!("{protocol}://{local_addr}")
     }
 }
 
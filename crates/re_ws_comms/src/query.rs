@@ -0,0 +1,215 @@
+//! What a viewer asks of the `RerunServer` when it connects.
+//!
+//! Without a query, the server replays everything it still has before streaming new data.
+//! That can mean gigabytes for a late joiner, so a viewer can instead ask for a single store,
+//! a time window on some timeline, or only the latest data of each entity.
+//!
+//! The query is part of the url the viewer connects to, e.g. `ws://localhost:9877?timeline=frame&latest`,
+//! so it works with any WebSocket client.
+
+use re_log_types::{StoreId, StoreKind, TimeInt, TimeRange, TimelineName};
+
+/// Which part of its history the server should send to a newly connected viewer.
+///
+/// The query only affects the history: once it has been sent, the viewer receives all new
+/// messages of the queried store as they come in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HistoryQuery {
+    /// Only send messages of this store. `None` means all stores.
+    pub store_id: Option<StoreId>,
+
+    /// Which messages of the history to send.
+    pub window: HistoryWindow,
+}
+
+/// See [`HistoryQuery::window`].
+///
+/// Store infos and timeless data are always sent, whatever the window.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum HistoryWindow {
+    /// Everything the server still has.
+    #[default]
+    All,
+
+    /// The messages with data in `range` on `timeline`.
+    Range {
+        timeline: TimelineName,
+        range: TimeRange,
+    },
+
+    /// For each component of each entity, only the latest message on `timeline`.
+    Latest { timeline: TimelineName },
+}
+
+impl HistoryQuery {
+    /// Reads the query from the parameters of a `ws://` url, ignoring parameters it doesn't know.
+    ///
+    /// * `store=<id>`: only this recording.
+    /// * `timeline=<name>`: the timeline `latest`, `from` and `to` refer to.
+    /// * `latest`: only the latest data of each component of each entity.
+    /// * `from=<time>` and `to=<time>`: only the data within this inclusive range, in raw
+    ///   timeline units, i.e. sequence numbers or nanoseconds. Both are optional.
+    ///
+    /// For instance `ws://localhost:9877?timeline=frame&from=100` or `ws://localhost:9877?timeline=log_time&latest`.
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        let mut store_id = None;
+        let mut timeline = None;
+        let mut latest = false;
+        let mut range: Option<TimeRange> = None;
+
+        // Errors end up in the logs.
+        let redacted_url = redact_token(url);
+        let parse_time = |key: &str, value: &str| {
+            value
+                .parse::<i64>()
+                .map(TimeInt::from)
+                .map_err(|err| format!("Bad {key:?} in {redacted_url:?}: {err}"))
+        };
+
+        for (key, value) in url_params(url) {
+            match key {
+                "store" => {
+                    store_id = Some(StoreId::from_string(StoreKind::Recording, value.to_owned()));
+                }
+                "timeline" => timeline = Some(TimelineName::new(value)),
+                "latest" => latest = value != "false",
+                "from" => {
+                    range.get_or_insert(TimeRange::EVERYTHING).min = parse_time(key, value)?;
+                }
+                "to" => {
+                    range.get_or_insert(TimeRange::EVERYTHING).max = parse_time(key, value)?;
+                }
+                _ => {}
+            }
+        }
+
+        let window = match (timeline, latest, range) {
+            (_, true, Some(_)) => {
+                return Err(format!(
+                    "{redacted_url:?} asks for both the latest data and a time range"
+                ));
+            }
+            (Some(timeline), true, None) => HistoryWindow::Latest { timeline },
+            (Some(timeline), false, Some(range)) => HistoryWindow::Range { timeline, range },
+            (None, true, _) | (None, _, Some(_)) => {
+                return Err(format!("{redacted_url:?} is missing the timeline to query"));
+            }
+            (_, false, None) => HistoryWindow::All,
+        };
+
+        Ok(Self { store_id, window })
+    }
+}
+
+/// The `key=value` pairs after the `?` of the url.
+///
+/// The value of a lone `key` is empty.
+pub(crate) fn url_params(url: &str) -> impl Iterator<Item = (&str, &str)> {
+    let params = url.split_once('?').map_or("", |(_, params)| params);
+    params
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| param.split_once('=').unwrap_or((param, "")))
+}
+
+/// Adds the shared secret a WebSocket server may require as the `token` parameter of the url.
+///
+/// Also adds the root path if the url has none, as not all WebSocket clients do that themselves.
+pub fn url_with_token(url: &str, token: &str) -> String {
+    let (base, params) = match url.split_once('?') {
+        Some((base, params)) => (base, format!("{params}&")),
+        None => (url, String::new()),
+    };
+    let host_and_path = base.split_once("://").map_or(base, |(_, rest)| rest);
+    let root = if host_and_path.contains('/') { "" } else { "/" };
+    format!("{base}{root}?{params}token={token}")
+}
+
+/// Hides the value of any `token` parameter, so the url can be logged or shown.
+///
+/// Also works for urls nested in other urls, e.g. the `?url=` of a web viewer.
+pub fn redact_token(url: &str) -> String {
+    const KEY: &str = "token=";
+
+    let mut redacted = String::with_capacity(url.len());
+    let mut rest = url;
+    while let Some(start) = rest.find(KEY) {
+        let (before, after) = rest.split_at(start + KEY.len());
+        redacted.push_str(before);
+        if before[..start].ends_with(['?', '&']) {
+            redacted.push_str("<redacted>");
+            rest = after.find('&').map_or("", |end| &after[end..]);
+        } else {
+            rest = after;
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_from_url() {
+        assert_eq!(
+            HistoryQuery::from_url("ws://localhost:9877"),
+            Ok(HistoryQuery::default())
+        );
+        assert_eq!(
+            HistoryQuery::from_url("ws://localhost:9877?store=rec&timeline=frame&latest"),
+            Ok(HistoryQuery {
+                store_id: Some(StoreId::from_string(StoreKind::Recording, "rec".to_owned())),
+                window: HistoryWindow::Latest {
+                    timeline: "frame".into()
+                },
+            })
+        );
+        assert_eq!(
+            HistoryQuery::from_url("ws://localhost:9877/?foo=bar&timeline=frame&from=-3"),
+            Ok(HistoryQuery {
+                store_id: None,
+                window: HistoryWindow::Range {
+                    timeline: "frame".into(),
+                    range: TimeRange::new((-3).into(), TimeInt::MAX),
+                },
+            })
+        );
+        assert!(HistoryQuery::from_url("ws://localhost:9877?from=1").is_err());
+        assert!(HistoryQuery::from_url("ws://localhost:9877?timeline=frame&to=x").is_err());
+        assert!(HistoryQuery::from_url("ws://localhost:9877?timeline=frame&latest&to=2").is_err());
+    }
+
+    #[test]
+    fn add_token_to_url() {
+        assert_eq!(
+            url_with_token("ws://localhost:9877", "secret"),
+            "ws://localhost:9877/?token=secret"
+        );
+        assert_eq!(
+            url_with_token("wss://localhost:9877/?timeline=frame&latest", "secret"),
+            "wss://localhost:9877/?timeline=frame&latest&token=secret"
+        );
+    }
+
+    #[test]
+    fn redact_token_in_url() {
+        assert_eq!(
+            redact_token("ws://localhost:9877/?token=secret"),
+            "ws://localhost:9877/?token=<redacted>"
+        );
+        assert_eq!(
+            redact_token("ws://localhost:9877/?token=secret&latest&mytoken=foo"),
+            "ws://localhost:9877/?token=<redacted>&latest&mytoken=foo"
+        );
+        assert_eq!(
+            redact_token("http://localhost:9090?url=ws://localhost:9877/?latest&token=secret"),
+            "http://localhost:9090?url=ws://localhost:9877/?latest&token=<redacted>"
+        );
+        assert_eq!(
+            redact_token("ws://localhost:9877/?timeline=frame"),
+            "ws://localhost:9877/?timeline=frame"
+        );
+    }
+}
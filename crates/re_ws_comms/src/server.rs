@@ -1,28 +1,247 @@
 //! The server is a pub-sub architecture.
 //!
 //! Each incoming log message is stored, and sent to any connected client.
-//! Each connecting client is first sent the history of stored log messages.
+//! Each connecting client is first sent the part of the stored history it asked for
+//! with a [`HistoryQuery`] in the url it connected to.
 //!
-//! In the future thing will be changed to a protocol where the clients can query
-//! for specific data based on e.g. time.
+//! The history is indexed by time as messages arrive, so answering a query only touches the
+//! messages it returns.
 
-use std::{collections::VecDeque, net::SocketAddr, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    net::SocketAddr,
+    sync::Arc,
+};
 
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::Mutex;
-use tokio::net::{TcpListener, TcpStream};
-use tokio_tungstenite::{accept_async, tungstenite::Error};
-
-use re_log_types::LogMsg;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpStream},
+};
+use tokio_tungstenite::{
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{ErrorResponse, Request, Response},
+        http::StatusCode,
+        Error,
+    },
+};
+
+use re_log_types::{
+    external::re_types_core::ComponentName, ArrowMsg, DataTable, EntityPath, LogMsg, StoreId,
+    TimeInt, TimelineName,
+};
 use re_memory::MemoryLimit;
 use re_smart_channel::ReceiveSet;
 
-use crate::{server_url, RerunServerError, RerunServerPort};
+use crate::{
+    query::url_params, server_url, server_url_with_protocol, HistoryQuery, HistoryWindow,
+    RerunServerError, RerunServerPort,
+};
+
+/// Identifies a message of the history. Increases with each message, so also orders them.
+type MsgId = u64;
+
+/// An encoded log message of the history.
+struct HistoryMsg {
+    bytes: Arc<[u8]>,
+    store_id: StoreId,
+
+    /// `None` for store infos.
+    summary: Option<DataSummary>,
+}
+
+impl HistoryMsg {
+    /// Encodes and summarizes the message, which takes a while: don't hold the history lock.
+    fn new(msg: &LogMsg) -> Self {
+        Self {
+            bytes: crate::encode_log_msg(msg).into(),
+            store_id: msg.store_id().clone(),
+            summary: match msg {
+                LogMsg::SetStoreInfo(_) => None,
+                LogMsg::ArrowMsg(_, arrow_msg) => Some(DataSummary::new(arrow_msg)),
+            },
+        }
+    }
+
+    fn is_store_info(&self) -> bool {
+        self.summary.is_none()
+    }
+
+    /// Store infos and timeless data are needed whatever the time window.
+    fn is_always_sent(&self) -> bool {
+        self.summary
+            .as_ref()
+            .map_or(true, |data| data.has_timeless_rows)
+    }
+}
+
+/// Where and when the data of a message was logged, as needed by the [`HistoryIndex`].
+#[derive(Default)]
+struct DataSummary {
+    /// Whether some rows aren't on any timeline.
+    has_timeless_rows: bool,
+
+    /// The times of the rows on each timeline.
+    times: BTreeMap<TimelineName, BTreeSet<TimeInt>>,
+
+    /// The latest time each component of each entity was logged at, on each timeline.
+    latest: BTreeMap<(TimelineName, EntityPath, ComponentName), TimeInt>,
+}
+
+impl DataSummary {
+    fn new(arrow_msg: &ArrowMsg) -> Self {
+        re_tracing::profile_function!();
+
+        let table = match DataTable::from_arrow_msg(arrow_msg) {
+            Ok(table) => table,
+            Err(err) => {
+                // We still relay it, but can't tell when it happened: treat it as timeless.
+                re_log::warn_once!("Failed to index log message: {err}");
+                return Self {
+                    has_timeless_rows: true,
+                    ..Default::default()
+                };
+            }
+        };
+
+        let mut summary = Self::default();
+        for (row, entity_path) in table.col_entity_path.iter().enumerate() {
+            let mut is_timeless = true;
+            for (timeline, times) in &table.col_timelines {
+                let Some(time) = times.get(row).copied().flatten() else {
+                    continue;
+                };
+                is_timeless = false;
+
+                let time = TimeInt::from(time);
+                summary
+                    .times
+                    .entry(*timeline.name())
+                    .or_default()
+                    .insert(time);
+                for (component, column) in &table.columns {
+                    if column.get(row).map_or(false, Option::is_some) {
+                        let latest = summary
+                            .latest
+                            .entry((*timeline.name(), entity_path.clone(), *component))
+                            .or_insert(time);
+                        *latest = time.max(*latest);
+                    }
+                }
+            }
+            summary.has_timeless_rows |= is_timeless;
+        }
+        summary
+    }
+}
+
+/// The messages with data at each time.
+type MsgsByTime = BTreeMap<TimeInt, BTreeSet<MsgId>>;
+
+/// Which messages of the history have data on which timeline, when.
+#[derive(Default)]
+struct HistoryIndex {
+    /// The store infos and the messages with timeless data, per store.
+    always_sent: HashMap<StoreId, BTreeSet<MsgId>>,
 
-#[derive(Clone)]
+    /// The messages with data at each time, per store and timeline.
+    times: HashMap<(StoreId, TimelineName), MsgsByTime>,
+
+    /// The messages with data of each component of each entity at each time, per store and timeline.
+    components: HashMap<(StoreId, TimelineName), HashMap<(EntityPath, ComponentName), MsgsByTime>>,
+}
+
+impl HistoryIndex {
+    fn insert(&mut self, id: MsgId, msg: &HistoryMsg) {
+        if msg.is_always_sent() {
+            self.always_sent
+                .entry(msg.store_id.clone())
+                .or_default()
+                .insert(id);
+        }
+
+        let Some(data) = &msg.summary else {
+            return;
+        };
+        for (timeline, times) in &data.times {
+            let msgs_by_time = self
+                .times
+                .entry((msg.store_id.clone(), *timeline))
+                .or_default();
+            for time in times {
+                msgs_by_time.entry(*time).or_default().insert(id);
+            }
+        }
+        for ((timeline, entity_path, component), time) in &data.latest {
+            self.components
+                .entry((msg.store_id.clone(), *timeline))
+                .or_default()
+                .entry((entity_path.clone(), *component))
+                .or_default()
+                .entry(*time)
+                .or_default()
+                .insert(id);
+        }
+    }
+
+    fn remove(&mut self, id: MsgId, msg: &HistoryMsg) {
+        fn remove_at(msgs_by_time: &mut MsgsByTime, time: TimeInt, id: MsgId) {
+            if let Some(ids) = msgs_by_time.get_mut(&time) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    msgs_by_time.remove(&time);
+                }
+            }
+        }
+
+        if let Some(ids) = self.always_sent.get_mut(&msg.store_id) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.always_sent.remove(&msg.store_id);
+            }
+        }
+
+        let Some(data) = &msg.summary else {
+            return;
+        };
+        for (timeline, times) in &data.times {
+            let key = (msg.store_id.clone(), *timeline);
+            if let Some(msgs_by_time) = self.times.get_mut(&key) {
+                for time in times {
+                    remove_at(msgs_by_time, *time, id);
+                }
+                if msgs_by_time.is_empty() {
+                    self.times.remove(&key);
+                }
+            }
+        }
+        for ((timeline, entity_path, component), time) in &data.latest {
+            let key = (msg.store_id.clone(), *timeline);
+            if let Some(by_component) = self.components.get_mut(&key) {
+                let component_key = (entity_path.clone(), *component);
+                if let Some(msgs_by_time) = by_component.get_mut(&component_key) {
+                    remove_at(msgs_by_time, *time, id);
+                    if msgs_by_time.is_empty() {
+                        by_component.remove(&component_key);
+                    }
+                }
+                if by_component.is_empty() {
+                    self.components.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// The history of a [`RerunServer`]: every message it was sent, up to its memory limit.
 struct MessageQueue {
     server_memory_limit: MemoryLimit,
-    messages: VecDeque<Arc<[u8]>>,
+    messages: BTreeMap<MsgId, Arc<HistoryMsg>>,
+    next_id: MsgId,
+    bytes_used: u64,
+    index: HistoryIndex,
 }
 
 impl MessageQueue {
@@ -30,12 +249,87 @@ impl MessageQueue {
         Self {
             server_memory_limit,
             messages: Default::default(),
+            next_id: 0,
+            bytes_used: 0,
+            index: Default::default(),
         }
     }
 
-    pub fn push(&mut self, msg: Arc<[u8]>) {
+    pub fn push(&mut self, msg: Arc<HistoryMsg>) {
         self.gc_if_using_too_much_ram();
-        self.messages.push_back(msg);
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.bytes_used += msg.bytes.len() as u64;
+        self.index.insert(id, &msg);
+        self.messages.insert(id, msg);
+    }
+
+    /// The messages of the history that match the query, oldest first.
+    ///
+    /// Looks them up in the index, so it doesn't go through the rest of the history.
+    fn query(&self, query: &HistoryQuery) -> Vec<Arc<HistoryMsg>> {
+        re_tracing::profile_function!();
+
+        let in_store = |store_id: &StoreId| {
+            query
+                .store_id
+                .as_ref()
+                .map_or(true, |queried| queried == store_id)
+        };
+        let on_timeline = |(store_id, msg_timeline): &(StoreId, TimelineName),
+                           timeline: &TimelineName| {
+            msg_timeline == timeline && in_store(store_id)
+        };
+
+        let mut ids: BTreeSet<MsgId> = match &query.window {
+            HistoryWindow::All => {
+                return self
+                    .messages
+                    .values()
+                    .filter(|msg| in_store(&msg.store_id))
+                    .cloned()
+                    .collect();
+            }
+
+            HistoryWindow::Range { timeline, range } => {
+                if range.min <= range.max {
+                    self.index
+                        .times
+                        .iter()
+                        .filter(|(key, _)| on_timeline(key, timeline))
+                        .flat_map(|(_, msgs_by_time)| msgs_by_time.range(range.min..=range.max))
+                        .flat_map(|(_, ids)| ids)
+                        .copied()
+                        .collect()
+                } else {
+                    BTreeSet::default()
+                }
+            }
+
+            HistoryWindow::Latest { timeline } => self
+                .index
+                .components
+                .iter()
+                .filter(|(key, _)| on_timeline(key, timeline))
+                .flat_map(|(_, by_component)| by_component.values())
+                // At equal times, the message received last wins.
+                .filter_map(|msgs_by_time| msgs_by_time.values().next_back()?.last())
+                .copied()
+                .collect(),
+        };
+
+        ids.extend(
+            self.index
+                .always_sent
+                .iter()
+                .filter(|(store_id, _)| in_store(store_id))
+                .flat_map(|(_, ids)| ids),
+        );
+
+        ids.into_iter()
+            .filter_map(|id| self.messages.get(&id).cloned())
+            .collect()
     }
 
     fn gc_if_using_too_much_ram(&mut self) {
@@ -43,28 +337,37 @@ impl MessageQueue {
 
         if let Some(max_bytes) = self.server_memory_limit.max_bytes {
             let max_bytes = max_bytes as u64;
-            let bytes_used = self.messages.iter().map(|m| m.len() as u64).sum::<u64>();
 
-            if max_bytes < bytes_used {
+            if max_bytes < self.bytes_used {
                 re_tracing::profile_scope!("Drop messages");
                 re_log::info_once!(
                     "Memory limit ({}) exceeded. Dropping old log messages from the server. Clients connecting after this will not see the full history.",
                     re_format::format_bytes(max_bytes as _)
                 );
 
-                let bytes_to_free = bytes_used - max_bytes;
+                let bytes_to_free = self.bytes_used - max_bytes;
 
                 let mut bytes_dropped = 0;
                 let mut messages_dropped = 0;
 
                 while bytes_dropped < bytes_to_free {
-                    if let Some(msg) = self.messages.pop_front() {
-                        bytes_dropped += msg.len() as u64;
+                    // Keep the store infos: without them the data is useless, and they are small.
+                    let oldest_data = self
+                        .messages
+                        .iter()
+                        .find(|(_, msg)| !msg.is_store_info())
+                        .map(|(id, _)| *id);
+                    if let Some((id, msg)) =
+                        oldest_data.and_then(|id| self.messages.remove_entry(&id))
+                    {
+                        self.index.remove(id, &msg);
+                        bytes_dropped += msg.bytes.len() as u64;
                         messages_dropped += 1;
                     } else {
                         break;
                     }
                 }
+                self.bytes_used -= bytes_dropped;
 
                 re_log::trace!(
                     "Dropped {} bytes in {messages_dropped} message(s)",
@@ -75,11 +378,34 @@ impl MessageQueue {
     }
 }
 
+/// Who may connect to a [`RerunServer`], and how.
+#[derive(Clone, Debug, Default)]
+pub struct RerunServerOptions {
+    /// Only accept viewers presenting this shared secret as the `token` parameter of the url
+    /// they connect to, e.g. `ws://localhost:9877?token=secret`.
+    pub token: Option<String>,
+
+    /// Only accept encrypted (`wss://`) connections.
+    #[cfg(feature = "tls")]
+    pub tls: Option<Arc<rustls::ServerConfig>>,
+}
+
+impl RerunServerOptions {
+    fn protocol(&self) -> &'static str {
+        #[cfg(feature = "tls")]
+        if self.tls.is_some() {
+            return "wss";
+        }
+        "ws"
+    }
+}
+
 /// Websocket host for relaying [`LogMsg`]s to a web viewer.
 pub struct RerunServer {
     server_memory_limit: MemoryLimit,
     listener: TcpListener,
     local_addr: std::net::SocketAddr,
+    options: RerunServerOptions,
 }
 
 impl RerunServer {
@@ -92,6 +418,16 @@ impl RerunServer {
         bind_ip: String,
         port: RerunServerPort,
         server_memory_limit: MemoryLimit,
+    ) -> Result<Self, RerunServerError> {
+        Self::with_options(bind_ip, port, server_memory_limit, Default::default()).await
+    }
+
+    /// Like [`Self::new`], but only accepting the viewers allowed by `options`.
+    pub async fn with_options(
+        bind_ip: String,
+        port: RerunServerPort,
+        server_memory_limit: MemoryLimit,
+        options: RerunServerOptions,
     ) -> Result<Self, RerunServerError> {
         let bind_addr = format!("{bind_ip}:{port}");
 
@@ -111,6 +447,7 @@ impl RerunServer {
             server_memory_limit,
             local_addr: listener.local_addr()?,
             listener,
+            options,
         };
 
         re_log::info!(
@@ -152,13 +489,14 @@ impl RerunServer {
                 peer,
                 tcp_stream,
                 history.clone(),
+                self.options.clone(),
             ));
         }
     }
 
     /// Contains the `ws://` or `wss://` prefix.
     pub fn server_url(&self) -> String {
-        server_url(&self.local_addr)
+        server_url_with_protocol(self.options.protocol(), &self.local_addr)
     }
 }
 
@@ -222,17 +560,20 @@ impl RerunServerHandle {
 fn to_broadcast_stream(
     log_rx: ReceiveSet<LogMsg>,
     history: Arc<Mutex<MessageQueue>>,
-) -> tokio::sync::broadcast::Sender<Arc<[u8]>> {
+) -> tokio::sync::broadcast::Sender<Arc<HistoryMsg>> {
     let (tx, _) = tokio::sync::broadcast::channel(1024 * 1024);
     let tx1 = tx.clone();
     tokio::task::spawn_blocking(move || {
         while let Ok(msg) = log_rx.recv() {
             match msg.payload {
                 re_smart_channel::SmartMessagePayload::Msg(data) => {
-                    let bytes = crate::encode_log_msg(&data);
-                    let bytes: Arc<[u8]> = bytes.into();
-                    history.lock().push(bytes.clone());
-                    if let Err(tokio::sync::broadcast::error::SendError(_bytes)) = tx1.send(bytes) {
+                    let msg = Arc::new(HistoryMsg::new(&data));
+
+                    // Holding the lock while broadcasting means a new client gets each message
+                    // exactly once: either in the history, or in the broadcast it subscribed to.
+                    let mut history = history.lock();
+                    history.push(msg.clone());
+                    if let Err(tokio::sync::broadcast::error::SendError(_msg)) = tx1.send(msg) {
                         // no receivers currently - that's fine!
                     }
                 }
@@ -250,48 +591,113 @@ fn to_broadcast_stream(
 }
 
 async fn accept_connection(
-    log_stream: tokio::sync::broadcast::Sender<Arc<[u8]>>,
-    _peer: SocketAddr,
+    log_stream: tokio::sync::broadcast::Sender<Arc<HistoryMsg>>,
+    peer: SocketAddr,
     tcp_stream: TcpStream,
     history: Arc<Mutex<MessageQueue>>,
+    options: RerunServerOptions,
 ) {
     // let span = re_log::span!(
     //     re_log::Level::INFO,
     //     "Connection",
-    //     peer = _peer.to_string().as_str()
+    //     peer = peer.to_string().as_str()
     // );
     // let _enter = span.enter();
 
     re_log::debug!("New WebSocket connection");
 
-    if let Err(err) = handle_connection(log_stream, tcp_stream, history).await {
+    #[cfg(feature = "tls")]
+    if let Some(tls) = options.tls.clone() {
+        match tokio_rustls::TlsAcceptor::from(tls)
+            .accept(tcp_stream)
+            .await
+        {
+            Ok(tls_stream) => {
+                log_connection_error(
+                    handle_connection(log_stream, tls_stream, history, &options, peer).await,
+                );
+            }
+            Err(err) => {
+                re_log::warn!("Rejecting WebSocket client {peer}: TLS handshake failed: {err}");
+            }
+        }
+        return;
+    }
+
+    log_connection_error(handle_connection(log_stream, tcp_stream, history, &options, peer).await);
+}
+
+fn log_connection_error(result: tungstenite::Result<()>) {
+    if let Err(err) = result {
         match err {
+            // Refused in the WebSocket handshake, which already logged why.
+            Error::Http(_) => (),
             Error::ConnectionClosed | Error::Protocol(_) | Error::Utf8 => (),
             err => re_log::error!("Error processing connection: {err}"),
         }
     }
 }
 
+/// Compares in constant time, so that the time it takes doesn't tell how much of a guess was right.
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 async fn handle_connection(
-    log_stream: tokio::sync::broadcast::Sender<Arc<[u8]>>,
-    tcp_stream: TcpStream,
+    log_stream: tokio::sync::broadcast::Sender<Arc<HistoryMsg>>,
+    stream: impl AsyncRead + AsyncWrite + Unpin,
     history: Arc<Mutex<MessageQueue>>,
+    options: &RerunServerOptions,
+    peer: SocketAddr,
 ) -> tungstenite::Result<()> {
-    let ws_stream = accept_async(tcp_stream).await?;
-    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+    let reject = |status: StatusCode, reason: String| {
+        re_log::warn!("Rejecting WebSocket client {peer}: {reason}");
+        let mut response = ErrorResponse::new(Some(reason));
+        *response.status_mut() = status;
+        response
+    };
+
+    let mut query = HistoryQuery::default();
+    let ws_stream = accept_hdr_async(stream, |request: &Request, response: Response| {
+        let url = request.uri().to_string();
+
+        if let Some(token) = &options.token {
+            let authorized = url_params(&url)
+                .find(|(key, _)| *key == "token")
+                .map_or(false, |(_, client_token)| tokens_match(client_token, token));
+            if !authorized {
+                return Err(reject(
+                    StatusCode::UNAUTHORIZED,
+                    "missing or wrong token".to_owned(),
+                ));
+            }
+        }
 
-    {
-        // Re-sending packet history - this is not water tight, but better than nothing.
-        // TODO(emilk): water-proof resending of history + streaming of new stuff, without anything missed.
-        let history: MessageQueue = history.lock().clone();
-        for packet in history.messages {
-            ws_sender
-                .send(tungstenite::Message::Binary(packet.to_vec()))
-                .await?;
+        match HistoryQuery::from_url(&url) {
+            Ok(requested) => {
+                query = requested;
+                Ok(response)
+            }
+            Err(err) => Err(reject(StatusCode::BAD_REQUEST, err)),
         }
-    }
+    })
+    .await?;
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
-    let mut log_rx = log_stream.subscribe();
+    re_log::debug!("Sending history matching {query:?}");
+    let (history, mut log_rx) = {
+        let history = history.lock();
+        (history.query(&query), log_stream.subscribe())
+    };
+    for msg in history {
+        ws_sender
+            .send(tungstenite::Message::Binary(msg.bytes.to_vec()))
+            .await?;
+    }
 
     loop {
         tokio::select! {
@@ -312,10 +718,177 @@ async fn handle_connection(
             data_msg = log_rx.recv() => {
                 let data_msg = data_msg.unwrap();
 
-                ws_sender.send(tungstenite::Message::Binary(data_msg.to_vec())).await?;
+                if query.store_id.as_ref().map_or(true, |store_id| store_id == &data_msg.store_id) {
+                    ws_sender.send(tungstenite::Message::Binary(data_msg.bytes.to_vec())).await?;
+                }
             }
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use re_log_types::{
+        external::arrow2, DataCell, DataRow, RowId, StoreKind, TableId, TimePoint, TimeRange,
+        Timeline,
+    };
+
+    use super::*;
+
+    fn data_msg(
+        store_id: &StoreId,
+        entity_path: &str,
+        frame: Option<i64>,
+        component: &str,
+    ) -> Arc<HistoryMsg> {
+        let timepoint = frame.map_or_else(TimePoint::timeless, |frame| {
+            TimePoint::from([(Timeline::new_sequence("frame"), frame.into())])
+        });
+        let values = arrow2::array::UInt64Array::from_vec(vec![0]).boxed();
+        let cell = DataCell::from_arrow(component.into(), values);
+        let row = DataRow::from_cells(RowId::new(), timepoint, entity_path, 1, [cell]).unwrap();
+        let table = DataTable::from_rows(TableId::new(), [row]);
+        let msg = LogMsg::ArrowMsg(store_id.clone(), table.to_arrow_msg().unwrap());
+        Arc::new(HistoryMsg::new(&msg))
+    }
+
+    #[test]
+    fn query_history() {
+        let rec = StoreId::from_string(StoreKind::Recording, "rec".to_owned());
+        let other_rec = StoreId::from_string(StoreKind::Recording, "other_rec".to_owned());
+
+        let mut history = MessageQueue::new(MemoryLimit::UNLIMITED);
+        for msg in [
+            data_msg(&rec, "a", Some(1), "color"),
+            data_msg(&rec, "b", Some(2), "color"),
+            data_msg(&rec, "a", Some(3), "color"),
+            data_msg(&rec, "c", None, "color"),
+            data_msg(&other_rec, "a", Some(5), "color"),
+            data_msg(&rec, "a", Some(2), "radius"),
+        ] {
+            history.push(msg);
+        }
+
+        let query = |store_id: Option<&StoreId>, window: HistoryWindow| {
+            history
+                .query(&HistoryQuery {
+                    store_id: store_id.cloned(),
+                    window,
+                })
+                .iter()
+                .map(|msg| {
+                    history
+                        .messages
+                        .values()
+                        .position(|stored| Arc::ptr_eq(stored, msg))
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(query(None, HistoryWindow::All), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(query(Some(&rec), HistoryWindow::All), [0, 1, 2, 3, 5]);
+
+        let range = |min: i64, max: i64| HistoryWindow::Range {
+            timeline: "frame".into(),
+            range: TimeRange::new(min.into(), max.into()),
+        };
+        assert_eq!(query(None, range(2, 3)), [1, 2, 3, 5]);
+        assert_eq!(query(Some(&other_rec), range(0, 10)), [4]);
+        assert_eq!(query(None, range(3, 2)), [3]);
+
+        // The latest data of each component, even if logged separately.
+        let latest = |timeline: &str| HistoryWindow::Latest {
+            timeline: timeline.into(),
+        };
+        assert_eq!(query(Some(&rec), latest("frame")), [1, 2, 3, 5]);
+        assert_eq!(query(None, latest("frame")), [1, 2, 3, 4, 5]);
+        assert_eq!(query(None, latest("log_time")), [3]);
+    }
+
+    #[test]
+    fn dropped_messages_leave_the_index() {
+        let rec = StoreId::from_string(StoreKind::Recording, "rec".to_owned());
+        let msgs = [
+            data_msg(&rec, "a", Some(1), "color"),
+            data_msg(&rec, "a", Some(2), "color"),
+            data_msg(&rec, "a", Some(3), "color"),
+        ];
+        let msg_size = msgs[0].bytes.len() as u64;
+
+        let mut history = MessageQueue::new(MemoryLimit::from_bytes(msg_size));
+        for msg in &msgs {
+            history.push(msg.clone());
+        }
+        // Pushing the third message dropped the first one.
+        assert_eq!(history.messages.len(), 2);
+        assert_eq!(history.bytes_used, 2 * msg_size);
+
+        let query = |window| {
+            history.query(&HistoryQuery {
+                store_id: None,
+                window,
+            })
+        };
+        let range = query(HistoryWindow::Range {
+            timeline: "frame".into(),
+            range: TimeRange::EVERYTHING,
+        });
+        assert_eq!(range.len(), 2);
+        assert!(Arc::ptr_eq(&range[0], &msgs[1]));
+        let latest = query(HistoryWindow::Latest {
+            timeline: "frame".into(),
+        });
+        assert_eq!(latest.len(), 1);
+        assert!(Arc::ptr_eq(&latest[0], &msgs[2]));
+
+        history.server_memory_limit = MemoryLimit::from_bytes(0);
+        history.gc_if_using_too_much_ram();
+        assert!(history.messages.is_empty());
+        assert!(history.index.times.is_empty());
+        assert!(history.index.components.is_empty());
+    }
+
+    #[test]
+    fn handshake_checks_token() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let options = RerunServerOptions {
+                token: Some("secret".to_owned()),
+                #[cfg(feature = "tls")]
+                tls: None,
+            };
+            let server = RerunServer::with_options(
+                "127.0.0.1".to_owned(),
+                RerunServerPort(0),
+                MemoryLimit::UNLIMITED,
+                options,
+            )
+            .await
+            .unwrap();
+            let url = server.server_url();
+            let addr = server.local_addr;
+            tokio::spawn(server.listen(ReceiveSet::new(vec![])));
+
+            let connect = |url: String| async move {
+                let stream = TcpStream::connect(addr).await.unwrap();
+                tokio_tungstenite::client_async(url, stream).await
+            };
+
+            assert!(
+                matches!(connect(url.clone()).await, Err(Error::Http(response)) if response.status() == StatusCode::UNAUTHORIZED)
+            );
+            assert!(matches!(
+                connect(crate::url_with_token(&url, "guess")).await,
+                Err(Error::Http(_))
+            ));
+            assert!(connect(crate::url_with_token(&url, "secret")).await.is_ok());
+        });
+    }
+}
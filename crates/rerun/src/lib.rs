@@ -119,6 +119,9 @@ pub mod native_viewer;
 #[cfg(feature = "demo")]
 pub mod demo_util;
 
+#[cfg(feature = "urdf")]
+pub mod urdf;
+
 #[cfg(feature = "log")]
 pub mod log_integration;
 
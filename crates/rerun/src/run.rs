@@ -8,6 +8,22 @@ use re_data_source::DataSource;
 use re_log_types::{DataTable, LogMsg, PythonVersion, SetStoreInfo};
 use re_smart_channel::{ReceiveSet, Receiver, SmartMessagePayload};
 
+mod export;
+#[cfg(feature = "server")]
+mod relay;
+mod replay;
+mod rrd;
+#[cfg(feature = "server")]
+mod serve;
+
+use self::export::ExportFormatArg;
+#[cfg(feature = "server")]
+use self::relay::RelayArgs;
+use self::replay::ReplayArgs;
+use self::rrd::RrdCommands;
+#[cfg(feature = "server")]
+use self::serve::ServeArgs;
+
 #[cfg(feature = "web_viewer")]
 use re_sdk::web_viewer::host_web_viewer;
 #[cfg(feature = "web_viewer")]
@@ -49,6 +65,15 @@ Examples:
 
     Listen for incoming TCP connections from the logging SDK and stream the results to disk:
         rerun --save new_recording.rrd
+
+    Listen for incoming TCP connections from the logging SDK, and answer queries about the data on 127.0.0.1:9878:
+        rerun serve
+
+    Cut an .rrd file down to some entities and a time range:
+        rerun rrd filter recording.rrd --entity "+ /world/**" --timeline frame --min 100 --max 200 -o cut.rrd
+
+    Export the contents of an .rrd file as Arrow IPC files, one per entity:
+        rerun export recording.rrd -o recording_tables/
 "#;
 
 #[derive(Debug, clap::Parser)]
@@ -66,6 +91,9 @@ struct Args {
     command: Option<Command>,
 
     /// What bind address IP to use.
+    ///
+    /// Use `unix:///path/to/socket` to have SDKs on the same machine connect over a Unix domain
+    /// socket instead of TCP. The WebSocket and web viewer servers then bind to `0.0.0.0`.
     #[clap(long, default_value = "0.0.0.0")]
     bind: String,
 
@@ -79,6 +107,13 @@ struct Args {
     #[clap(long)]
     drop_at_latency: Option<String>,
 
+    /// Stop reading from any SDK sending more than this many bytes per second, e.g. "50MB".
+    ///
+    /// Keeps a single runaway SDK from starving the others.
+    #[cfg(feature = "server")]
+    #[clap(long)]
+    max_client_rate: Option<String>,
+
     #[clap(
         long,
         default_value = "75%",
@@ -88,6 +123,23 @@ Example: `16GB` or `50%` (of system total)."
     )]
     memory_limit: String,
 
+    #[clap(
+        long,
+        long_help = r"Only keep the latest window of data on the given timeline in the Rerun Viewer.
+Older data is dropped as new data comes in.
+The window is either a duration, for temporal timelines, or a number of steps.
+Example: `60s@log_time` or `1000@frame_nr`."
+    )]
+    retention: Option<re_data_store::RetentionPolicy>,
+
+    #[clap(
+        long,
+        long_help = r"When the Rerun Viewer reaches its --memory-limit, write the oldest data to this
+directory instead of dropping it. It is read back from disk when needed.
+The files are removed once the Viewer no longer needs them."
+    )]
+    spill_dir: Option<std::path::PathBuf>,
+
     #[clap(
         long,
         default_value = "25%",
@@ -143,6 +195,24 @@ When persisted, the state will be stored at the following locations:
     #[clap(long)]
     skip_welcome_screen: bool,
 
+    /// The PEM certificate chain to present to SDKs and viewers, together with `--tls-key`.
+    ///
+    /// With it, both the TCP server and the WebSocket server only accept TLS connections.
+    #[cfg(feature = "tls")]
+    #[clap(long, requires = "tls_key")]
+    tls_cert: Option<std::path::PathBuf>,
+
+    /// The PEM private key of the `--tls-cert`.
+    #[cfg(feature = "tls")]
+    #[clap(long, requires = "tls_cert")]
+    tls_key: Option<std::path::PathBuf>,
+
+    /// A shared secret that SDKs and viewers must present to connect.
+    ///
+    /// Also presented to the WebSocket servers we connect to, and to the viewer of `rerun replay`.
+    #[clap(long)]
+    token: Option<String>,
+
     #[clap(long_help = r"Any combination of:
 - A WebSocket url to a Rerun Server
 - An HTTP(S) URL to an .rrd file to load
@@ -192,6 +262,17 @@ If no arguments are given, a server will be hosted which a Rerun SDK can connect
     test_receive: bool,
 }
 
+impl Args {
+    /// The IP address the WebSocket and web viewer servers bind to.
+    fn bind_ip(&self) -> String {
+        if self.bind.starts_with(re_sdk_comms::UNIX_SOCKET_SCHEME) {
+            "0.0.0.0".to_owned()
+        } else {
+            self.bind.clone()
+        }
+    }
+}
+
 #[derive(Debug, Clone, Subcommand)]
 enum Command {
     /// Configure the behavior of our analytics.
@@ -212,9 +293,49 @@ enum Command {
         full_dump: bool,
     },
 
+    /// Export the recordings of an .rrd file as Arrow IPC or Parquet files, one per entity.
+    ///
+    /// Every timeline becomes its own column, and component columns keep their extension types.
+    Export {
+        rrd_path: String,
+
+        /// The directory to write the files to.
+        #[clap(long, short)]
+        output: String,
+
+        /// Parquet requires the `parquet` feature.
+        #[clap(long, value_enum, default_value_t = ExportFormatArg::Arrow)]
+        format: ExportFormatArg,
+    },
+
     /// Print the contents of an .rrd file.
     Print { rrd_path: String },
 
+    /// Forward the data of many SDKs to a single upstream Rerun Viewer or .rrd file.
+    ///
+    /// Listens for SDKs just like the viewer does, see `--bind`, `--port` and `--token`.
+    /// Reconnects to the upstream viewer whenever it comes back.
+    #[cfg(feature = "server")]
+    Relay(RelayArgs),
+
+    /// Stream an .rrd file to a Rerun Viewer as if it was being logged right now.
+    ///
+    /// The messages are sent at the pace given by their times on a timeline.
+    Replay(ReplayArgs),
+
+    /// Filter, merge and compact .rrd files.
+    #[command(subcommand)]
+    Rrd(RrdCommands),
+
+    /// Ingest the data of SDKs without a viewer, and answer queries about it over a socket.
+    ///
+    /// Listens for SDKs just like the viewer does, see `--bind`, `--port` and `--token`.
+    /// Queries are JSON objects, one per line, e.g.
+    /// `{"query": "latest_at", "entity": "world/points", "timeline": "frame"}`.
+    /// Each is answered with a single line of JSON.
+    #[cfg(feature = "server")]
+    Serve(ServeArgs),
+
     /// Reset the memory of the Rerun Viewer.
     ///
     /// Only run this if you're having trouble with the Viewer,
@@ -338,11 +459,31 @@ where
                 run_compare(&path_to_rrd1, &path_to_rrd2, *full_dump)
             }
 
+            Command::Export {
+                rrd_path,
+                output,
+                format,
+            } => {
+                let rrd_path = PathBuf::from(&rrd_path);
+                export::export_rrd(&rrd_path, Path::new(output), *format)
+                    .with_context(|| format!("path: {rrd_path:?}"))
+            }
+
             Command::Print { rrd_path } => {
                 let rrd_path = PathBuf::from(&rrd_path);
                 print_rrd(&rrd_path).with_context(|| format!("path: {rrd_path:?}"))
             }
 
+            #[cfg(feature = "server")]
+            Command::Relay(relay_args) => relay::run_relay(&args, relay_args).await,
+
+            Command::Replay(replay_args) => replay::run_replay(&args, replay_args),
+
+            Command::Rrd(rrd) => rrd::run_rrd(rrd),
+
+            #[cfg(feature = "server")]
+            Command::Serve(serve_args) => serve::run_serve(&args, serve_args).await,
+
             #[cfg(feature = "native_viewer")]
             Command::Reset => reset_viewer(),
         }
@@ -487,6 +628,8 @@ async fn run_impl(
         re_viewer::StartupOptions {
             memory_limit: re_memory::MemoryLimit::parse(&args.memory_limit)
                 .map_err(|err| anyhow::format_err!("Bad --memory-limit: {err}"))?,
+            retention: args.retention,
+            spill_dir: args.spill_dir.clone(),
             persist_state: args.persist_state,
             is_in_notebook: false,
             screenshot_to_path_then_quit: args.screenshot_to.clone(),
@@ -502,18 +645,15 @@ async fn run_impl(
         }
     };
 
+    // `rerun.spawn()` doesn't need to log that a connection has been made
+    #[cfg(feature = "server")]
+    let server_options = sdk_server_options(&args, call_source.is_python())?;
+
     // Where do we get the data from?
     let rx: Vec<Receiver<LogMsg>> = if args.url_or_paths.is_empty() {
         #[cfg(feature = "server")]
         {
-            let server_options = re_sdk_comms::ServerOptions {
-                max_latency_sec: parse_max_latency(args.drop_at_latency.as_ref()),
-
-                // `rerun.spawn()` doesn't need to log that a connection has been made
-                quiet: call_source.is_python(),
-            };
-            let rx = re_sdk_comms::serve(&args.bind, args.port, server_options).await?;
-            vec![rx]
+            vec![serve_sdks(&args, server_options.clone()).await?]
         }
 
         #[cfg(not(feature = "server"))]
@@ -524,6 +664,12 @@ async fn run_impl(
             .iter()
             .cloned()
             .map(|uri| DataSource::from_uri(re_log_types::FileSource::Cli, uri))
+            .map(|data_source| match (data_source, &args.token) {
+                (DataSource::WebSocketAddr(url), Some(token)) => {
+                    DataSource::WebSocketAddr(re_ws_comms::url_with_token(&url, token))
+                }
+                (data_source, _) => data_source,
+            })
             .collect_vec();
 
         #[cfg(feature = "web_viewer")]
@@ -532,7 +678,7 @@ async fn run_impl(
                 // Special case! We are connecting a web-viewer to a web-socket address.
                 // Instead of piping, just host a web-viewer that connects to the web-socket directly:
                 return host_web_viewer(
-                    args.bind.clone(),
+                    args.bind_ip(),
                     args.web_viewer_port,
                     true,
                     rerun_server_ws_url,
@@ -587,13 +733,21 @@ async fn run_impl(
                 .map_err(|err| anyhow::format_err!("Bad --server-memory-limit: {err}"))?;
 
             // This is the server which the web viewer will talk to:
-            let ws_server = re_ws_comms::RerunServer::new(
-                args.bind.clone(),
+            let ws_server = re_ws_comms::RerunServer::with_options(
+                args.bind_ip(),
                 args.ws_server_port,
                 server_memory_limit,
+                re_ws_comms::RerunServerOptions {
+                    token: args.token.clone(),
+                    #[cfg(feature = "tls")]
+                    tls: server_options.tls,
+                },
             )
             .await?;
-            let _ws_server_url = ws_server.server_url();
+            let _ws_server_url = match &args.token {
+                Some(token) => re_ws_comms::url_with_token(&ws_server.server_url(), token),
+                None => ws_server.server_url(),
+            };
             let rx = ReceiveSet::new(rx);
             let ws_server_handle = tokio::spawn(ws_server.listen(rx));
 
@@ -606,7 +760,7 @@ async fn run_impl(
 
                 // This is the server that serves the Wasm+HTML:
                 let web_server_handle = tokio::spawn(host_web_viewer(
-                    args.bind.clone(),
+                    args.bind_ip(),
                     args.web_viewer_port,
                     open_browser,
                     _ws_server_url,
@@ -753,6 +907,70 @@ fn stream_to_rrd_on_disk(
     Ok(())
 }
 
+/// Parses the address of a Rerun Viewer, e.g. "127.0.0.1:9876" or "unix:///tmp/rerun.sock".
+fn parse_viewer_addr(addr: &str) -> anyhow::Result<re_sdk::sink::ServerAddr> {
+    #[cfg(unix)]
+    if let Some(path) = addr.strip_prefix(re_sdk_comms::UNIX_SOCKET_SCHEME) {
+        return Ok(re_sdk::sink::ServerAddr::Unix(path.into()));
+    }
+
+    let socket_addr: std::net::SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Expected the address of a Rerun Viewer, got {addr:?}"))?;
+    Ok(socket_addr.into())
+}
+
+/// How the TCP server listens for SDKs, as configured on the command line.
+#[cfg(feature = "server")]
+fn sdk_server_options(args: &Args, quiet: bool) -> anyhow::Result<re_sdk_comms::ServerOptions> {
+    #[cfg(feature = "tls")]
+    let tls = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert_path), Some(key_path)) => {
+            Some(re_sdk_comms::load_server_config(cert_path, key_path)?)
+        }
+        _ => None,
+    };
+
+    let max_bytes_per_sec = args
+        .max_client_rate
+        .as_ref()
+        .map(|rate| {
+            re_format::parse_bytes(rate)
+                .and_then(|bytes| u64::try_from(bytes).ok())
+                .filter(|&bytes| 0 < bytes)
+                .ok_or_else(|| anyhow::anyhow!("Bad --max-client-rate: {rate:?}"))
+        })
+        .transpose()?;
+
+    Ok(re_sdk_comms::ServerOptions {
+        max_latency_sec: parse_max_latency(args.drop_at_latency.as_ref()),
+        quiet,
+
+        // The SDK is usually on the same machine, where compression isn't worth it.
+        compression: re_log_encoding::Compression::Off,
+
+        token: args.token.clone(),
+        max_bytes_per_sec,
+
+        #[cfg(feature = "tls")]
+        tls,
+    })
+}
+
+/// Listens for SDKs on `--bind` and `--port`, or on a Unix domain socket if `--bind` is `unix://…`.
+#[cfg(feature = "server")]
+async fn serve_sdks(
+    args: &Args,
+    server_options: re_sdk_comms::ServerOptions,
+) -> anyhow::Result<Receiver<LogMsg>> {
+    #[cfg(unix)]
+    if let Some(path) = args.bind.strip_prefix(re_sdk_comms::UNIX_SOCKET_SCHEME) {
+        return Ok(re_sdk_comms::serve_unix(path.as_ref(), server_options).await?);
+    }
+
+    Ok(re_sdk_comms::serve(&args.bind, args.port, server_options).await?)
+}
+
 #[cfg(feature = "server")]
 fn parse_max_latency(max_latency: Option<&String>) -> f32 {
     max_latency.as_ref().map_or(f32::INFINITY, |time| {
@@ -0,0 +1,82 @@
+//! `rerun export`: write the contents of an .rrd file as Arrow IPC or Parquet files.
+
+use std::path::Path;
+
+use anyhow::Context as _;
+
+use re_data_store::ExportFormat;
+use re_entity_db::EntityDb;
+use re_log_types::{StoreId, StoreKind};
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ExportFormatArg {
+    /// Arrow IPC files, a.k.a. Feather v2.
+    Arrow,
+
+    /// Apache Parquet files.
+    ///
+    /// Requires the `parquet` feature.
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(format: ExportFormatArg) -> Self {
+        match format {
+            ExportFormatArg::Arrow => Self::ArrowIpc,
+            #[cfg(feature = "parquet")]
+            ExportFormatArg::Parquet => Self::Parquet,
+        }
+    }
+}
+
+/// Writes one file per entity of every recording in `rrd_path` to `output_dir`.
+///
+/// If there is more than one recording, each gets its own subdirectory named after its id.
+pub fn export_rrd(
+    rrd_path: &Path,
+    output_dir: &Path,
+    format: ExportFormatArg,
+) -> anyhow::Result<()> {
+    let rrd_file =
+        std::fs::File::open(rrd_path).with_context(|| format!("Failed to open {rrd_path:?}"))?;
+
+    let mut stores: std::collections::BTreeMap<StoreId, EntityDb> = Default::default();
+    let version_policy = re_log_encoding::decoder::VersionPolicy::Warn;
+    let decoder = re_log_encoding::decoder::Decoder::new(version_policy, rrd_file)?;
+    for msg in decoder {
+        let msg = msg.context("decode rrd message")?;
+        stores
+            .entry(msg.store_id().clone())
+            .or_insert_with(|| EntityDb::new(msg.store_id().clone()))
+            .add(&msg)
+            .context("decode rrd file contents")?;
+    }
+
+    let recordings = stores
+        .into_values()
+        .filter(|db| db.store_kind() == StoreKind::Recording)
+        .collect::<Vec<_>>();
+    anyhow::ensure!(
+        !recordings.is_empty(),
+        "no data recording found in rrd file"
+    );
+
+    let num_recordings = recordings.len();
+    for db in recordings {
+        let dir = if num_recordings == 1 {
+            output_dir.to_owned()
+        } else {
+            output_dir.join(db.store_id().as_str())
+        };
+
+        let paths = db
+            .store()
+            .export_to_dir(&dir, format.into())
+            .with_context(|| format!("Failed to export recording {}", db.store_id()))?;
+
+        re_log::info!("Exported {} entities to {dir:?}", paths.len());
+    }
+
+    Ok(())
+}
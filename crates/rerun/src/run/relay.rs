@@ -0,0 +1,579 @@
+//! `rerun relay`: forward the streams of many SDKs to a single upstream viewer or .rrd file.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use parking_lot::{Condvar, Mutex};
+
+use re_log_types::external::arrow2::array::Utf8Array;
+use re_log_types::{
+    ApplicationId, DataCell, DataRow, DataTable, EntityPath, EntityPathFilter, EntityPathRule,
+    LogMsg, StoreId, StoreKind,
+};
+use re_sdk::sink::LogSink;
+use re_smart_channel::{SmartMessagePayload, SmartMessageSource};
+use re_types::SizeBytes as _;
+
+use super::Args;
+
+/// How many messages may wait for the upstream viewer to acknowledge them.
+///
+/// Beyond that, messages pile up in the relay's own buffer, bounded by `--buffer-limit`.
+const UPSTREAM_MAX_IN_FLIGHT: u64 = 256;
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct RelayArgs {
+    /// Where to forward the data: the address of a Rerun Viewer (e.g. "192.168.1.2:9876"
+    /// or "unix:///tmp/rerun.sock"), or the path of an .rrd file to write.
+    upstream: String,
+
+    /// Prefix the entity paths of every recording with the application id and the host
+    /// of the SDK that logged it, e.g. "/my_app@192.168.1.7/world/points".
+    ///
+    /// SDKs connected over a Unix socket are told apart by their process id instead of their host.
+    /// The blueprints of the recordings are rewritten to match.
+    #[clap(long)]
+    prefix_entity_paths: bool,
+
+    /// Append the application id and the host of the SDK to the ids of its recordings,
+    /// so that SDKs using the same recording id don't end up in the same recording.
+    #[clap(long)]
+    rewrite_store_ids: bool,
+
+    /// How much data to hold on to while the upstream viewer is slow or unreachable,
+    /// e.g. "1GB" or "10%" of the total RAM.
+    ///
+    /// Once reached, the oldest data is dropped first.
+    #[clap(long, default_value = "10%")]
+    buffer_limit: String,
+
+    /// A shared secret to present to the upstream viewer.
+    #[clap(long)]
+    upstream_token: Option<String>,
+}
+
+/// Listens for SDKs like the viewer does (see `--bind`, `--port`, `--token`…),
+/// and forwards everything they send upstream, until killed.
+pub async fn run_relay(args: &Args, relay: &RelayArgs) -> anyhow::Result<()> {
+    let buffer_limit = re_memory::MemoryLimit::parse(&relay.buffer_limit)
+        .map_err(|err| anyhow::format_err!("Bad --buffer-limit: {err}"))?;
+    let rewrite = Rewrite {
+        prefix_entity_paths: relay.prefix_entity_paths,
+        rewrite_store_ids: relay.rewrite_store_ids,
+    };
+
+    let sink = upstream_sink(relay)?;
+    let queue = Arc::new(RelayQueue::new(buffer_limit));
+
+    let forwarder = std::thread::Builder::new()
+        .name("relay_forwarder".to_owned())
+        .spawn({
+            let queue = queue.clone();
+            move || {
+                while let Some(msg) = queue.pop() {
+                    sink.send(msg);
+                }
+                sink.flush_blocking();
+            }
+        })
+        .context("Failed to spawn the forwarding thread")?;
+
+    let mut server_options = super::sdk_server_options(args, false)?;
+    // The SDKs are usually on other machines, where compression is worth it.
+    server_options.compression = re_log_encoding::Compression::LZ4;
+    let rx = super::serve_sdks(args, server_options).await?;
+
+    re_log::info!("Relaying to {}", relay.upstream);
+
+    let mut client_names = ClientNames::default();
+    while let Ok(msg) = rx.recv() {
+        match msg.payload {
+            SmartMessagePayload::Msg(log_msg) => {
+                let log_msg = match client_names.name(&msg.source, &log_msg) {
+                    Some(client) => match rewrite.apply(log_msg, &client) {
+                        Ok(log_msg) => log_msg,
+                        Err(err) => {
+                            re_log::warn!("Failed to rewrite a message from {client}: {err}");
+                            continue;
+                        }
+                    },
+                    None => log_msg,
+                };
+
+                let num_dropped = queue.push(log_msg);
+                if num_dropped > 0 {
+                    re_log::warn_once!(
+                        "The upstream can't keep up: dropping the oldest data to stay within --buffer-limit"
+                    );
+                }
+            }
+            SmartMessagePayload::Quit(err) => {
+                if let Some(err) = err {
+                    re_log::warn!("Data source {} has left unexpectedly: {err}", msg.source);
+                }
+            }
+        }
+    }
+
+    queue.close();
+    forwarder
+        .join()
+        .map_err(|_err| anyhow::format_err!("The forwarding thread panicked"))?;
+
+    Ok(())
+}
+
+fn upstream_sink(relay: &RelayArgs) -> anyhow::Result<Box<dyn LogSink>> {
+    if relay.upstream.ends_with(".rrd") {
+        let file_sink = re_sdk::sink::FileSink::new(&relay.upstream)
+            .with_context(|| format!("Failed to create {:?}", relay.upstream))?;
+        return Ok(Box::new(file_sink));
+    }
+
+    let options = re_sdk::sink::ClientOptions {
+        // Block rather than drop, so that the relay's own buffer decides what gets dropped.
+        backpressure: re_sdk::sink::BackpressurePolicy::Block {
+            max_in_flight: UPSTREAM_MAX_IN_FLIGHT,
+        },
+        token: relay.upstream_token.clone(),
+        #[cfg(feature = "tls")]
+        tls: None,
+        spool: None,
+    };
+
+    // The client reconnects on its own whenever the upstream viewer comes back.
+    Ok(Box::new(re_sdk::sink::TcpSink::with_options(
+        super::parse_viewer_addr(&relay.upstream)
+            .context("The upstream must be a Rerun Viewer or an .rrd file")?,
+        None,
+        options,
+    )))
+}
+
+/// Names the SDKs messages come from, e.g. `my_app@192.168.1.7`.
+///
+/// The names stay the same when an SDK reconnects, so that its data ends up in the same
+/// recording and under the same entity paths as before.
+/// That is why they don't include the port of the SDK, which changes with every connection.
+#[derive(Default)]
+struct ClientNames {
+    /// The application of each recording of each host, as announced by its store info.
+    application_ids: HashMap<(String, StoreId), ApplicationId>,
+}
+
+impl ClientNames {
+    /// The name of the SDK that sent this message, if it came from one.
+    fn name(&mut self, source: &SmartMessageSource, msg: &LogMsg) -> Option<String> {
+        let host = match source {
+            SmartMessageSource::TcpClient { addr: Some(addr) } => addr.ip().to_string(),
+            SmartMessageSource::UnixSocketClient { pid: Some(pid) } => format!("pid_{pid}"),
+            _ => return None,
+        };
+
+        let key = (host, msg.store_id().clone());
+        if let LogMsg::SetStoreInfo(set_store_info) = msg {
+            self.application_ids
+                .insert(key.clone(), set_store_info.info.application_id.clone());
+        }
+        let (host, _) = &key;
+        Some(match self.application_ids.get(&key) {
+            Some(application_id) => format!("{application_id}@{host}"),
+            None => host.clone(),
+        })
+    }
+}
+
+// ---
+
+/// How to tell the data of different SDKs apart once merged into one stream.
+#[derive(Clone, Copy, Debug, Default)]
+struct Rewrite {
+    prefix_entity_paths: bool,
+    rewrite_store_ids: bool,
+}
+
+impl Rewrite {
+    fn apply(&self, msg: LogMsg, client: &str) -> anyhow::Result<LogMsg> {
+        match msg {
+            LogMsg::SetStoreInfo(mut set_store_info) => {
+                if self.rewrite_store_ids {
+                    set_store_info.info.store_id =
+                        rewrite_store_id(&set_store_info.info.store_id, client);
+                }
+                Ok(LogMsg::SetStoreInfo(set_store_info))
+            }
+
+            LogMsg::ArrowMsg(mut store_id, mut arrow_msg) => {
+                if self.prefix_entity_paths {
+                    let table = DataTable::from_arrow_msg(&arrow_msg)?;
+                    let prefix = EntityPath::from_single_string(client);
+                    let rows = table
+                        .to_rows()
+                        .map(|row| match store_id.kind {
+                            StoreKind::Recording => {
+                                let mut row = row?;
+                                row.entity_path = prefix.join(&row.entity_path);
+                                Ok(row)
+                            }
+                            StoreKind::Blueprint => prefix_blueprint_row(row?, &prefix),
+                        })
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    arrow_msg = DataTable::from_rows(table.table_id, rows).to_arrow_msg()?;
+                }
+                if self.rewrite_store_ids {
+                    store_id = rewrite_store_id(&store_id, client);
+                }
+                Ok(LogMsg::ArrowMsg(store_id, arrow_msg))
+            }
+        }
+    }
+}
+
+fn rewrite_store_id(store_id: &StoreId, client: &str) -> StoreId {
+    StoreId::from_string(store_id.kind, format!("{}@{client}", store_id.as_str()))
+}
+
+/// The blueprint components holding entity paths of the recording, as strings.
+///
+/// Named rather than typed, as `QueryExpressions` lives in the viewer.
+const SPACE_VIEW_ORIGIN: &str = "rerun.blueprint.components.SpaceViewOrigin";
+const QUERY_EXPRESSIONS: &str = "rerun.blueprint.components.QueryExpressions";
+
+/// Makes the entity paths a blueprint row refers to match the prefixed ones of its recording.
+///
+/// The entity paths of the row itself belong to the viewer, so they are left alone.
+fn prefix_blueprint_row(mut row: DataRow, prefix: &EntityPath) -> anyhow::Result<DataRow> {
+    for cell in row.cells.0.iter_mut() {
+        let prefix_value: fn(&str, &EntityPath) -> String = match cell.component_name().as_str() {
+            SPACE_VIEW_ORIGIN => |origin: &str, prefix: &EntityPath| {
+                prefix
+                    .join(&EntityPath::parse_forgiving(origin))
+                    .to_string()
+            },
+            QUERY_EXPRESSIONS => |expressions: &str, prefix: &EntityPath| {
+                let mut filter = EntityPathFilter::default();
+                for (rule, effect) in EntityPathFilter::parse_forgiving(expressions).iter() {
+                    filter.add_rule(
+                        effect,
+                        EntityPathRule {
+                            path: prefix.join(&rule.path),
+                            include_subtree: rule.include_subtree,
+                        },
+                    );
+                }
+                filter.formatted()
+            },
+            _ => continue,
+        };
+
+        let values = cell
+            .as_arrow_ref()
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .with_context(|| format!("Expected {} to be strings", cell.component_name()))?;
+        let prefixed = values
+            .iter()
+            .map(|value| value.map(|value| prefix_value(value, prefix)))
+            .collect::<Utf8Array<i32>>();
+        *cell = DataCell::from_arrow(cell.component_name(), prefixed.boxed());
+    }
+    Ok(row)
+}
+
+// ---
+
+/// The messages waiting to be forwarded upstream, bounded in size.
+struct RelayQueue {
+    state: Mutex<RelayQueueState>,
+    cv: Condvar,
+
+    /// `None` means unlimited.
+    max_bytes: Option<u64>,
+}
+
+#[derive(Default)]
+struct RelayQueueState {
+    messages: VecDeque<(LogMsg, u64)>,
+    num_bytes: u64,
+    closed: bool,
+}
+
+impl RelayQueue {
+    fn new(limit: re_memory::MemoryLimit) -> Self {
+        Self {
+            state: Default::default(),
+            cv: Condvar::new(),
+            max_bytes: limit.max_bytes.map(|max_bytes| max_bytes.max(0) as u64),
+        }
+    }
+
+    /// Queues a message, dropping the oldest data if over the limit.
+    ///
+    /// Store infos are never dropped. Returns the number of dropped messages.
+    fn push(&self, msg: LogMsg) -> usize {
+        let msg_bytes = msg_size_bytes(&msg);
+
+        let mut state = self.state.lock();
+        state.messages.push_back((msg, msg_bytes));
+        state.num_bytes += msg_bytes;
+
+        let mut num_dropped = 0;
+        if let Some(max_bytes) = self.max_bytes {
+            while state.num_bytes > max_bytes {
+                let Some(oldest) = state
+                    .messages
+                    .iter()
+                    .position(|(msg, _)| matches!(msg, LogMsg::ArrowMsg(..)))
+                else {
+                    break;
+                };
+                if let Some((_, dropped_bytes)) = state.messages.remove(oldest) {
+                    state.num_bytes -= dropped_bytes;
+                    num_dropped += 1;
+                }
+            }
+        }
+
+        self.cv.notify_one();
+        num_dropped
+    }
+
+    /// Waits for the next message, or returns `None` once closed and empty.
+    fn pop(&self) -> Option<LogMsg> {
+        let mut state = self.state.lock();
+        loop {
+            if let Some((msg, msg_bytes)) = state.messages.pop_front() {
+                state.num_bytes -= msg_bytes;
+                return Some(msg);
+            }
+            if state.closed {
+                return None;
+            }
+            self.cv.wait(&mut state);
+        }
+    }
+
+    fn close(&self) {
+        self.state.lock().closed = true;
+        self.cv.notify_all();
+    }
+}
+
+fn msg_size_bytes(msg: &LogMsg) -> u64 {
+    match msg {
+        LogMsg::SetStoreInfo(_) => 0,
+        LogMsg::ArrowMsg(_, arrow_msg) => arrow_msg
+            .chunk
+            .arrays()
+            .iter()
+            .map(|array| array.as_ref().total_size_bytes())
+            .sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use re_log_types::{
+        RowId, RuleEffect, SetStoreInfo, StoreInfo, StoreSource, TableId, Time, TimePoint,
+    };
+    use re_types::{blueprint::components::SpaceViewOrigin, components::Text, Loggable as _};
+
+    use super::*;
+
+    fn store_info(store_id: &StoreId) -> LogMsg {
+        LogMsg::SetStoreInfo(SetStoreInfo {
+            row_id: RowId::new(),
+            info: StoreInfo {
+                application_id: ApplicationId("test".to_owned()),
+                store_id: store_id.clone(),
+                is_official_example: false,
+                started: Time::from_ns_since_epoch(0),
+                store_source: StoreSource::Unknown,
+                store_kind: store_id.kind,
+            },
+        })
+    }
+
+    fn text_msg(store_id: &StoreId, entity_path: &str) -> LogMsg {
+        let row = DataRow::from_cells1_sized(
+            RowId::new(),
+            EntityPath::from(entity_path),
+            TimePoint::default(),
+            1,
+            [Text::from("hello")].as_slice(),
+        )
+        .unwrap();
+        let table = DataTable::from_rows(TableId::new(), [row]);
+        LogMsg::ArrowMsg(store_id.clone(), table.to_arrow_msg().unwrap())
+    }
+
+    fn entity_paths(msg: &LogMsg) -> Vec<EntityPath> {
+        let LogMsg::ArrowMsg(_, arrow_msg) = msg else {
+            panic!("expected an ArrowMsg");
+        };
+        DataTable::from_arrow_msg(arrow_msg)
+            .unwrap()
+            .to_rows()
+            .map(|row| row.unwrap().entity_path)
+            .collect()
+    }
+
+    #[test]
+    fn rewrite() {
+        let recording_id = StoreId::from_string(StoreKind::Recording, "rec".to_owned());
+        let blueprint_id = StoreId::from_string(StoreKind::Blueprint, "bp".to_owned());
+        let client = "10.0.0.7";
+
+        let rewrite = Rewrite::default();
+        let msg = rewrite
+            .apply(text_msg(&recording_id, "world/points"), client)
+            .unwrap();
+        assert_eq!(msg.store_id(), &recording_id);
+        assert_eq!(entity_paths(&msg), vec![EntityPath::from("world/points")]);
+
+        let rewrite = Rewrite {
+            prefix_entity_paths: true,
+            rewrite_store_ids: true,
+        };
+        let expected_id = StoreId::from_string(StoreKind::Recording, "rec@10.0.0.7".to_owned());
+
+        let msg = rewrite.apply(store_info(&recording_id), client).unwrap();
+        assert_eq!(msg.store_id(), &expected_id);
+
+        let msg = rewrite
+            .apply(text_msg(&recording_id, "world/points"), client)
+            .unwrap();
+        assert_eq!(msg.store_id(), &expected_id);
+        assert_eq!(
+            entity_paths(&msg),
+            vec![EntityPath::from_single_string(client).join(&EntityPath::from("world/points"))]
+        );
+
+        // Blueprints keep their entity paths.
+        let msg = rewrite
+            .apply(text_msg(&blueprint_id, "world/points"), client)
+            .unwrap();
+        assert_eq!(msg.store_id().as_str(), "bp@10.0.0.7");
+        assert_eq!(entity_paths(&msg), vec![EntityPath::from("world/points")]);
+    }
+
+    #[test]
+    fn rewrite_blueprint() {
+        let blueprint_id = StoreId::from_string(StoreKind::Blueprint, "bp".to_owned());
+        let client = "10.0.0.7";
+        assert_eq!(SpaceViewOrigin::name(), SPACE_VIEW_ORIGIN);
+
+        let space_view_path = EntityPath::from("space_view/1234");
+        let row = DataRow::from_cells(
+            RowId::new(),
+            TimePoint::default(),
+            space_view_path.clone(),
+            1,
+            [
+                DataCell::from_native([SpaceViewOrigin::from(&EntityPath::from("world"))]),
+                DataCell::from_arrow(
+                    QUERY_EXPRESSIONS.into(),
+                    Utf8Array::<i32>::from_slice(["+ /world/**\n- /world/car"]).boxed(),
+                ),
+            ],
+        )
+        .unwrap();
+        let table = DataTable::from_rows(TableId::new(), [row]);
+        let msg = LogMsg::ArrowMsg(blueprint_id, table.to_arrow_msg().unwrap());
+
+        let rewrite = Rewrite {
+            prefix_entity_paths: true,
+            rewrite_store_ids: false,
+        };
+        let LogMsg::ArrowMsg(_, arrow_msg) = rewrite.apply(msg, client).unwrap() else {
+            panic!("expected an ArrowMsg");
+        };
+        let row = DataTable::from_arrow_msg(&arrow_msg)
+            .unwrap()
+            .to_rows()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        // The space view stays where the viewer expects it, but now shows the prefixed data.
+        let world = EntityPath::from_single_string(client).join(&EntityPath::from("world"));
+        assert_eq!(row.entity_path, space_view_path);
+        let origin = row.cells.0[row.find_cell(&SPACE_VIEW_ORIGIN.into()).unwrap()]
+            .to_native::<SpaceViewOrigin>();
+        assert_eq!(EntityPath::from(origin[0].0.clone()), world);
+        let expressions = row.cells.0[row.find_cell(&QUERY_EXPRESSIONS.into()).unwrap()].to_arrow();
+        let expressions = expressions
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .unwrap();
+        let mut expected = EntityPathFilter::default();
+        expected.add_rule(
+            RuleEffect::Include,
+            EntityPathRule::including_subtree(world.clone()),
+        );
+        expected.add_rule(
+            RuleEffect::Exclude,
+            EntityPathRule::exact(world.join(&EntityPath::from("car"))),
+        );
+        assert_eq!(
+            EntityPathFilter::parse_forgiving(expressions.value(0)).formatted(),
+            expected.formatted()
+        );
+    }
+
+    #[test]
+    fn client_names_survive_reconnecting() {
+        let store_id = StoreId::from_string(StoreKind::Recording, "rec".to_owned());
+        let source = |port| SmartMessageSource::TcpClient {
+            addr: Some(std::net::SocketAddr::from(([10, 0, 0, 7], port))),
+        };
+
+        let mut client_names = ClientNames::default();
+        assert_eq!(
+            client_names.name(&source(50314), &text_msg(&store_id, "a")),
+            Some("10.0.0.7".to_owned())
+        );
+        assert_eq!(
+            client_names.name(&source(50314), &store_info(&store_id)),
+            Some("test@10.0.0.7".to_owned())
+        );
+        assert_eq!(
+            client_names.name(&source(50315), &text_msg(&store_id, "a")),
+            Some("test@10.0.0.7".to_owned())
+        );
+        assert_eq!(
+            client_names.name(
+                &SmartMessageSource::UnixSocketClient { pid: Some(42) },
+                &text_msg(&store_id, "a")
+            ),
+            Some("pid_42".to_owned())
+        );
+        assert_eq!(
+            client_names.name(&SmartMessageSource::Unknown, &text_msg(&store_id, "a")),
+            None
+        );
+    }
+
+    #[test]
+    fn queue_drops_oldest_data() {
+        let store_id = StoreId::from_string(StoreKind::Recording, "rec".to_owned());
+        let msg_bytes = msg_size_bytes(&text_msg(&store_id, "a"));
+        assert!(msg_bytes > 0);
+
+        // Room for two messages.
+        let queue = RelayQueue::new(re_memory::MemoryLimit::from_bytes(2 * msg_bytes + 1));
+
+        assert_eq!(queue.push(store_info(&store_id)), 0);
+        assert_eq!(queue.push(text_msg(&store_id, "a")), 0);
+        assert_eq!(queue.push(text_msg(&store_id, "b")), 0);
+        assert_eq!(queue.push(text_msg(&store_id, "c")), 1);
+        queue.close();
+
+        let messages = std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>();
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(messages[0], LogMsg::SetStoreInfo(_)));
+        assert_eq!(entity_paths(&messages[1]), vec![EntityPath::from("b")]);
+        assert_eq!(entity_paths(&messages[2]), vec![EntityPath::from("c")]);
+    }
+}
@@ -0,0 +1,255 @@
+//! `rerun replay`: stream an .rrd file to a viewer at the pace it was recorded.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+
+use re_log_types::{DataTable, LogMsg, Time, TimeInt, TimeType, Timeline};
+use re_sdk::sink::LogSink as _;
+
+use super::Args;
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct ReplayArgs {
+    /// The .rrd file to play back.
+    rrd_path: String,
+
+    /// The timeline whose times decide when each message is sent.
+    ///
+    /// Messages without a time on it are sent right away.
+    #[clap(long, default_value = "log_time")]
+    timeline: String,
+
+    /// How much faster than real time to play back, e.g. 0.5 for half speed.
+    #[clap(long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// How long a step of a sequence timeline lasts, in seconds.
+    #[clap(long, default_value_t = 1.0)]
+    seconds_per_step: f64,
+
+    /// The Rerun Viewer to send the data to, e.g. "127.0.0.1:9876" or "unix:///tmp/rerun.sock".
+    ///
+    /// The viewer's `--token`, if any, is given with `rerun --token … replay`.
+    #[clap(long, default_value_t = re_sdk_comms::default_server_addr().to_string())]
+    connect: String,
+
+    /// Shift the `log_time` of the data so that it looks like it is being logged right now.
+    #[clap(long)]
+    rewrite_log_time: bool,
+}
+
+/// Sends the contents of the .rrd file to a viewer, sleeping between messages
+/// according to their times on the chosen timeline.
+pub fn run_replay(args: &Args, replay: &ReplayArgs) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        0.0 < replay.speed && 0.0 < replay.seconds_per_step,
+        "--speed and --seconds-per-step must be positive"
+    );
+
+    let rrd_path = Path::new(&replay.rrd_path);
+    let file =
+        std::fs::File::open(rrd_path).with_context(|| format!("Failed to open {rrd_path:?}"))?;
+    let version_policy = re_log_encoding::decoder::VersionPolicy::Warn;
+    let decoder =
+        re_log_encoding::decoder::Decoder::new(version_policy, std::io::BufReader::new(file))
+            .with_context(|| format!("Failed to decode {rrd_path:?}"))?;
+
+    let sink = re_sdk::sink::TcpSink::with_options(
+        super::parse_viewer_addr(&replay.connect)?,
+        re_sdk_comms::default_flush_timeout(),
+        re_sdk::sink::ClientOptions {
+            token: args.token.clone(),
+            ..Default::default()
+        },
+    );
+
+    let mut pacer = Pacer {
+        timeline: replay.timeline.clone(),
+        speed: replay.speed,
+        seconds_per_step: replay.seconds_per_step,
+        start: None,
+    };
+
+    re_log::info!("Replaying {rrd_path:?} to {}…", replay.connect);
+
+    let mut num_messages = 0;
+    for msg in decoder {
+        let mut msg = msg.context("decode rrd message")?;
+
+        if let Some(deadline) = pacer.deadline(&msg) {
+            std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        }
+
+        if replay.rewrite_log_time {
+            msg = shift_log_time_to_now(msg)?;
+        }
+
+        sink.send(msg);
+        num_messages += 1;
+    }
+
+    if pacer.start.is_none() {
+        re_log::warn!(
+            "No message had a time on the timeline {:?}: everything was sent right away",
+            replay.timeline
+        );
+    }
+
+    sink.flush_blocking();
+    re_log::info!("Replayed {num_messages} messages.");
+
+    Ok(())
+}
+
+/// Decides when to send each message.
+struct Pacer {
+    timeline: String,
+    speed: f64,
+    seconds_per_step: f64,
+
+    /// The time of the first message with a time on the timeline, and when it was sent.
+    start: Option<(TimeInt, Instant)>,
+}
+
+impl Pacer {
+    /// When to send this message, or `None` to send it right away.
+    fn deadline(&mut self, msg: &LogMsg) -> Option<Instant> {
+        let LogMsg::ArrowMsg(_, arrow_msg) = msg else {
+            return None;
+        };
+        let (timeline, time) = arrow_msg
+            .timepoint_max
+            .iter()
+            .find(|(timeline, _)| timeline.name().as_str() == self.timeline)?;
+
+        let (start_time, start_instant) = *self.start.get_or_insert((*time, Instant::now()));
+        Some(start_instant + self.offset(timeline, *time - start_time))
+    }
+
+    /// How long after the first message to send one this much later on the timeline.
+    ///
+    /// Messages earlier than the first one are sent right away.
+    fn offset(&self, timeline: &Timeline, delta: TimeInt) -> Duration {
+        let seconds = match timeline.typ() {
+            TimeType::Time => delta.as_f64() * 1e-9,
+            TimeType::Sequence => delta.as_f64() * self.seconds_per_step,
+        };
+        Duration::from_secs_f64((seconds / self.speed).max(0.0))
+    }
+}
+
+/// Shifts the `log_time` of all rows, so that the latest of them is now.
+fn shift_log_time_to_now(msg: LogMsg) -> anyhow::Result<LogMsg> {
+    let LogMsg::ArrowMsg(store_id, arrow_msg) = msg else {
+        return Ok(msg);
+    };
+    let log_time = Timeline::log_time();
+    let Some(latest) = arrow_msg.timepoint_max.get(&log_time).copied() else {
+        return Ok(LogMsg::ArrowMsg(store_id, arrow_msg));
+    };
+
+    let shift = TimeInt::from(Time::now()) - latest;
+    let table = DataTable::from_arrow_msg(&arrow_msg)?;
+    let rows = table
+        .to_rows()
+        .map(|row| {
+            row.map(|mut row| {
+                if let Some(time) = row.timepoint.get(&log_time).copied() {
+                    row.timepoint.insert(log_time, time + shift);
+                }
+                row
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let table = DataTable::from_rows(table.table_id, rows);
+    Ok(LogMsg::ArrowMsg(store_id, table.to_arrow_msg()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use re_log_types::{DataRow, EntityPath, RowId, StoreId, StoreKind, TableId, TimePoint};
+    use re_types::components::Text;
+
+    use super::*;
+
+    fn text_msg(timepoint: TimePoint) -> LogMsg {
+        let row = DataRow::from_cells1_sized(
+            RowId::new(),
+            EntityPath::from("text"),
+            timepoint,
+            1,
+            [Text::from("hello")].as_slice(),
+        )
+        .unwrap();
+        let table = DataTable::from_rows(TableId::new(), [row]);
+        LogMsg::ArrowMsg(
+            StoreId::from_string(StoreKind::Recording, "rec".to_owned()),
+            table.to_arrow_msg().unwrap(),
+        )
+    }
+
+    #[test]
+    fn pace_by_timeline() {
+        let frame = Timeline::new("frame", TimeType::Sequence);
+        let mut pacer = Pacer {
+            timeline: "frame".to_owned(),
+            speed: 2.0,
+            seconds_per_step: 0.5,
+            start: None,
+        };
+
+        // Timeless data goes right away, and doesn't start the clock.
+        assert_eq!(pacer.deadline(&text_msg(TimePoint::timeless())), None);
+        assert!(pacer.start.is_none());
+
+        let first = pacer
+            .deadline(&text_msg(TimePoint::from_iter([(frame, 10.into())])))
+            .unwrap();
+        let later = pacer
+            .deadline(&text_msg(TimePoint::from_iter([(frame, 14.into())])))
+            .unwrap();
+        let earlier = pacer
+            .deadline(&text_msg(TimePoint::from_iter([(frame, 3.into())])))
+            .unwrap();
+
+        // 4 steps of half a second, played at twice the speed.
+        assert_eq!(later - first, Duration::from_secs(1));
+        assert_eq!(earlier, first);
+
+        let log_time = Timeline::log_time();
+        assert_eq!(
+            pacer.offset(&log_time, TimeInt::from_seconds(3)),
+            Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn rewrite_log_time() {
+        let log_time = Timeline::log_time();
+        let frame = Timeline::new("frame", TimeType::Sequence);
+        let recorded = TimeInt::from_seconds(1_000_000);
+        let msg = text_msg(TimePoint::from_iter([
+            (log_time, recorded),
+            (frame, 7.into()),
+        ]));
+
+        let before = TimeInt::from(Time::now());
+        let msg = shift_log_time_to_now(msg).unwrap();
+        let after = TimeInt::from(Time::now());
+
+        let LogMsg::ArrowMsg(_, arrow_msg) = msg else {
+            unreachable!();
+        };
+        let rows = DataTable::from_arrow_msg(&arrow_msg)
+            .unwrap()
+            .to_rows()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let shifted = *rows[0].timepoint.get(&log_time).unwrap();
+        assert!(before <= shifted && shifted <= after);
+        assert_eq!(rows[0].timepoint.get(&frame), Some(&7.into()));
+    }
+}
@@ -0,0 +1,573 @@
+//! `rerun rrd …`: tools for manipulating .rrd files without loading them into a viewer.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use re_log_types::{
+    DataRow, DataTable, EntityPathFilter, LogMsg, StoreId, TableId, TimeInt, TimeRange,
+};
+use re_types::SizeBytes as _;
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum RrdCommands {
+    /// Copy the parts of an .rrd file that match the given entity paths and time range.
+    ///
+    /// Rows without a time on the selected timeline are timeless, and are always kept.
+    Filter {
+        /// The .rrd file to read.
+        input: String,
+
+        /// Where to write the filtered .rrd file.
+        #[clap(long, short)]
+        output: String,
+
+        /// An entity path filter, e.g. "+ /world/** - /world/camera".
+        ///
+        /// Can be given several times; the rules are concatenated.
+        /// Keeps all entities if not given.
+        #[clap(long)]
+        entity: Vec<String>,
+
+        /// Name of the timeline that `--min` and `--max` refer to.
+        #[clap(long)]
+        timeline: Option<String>,
+
+        /// Keep rows at or after this time on `--timeline`.
+        ///
+        /// Sequence timelines use the raw sequence number, temporal timelines nanoseconds since the Unix epoch.
+        #[clap(long, allow_hyphen_values = true)]
+        min: Option<i64>,
+
+        /// Keep rows at or before this time on `--timeline`.
+        #[clap(long, allow_hyphen_values = true)]
+        max: Option<i64>,
+
+        /// Write an index at the end of the output, for random access to its messages.
+        ///
+        /// Older versions of Rerun can't read indexed files.
+        #[clap(long)]
+        index: bool,
+    },
+
+    /// Merge several .rrd files into one.
+    ///
+    /// Recordings with the same id in different files are merged into a single recording.
+    Merge {
+        /// The .rrd files to read, in order.
+        #[clap(required = true)]
+        inputs: Vec<String>,
+
+        /// Where to write the merged .rrd file.
+        #[clap(long, short)]
+        output: String,
+
+        /// Write an index at the end of the output, for random access to its messages.
+        ///
+        /// Older versions of Rerun can't read indexed files.
+        #[clap(long)]
+        index: bool,
+    },
+
+    /// Re-batch the rows of an .rrd file into fewer, larger messages.
+    ///
+    /// Useful for files made of many tiny messages, which are slow to load.
+    Compact {
+        /// The .rrd file to read.
+        input: String,
+
+        /// Where to write the compacted .rrd file.
+        #[clap(long, short)]
+        output: String,
+
+        /// Maximum number of rows per message, at least 1.
+        #[clap(long, default_value_t = 4096)]
+        max_rows: usize,
+
+        /// Maximum size of a message, e.g. "8MiB". Must be positive.
+        #[clap(long, default_value = "8MiB")]
+        max_bytes: String,
+
+        /// Write an index at the end of the output, for random access to its messages.
+        ///
+        /// Older versions of Rerun can't read indexed files.
+        #[clap(long)]
+        index: bool,
+    },
+}
+
+pub fn run_rrd(cmd: &RrdCommands) -> anyhow::Result<()> {
+    match cmd {
+        RrdCommands::Filter {
+            input,
+            output,
+            entity,
+            timeline,
+            min,
+            max,
+            index,
+        } => {
+            let entity_path_filter = if entity.is_empty() {
+                None
+            } else {
+                Some(EntityPathFilter::parse_forgiving(&entity.join("\n")))
+            };
+
+            let time_filter = match (timeline, min, max) {
+                (None, None, None) => None,
+                (Some(timeline), min, max) => Some((
+                    timeline.clone(),
+                    TimeRange::new(
+                        min.map_or(TimeInt::MIN, TimeInt::from),
+                        max.map_or(TimeInt::MAX, TimeInt::from),
+                    ),
+                )),
+                (None, _, _) => anyhow::bail!("--min and --max require --timeline"),
+            };
+
+            let filter = RowFilter {
+                entity_path_filter,
+                time_filter,
+            };
+            filter_rrd(Path::new(input), Path::new(output), &filter, *index)
+        }
+
+        RrdCommands::Merge {
+            inputs,
+            output,
+            index,
+        } => {
+            let inputs = inputs.iter().map(PathBuf::from).collect::<Vec<_>>();
+            merge_rrds(&inputs, Path::new(output), *index)
+        }
+
+        RrdCommands::Compact {
+            input,
+            output,
+            max_rows,
+            max_bytes,
+            index,
+        } => {
+            anyhow::ensure!(0 < *max_rows, "--max-rows must be at least 1");
+            let max_bytes = re_format::parse_bytes(max_bytes)
+                .filter(|&max_bytes| 0 < max_bytes)
+                .ok_or_else(|| anyhow::anyhow!("Bad --max-bytes: {max_bytes:?}"))?;
+            compact_rrd(
+                Path::new(input),
+                Path::new(output),
+                *max_rows,
+                max_bytes as u64,
+                *index,
+            )
+        }
+    }
+}
+
+// ---
+
+fn open_decoder(
+    path: &Path,
+) -> anyhow::Result<re_log_encoding::decoder::Decoder<std::io::BufReader<std::fs::File>>> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {path:?}"))?;
+    let version_policy = re_log_encoding::decoder::VersionPolicy::Warn;
+    re_log_encoding::decoder::Decoder::new(version_policy, std::io::BufReader::new(file))
+        .with_context(|| format!("Failed to decode {path:?}"))
+}
+
+/// Indexed files are for random access, but can't be read by older versions of Rerun.
+fn create_encoder(
+    path: &Path,
+    index: bool,
+) -> anyhow::Result<re_log_encoding::encoder::Encoder<std::io::BufWriter<std::fs::File>>> {
+    let file = std::fs::File::create(path).with_context(|| format!("Failed to create {path:?}"))?;
+    let encoding_options = re_log_encoding::EncodingOptions::COMPRESSED;
+    let write = std::io::BufWriter::new(file);
+    Ok(if index {
+        re_log_encoding::encoder::Encoder::new_indexed(encoding_options, write)?
+    } else {
+        re_log_encoding::encoder::Encoder::new(encoding_options, write)?
+    })
+}
+
+fn table_to_msg(store_id: StoreId, table: &DataTable) -> anyhow::Result<LogMsg> {
+    Ok(LogMsg::ArrowMsg(store_id, table.to_arrow_msg()?))
+}
+
+// --- filter ---
+
+struct RowFilter {
+    entity_path_filter: Option<EntityPathFilter>,
+
+    /// Timeline name and the range to keep on it.
+    time_filter: Option<(String, TimeRange)>,
+}
+
+impl RowFilter {
+    fn keep(&self, row: &DataRow) -> bool {
+        if let Some(filter) = &self.entity_path_filter {
+            if !filter.is_included(row.entity_path()) {
+                return false;
+            }
+        }
+
+        if let Some((timeline_name, range)) = &self.time_filter {
+            let time = row
+                .timepoint()
+                .iter()
+                .find(|(timeline, _)| timeline.name().as_str() == timeline_name)
+                .map(|(_, time)| *time);
+            if let Some(time) = time {
+                if !range.contains(time) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+fn filter_rrd(input: &Path, output: &Path, filter: &RowFilter, index: bool) -> anyhow::Result<()> {
+    let decoder = open_decoder(input)?;
+    let mut encoder = create_encoder(output, index)?;
+
+    let mut num_rows_in = 0;
+    let mut num_rows_out = 0;
+
+    for msg in decoder {
+        let msg = msg.context("decode rrd message")?;
+        match &msg {
+            LogMsg::SetStoreInfo(_) => encoder.append(&msg)?,
+            LogMsg::ArrowMsg(store_id, arrow_msg) => {
+                let table = DataTable::from_arrow_msg(arrow_msg).context("decode arrow message")?;
+                num_rows_in += table.num_rows() as usize;
+
+                let rows = table
+                    .to_rows()
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("decode rows")?;
+                let num_rows = rows.len();
+                let rows = rows
+                    .into_iter()
+                    .filter(|row| filter.keep(row))
+                    .collect::<Vec<_>>();
+                num_rows_out += rows.len();
+
+                if rows.len() == num_rows {
+                    encoder.append(&msg)?;
+                } else if !rows.is_empty() {
+                    let table = DataTable::from_rows(table.table_id, rows);
+                    encoder.append(&table_to_msg(store_id.clone(), &table)?)?;
+                }
+            }
+        }
+    }
+
+    encoder.finish()?;
+
+    re_log::info!("Kept {num_rows_out} of {num_rows_in} rows, written to {output:?}");
+
+    Ok(())
+}
+
+// --- merge ---
+
+fn merge_rrds(inputs: &[PathBuf], output: &Path, index: bool) -> anyhow::Result<()> {
+    let mut encoder = create_encoder(output, index)?;
+
+    // Only the first `SetStoreInfo` of each store is kept.
+    let mut known_stores: HashSet<StoreId> = HashSet::new();
+
+    for input in inputs {
+        for msg in open_decoder(input)? {
+            let msg = msg.with_context(|| format!("decode rrd message in {input:?}"))?;
+            if let LogMsg::SetStoreInfo(info) = &msg {
+                if !known_stores.insert(info.info.store_id.clone()) {
+                    continue;
+                }
+            }
+            encoder.append(&msg)?;
+        }
+    }
+
+    encoder.finish()?;
+
+    re_log::info!("Merged {} files into {output:?}", inputs.len());
+
+    Ok(())
+}
+
+// --- compact ---
+
+/// Rows waiting to be written as a single message.
+#[derive(Default)]
+struct PendingRows {
+    rows: Vec<DataRow>,
+    num_bytes: u64,
+}
+
+fn flush_pending(
+    encoder: &mut re_log_encoding::encoder::Encoder<impl std::io::Write>,
+    store_id: &StoreId,
+    pending: &mut PendingRows,
+) -> anyhow::Result<()> {
+    let table = DataTable::from_rows(TableId::new(), pending.rows.drain(..));
+    encoder.append(&table_to_msg(store_id.clone(), &table)?)?;
+    pending.num_bytes = 0;
+    Ok(())
+}
+
+fn compact_rrd(
+    input: &Path,
+    output: &Path,
+    max_rows: usize,
+    max_bytes: u64,
+    index: bool,
+) -> anyhow::Result<()> {
+    let decoder = open_decoder(input)?;
+    let mut encoder = create_encoder(output, index)?;
+
+    let mut pending: BTreeMap<StoreId, PendingRows> = BTreeMap::new();
+
+    let mut num_msgs_in = 0;
+    let mut num_msgs_out = 0;
+
+    for msg in decoder {
+        let msg = msg.context("decode rrd message")?;
+        num_msgs_in += 1;
+
+        match msg {
+            LogMsg::SetStoreInfo(_) => {
+                encoder.append(&msg)?;
+                num_msgs_out += 1;
+            }
+            LogMsg::ArrowMsg(store_id, arrow_msg) => {
+                let table =
+                    DataTable::from_arrow_msg(&arrow_msg).context("decode arrow message")?;
+                let store_pending = pending.entry(store_id.clone()).or_default();
+
+                for row in table.to_rows() {
+                    let row = row.context("decode row")?;
+                    let row_bytes = row.total_size_bytes();
+
+                    if !store_pending.rows.is_empty()
+                        && (max_rows <= store_pending.rows.len()
+                            || max_bytes < store_pending.num_bytes + row_bytes)
+                    {
+                        flush_pending(&mut encoder, &store_id, store_pending)?;
+                        num_msgs_out += 1;
+                    }
+
+                    store_pending.num_bytes += row_bytes;
+                    store_pending.rows.push(row);
+                }
+            }
+        }
+    }
+
+    for (store_id, store_pending) in &mut pending {
+        if !store_pending.rows.is_empty() {
+            flush_pending(&mut encoder, store_id, store_pending)?;
+            num_msgs_out += 1;
+        }
+    }
+
+    encoder.finish()?;
+
+    re_log::info!("Compacted {num_msgs_in} messages into {num_msgs_out}, written to {output:?}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use re_log_types::{
+        ApplicationId, EntityPath, RowId, SetStoreInfo, StoreInfo, StoreKind, StoreSource, Time,
+        TimePoint, TimeType, Timeline,
+    };
+    use re_types::components::Text;
+
+    use super::*;
+
+    fn store_info(store_id: &StoreId) -> LogMsg {
+        LogMsg::SetStoreInfo(SetStoreInfo {
+            row_id: RowId::new(),
+            info: StoreInfo {
+                application_id: ApplicationId("test".to_owned()),
+                store_id: store_id.clone(),
+                is_official_example: false,
+                started: Time::from_ns_since_epoch(0),
+                store_source: StoreSource::Unknown,
+                store_kind: StoreKind::Recording,
+            },
+        })
+    }
+
+    /// One message per row: entities "a" and "b" at frames `0..num_frames`.
+    fn test_messages(store_id: &StoreId, num_frames: i64) -> Vec<LogMsg> {
+        let timeline = Timeline::new("frame", TimeType::Sequence);
+
+        let mut messages = vec![store_info(store_id)];
+        for frame in 0..num_frames {
+            for entity_path in ["a", "b"] {
+                let row = DataRow::from_cells1_sized(
+                    RowId::new(),
+                    EntityPath::from(entity_path),
+                    TimePoint::from_iter([(timeline, TimeInt::from(frame))]),
+                    1,
+                    [Text::from("hello")].as_slice(),
+                )
+                .unwrap();
+                let table = DataTable::from_rows(TableId::new(), [row]);
+                messages.push(table_to_msg(store_id.clone(), &table).unwrap());
+            }
+        }
+        messages
+    }
+
+    fn write_rrd(path: &Path, messages: &[LogMsg]) {
+        let mut encoder = create_encoder(path, false).unwrap();
+        for msg in messages {
+            encoder.append(msg).unwrap();
+        }
+        encoder.finish().unwrap();
+    }
+
+    fn read_rrd(path: &Path) -> Vec<LogMsg> {
+        open_decoder(path)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    /// The number of store infos, and the (entity, frame) of every row, in order.
+    fn summarize(messages: &[LogMsg]) -> (usize, Vec<(String, i64)>) {
+        let timeline = Timeline::new("frame", TimeType::Sequence);
+
+        let mut num_store_infos = 0;
+        let mut rows = Vec::new();
+        for msg in messages {
+            match msg {
+                LogMsg::SetStoreInfo(_) => num_store_infos += 1,
+                LogMsg::ArrowMsg(_, arrow_msg) => {
+                    let table = DataTable::from_arrow_msg(arrow_msg).unwrap();
+                    for row in table.to_rows() {
+                        let row = row.unwrap();
+                        let frame = row.timepoint().get(&timeline).unwrap().as_i64();
+                        rows.push((row.entity_path().to_string(), frame));
+                    }
+                }
+            }
+        }
+        (num_store_infos, rows)
+    }
+
+    #[test]
+    fn filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.rrd");
+        let output = dir.path().join("output.rrd");
+
+        let store_id = StoreId::random(StoreKind::Recording);
+        write_rrd(&input, &test_messages(&store_id, 10));
+
+        run_rrd(&RrdCommands::Filter {
+            input: input.to_string_lossy().to_string(),
+            output: output.to_string_lossy().to_string(),
+            entity: vec!["+ a".to_owned()],
+            timeline: Some("frame".to_owned()),
+            min: Some(2),
+            max: Some(4),
+            index: false,
+        })
+        .unwrap();
+
+        let (num_store_infos, rows) = summarize(&read_rrd(&output));
+        assert_eq!(num_store_infos, 1);
+        assert_eq!(
+            rows,
+            vec![
+                ("/a".to_owned(), 2),
+                ("/a".to_owned(), 3),
+                ("/a".to_owned(), 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.rrd");
+        let second = dir.path().join("second.rrd");
+        let output = dir.path().join("output.rrd");
+
+        // The same recording, split across two files.
+        let store_id = StoreId::random(StoreKind::Recording);
+        let messages = test_messages(&store_id, 4);
+        write_rrd(&first, &messages[..5]);
+        write_rrd(&second, &[&messages[..1], &messages[5..]].concat());
+
+        run_rrd(&RrdCommands::Merge {
+            inputs: vec![
+                first.to_string_lossy().to_string(),
+                second.to_string_lossy().to_string(),
+            ],
+            output: output.to_string_lossy().to_string(),
+            index: true,
+        })
+        .unwrap();
+
+        let merged = read_rrd(&output);
+        assert_eq!(summarize(&merged), summarize(&messages));
+        assert!(merged.iter().all(|msg| msg.store_id() == &store_id));
+    }
+
+    #[test]
+    fn compact() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.rrd");
+        let output = dir.path().join("output.rrd");
+
+        let store_id = StoreId::random(StoreKind::Recording);
+        let messages = test_messages(&store_id, 10);
+        write_rrd(&input, &messages);
+
+        run_rrd(&RrdCommands::Compact {
+            input: input.to_string_lossy().to_string(),
+            output: output.to_string_lossy().to_string(),
+            max_rows: 4,
+            max_bytes: "8MiB".to_owned(),
+            index: false,
+        })
+        .unwrap();
+
+        let compacted = read_rrd(&output);
+        assert_eq!(summarize(&compacted), summarize(&messages));
+
+        // The store info, then 20 rows in messages of at most 4 rows.
+        assert_eq!(compacted.len(), 1 + 5);
+    }
+
+    #[test]
+    fn compact_rejects_bad_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.rrd");
+        let output = dir.path().join("output.rrd");
+
+        let store_id = StoreId::random(StoreKind::Recording);
+        write_rrd(&input, &test_messages(&store_id, 1));
+
+        for (max_rows, max_bytes) in [(0, "8MiB"), (4, "-1B"), (4, "0B"), (4, "lots")] {
+            let result = run_rrd(&RrdCommands::Compact {
+                input: input.to_string_lossy().to_string(),
+                output: output.to_string_lossy().to_string(),
+                max_rows,
+                max_bytes: max_bytes.to_owned(),
+                index: false,
+            });
+            assert!(result.is_err(), "{max_rows} rows, {max_bytes}");
+        }
+        assert!(!output.exists());
+    }
+}
@@ -0,0 +1,581 @@
+//! `rerun serve`: ingest the streams of SDKs without a viewer, and answer queries about them.
+//!
+//! The query protocol is newline-delimited JSON: each request is a single line holding a JSON
+//! object, answered by a single line holding either `{"ok": …}` or `{"error": "…"}`.
+//!
+//! Requests:
+//! * `{"query": "recordings"}`: the id, application id and timelines of every recording.
+//! * `{"query": "entities"}`: the components of every entity.
+//! * `{"query": "latest_at", "entity": "world/points", "timeline": "frame", "at": 42}`:
+//!   the latest value of every component of an entity at a time, or right now if `at` is omitted.
+//! * `{"query": "range", "entity": "world/points", "timeline": "frame", "min": 0, "max": 100}`:
+//!   every row of an entity logged within a time range, inclusive. Both bounds are optional.
+//!
+//! Times are integers: nanoseconds since the Unix epoch on temporal timelines,
+//! or sequence numbers. A time of `null` means the value is timeless.
+//!
+//! All requests but `recordings` take an optional `"recording"` id, and default to the recording
+//! that last received data. `latest_at` and `range` take an optional `"components"` list,
+//! with either full or short names, e.g. `"rerun.components.Position3D"` or `"Position3D"`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use parking_lot::RwLock;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt as _, AsyncRead, AsyncWrite, AsyncWriteExt as _, BufReader};
+
+use re_data_store::{LatestAtQuery, RangeQuery};
+use re_entity_db::EntityDb;
+use re_log_types::external::arrow2::{self, array::Array};
+use re_log_types::{
+    DataCell, EntityPath, LogMsg, RowId, StoreId, StoreKind, TimeInt, TimeRange, Timeline,
+};
+use re_smart_channel::SmartMessagePayload;
+use re_types::ComponentName;
+
+use super::Args;
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct ServeArgs {
+    /// Where to answer queries, e.g. "127.0.0.1:9878" or "unix:///tmp/rerun-query.sock".
+    #[clap(long, default_value = "127.0.0.1:9878")]
+    query_addr: String,
+}
+
+/// Listens for SDKs like the viewer does (see `--bind`, `--port`, `--token`…),
+/// and answers queries about what they logged, until killed.
+///
+/// Honors `--memory-limit` and `--retention`.
+pub async fn run_serve(args: &Args, serve: &ServeArgs) -> anyhow::Result<()> {
+    let memory_limit = re_memory::MemoryLimit::parse(&args.memory_limit)
+        .map_err(|err| anyhow::format_err!("Bad --memory-limit: {err}"))?;
+
+    let stores = Arc::new(RwLock::new(Stores {
+        store_config: re_data_store::DataStoreConfig {
+            retention: args.retention,
+            ..Default::default()
+        },
+        ..Default::default()
+    }));
+
+    let rx = super::serve_sdks(args, super::sdk_server_options(args, false)?).await?;
+    std::thread::Builder::new()
+        .name("serve_ingest".to_owned())
+        .spawn({
+            let stores = stores.clone();
+            move || {
+                let mut last_gc = Instant::now();
+                while let Ok(msg) = rx.recv() {
+                    match msg.payload {
+                        SmartMessagePayload::Msg(log_msg) => {
+                            if let Err(err) = stores.write().add(&log_msg) {
+                                re_log::warn!("Failed to ingest data from {}: {err}", msg.source);
+                            }
+                        }
+                        SmartMessagePayload::Quit(err) => {
+                            if let Some(err) = err {
+                                re_log::warn!(
+                                    "Data source {} has left unexpectedly: {err}",
+                                    msg.source
+                                );
+                            }
+                        }
+                    }
+
+                    // Every GC has to go through all rows, so don't do it for every message.
+                    if Duration::from_secs(1) < last_gc.elapsed() {
+                        last_gc = Instant::now();
+                        stores.write().gc(memory_limit);
+                    }
+                }
+            }
+        })
+        .context("Failed to spawn the ingestion thread")?;
+
+    #[cfg(unix)]
+    if let Some(path) = serve
+        .query_addr
+        .strip_prefix(re_sdk_comms::UNIX_SOCKET_SCHEME)
+    {
+        let path = std::path::Path::new(path);
+        re_sdk_comms::remove_stale_socket(path)
+            .with_context(|| format!("Failed to bind the query socket {path:?}"))?;
+        let listener = tokio::net::UnixListener::bind(path)
+            .with_context(|| format!("Failed to bind the query socket {path:?}"))?;
+        re_log::info!("Answering queries at {}", serve.query_addr);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            tokio::spawn(answer_queries(stream, stores.clone()));
+        }
+    }
+
+    let listener = tokio::net::TcpListener::bind(&serve.query_addr)
+        .await
+        .with_context(|| format!("Failed to bind the query address {:?}", serve.query_addr))?;
+    re_log::info!("Answering queries at {}", serve.query_addr);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(answer_queries(stream, stores.clone()));
+    }
+}
+
+/// Answers the requests of a single client, one per line, until it disconnects.
+async fn answer_queries(stream: impl AsyncRead + AsyncWrite, stores: Arc<RwLock<Stores>>) {
+    let (read, mut write) = tokio::io::split(stream);
+    let mut lines = BufReader::new(read).lines();
+
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => return,
+            Err(err) => {
+                re_log::debug!("Failed to read a query: {err}");
+                return;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => match stores.read().answer(&request) {
+                Ok(value) => Response::Ok(value),
+                Err(err) => Response::Error(format!("{err:#}")),
+            },
+            Err(err) => Response::Error(format!("Bad request: {err}")),
+        };
+
+        let mut response = serde_json::to_vec(&response).unwrap_or_default();
+        response.push(b'\n');
+        if let Err(err) = write.write_all(&response).await {
+            re_log::debug!("Failed to answer a query: {err}");
+            return;
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "query", rename_all = "snake_case", deny_unknown_fields)]
+enum Request {
+    Recordings,
+
+    Entities {
+        recording: Option<String>,
+    },
+
+    LatestAt {
+        recording: Option<String>,
+        entity: String,
+        timeline: String,
+        at: Option<i64>,
+        components: Option<Vec<String>>,
+    },
+
+    Range {
+        recording: Option<String>,
+        entity: String,
+        timeline: String,
+        min: Option<i64>,
+        max: Option<i64>,
+        components: Option<Vec<String>>,
+    },
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Response {
+    Ok(Value),
+    Error(String),
+}
+
+/// Every recording received so far.
+#[derive(Default)]
+struct Stores {
+    recordings: BTreeMap<StoreId, EntityDb>,
+
+    /// The recording that last received data, queried by default.
+    latest: Option<StoreId>,
+
+    /// The config of the store of each new recording.
+    store_config: re_data_store::DataStoreConfig,
+}
+
+impl Stores {
+    fn add(&mut self, msg: &LogMsg) -> anyhow::Result<()> {
+        let store_id = msg.store_id();
+        // Blueprints only matter to viewers.
+        if store_id.kind != StoreKind::Recording {
+            return Ok(());
+        }
+
+        self.recordings
+            .entry(store_id.clone())
+            .or_insert_with(|| {
+                EntityDb::with_store_config(store_id.clone(), self.store_config.clone())
+            })
+            .add(msg)?;
+        self.latest = Some(store_id.clone());
+        Ok(())
+    }
+
+    fn gc(&mut self, memory_limit: re_memory::MemoryLimit) {
+        re_tracing::profile_function!();
+
+        for db in self.recordings.values_mut() {
+            db.enforce_retention();
+        }
+
+        if let Some(minimum_fraction_to_purge) =
+            memory_limit.is_exceeded_by(&re_memory::MemoryUse::capture())
+        {
+            re_log::info_once!("Reached --memory-limit, dropping oldest data.");
+            let fraction_to_purge = (minimum_fraction_to_purge + 0.2).clamp(0.25, 1.0);
+            for db in self.recordings.values_mut() {
+                db.purge_fraction_of_ram(fraction_to_purge);
+            }
+        }
+    }
+
+    fn recording(&self, recording: Option<&str>) -> anyhow::Result<&EntityDb> {
+        match recording {
+            Some(id) => self
+                .recordings
+                .iter()
+                .find(|(store_id, _)| store_id.as_str() == id)
+                .map(|(_, db)| db)
+                .with_context(|| format!("Unknown recording {id:?}")),
+            None => self
+                .latest
+                .as_ref()
+                .and_then(|store_id| self.recordings.get(store_id))
+                .context("No data has been received yet"),
+        }
+    }
+
+    fn answer(&self, request: &Request) -> anyhow::Result<Value> {
+        match request {
+            Request::Recordings => Ok(self
+                .recordings
+                .iter()
+                .map(|(store_id, db)| {
+                    serde_json::json!({
+                        "id": store_id.as_str(),
+                        "application_id": db.app_id().map(|app_id| app_id.to_string()),
+                        "timelines": db.timelines().map(|timeline| timeline.name().as_str()).collect::<Vec<_>>(),
+                    })
+                })
+                .collect()),
+
+            Request::Entities { recording } => {
+                let db = self.recording(recording.as_deref())?;
+                let entities = db
+                    .entity_paths()
+                    .into_iter()
+                    .map(|entity_path| {
+                        let components = all_components(db, entity_path)
+                            .into_iter()
+                            .map(|component| component.to_string())
+                            .collect::<Vec<_>>();
+                        (entity_path.to_string(), Value::from(components))
+                    })
+                    .collect::<serde_json::Map<_, _>>();
+                Ok(entities.into())
+            }
+
+            Request::LatestAt {
+                recording,
+                entity,
+                timeline,
+                at,
+                components,
+            } => {
+                let db = self.recording(recording.as_deref())?;
+                let entity_path = EntityPath::parse_forgiving(entity);
+                let query = LatestAtQuery::new(
+                    find_timeline(db, timeline)?,
+                    at.map_or(TimeInt::MAX, TimeInt::from),
+                );
+
+                let mut values = serde_json::Map::default();
+                for component in select_components(db, &entity_path, components.as_deref()) {
+                    if let Some((time, _, [Some(cell)])) =
+                        db.store()
+                            .try_latest_at(&query, &entity_path, component, &[component])?
+                    {
+                        values.insert(
+                            component.to_string(),
+                            serde_json::json!({
+                                "time": time.map(|time| time.as_i64()),
+                                "values": cell_to_json(&cell),
+                            }),
+                        );
+                    }
+                }
+                Ok(values.into())
+            }
+
+            Request::Range {
+                recording,
+                entity,
+                timeline,
+                min,
+                max,
+                components,
+            } => {
+                let db = self.recording(recording.as_deref())?;
+                let entity_path = EntityPath::parse_forgiving(entity);
+                let query = RangeQuery::new(
+                    find_timeline(db, timeline)?,
+                    TimeRange::new(
+                        min.map_or(TimeInt::MIN, TimeInt::from),
+                        max.map_or(TimeInt::MAX, TimeInt::from),
+                    ),
+                );
+
+                // Each component is ranged over on its own, then put back together by row.
+                let mut rows: BTreeMap<(Option<TimeInt>, RowId), serde_json::Map<_, _>> =
+                    Default::default();
+                for component in select_components(db, &entity_path, components.as_deref()) {
+                    for row in db.store().try_range(&query, &entity_path, [component]) {
+                        if let (time, row_id, [Some(cell)]) = row? {
+                            rows.entry((time, row_id))
+                                .or_default()
+                                .insert(component.to_string(), cell_to_json(&cell));
+                        }
+                    }
+                }
+
+                Ok(rows
+                    .into_iter()
+                    .map(|((time, _), components)| {
+                        serde_json::json!({
+                            "time": time.map(|time| time.as_i64()),
+                            "components": components,
+                        })
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+fn find_timeline(db: &EntityDb, name: &str) -> anyhow::Result<Timeline> {
+    db.timelines()
+        .find(|timeline| timeline.name().as_str() == name)
+        .copied()
+        .with_context(|| format!("Unknown timeline {name:?}"))
+}
+
+/// The components of an entity, on all timelines.
+fn all_components(
+    db: &EntityDb,
+    entity_path: &EntityPath,
+) -> std::collections::BTreeSet<ComponentName> {
+    // Timeless components are found on any timeline, even one without data.
+    db.timelines()
+        .chain([&Timeline::log_time()])
+        .filter_map(|timeline| db.store().all_components(timeline, entity_path))
+        .flatten()
+        .collect()
+}
+
+/// The components to query: the requested ones, or all of those holding actual data.
+fn select_components(
+    db: &EntityDb,
+    entity_path: &EntityPath,
+    requested: Option<&[String]>,
+) -> Vec<ComponentName> {
+    let cluster_key = db.store().cluster_key();
+    all_components(db, entity_path)
+        .into_iter()
+        .filter(|component| match requested {
+            Some(requested) => requested
+                .iter()
+                .any(|name| name == component.as_str() || name == component.short_name()),
+            None => *component != cluster_key && !component.is_indicator_component(),
+        })
+        .collect()
+}
+
+fn cell_to_json(cell: &DataCell) -> Value {
+    let array = cell.as_arrow_ref();
+    (0..array.len())
+        .map(|index| arrow_to_json(array, index))
+        .collect()
+}
+
+/// The value at `index` of `array`, as JSON.
+///
+/// Types without a natural JSON form, e.g. binary data, are formatted as strings.
+fn arrow_to_json(array: &dyn Array, index: usize) -> Value {
+    use arrow2::array::{
+        BooleanArray, FixedSizeListArray, ListArray, PrimitiveArray, StructArray, UnionArray,
+        Utf8Array,
+    };
+    use arrow2::datatypes::DataType;
+
+    fn downcast<T: 'static>(array: &dyn Array) -> Option<&T> {
+        array.as_any().downcast_ref::<T>()
+    }
+
+    fn list_to_json(values: &dyn Array) -> Value {
+        (0..values.len())
+            .map(|index| arrow_to_json(values, index))
+            .collect()
+    }
+
+    if array.is_null(index) {
+        return Value::Null;
+    }
+
+    let value = match array.data_type().to_logical_type() {
+        DataType::Boolean => downcast::<BooleanArray>(array).map(|a| a.value(index).into()),
+        DataType::Int8 => downcast::<PrimitiveArray<i8>>(array).map(|a| a.value(index).into()),
+        DataType::Int16 => downcast::<PrimitiveArray<i16>>(array).map(|a| a.value(index).into()),
+        DataType::Int32 => downcast::<PrimitiveArray<i32>>(array).map(|a| a.value(index).into()),
+        DataType::Int64 => downcast::<PrimitiveArray<i64>>(array).map(|a| a.value(index).into()),
+        DataType::UInt8 => downcast::<PrimitiveArray<u8>>(array).map(|a| a.value(index).into()),
+        DataType::UInt16 => downcast::<PrimitiveArray<u16>>(array).map(|a| a.value(index).into()),
+        DataType::UInt32 => downcast::<PrimitiveArray<u32>>(array).map(|a| a.value(index).into()),
+        DataType::UInt64 => downcast::<PrimitiveArray<u64>>(array).map(|a| a.value(index).into()),
+        DataType::Float32 => downcast::<PrimitiveArray<f32>>(array).map(|a| a.value(index).into()),
+        DataType::Float64 => downcast::<PrimitiveArray<f64>>(array).map(|a| a.value(index).into()),
+        DataType::Utf8 => downcast::<Utf8Array<i32>>(array).map(|a| a.value(index).into()),
+        DataType::LargeUtf8 => downcast::<Utf8Array<i64>>(array).map(|a| a.value(index).into()),
+        DataType::List(_) => {
+            downcast::<ListArray<i32>>(array).map(|a| list_to_json(a.value(index).as_ref()))
+        }
+        DataType::LargeList(_) => {
+            downcast::<ListArray<i64>>(array).map(|a| list_to_json(a.value(index).as_ref()))
+        }
+        DataType::FixedSizeList(_, _) => {
+            downcast::<FixedSizeListArray>(array).map(|a| list_to_json(a.value(index).as_ref()))
+        }
+        DataType::Struct(fields) => downcast::<StructArray>(array).map(|a| {
+            fields
+                .iter()
+                .zip(a.values())
+                .map(|(field, values)| (field.name.clone(), arrow_to_json(values.as_ref(), index)))
+                .collect::<serde_json::Map<_, _>>()
+                .into()
+        }),
+        DataType::Union(fields, _, _) => downcast::<UnionArray>(array).map(|a| {
+            let (field, slot) = a.index(index);
+            let mut variant = serde_json::Map::default();
+            variant.insert(
+                fields[field].name.clone(),
+                arrow_to_json(a.fields()[field].as_ref(), slot),
+            );
+            variant.into()
+        }),
+        _ => None,
+    };
+
+    value.unwrap_or_else(|| {
+        let mut formatted = String::new();
+        let display = arrow2::array::get_display(array, "null");
+        display(&mut formatted, index).ok();
+        formatted.into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use re_log_types::{DataRow, TimePoint, TimeType};
+    use re_types::components::{Position3D, Text};
+
+    use super::*;
+
+    fn request(stores: &Stores, request: Value) -> Value {
+        let request = serde_json::from_value(request).unwrap();
+        stores.answer(&request).unwrap()
+    }
+
+    #[test]
+    fn latest_at_and_range() {
+        let store_id = StoreId::from_string(StoreKind::Recording, "rec".to_owned());
+        let frame = Timeline::new("frame", TimeType::Sequence);
+        let mut db = EntityDb::new(store_id.clone());
+        db.add_data_row(
+            DataRow::from_cells1_sized(
+                RowId::new(),
+                "label",
+                TimePoint::timeless(),
+                1,
+                [Text::from("hello")].as_slice(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        for frame_nr in [1, 2, 3] {
+            db.add_data_row(
+                DataRow::from_cells1_sized(
+                    RowId::new(),
+                    "points",
+                    TimePoint::from_iter([(frame, frame_nr.into())]),
+                    2,
+                    [
+                        Position3D::new(frame_nr as f32, 0.0, 0.0),
+                        Position3D::new(0.0, 1.0, 0.0),
+                    ]
+                    .as_slice(),
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        }
+        let stores = Stores {
+            recordings: [(store_id.clone(), db)].into_iter().collect(),
+            latest: Some(store_id),
+            store_config: Default::default(),
+        };
+
+        assert_eq!(
+            request(&stores, serde_json::json!({"query": "recordings"})),
+            serde_json::json!([{"id": "rec", "application_id": null, "timelines": ["frame", "log_time"]}])
+        );
+
+        assert_eq!(
+            request(
+                &stores,
+                serde_json::json!({"query": "latest_at", "entity": "points", "timeline": "frame", "at": 2})
+            ),
+            serde_json::json!({
+                "rerun.components.Position3D": {"time": 2, "values": [[2.0, 0.0, 0.0], [0.0, 1.0, 0.0]]},
+            })
+        );
+        assert_eq!(
+            request(
+                &stores,
+                serde_json::json!({"query": "latest_at", "entity": "label", "timeline": "frame", "components": ["Text"]})
+            ),
+            serde_json::json!({"rerun.components.Text": {"time": null, "values": ["hello"]}})
+        );
+
+        let rows = request(
+            &stores,
+            serde_json::json!({"query": "range", "entity": "points", "timeline": "frame", "min": 2}),
+        );
+        assert_eq!(
+            rows.as_array()
+                .unwrap()
+                .iter()
+                .map(|row| row["time"].clone())
+                .collect::<Vec<_>>(),
+            vec![Value::from(2), Value::from(3)]
+        );
+        assert_eq!(
+            rows[1]["components"]["rerun.components.Position3D"][0],
+            serde_json::json!([3.0, 0.0, 0.0])
+        );
+
+        let request: Request = serde_json::from_value(
+            serde_json::json!({"query": "latest_at", "entity": "points", "timeline": "time"}),
+        )
+        .unwrap();
+        assert!(stores.answer(&request).is_err());
+    }
+}
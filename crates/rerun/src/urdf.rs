@@ -0,0 +1,43 @@
+//! Logging robots described by [URDF](https://wiki.ros.org/urdf/XML) files, and moving their joints.
+//!
+//! ```no_run
+//! # fn main() -> anyhow::Result<()> {
+//! let rec = rerun::RecordingStreamBuilder::new("rerun_example_urdf").spawn()?;
+//!
+//! let robot = rerun::urdf::UrdfTree::from_file_path("robot.urdf")?;
+//! rerun::urdf::log_robot(&rec, &robot)?;
+//!
+//! for frame in 0..100 {
+//!     rec.set_time_sequence("frame", frame);
+//!     rerun::urdf::log_joint_position(&rec, &robot, "elbow", frame as f64 * 0.01)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub use re_data_source::UrdfTree;
+
+use crate::RecordingStream;
+
+/// Logs a robot at rest, timeless: one entity per link, with the visuals of each.
+pub fn log_robot(rec: &RecordingStream, robot: &UrdfTree) -> anyhow::Result<()> {
+    for row in robot.to_data_rows()? {
+        rec.record_row(row, false);
+    }
+    Ok(())
+}
+
+/// Moves a joint of a robot at the current time of the recording stream.
+///
+/// The position is an angle in radians for `revolute` and `continuous` joints, and a distance
+/// in meters for `prismatic` ones.
+pub fn log_joint_position(
+    rec: &RecordingStream,
+    robot: &UrdfTree,
+    joint: &str,
+    position: f64,
+) -> anyhow::Result<()> {
+    let (entity_path, transform) = robot.joint_transform(joint, position)?;
+    rec.log(entity_path, &transform)?;
+    Ok(())
+}
@@ -67,6 +67,14 @@ namespace rerun::components {
             return "model/obj";
         }
 
+        /// [Stereolithography Model `stl`](https://en.wikipedia.org/wiki/STL_(file_format)): `model/stl`.
+        /// Either binary or ASCII.
+        ///
+        /// <https://www.iana.org/assignments/media-types/model/stl>
+        static MediaType stl() {
+            return "model/stl";
+        }
+
       public:
         MediaType() = default;
 
@@ -20,6 +20,8 @@ def guess_media_type(path: str | pathlib.Path) -> MediaType | None:
         return MediaType.GLTF
     elif ext == ".obj":
         return MediaType.OBJ
+    elif ext == ".stl":
+        return MediaType.STL
     else:
         return None
 
@@ -54,6 +56,7 @@ class Asset3DExt:
             For instance:
              * `model/gltf-binary`
              * `model/obj`
+             * `model/stl`
 
             If omitted, it will be guessed from the `path` (if any),
             or the viewer will try to guess from the contents (magic header).